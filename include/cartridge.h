@@ -0,0 +1,58 @@
+/*
+ * C interface of cartridge, built with
+ *
+ *     cargo rustc --release --lib --features ffi --crate-type cdylib
+ *
+ * Functions returning int return 0 on success and -1 on failure; the message of the last
+ * error on the calling thread is returned by cartridge_last_error().
+ */
+#ifndef CARTRIDGE_H
+#define CARTRIDGE_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct Cartridge Cartridge;
+
+enum {
+    CARTRIDGE_BACKUP_STARTED = 0,
+    CARTRIDGE_BACKUP_FILE = 1,
+    CARTRIDGE_BACKUP_FINISHED = 2,
+    CARTRIDGE_BACKUP_FAILED = 3,
+    CARTRIDGE_RESTORE_STARTED = 10,
+    CARTRIDGE_RESTORE_FILE = 11,
+    CARTRIDGE_RESTORE_FINISHED = 12,
+    CARTRIDGE_RESTORE_FAILED = 13,
+};
+
+/* path is NULL except for *_FILE events. The strings are only valid during the call. */
+typedef void (*cartridge_progress_fn)(void *user_data, int event, const char *game,
+                                      const char *path, uint64_t bytes);
+
+/* Loads a configuration file. Returns NULL on failure. */
+Cartridge *cartridge_open(const char *config_path);
+void cartridge_close(Cartridge *cartridge);
+
+/* Valid until the next call into cartridge on this thread, NULL if there was no error. */
+const char *cartridge_last_error(void);
+void cartridge_string_free(char *string);
+
+/* JSON array of {"name", "has_backup", "save_locations"}, free with cartridge_string_free. */
+char *cartridge_list_games(const Cartridge *cartridge);
+
+/* A NULL callback removes the current one. */
+int cartridge_set_progress(Cartridge *cartridge, cartridge_progress_fn callback,
+                           void *user_data);
+
+/* A NULL game_name backs up or restores every enabled game. */
+int cartridge_backup(const Cartridge *cartridge, const char *game_name);
+int cartridge_restore(const Cartridge *cartridge, const char *game_name);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
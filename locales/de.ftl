@@ -0,0 +1,357 @@
+# Deutsche Meldungen der cartridge-CLI. Fehlende Meldungen werden auf Englisch angezeigt.
+
+## Rückfragen
+
+confirm-choices = [j/N]
+# Antworten, die als Ja gelten, getrennt durch |
+confirm-yes = j|ja|y|yes
+restore-conflict-ask = { $path } unterscheidet sich von der Sicherung. [ü]berschreiben, über[s]pringen oder [b]estehende Datei sichern?
+# Antworten auf die Frage bei Konflikten beim Wiederherstellen, getrennt durch |
+restore-conflict-overwrite = ü|u|überschreiben|o|overwrite
+restore-conflict-skip = s|überspringen|skip
+restore-conflict-backup = b|sichern|backup
+restore-conflict-retry = Bitte mit ü, s oder b antworten.
+nothing-deleted = Nichts gelöscht.
+rewrite-confirm = { $path } umschreiben?
+rewrite-done = { $count ->
+    [one] { $count } Speicherpfad
+   *[other] { $count } Speicherpfade
+} umgeschrieben.
+
+## Arbeitsbereiche
+
+workspace-header = == { $config } ==
+workspace-summary = { $total } Konfigurationen: { $succeeded } erfolgreich, { $failed } fehlgeschlagen
+
+## Zusammenfassung
+
+summary-backup = { $succeeded } von { $total ->
+    [one] { $total } Spiel
+   *[other] { $total } Spielen
+} gesichert
+summary-restore = { $succeeded } von { $total ->
+    [one] { $total } Spiel
+   *[other] { $total } Spielen
+} wiederhergestellt
+summary-succeeded = ✓ { $games }
+summary-failed = ✗ { $game }: { $error }
+summary-next-steps = Nächste Schritte:
+summary-doctor = `cartridge doctor` zeigt, welche Speicherpfade fehlen und warum
+summary-skip-missing = `cartridge backup { $game } --skip-missing` sichert den Rest und behält fehlende Orte aus der aktuellen Sicherung
+summary-free-space = `cartridge prune --dry-run` zeigt, welche alten Snapshots Platz freigeben können
+summary-retry = `cartridge --verbose { $command } { $game }` versucht es mit Details erneut
+
+## Sicherungs- und Wiederherstellungspläne
+
+backup-plan-title = Sicherungsplan für '{ $game }'
+backup-no-recent = Innerhalb von { $period } wurde kein Spiel gespielt
+backup-recent-title = Innerhalb von { $period } gespielte Spiele:
+backup-recent-game = { $game }: zuletzt gespielt am { $date }
+restore-plan-title = Wiederherstellungsplan für '{ $game }'
+restore-origin = '{ $game }' aus der Sicherung von { $machine } vom { $date } wiederhergestellt
+restore-origin-unknown = '{ $game }' aus der Sicherung vom { $date } von einem nicht erfassten Rechner wiederhergestellt
+action-create = anlegen
+action-overwrite = ersetzen
+action-skip = auslassen
+action-stash = beiseite
+action-ask = fragen
+plan-replaces = { $source } -> ersetzt { $dest }
+plan-stashed = vorhandene Datei verschoben nach { $path }
+plan-summary = { $changed } Dateien würden sich ändern, { $unchanged } unverändert
+state-modified = { $size }, geändert { $modified }
+state-read-only = { $state }, schreibgeschützt
+
+## Probe-Wiederherstellung
+
+rehearsal-title = Probe-Wiederherstellung für '{ $game }':
+rehearsal-create = anlegen
+rehearsal-overwrite-newer = ersetzen (aktuelle Datei ist neuer)
+rehearsal-overwrite = ersetzen
+rehearsal-keep = behalten (nicht gesichert)
+
+## Wiederherstellen und starten
+
+restore-check-mismatch = stimmt nicht mit der Sicherung überein: { $path }
+restore-check-invalid = Prüfung fehlgeschlagen: { $path }: { $problem }
+restore-check-ok = Wiederhergestellte Spielstände von '{ $game }' geprüft ({ $verified } Dateien).
+launch-started = '{ $game }' gestartet.
+run-started = '{ $game }' gestartet, es wird nach dem Beenden gesichert.
+run-backed-up = '{ $game }' wurde nach { $duration } beendet und gesichert ({ $changed } an Spielständen geändert).
+
+## conflicts
+
+conflicts-none = Keine offenen Konflikte für '{ $game }'.
+conflicts-title = Dateien von '{ $game }', die eine Wiederherstellung trotz Abweichung von der Sicherung behalten hat:
+conflict-live = aktuell:   { $size }, geändert { $modified }
+conflict-live-changed = aktuell:   { $size }, geändert { $modified } (seit der Wiederherstellung verändert)
+conflict-backup = Sicherung: { $size }, geändert { $modified } (Snapshot { $snapshot })
+conflicts-hint = Mit `cartridge conflicts { $game } --keep-live PFAD` oder `--use-backup PFAD` auflösen.
+conflict-kept-live = { $path } behalten.
+conflict-used-backup = { $path } durch die Kopie aus der Sicherung ersetzt.
+
+## list
+
+list-empty = Keine aktivierten Spiele in der Konfiguration gefunden.
+list-title = Verfügbare Spiele:
+list-has-backup = Gesichert
+list-no-backup = Keine Sicherung
+list-disabled = { $status }, deaktiviert
+list-archived = { $status }, archiviert
+list-game = { $game } - { $status } ({ $locations ->
+    [one] { $locations } Speicherort
+   *[other] { $locations } Speicherorte
+})
+
+## verify
+
+verify-ok = { $game }: OK ({ $checked } Dateien geprüft)
+verify-ok-chain = { $game }: OK ({ $checked } Dateien geprüft, { $linked } von { $links } Snapshots verkettet)
+verify-failed-game = { $game }: FEHLGESCHLAGEN ({ $checked } Dateien geprüft, { $missing } fehlen, { $corrupted } beschädigt)
+verify-missing = fehlt:      { $path }
+verify-corrupted = beschädigt: { $path }
+chain-missing = Kette:      Snapshot { $snapshot } verweist auf { $missing }, der außerhalb von cartridge gelöscht wurde
+chain-replaced = Kette:      Snapshot { $snapshot } verweist auf { $replaced }, der seitdem ersetzt wurde
+chain-unlinked = Kette:      auf Snapshot { $snapshot } verweist keine neuere Sicherung
+
+## snapshots
+
+snapshots-none = Keine Sicherungen für '{ $game }' gefunden.
+snapshots-title = Snapshots von '{ $game }':
+machines-title = Rechner mit Sicherungen von '{ $game }', neueste zuerst:
+machines-entry = { $machine }: neueste { $id } ({ $date }), { $count ->
+    [one] 1 Snapshot
+   *[other] { $count } Snapshots
+}
+machines-this = { $machine } (dieser Rechner)
+machines-unknown = nicht erfasster Rechner
+snapshot-latest = aktuell
+snapshot-cold = Cold Storage
+snapshot-last-known-good = ZULETZT FUNKTIONIEREND
+snapshot-label = „{ $label }“
+snapshot-pinned = angeheftet: { $reason }
+snapshot-pinned-plain = angeheftet
+snapshot-suspect = verdächtig, { $issues ->
+    [one] { $issues } Prüfungsproblem
+   *[other] { $issues } Prüfungsprobleme
+}
+pin-done = Snapshot { $snapshot } von '{ $game }' angeheftet, er bleibt bis zum Lösen erhalten.
+unpin-done = Snapshot { $snapshot } von '{ $game }' gelöst.
+mount-done = Snapshot { $snapshot } von '{ $game }' schreibgeschützt unter { $dir } eingehängt:
+mount-location = (wird nach { $path } wiederhergestellt)
+mount-hint = Mit `cartridge unmount { $dir }` wieder entfernen.
+unmount-done = Snapshot { $snapshot } von '{ $game }' aus { $dir } entfernt.
+unknown = unbekannt
+
+## prune und forget
+
+delete-none = Keine Snapshots zu löschen.
+delete-title = Zu löschende Snapshots:
+delete-total = { $count } Snapshots, { $size } werden frei.
+delete-confirm = Diese Snapshots löschen?
+delete-done = { $count } Snapshots gelöscht, { $size } freigegeben.
+delete-trashed = { $count } Snapshots ({ $size }) in den Papierkorb verschoben, endgültig gelöscht nach { $purge_after }. Rückgängig mit `cartridge trash restore`.
+age-unknown = Alter unbekannt
+age-minutes = { $age } Min. alt
+age-hours = { $age } Std. alt
+age-days = { $age } Tage alt
+
+## doctor
+
+doctor-title = Speicherpfade unter { $os }:
+doctor-disabled = { $game } (deaktiviert)
+doctor-problems = { $game } (Probleme gefunden)
+doctor-ok = ok           { $path }
+doctor-missing = fehlt        { $path }
+doctor-skipped = übersprungen kein Pfad für diese Plattform (konfiguriert für: { $platforms })
+doctor-invalid = ungültig     { $error }
+doctor-cloud-synced = Cloud-Sync   durch { $service }, Wiederherstellungen können Konfliktkopien erzeugen (cloud_sync_ok = true setzen, um das zu akzeptieren)
+
+## audit
+
+audit-title = Was eine Sicherung kopieren würde:
+audit-game = { $game }: { $files } Dateien, { $size }
+audit-clean = Keine Probleme gefunden.
+audit-error = Fehler   { $game }: { $message }
+audit-warning = Warnung  { $game }: { $message }
+audit-invalid-path = Speicherort { $location } kann nicht aufgelöst werden: { $error }
+audit-missing-path = Speicherort { $location } existiert nicht: { $path }
+audit-cloud-synced = Speicherort { $location } wird von { $service } synchronisiert, Wiederherstellungen können Konfliktkopien erzeugen
+audit-overlapping-saves = Speicherort { $location } überschneidet sich mit Speicherort { $other_location } von '{ $other }' in { $path }
+audit-overlaps-backups = Speicherort { $location } überschneidet sich mit den Sicherungen in { $backups }
+audit-too-many-files = { $files } Dateien, mehr als die { $limit }, die eine Sicherung annimmt (siehe max_files)
+audit-unreadable = Spielstände können nicht gelesen werden: { $error }
+audit-never-backed-up = noch nie gesichert
+audit-outdated-backup = Spielstände am { $modified } geändert, nach der letzten Sicherung am { $backed_up }
+audit-stale = Spielstände zuletzt am { $modified } geändert, vielleicht deinstalliert
+audit-stale-empty = keine Spielstanddateien gefunden, vielleicht deinstalliert
+
+## diff
+
+diff-same = Aktuelle Spielstände von '{ $game }' stimmen mit der Sicherung überein ({ $unchanged } Dateien).
+diff-title = Unterschiede für '{ $game }' (aktuelle Spielstände gegen Sicherung):
+diff-added = nur in aktuellen Spielständen
+diff-live-newer = aktuelle Datei ist neuer
+diff-backup-newer = Sicherung ist neuer
+diff-removed = fehlt in aktuellen Spielständen
+diff-unchanged = { $unchanged } Dateien unverändert.
+
+## mods
+
+mods-none = Keine Mods für '{ $game }' gesichert oder installiert.
+mods-title = Mods von '{ $game }' (Sicherung gegen installiert):
+mod-installed = installiert
+mod-missing = fehlt
+mod-version-changed = installiert: { $installed }
+mod-no-version = keine Version
+mod-not-in-backup = nicht gesichert
+mods-changed = { $count ->
+    [one] { $count } Mod fehlt oder ist in einer anderen Version installiert.
+   *[other] { $count } Mods fehlen oder sind in einer anderen Version installiert.
+}
+
+## Weitere Befehle
+
+export-diff-done = { $changed ->
+    [one] { $changed } geänderte Datei
+   *[other] { $changed } geänderte Dateien
+} zwischen { $from } und { $to } nach { $file } exportiert ({ $removed } entfernt, aufgeführt in delta.json).
+import-done = Sicherungen importiert für Spiel: { $game }
+share-done = Version { $version } von '{ $game }' ({ $files } Dateien) nach { $file } geteilt.
+receive-up-to-date = Die Spielstände von '{ $game }' sind bereits Version { $version }.
+receive-plan-title = Änderungen durch das Empfangen an '{ $game }'
+receive-diverged = Deine Änderungen an '{ $game }' seit dem letzten Austausch wurden ersetzt, sie sind in der vorher angelegten Sicherung.
+receive-done = Version { $version } von '{ $game }' empfangen ({ $removed } Dateien entfernt).
+import-diff-plan-title = Änderungen des Deltas an '{ $game }'
+import-diff-remove = entfernen { $path }
+import-diff-done = Delta von '{ $game }' von { $from } nach { $to } angewendet: { $written } geschrieben, { $removed } entfernt.
+train-done = Wörterbuch mit { $size } für '{ $game }' aus { $samples } Dateien trainiert.
+infer-none = Keine gemeinsamen Speicherverzeichnisse gefunden.
+infer-new = Neue Variable ${ "{" }{ $name }{ "}" } = { $value }
+infer-existing = Vorhandene Variable ${ "{" }{ $name }{ "}" }
+probe-nothing = Alle Speicherpfade von '{ $game }' existieren, nichts zu beobachten.
+probe-watching = { $configured } existiert nicht, beobachte { $watched }
+probe-start = Starte '{ $game }' und speichere das Spiel (Abbruch nach { $timeout } s)...
+probe-none = Innerhalb von { $timeout } s wurden keine Spielstände geschrieben.
+probe-location = Speicherort { $index }:
+probe-configured = konfiguriert: { $path }
+probe-written = geschrieben:  { $path }
+probe-suggested = Vorschlag:    { $path }
+stats-all-time = gesamten Zeitraum
+stats-none = Keine Übertragungen für { $period } aufgezeichnet.
+stats-title = Übertragungen für { $period }:
+stats-destination = { $destination }: { $uploaded } hochgeladen, { $downloaded } heruntergeladen ({ $transfers } Übertragungen)
+invalid-month = Ungültiger Monat '{ $month }', erwartet JJJJ-MM
+config-exported = Konfiguration exportiert nach { $path }
+
+## status
+
+status-none = Bisher lief keine Sicherung oder Wiederherstellung.
+status-backup = Sicherung
+status-restore = Wiederherstellung
+status-starting = { $operation } beginnt ({ $total ->
+    [one] { $total } Spiel
+   *[other] { $total } Spiele
+})
+status-running = { $operation } von '{ $game }' läuft (Spiel { $done } von { $total }, { $percent } %)
+status-files-of = { $done } von { $total } Dateien
+status-files = { $done } Dateien
+status-finished = { $operation } abgeschlossen um { $time } ({ $games ->
+    [one] { $games } Spiel
+   *[other] { $games } Spiele
+})
+status-failed = { $operation } fehlgeschlagen um { $time } ({ $done } von { $total } Spielen erledigt)
+status-stale = { $operation } von '{ $game }' reagiert seit { $time } nicht mehr (Prozess { $pid })
+stale-title = Spiele, deren Spielstände sich lange nicht geändert haben, vielleicht deinstalliert:
+stale-game = { $game }: zuletzt geändert am { $date } ({ $size })
+stale-game-empty = { $game }: keine Spielstände gefunden
+stale-hint = Mit `cartridge archive-game <Name>` bleiben ihre Sicherungen erhalten, sie werden aber bei regulären Läufen übersprungen.
+archive-game-done = '{ $game }' archiviert. Es wird nicht mehr gesichert, und seine Sicherungen sind vor dem Aufräumen geschützt.
+archive-game-undone = '{ $game }' ist nicht mehr archiviert.
+
+## daemon
+
+daemon-start = Alte Snapshots werden zwischen { $window } geprüft. Mit Strg+C beenden.
+daemon-verified = { $game } { $snapshot }: { $checked ->
+    [one] 1 Datei intakt
+   *[other] { $checked } Dateien intakt
+}
+daemon-failed = { $game } { $snapshot }: Snapshot hat die Prüfung nicht bestanden
+daemon-backed-up = { $game }: gesichert ({ $priority })
+daemon-backup-failed = { $game }: Sicherung fehlgeschlagen: { $error }
+priority-manual = manuell
+priority-watch = Überwachung
+queue-empty = Keine Sicherungen warten auf den Daemon.
+queue-running = läuft seit { $since }
+queue-waiting = wartet seit { $since }{ $requests ->
+    [one] {""}
+   *[other] , { $requests } Anfragen zusammengeführt
+}
+queue-added = Sicherung von '{ $game }' für den Daemon eingereiht.
+queue-merged = Eine Sicherung von '{ $game }' wartet bereits, die Anfrage wurde mit ihr zusammengeführt.
+queue-cleared = { $count ->
+    [one] { $count } wartende Sicherung
+   *[other] { $count } wartende Sicherungen
+} aus der Warteschlange entfernt.
+service-installed = Windows-Dienst '{ $name }' installiert und gestartet. Er protokolliert in die Ereignisanzeige.
+service-uninstalled = Windows-Dienst '{ $name }' beendet und entfernt.
+
+## history
+
+history-none = Noch keine Spielsitzungen aufgezeichnet. Spiele mit `cartridge run` starten, um sie aufzuzeichnen.
+history-title = Spielsitzungen:
+history-session = { $started }  { $game }  { $duration }  { $files ->
+    [one] 1 Datei geändert
+   *[other] { $files } Dateien geändert
+} ({ $changed }){ $removed ->
+    [0] {""}
+    [one] , 1 Datei entfernt
+   *[other] , { $removed } Dateien entfernt
+}, Spielstände { $total }
+history-first-session = { $started }  { $game }  { $duration }  erste Sicherung, Spielstände { $total }
+duration-minutes = { $minutes } Min.
+duration-hours = { $hours } Std. { $minutes } Min.
+
+## trash
+
+trash-empty = Der Papierkorb ist leer.
+trash-title = Gelöschte Snapshots (endgültig gelöscht { $purge_after } nach dem Löschen):
+trash-purged-at = endgültig gelöscht { $time }
+trash-cold = [Cold Storage]
+trash-restored = Snapshot { $snapshot } von '{ $game }' wiederhergestellt.
+trash-confirm = Alle Snapshots im Papierkorb endgültig löschen?
+trash-purged = { $count } Snapshots endgültig gelöscht, { $size } freigegeben.
+
+## quarantine
+
+quarantine-none = Keine Sicherungen in Quarantäne.
+quarantine-title = Sicherungen in Quarantäne:
+quarantine-entry = { $id } - { $game } (fehlgeschlagen { $time })
+quarantine-id = Id:             { $id }
+quarantine-game = Spiel:          { $game }
+quarantine-failed-at = Fehlgeschlagen: { $time }
+quarantine-error = Fehler:         { $error }
+quarantine-data = Daten:          { $path }
+quarantine-purged = { $count } Quarantäne-Einträge gelöscht.
+
+## dedup
+
+dedup-summary = { $files } Dateien, { $stored } gespeichert, { $unique } eindeutige Inhalte ({ $savings } durch Deduplizierung einsparbar)
+dedup-cross-game = Von mehreren Spielen geteilt:
+dedup-same-game = Innerhalb eines Spiels wiederholt:
+dedup-more = { $count } weitere Inhalte wiederholen sich zwischen Snapshots desselben Spiels ({ $size }), mit --all auflisten
+dedup-group = { $hash } x{ $copies } (je { $size }, { $savings } gespart) { $games }
+
+## info
+
+info-none = (keine)
+info-platform = Plattform:       { $os } ({ $family }, { $arch })
+info-backends = Backends:        { $backends }
+info-features = Features:        { $features }
+info-formats = Formate:         { $formats }
+info-language = Sprache:         { $language }
+info-home = Home:            { $path }
+info-config-file = Konfiguration:   { $path }
+info-backup-root = Sicherungen:     { $path }
+self-update-up-to-date = cartridge { $version } ist die neueste Version
+self-update-available = cartridge { $latest } ist verfügbar (installiert ist { $current }), aktualisieren mit `cartridge self-update`
+self-update-done = cartridge von { $current } auf { $latest } aktualisiert: { $path }
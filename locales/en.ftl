@@ -0,0 +1,370 @@
+# English messages of the cartridge CLI. This is the fallback for messages missing from
+# other translations, so every message the CLI shows must be defined here.
+
+## Prompts
+
+confirm-choices = [y/N]
+# Answers that count as yes, separated by |
+confirm-yes = y|yes
+restore-conflict-ask = { $path } differs from the backup. [o]verwrite, [s]kip or [b]ackup existing?
+# Answers to the restore conflict question, separated by |
+restore-conflict-overwrite = o|overwrite
+restore-conflict-skip = s|skip
+restore-conflict-backup = b|backup
+restore-conflict-retry = Please answer o, s or b.
+nothing-deleted = Nothing deleted.
+rewrite-confirm = Rewrite { $path }?
+rewrite-done = Rewrote { $count ->
+    [one] { $count } save path
+   *[other] { $count } save paths
+}.
+
+## Workspaces
+
+workspace-header = == { $config } ==
+workspace-summary = { $total } configs: { $succeeded } succeeded, { $failed } failed
+
+## Run summary
+
+summary-backup = Backed up { $succeeded } of { $total ->
+    [one] { $total } game
+   *[other] { $total } games
+}
+summary-restore = Restored { $succeeded } of { $total ->
+    [one] { $total } game
+   *[other] { $total } games
+}
+summary-succeeded = ✓ { $games }
+summary-failed = ✗ { $game }: { $error }
+summary-next-steps = Next steps:
+summary-doctor = Run `cartridge doctor` to see which save paths are missing and why
+summary-skip-missing = Run `cartridge backup { $game } --skip-missing` to back up the rest, keeping missing locations from the current backup
+summary-free-space = Run `cartridge prune --dry-run` to see which old snapshots can go to free up space
+summary-retry = Run `cartridge --verbose { $command } { $game }` to try again with details
+
+## Backup and restore plans
+
+backup-plan-title = Backup plan for '{ $game }'
+backup-no-recent = No games were played within { $period }
+backup-recent-title = Games played within { $period }:
+backup-recent-game = { $game }: last played { $date }
+restore-plan-title = Restore plan for '{ $game }'
+restore-origin = Restored '{ $game }' from the backup taken on { $machine } at { $date }
+restore-origin-unknown = Restored '{ $game }' from the backup taken at { $date } on an unrecorded machine
+action-create = create
+action-overwrite = overwrite
+action-skip = skip
+action-stash = stash
+action-ask = ask
+plan-replaces = { $source } -> replaces { $dest }
+plan-stashed = existing file moved to { $path }
+plan-summary = { $changed } files would change, { $unchanged } unchanged
+state-modified = { $size }, modified { $modified }
+state-read-only = { $state }, read-only
+
+## Restore rehearsal
+
+rehearsal-title = Restore rehearsal for '{ $game }':
+rehearsal-create = create
+rehearsal-overwrite-newer = overwrite (live file is newer)
+rehearsal-overwrite = overwrite
+rehearsal-keep = keep (not in backup)
+
+## Restore then launch
+
+restore-check-mismatch = does not match the backup: { $path }
+restore-check-invalid = failed validation: { $path }: { $problem }
+restore-check-ok = Restored saves of '{ $game }' verified ({ $verified } files).
+launch-started = Launched '{ $game }'.
+run-started = Started '{ $game }', it is backed up once it exits.
+run-backed-up = '{ $game }' exited after { $duration } and was backed up ({ $changed } of saves changed).
+
+## conflicts
+
+conflicts-none = No unresolved conflicts for '{ $game }'.
+conflicts-title = Live files of '{ $game }' that a restore kept although they differ from the backup:
+conflict-live = live:   { $size }, modified { $modified }
+conflict-live-changed = live:   { $size }, modified { $modified } (changed since the restore)
+conflict-backup = backup: { $size }, modified { $modified } (snapshot { $snapshot })
+conflicts-hint = Resolve them with `cartridge conflicts { $game } --keep-live PATH` or `--use-backup PATH`.
+conflict-kept-live = Kept { $path }.
+conflict-used-backup = Replaced { $path } with the backup copy.
+
+## list
+
+list-empty = No enabled games found in configuration.
+list-title = Available games:
+list-has-backup = Has backup
+list-no-backup = No backup
+list-disabled = { $status }, disabled
+list-archived = { $status }, archived
+list-game = { $game } - { $status } ({ $locations ->
+    [one] { $locations } save location
+   *[other] { $locations } save locations
+})
+
+## verify
+
+verify-ok = { $game }: OK ({ $checked } files verified)
+verify-ok-chain = { $game }: OK ({ $checked } files verified, { $linked } of { $links } snapshots linked)
+verify-failed-game = { $game }: FAILED ({ $checked } files checked, { $missing } missing, { $corrupted } corrupted)
+verify-missing = missing:   { $path }
+verify-corrupted = corrupted: { $path }
+chain-missing = chain:     snapshot { $snapshot } links to { $missing }, which was deleted outside of cartridge
+chain-replaced = chain:     snapshot { $snapshot } links to { $replaced }, which was replaced since
+chain-unlinked = chain:     snapshot { $snapshot } is not linked from any newer backup
+
+## snapshots
+
+snapshots-none = No backups found for '{ $game }'.
+snapshots-title = Snapshots of '{ $game }':
+machines-title = Machines with backups of '{ $game }', newest first:
+machines-entry = { $machine }: newest { $id } ({ $date }), { $count ->
+    [one] 1 snapshot
+   *[other] { $count } snapshots
+}
+machines-this = { $machine } (this machine)
+machines-unknown = unrecorded machine
+snapshot-latest = latest
+snapshot-cold = cold storage
+snapshot-last-known-good = LAST KNOWN GOOD
+snapshot-label = "{ $label }"
+snapshot-pinned = pinned: { $reason }
+snapshot-pinned-plain = pinned
+snapshot-suspect = suspect, { $issues ->
+    [one] { $issues } validation issue
+   *[other] { $issues } validation issues
+}
+pin-done = Pinned snapshot { $snapshot } of '{ $game }', it is kept until unpinned.
+unpin-done = Unpinned snapshot { $snapshot } of '{ $game }'.
+mount-done = Mounted snapshot { $snapshot } of '{ $game }' read-only at { $dir }:
+mount-location = (restores to { $path })
+mount-hint = Remove it with `cartridge unmount { $dir }` when done.
+unmount-done = Unmounted snapshot { $snapshot } of '{ $game }' from { $dir }.
+unknown = unknown
+
+## prune and forget
+
+delete-none = No snapshots to delete.
+delete-title = Snapshots to delete:
+delete-total = { $count ->
+    [one] { $count } snapshot
+   *[other] { $count } snapshots
+}, { $size } to reclaim.
+delete-confirm = Delete these snapshots?
+delete-done = Deleted { $count ->
+    [one] { $count } snapshot
+   *[other] { $count } snapshots
+}, reclaimed { $size }.
+delete-trashed = Moved { $count ->
+    [one] { $count } snapshot
+   *[other] { $count } snapshots
+} ({ $size }) to the trash, purged after { $purge_after }. Undo with `cartridge trash restore`.
+age-unknown = unknown age
+age-minutes = { $age }m old
+age-hours = { $age }h old
+age-days = { $age }d old
+
+## doctor
+
+doctor-title = Save paths on { $os }:
+doctor-disabled = { $game } (disabled)
+doctor-problems = { $game } (problems found)
+doctor-ok = ok       { $path }
+doctor-missing = missing  { $path }
+doctor-skipped = skipped  no path for this platform (configured for: { $platforms })
+doctor-invalid = invalid  { $error }
+doctor-cloud-synced = synced   by { $service }, restores may leave conflicted copies (set cloud_sync_ok = true to accept)
+
+## audit
+
+audit-title = What a backup would copy:
+audit-game = { $game }: { $files } files, { $size }
+audit-clean = No problems found.
+audit-error = error    { $game }: { $message }
+audit-warning = warning  { $game }: { $message }
+audit-invalid-path = save location { $location } cannot be resolved: { $error }
+audit-missing-path = save location { $location } does not exist: { $path }
+audit-cloud-synced = save location { $location } is synced by { $service }, restores may leave conflicted copies
+audit-overlapping-saves = save location { $location } overlaps location { $other_location } of '{ $other }' at { $path }
+audit-overlaps-backups = save location { $location } overlaps the backups in { $backups }
+audit-too-many-files = { $files } files, more than the { $limit } a backup accepts (see max_files)
+audit-unreadable = saves cannot be read: { $error }
+audit-never-backed-up = never backed up
+audit-outdated-backup = saves changed at { $modified }, after the last backup at { $backed_up }
+audit-stale = saves last changed at { $modified }, maybe uninstalled
+audit-stale-empty = no save files found, maybe uninstalled
+
+## diff
+
+diff-same = Live saves for '{ $game }' match the backup ({ $unchanged } files).
+diff-title = Differences for '{ $game }' (live saves vs backup):
+diff-added = only in live saves
+diff-live-newer = live file is newer
+diff-backup-newer = backup is newer
+diff-removed = missing from live saves
+diff-unchanged = { $unchanged } files unchanged.
+
+## mods
+
+mods-none = No mods recorded or installed for '{ $game }'.
+mods-title = Mods of '{ $game }' (backup vs installed):
+mod-installed = installed
+mod-missing = missing
+mod-version-changed = installed: { $installed }
+mod-no-version = no version
+mod-not-in-backup = not in backup
+mods-changed = { $count ->
+    [one] { $count } mod is missing or has another version installed.
+   *[other] { $count } mods are missing or have another version installed.
+}
+
+## Other commands
+
+export-diff-done = Exported { $changed ->
+    [one] { $changed } changed file
+   *[other] { $changed } changed files
+} between { $from } and { $to } to { $file } ({ $removed } removed, listed in delta.json).
+import-done = Imported backups for game: { $game }
+share-done = Shared version { $version } of '{ $game }' ({ $files } files) to { $file }.
+receive-up-to-date = The saves of '{ $game }' are already version { $version }.
+receive-plan-title = Changes receiving makes to '{ $game }'
+receive-diverged = Your changes to '{ $game }' since the last exchange were replaced, they are in the backup taken before.
+receive-done = Received version { $version } of '{ $game }' ({ $removed } files removed).
+import-diff-plan-title = Changes the delta makes to '{ $game }'
+import-diff-remove = remove { $path }
+import-diff-done = Applied the delta of '{ $game }' from { $from } to { $to }: { $written } written, { $removed } removed.
+train-done = Trained a { $size } dictionary for '{ $game }' from { $samples } files.
+infer-none = No shared save directories found.
+infer-new = New variable ${ "{" }{ $name }{ "}" } = { $value }
+infer-existing = Existing variable ${ "{" }{ $name }{ "}" }
+probe-nothing = All save paths of '{ $game }' exist, nothing to probe.
+probe-watching = { $configured } does not exist, watching { $watched }
+probe-start = Start '{ $game }' and save the game (giving up after { $timeout }s)...
+probe-none = No saves were written within { $timeout }s.
+probe-location = Save location { $index }:
+probe-configured = configured: { $path }
+probe-written = written:    { $path }
+probe-suggested = suggested:  { $path }
+stats-all-time = all time
+stats-none = No transfers recorded for { $period }.
+stats-title = Transfers for { $period }:
+stats-destination = { $destination }: { $uploaded } uploaded, { $downloaded } downloaded ({ $transfers } transfers)
+invalid-month = Invalid month '{ $month }', expected YYYY-MM
+config-exported = Exported config to { $path }
+
+## status
+
+status-none = No backup or restore has run yet.
+status-backup = Backup
+status-restore = Restore
+status-starting = { $operation } starting ({ $total ->
+    [one] { $total } game
+   *[other] { $total } games
+})
+status-running = { $operation } of '{ $game }' running (game { $done } of { $total }, { $percent }%)
+status-files-of = { $done } of { $total } files
+status-files = { $done } files
+status-finished = { $operation } finished at { $time } ({ $games ->
+    [one] { $games } game
+   *[other] { $games } games
+})
+status-failed = { $operation } failed at { $time } ({ $done } of { $total } games done)
+status-stale = { $operation } of '{ $game }' stopped responding at { $time } (process { $pid })
+stale-title = Games whose saves have not changed in a long time, maybe uninstalled:
+stale-game = { $game }: last changed { $date } ({ $size })
+stale-game-empty = { $game }: no save files found
+stale-hint = Run `cartridge archive-game <name>` to keep their backups but leave them out of routine runs.
+archive-game-done = Archived '{ $game }'. It is no longer backed up, and its backups are kept from pruning.
+archive-game-undone = '{ $game }' is no longer archived.
+
+## daemon
+
+daemon-start = Verifying old snapshots between { $window }. Press Ctrl+C to stop.
+daemon-verified = { $game } { $snapshot }: { $checked ->
+    [one] 1 file intact
+   *[other] { $checked } files intact
+}
+daemon-failed = { $game } { $snapshot }: snapshot failed verification
+daemon-backed-up = { $game }: backed up ({ $priority })
+daemon-backup-failed = { $game }: backup failed: { $error }
+priority-manual = manual
+priority-watch = watch
+queue-empty = No backups are waiting for the daemon.
+queue-running = running since { $since }
+queue-waiting = waiting since { $since }{ $requests ->
+    [one] {""}
+   *[other] , { $requests } requests merged
+}
+queue-added = Queued a backup of '{ $game }' for the daemon.
+queue-merged = A backup of '{ $game }' is already waiting, merged the request into it.
+queue-cleared = Removed { $count ->
+    [one] { $count } waiting backup
+   *[other] { $count } waiting backups
+} from the queue.
+service-installed = Installed and started the Windows service '{ $name }'. It logs to the event log.
+service-uninstalled = Stopped and removed the Windows service '{ $name }'.
+
+## history
+
+history-none = No play sessions recorded yet. Start games with `cartridge run` to record them.
+history-title = Play sessions:
+history-session = { $started }  { $game }  { $duration }  { $files ->
+    [one] 1 file changed
+   *[other] { $files } files changed
+} ({ $changed }){ $removed ->
+    [0] {""}
+    [one] , 1 file removed
+   *[other] , { $removed } files removed
+}, saves { $total }
+history-first-session = { $started }  { $game }  { $duration }  first backup, saves { $total }
+duration-minutes = { $minutes }m
+duration-hours = { $hours }h { $minutes }m
+
+## trash
+
+trash-empty = The trash is empty.
+trash-title = Deleted snapshots (purged { $purge_after } after deletion):
+trash-purged-at = purged { $time }
+trash-cold = [cold storage]
+trash-restored = Restored snapshot { $snapshot } of '{ $game }'.
+trash-confirm = Delete every snapshot in the trash for good?
+trash-purged = Purged { $count ->
+    [one] { $count } snapshot
+   *[other] { $count } snapshots
+}, reclaimed { $size }.
+
+## quarantine
+
+quarantine-none = No quarantined backups.
+quarantine-title = Quarantined backups:
+quarantine-entry = { $id } - { $game } (failed { $time })
+quarantine-id = Id:        { $id }
+quarantine-game = Game:      { $game }
+quarantine-failed-at = Failed at: { $time }
+quarantine-error = Error:     { $error }
+quarantine-data = Data:      { $path }
+quarantine-purged = Purged { $count } quarantine entries.
+
+## dedup
+
+dedup-summary = { $files } files, { $stored } stored, { $unique } unique content ({ $savings } could be saved by deduplication)
+dedup-cross-game = Shared between games:
+dedup-same-game = Repeated within a game:
+dedup-more = { $count } more contents repeat between snapshots of the same game ({ $size }), use --all to list them
+dedup-group = { $hash } x{ $copies } ({ $size } each, { $savings } saved) { $games }
+
+## info
+
+info-none = (none)
+info-platform = Platform:    { $os } ({ $family }, { $arch })
+info-backends = Backends:    { $backends }
+info-features = Features:    { $features }
+info-formats = Formats:     { $formats }
+info-language = Language:    { $language }
+info-home = Home:        { $path }
+info-config-file = Config file: { $path }
+info-backup-root = Backup root: { $path }
+self-update-up-to-date = cartridge { $version } is the latest release
+self-update-available = cartridge { $latest } is available (this is { $current }), install it with `cartridge self-update`
+self-update-done = Updated cartridge from { $current } to { $latest }: { $path }
@@ -0,0 +1,568 @@
+// With every archive feature disabled the writer can never be constructed
+#![cfg_attr(
+    not(any(feature = "zip", feature = "tar-zst")),
+    allow(
+        unused_imports,
+        unused_mut,
+        unused_variables,
+        unreachable_code,
+        dead_code
+    )
+)]
+
+use anyhow::{Context, Result, anyhow};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::manifest::{self, HashingReader};
+use crate::volume::{VolumeReader, VolumeWriter};
+
+/// Zstd dictionary a `tar.zst` backup was compressed with, stored next to the archive.
+pub const DICTIONARY_FILE: &str = "saves.dict";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarZst => "tar.zst",
+        }
+    }
+
+    /// Cargo feature that has to be enabled for this format to be usable.
+    pub fn feature(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarZst => "tar-zst",
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        match self {
+            ArchiveFormat::Zip => cfg!(feature = "zip"),
+            ArchiveFormat::TarZst => cfg!(feature = "tar-zst"),
+        }
+    }
+
+    pub fn ensure_available(&self) -> Result<()> {
+        if self.is_available() {
+            return Ok(());
+        }
+        Err(anyhow!(
+            "Support for {} archives is not compiled into this build. Recompile with `--features {}`",
+            self.extension(),
+            self.feature()
+        ))
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+        if file_name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if file_name.ends_with(".tar.zst") || file_name.ends_with(".tzst") {
+            Some(ArchiveFormat::TarZst)
+        } else {
+            None
+        }
+    }
+}
+
+enum Writer {
+    #[cfg(feature = "zip")]
+    Zip(Box<zip::ZipWriter<VolumeWriter>>),
+    #[cfg(feature = "tar-zst")]
+    TarZst(tar::Builder<zstd::Encoder<'static, VolumeWriter>>),
+}
+
+pub struct ArchiveWriter {
+    writer: Writer,
+    path: PathBuf,
+}
+
+impl ArchiveWriter {
+    /// Creates an archive. A zstd dictionary, if given, is used for `tar.zst` archives and
+    /// ignored for zip. With a volume size, the archive is split into `<path>.001`,
+    /// `<path>.002`, ... of at most that many bytes each.
+    pub fn create(
+        path: &Path,
+        format: ArchiveFormat,
+        dictionary: Option<&[u8]>,
+        volume_size: Option<u64>,
+    ) -> Result<Self> {
+        log::debug!(
+            "Creating {} archive: {}",
+            format.extension(),
+            path.display()
+        );
+
+        let file = VolumeWriter::create(path, volume_size)
+            .with_context(|| format!("Failed to create archive: {}", path.display()))?;
+
+        let writer = match format {
+            #[cfg(feature = "zip")]
+            ArchiveFormat::Zip => Writer::Zip(Box::new(zip::ZipWriter::new(file))),
+            #[cfg(feature = "tar-zst")]
+            ArchiveFormat::TarZst => {
+                let encoder = match dictionary {
+                    Some(dictionary) => zstd::Encoder::with_dictionary(file, 0, dictionary),
+                    None => zstd::Encoder::new(file, 0),
+                }
+                .with_context(|| {
+                    format!("Failed to initialize zstd encoder: {}", path.display())
+                })?;
+                Writer::TarZst(tar::Builder::new(encoder))
+            }
+            #[cfg(not(all(feature = "zip", feature = "tar-zst")))]
+            _ => return Err(format.ensure_available().unwrap_err()),
+        };
+
+        Ok(Self {
+            writer,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Adds a file to the archive and returns the hash of its content.
+    pub fn add_file(&mut self, source: &Path, name: &Path) -> Result<String> {
+        let entry_name = entry_name(name)?;
+        log::debug!(
+            "Adding file to archive: {} -> {}",
+            source.display(),
+            entry_name.display()
+        );
+
+        let file = File::open(source)
+            .with_context(|| format!("Failed to open file: {}", source.display()))?;
+        let metadata = file
+            .metadata()
+            .with_context(|| format!("Failed to read metadata: {}", source.display()))?;
+        let mut reader = HashingReader::new(file);
+
+        match self.writer {
+            #[cfg(feature = "zip")]
+            Writer::Zip(ref mut zip) => {
+                let options = zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .large_file(metadata.len() >= u32::MAX as u64);
+                zip.start_file(zip_entry_name(&entry_name)?, options)
+                    .with_context(|| {
+                        format!("Failed to add file to archive: {}", source.display())
+                    })?;
+                io::copy(&mut reader, zip.as_mut()).with_context(|| {
+                    format!("Failed to write file to archive: {}", source.display())
+                })?;
+            }
+            #[cfg(feature = "tar-zst")]
+            Writer::TarZst(ref mut tar) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_metadata(&metadata);
+                tar.append_data(&mut header, &entry_name, &mut reader)
+                    .with_context(|| {
+                        format!("Failed to add file to archive: {}", source.display())
+                    })?;
+            }
+        }
+
+        Ok(reader.finish())
+    }
+
+    pub fn add_dir(&mut self, name: &Path) -> Result<()> {
+        let entry_name = entry_name(name)?;
+        if entry_name.as_os_str().is_empty() {
+            return Ok(());
+        }
+
+        match self.writer {
+            #[cfg(feature = "zip")]
+            Writer::Zip(ref mut zip) => {
+                zip.add_directory(
+                    zip_entry_name(&entry_name)?,
+                    zip::write::SimpleFileOptions::default(),
+                )
+                .with_context(|| {
+                    format!("Failed to add directory to archive: {}", name.display())
+                })?;
+            }
+            #[cfg(feature = "tar-zst")]
+            Writer::TarZst(ref mut tar) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_entry_type(tar::EntryType::Directory);
+                header.set_mode(0o755);
+                header.set_size(0);
+                tar.append_data(&mut header, &entry_name, io::empty())
+                    .with_context(|| {
+                        format!("Failed to add directory to archive: {}", name.display())
+                    })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn add_dir_all(&mut self, source: &Path, name: &Path) -> Result<()> {
+        if source.is_file() {
+            let file_name = source
+                .file_name()
+                .ok_or_else(|| anyhow!("Invalid file name: {}", source.display()))?;
+            self.add_file(source, &name.join(file_name))?;
+            return Ok(());
+        }
+
+        self.add_dir(name)?;
+
+        let entries = fs::read_dir(source)
+            .with_context(|| format!("Failed to read directory: {}", source.display()))?;
+
+        for entry in entries {
+            let entry = entry.with_context(|| {
+                format!("Failed to read directory entry in: {}", source.display())
+            })?;
+            let path = entry.path();
+            let entry_path = name.join(entry.file_name());
+
+            if path.is_dir() {
+                self.add_dir_all(&path, &entry_path)?;
+            } else {
+                self.add_file(&path, &entry_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<()> {
+        match self.writer {
+            #[cfg(feature = "zip")]
+            Writer::Zip(zip) => {
+                zip.finish().with_context(|| {
+                    format!("Failed to finalize archive: {}", self.path.display())
+                })?;
+            }
+            #[cfg(feature = "tar-zst")]
+            Writer::TarZst(tar) => {
+                let encoder = tar.into_inner().with_context(|| {
+                    format!("Failed to finalize archive: {}", self.path.display())
+                })?;
+                encoder.finish().with_context(|| {
+                    format!("Failed to finalize archive: {}", self.path.display())
+                })?;
+            }
+        }
+
+        log::debug!("Finalized archive: {}", self.path.display());
+        Ok(())
+    }
+}
+
+/// Lists the relative paths of all entries stored in an archive.
+pub fn list(
+    archive: &Path,
+    format: ArchiveFormat,
+    dictionary: Option<&[u8]>,
+) -> Result<Vec<PathBuf>> {
+    let mut names = Vec::new();
+
+    match format {
+        #[cfg(feature = "zip")]
+        ArchiveFormat::Zip => {
+            let mut zip = open_zip(archive)?;
+            for i in 0..zip.len() {
+                let file = zip.by_index(i).with_context(|| {
+                    format!("Failed to read archive entry in: {}", archive.display())
+                })?;
+                names.push(enclosed_zip_name(&file, archive)?);
+            }
+        }
+        #[cfg(feature = "tar-zst")]
+        ArchiveFormat::TarZst => {
+            let mut tar = open_tar(archive, dictionary)?;
+            let entries = tar
+                .entries()
+                .with_context(|| format!("Failed to read archive: {}", archive.display()))?;
+            for entry in entries {
+                let entry = entry.with_context(|| {
+                    format!("Failed to read archive entry in: {}", archive.display())
+                })?;
+                names.push(enclosed_tar_name(&entry, archive)?);
+            }
+        }
+        #[cfg(not(all(feature = "zip", feature = "tar-zst")))]
+        _ => format.ensure_available()?,
+    }
+
+    Ok(names)
+}
+
+/// Reads every file stored in an archive, returning its path, size and content hash.
+pub fn hash_files(
+    archive: &Path,
+    format: ArchiveFormat,
+    dictionary: Option<&[u8]>,
+) -> Result<Vec<(PathBuf, u64, String)>> {
+    let mut files = Vec::new();
+
+    match format {
+        #[cfg(feature = "zip")]
+        ArchiveFormat::Zip => {
+            let mut zip = open_zip(archive)?;
+            for i in 0..zip.len() {
+                let mut file = zip.by_index(i).with_context(|| {
+                    format!("Failed to read archive entry in: {}", archive.display())
+                })?;
+                if file.is_dir() {
+                    continue;
+                }
+                let name = enclosed_zip_name(&file, archive)?;
+                let hash = manifest::hash_reader(&mut file)
+                    .with_context(|| format!("Failed to read archive entry: {}", name.display()))?;
+                files.push((name, file.size(), hash));
+            }
+        }
+        #[cfg(feature = "tar-zst")]
+        ArchiveFormat::TarZst => {
+            let mut tar = open_tar(archive, dictionary)?;
+            let entries = tar
+                .entries()
+                .with_context(|| format!("Failed to read archive: {}", archive.display()))?;
+            for entry in entries {
+                let mut entry = entry.with_context(|| {
+                    format!("Failed to read archive entry in: {}", archive.display())
+                })?;
+                if entry.header().entry_type().is_dir() {
+                    continue;
+                }
+                let name = enclosed_tar_name(&entry, archive)?;
+                let size = entry.size();
+                let hash = manifest::hash_reader(&mut entry)
+                    .with_context(|| format!("Failed to read archive entry: {}", name.display()))?;
+                files.push((name, size, hash));
+            }
+        }
+        #[cfg(not(all(feature = "zip", feature = "tar-zst")))]
+        _ => format.ensure_available()?,
+    }
+
+    Ok(files)
+}
+
+/// Extracts every entry located under `prefix` into `dest`, with the prefix stripped.
+/// Returns the number of files written.
+pub fn extract(
+    archive: &Path,
+    format: ArchiveFormat,
+    dictionary: Option<&[u8]>,
+    prefix: &Path,
+    dest: &Path,
+) -> Result<usize> {
+    log::debug!(
+        "Extracting '{}' from {} to {}",
+        prefix.display(),
+        archive.display(),
+        dest.display()
+    );
+
+    let mut file_count = 0;
+
+    match format {
+        #[cfg(feature = "zip")]
+        ArchiveFormat::Zip => {
+            let mut zip = open_zip(archive)?;
+            for i in 0..zip.len() {
+                let mut file = zip.by_index(i).with_context(|| {
+                    format!("Failed to read archive entry in: {}", archive.display())
+                })?;
+                let name = enclosed_zip_name(&file, archive)?;
+                let Some(dest_path) = destination(&name, prefix, dest) else {
+                    continue;
+                };
+
+                if file.is_dir() {
+                    create_dir(&dest_path)?;
+                } else {
+                    if let Some(parent) = dest_path.parent() {
+                        create_dir(parent)?;
+                    }
+                    log::debug!("Extracting file: {}", dest_path.display());
+                    let mut out = File::create(&dest_path).with_context(|| {
+                        format!("Failed to create file: {}", dest_path.display())
+                    })?;
+                    io::copy(&mut file, &mut out).with_context(|| {
+                        format!("Failed to extract file: {}", dest_path.display())
+                    })?;
+                    file_count += 1;
+                }
+            }
+        }
+        #[cfg(feature = "tar-zst")]
+        ArchiveFormat::TarZst => {
+            let mut tar = open_tar(archive, dictionary)?;
+            let entries = tar
+                .entries()
+                .with_context(|| format!("Failed to read archive: {}", archive.display()))?;
+            for entry in entries {
+                let mut entry = entry.with_context(|| {
+                    format!("Failed to read archive entry in: {}", archive.display())
+                })?;
+                let name = enclosed_tar_name(&entry, archive)?;
+                let Some(dest_path) = destination(&name, prefix, dest) else {
+                    continue;
+                };
+
+                match entry.header().entry_type() {
+                    tar::EntryType::Directory => create_dir(&dest_path)?,
+                    tar::EntryType::Regular | tar::EntryType::Continuous => {
+                        if let Some(parent) = dest_path.parent() {
+                            create_dir(parent)?;
+                        }
+                        log::debug!("Extracting file: {}", dest_path.display());
+                        entry.unpack(&dest_path).with_context(|| {
+                            format!("Failed to extract file: {}", dest_path.display())
+                        })?;
+                        file_count += 1;
+                    }
+                    other => {
+                        log::warn!(
+                            "Skipping unsupported archive entry type {:?}: {}",
+                            other,
+                            name.display()
+                        );
+                    }
+                }
+            }
+        }
+        #[cfg(not(all(feature = "zip", feature = "tar-zst")))]
+        _ => format.ensure_available()?,
+    }
+
+    Ok(file_count)
+}
+
+#[cfg(feature = "zip")]
+fn open_zip(archive: &Path) -> Result<zip::ZipArchive<VolumeReader>> {
+    let file = VolumeReader::open(archive)
+        .with_context(|| format!("Failed to open archive: {}", archive.display()))?;
+    zip::ZipArchive::new(file)
+        .with_context(|| format!("Failed to read zip archive: {}", archive.display()))
+}
+
+#[cfg(feature = "tar-zst")]
+fn open_tar(
+    archive: &Path,
+    dictionary: Option<&[u8]>,
+) -> Result<tar::Archive<zstd::Decoder<'static, io::BufReader<VolumeReader>>>> {
+    let file = VolumeReader::open(archive)
+        .with_context(|| format!("Failed to open archive: {}", archive.display()))?;
+    let decoder = match dictionary {
+        Some(dictionary) => zstd::Decoder::with_dictionary(io::BufReader::new(file), dictionary),
+        None => zstd::Decoder::new(file),
+    }
+    .with_context(|| format!("Failed to initialize zstd decoder: {}", archive.display()))?;
+    Ok(tar::Archive::new(decoder))
+}
+
+#[cfg(feature = "zip")]
+fn enclosed_zip_name(file: &zip::read::ZipFile<'_>, archive: &Path) -> Result<PathBuf> {
+    file.enclosed_name().ok_or_else(|| {
+        anyhow!(
+            "Archive entry '{}' has an unsafe path: {}",
+            file.name(),
+            archive.display()
+        )
+    })
+}
+
+#[cfg(feature = "tar-zst")]
+fn enclosed_tar_name<R: io::Read>(entry: &tar::Entry<'_, R>, archive: &Path) -> Result<PathBuf> {
+    let name = entry
+        .path()
+        .with_context(|| format!("Invalid archive entry path in: {}", archive.display()))?
+        .into_owned();
+
+    // Only plain relative components are allowed, so entries can never escape the destination
+    let mut enclosed = PathBuf::new();
+    for component in name.components() {
+        match component {
+            Component::Normal(part) => enclosed.push(part),
+            Component::CurDir => {}
+            _ => {
+                return Err(anyhow!(
+                    "Archive entry '{}' has an unsafe path: {}",
+                    name.display(),
+                    archive.display()
+                ));
+            }
+        }
+    }
+
+    Ok(enclosed)
+}
+
+/// Reads the zstd dictionary stored in a backup directory, if the backup used one.
+pub fn read_dictionary(backup_dir: &Path) -> Result<Option<Vec<u8>>> {
+    let path = backup_dir.join(DICTIONARY_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    fs::read(&path)
+        .map(Some)
+        .with_context(|| format!("Failed to read dictionary: {}", path.display()))
+}
+
+/// Trains a zstd dictionary of at most `max_size` bytes from sample files.
+pub fn train_dictionary(samples: &[PathBuf], max_size: usize) -> Result<Vec<u8>> {
+    #[cfg(feature = "tar-zst")]
+    {
+        zstd::dict::from_files(samples, max_size)
+            .with_context(|| format!("Failed to train dictionary from {} files", samples.len()))
+    }
+    #[cfg(not(feature = "tar-zst"))]
+    {
+        Err(ArchiveFormat::TarZst.ensure_available().unwrap_err())
+    }
+}
+
+fn destination(name: &Path, prefix: &Path, dest: &Path) -> Option<PathBuf> {
+    let relative = name.strip_prefix(prefix).ok()?;
+    Some(dest.join(relative))
+}
+
+fn create_dir(path: &Path) -> Result<()> {
+    fs::create_dir_all(path)
+        .with_context(|| format!("Failed to create directory: {}", path.display()))
+}
+
+/// Path of an entry inside an archive, with the file names kept as they are on disk.
+fn entry_name(name: &Path) -> Result<PathBuf> {
+    let mut entry_name = PathBuf::new();
+    for component in name.components() {
+        match component {
+            Component::Normal(part) => entry_name.push(part),
+            Component::CurDir => {}
+            _ => return Err(anyhow!("Invalid archive entry path: {}", name.display())),
+        }
+    }
+    Ok(entry_name)
+}
+
+/// Zip entries are named in UTF-8, so other file names cannot be stored without changing them.
+#[cfg(feature = "zip")]
+fn zip_entry_name(entry_name: &Path) -> Result<String> {
+    let parts = entry_name
+        .iter()
+        .map(|part| part.to_str())
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| {
+            anyhow!(
+                "File name is not valid UTF-8 and cannot be stored in a zip archive, use \
+                 format = \"tar.zst\" or \"dir\": {}",
+                entry_name.display()
+            )
+        })?;
+    Ok(parts.join("/"))
+}
@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::durable;
+use crate::manifest::escaped_path;
+use crate::plan::{ActionKind, FileAction};
+
+/// Append-only record of restores, one JSON object per line, kept in the backup root.
+pub const AUDIT_FILE: &str = "restore-audit.jsonl";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub time: DateTime<Utc>,
+    pub user: Option<String>,
+    pub game: String,
+    /// Id of the snapshot that was restored
+    pub snapshot: Option<String>,
+    #[serde(with = "escaped_path")]
+    pub source: PathBuf,
+    /// Live files that were written, in the order the restore touched them
+    pub files: Vec<AuditFile>,
+    /// Why the restore stopped early, if it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditFile {
+    #[serde(with = "escaped_path")]
+    pub path: PathBuf,
+    /// `create`, `overwrite` or `stash`
+    pub action: String,
+    /// Where the replaced live file was moved to
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "escaped_path::option"
+    )]
+    pub stash: Option<PathBuf>,
+}
+
+impl AuditEntry {
+    pub fn new(
+        game: &str,
+        snapshot: Option<String>,
+        source: &Path,
+        actions: &[FileAction],
+    ) -> Self {
+        let files = actions
+            .iter()
+            .filter_map(|action| {
+                let (name, stash) = match &action.kind {
+                    ActionKind::Create => ("create", None),
+                    ActionKind::Overwrite => ("overwrite", None),
+                    ActionKind::BackupExisting { stash } => ("stash", Some(stash.clone())),
+                    _ => return None,
+                };
+                Some(AuditFile {
+                    path: action.dest.clone(),
+                    action: name.to_string(),
+                    stash,
+                })
+            })
+            .collect();
+
+        Self {
+            time: Utc::now(),
+            user: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .ok(),
+            game: game.to_string(),
+            snapshot,
+            source: source.to_path_buf(),
+            files,
+            error: None,
+        }
+    }
+}
+
+/// Appends an entry to the audit file in the backup root.
+pub fn append(backup_root: &Path, entry: &AuditEntry) -> Result<()> {
+    let audit_path = backup_root.join(AUDIT_FILE);
+    let line = serde_json::to_string(entry).with_context(|| "Failed to serialize audit entry")?;
+
+    durable::append_line(&audit_path, &line)
+        .with_context(|| format!("Failed to write audit log: {}", audit_path.display()))
+}
@@ -0,0 +1,223 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::archive::ArchiveWriter;
+use crate::manifest::{self, IgnoreRules, Manifest, ManifestEntry};
+use crate::mods::ModEntry;
+use crate::{format_size, fserror};
+
+pub(crate) enum BackupTarget<'a> {
+    Directory(&'a Path),
+    Archive(&'a mut ArchiveWriter),
+}
+
+/// The last completed backup of a game, used to skip files that have not changed since.
+pub(crate) struct PreviousBackup {
+    pub dir: PathBuf,
+    pub manifest: Manifest,
+}
+
+/// Writes backed-up files into a target while recording them in a fresh manifest.
+pub(crate) struct BackupWriter<'a> {
+    target: BackupTarget<'a>,
+    previous: Option<PreviousBackup>,
+    /// Whether unchanged files share their inode with the backup they come from
+    link_files: bool,
+    summary: BackupSummary,
+    started: Instant,
+}
+
+pub(crate) struct BackupSummary {
+    pub manifest: Manifest,
+    pub copied: usize,
+    pub unchanged: usize,
+    pub bytes_copied: u64,
+    pub bytes_unchanged: u64,
+    pub elapsed: Duration,
+}
+
+impl<'a> BackupWriter<'a> {
+    pub fn new(target: BackupTarget<'a>, previous: Option<PreviousBackup>) -> Self {
+        Self {
+            target,
+            previous,
+            link_files: true,
+            summary: BackupSummary {
+                manifest: Manifest::new(),
+                copied: 0,
+                unchanged: 0,
+                bytes_copied: 0,
+                bytes_unchanged: 0,
+                elapsed: Duration::ZERO,
+            },
+            started: Instant::now(),
+        }
+    }
+
+    /// Copies unchanged files instead of hard linking them. Protected backups must not share
+    /// files: lifting the protection of an old snapshot would lift it from the current backup
+    /// too, and immutable files cannot be linked at all.
+    pub fn copy_unchanged(&mut self) {
+        self.link_files = false;
+    }
+
+    /// Whether unchanged files were linked rather than written again.
+    pub fn links_files(&self) -> bool {
+        self.link_files
+    }
+
+    pub fn finish(mut self) -> BackupSummary {
+        self.summary.elapsed = self.started.elapsed();
+        self.summary
+    }
+
+    pub fn record_location(&mut self, index: usize, prefix: &Path, rules: IgnoreRules) {
+        self.summary.manifest.set_location(index, prefix);
+        self.summary.manifest.set_ignore_rules(index, rules);
+    }
+
+    pub fn record_mods(&mut self, index: usize, mods: Vec<ModEntry>) {
+        self.summary.manifest.set_mods(index, mods);
+    }
+
+    /// Files added so far, from every save location.
+    pub fn file_count(&self) -> usize {
+        self.summary.manifest.files.len()
+    }
+
+    pub fn add_dir(&mut self, name: &Path) -> Result<()> {
+        match &mut self.target {
+            BackupTarget::Directory(root) => {
+                let dest_dir = root.join(name);
+                log::debug!("Creating directory: {}", dest_dir.display());
+                fs::create_dir_all(&dest_dir)
+                    .with_context(|| format!("Failed to create directory: {}", dest_dir.display()))
+            }
+            BackupTarget::Archive(writer) => writer.add_dir(name),
+        }
+    }
+
+    pub fn add_file(&mut self, source: &Path, name: &Path) -> Result<()> {
+        let metadata = fs::metadata(source)
+            .with_context(|| format!("Failed to read metadata: {}", source.display()))?;
+        let modified = metadata
+            .modified()
+            .with_context(|| format!("Failed to read modification time: {}", source.display()))?;
+
+        if let Some(entry) = self.reuse_unchanged(name, &metadata) {
+            log::debug!(
+                "Unchanged since last backup: {} ({})",
+                source.display(),
+                format_size(metadata.len())
+            );
+            self.summary.manifest.insert(name, entry);
+            self.summary.unchanged += 1;
+            self.summary.bytes_unchanged += metadata.len();
+            return Ok(());
+        }
+
+        let hash = match &mut self.target {
+            BackupTarget::Directory(root) => {
+                let dest_file = root.join(name);
+                if let Some(parent) = dest_file.parent() {
+                    fs::create_dir_all(parent).with_context(|| {
+                        format!("Failed to create directory: {}", parent.display())
+                    })?;
+                }
+                log::debug!(
+                    "Copying file: {} -> {}",
+                    source.display(),
+                    dest_file.display()
+                );
+                manifest::copy_and_hash(source, &dest_file)?
+            }
+            BackupTarget::Archive(writer) => writer.add_file(source, name)?,
+        };
+
+        self.summary.manifest.insert(
+            name,
+            ManifestEntry {
+                size: metadata.len(),
+                modified,
+                hash,
+            },
+        );
+        self.summary.copied += 1;
+        self.summary.bytes_copied += metadata.len();
+        log::debug!(
+            "Backed up {} ({}, {} copied so far)",
+            source.display(),
+            format_size(metadata.len()),
+            format_size(self.summary.bytes_copied)
+        );
+        Ok(())
+    }
+
+    /// Adds a file taken from an existing backup, keeping the manifest entry it was recorded with.
+    pub fn carry_file(&mut self, source: &Path, name: &Path, entry: ManifestEntry) -> Result<()> {
+        let link_files = self.link_files;
+        match &mut self.target {
+            BackupTarget::Directory(root) => {
+                let dest_file = root.join(name);
+                if let Some(parent) = dest_file.parent() {
+                    fs::create_dir_all(parent).with_context(|| {
+                        format!("Failed to create directory: {}", parent.display())
+                    })?;
+                }
+                if !link_files || !hard_link(source, &dest_file) {
+                    fs::copy(source, &dest_file)
+                        .map_err(|e| fserror::copy_error(e, source, &dest_file))?;
+                }
+            }
+            BackupTarget::Archive(writer) => {
+                writer.add_file(source, name)?;
+            }
+        }
+
+        self.summary.unchanged += 1;
+        self.summary.bytes_unchanged += entry.size;
+        self.summary.manifest.insert(name, entry);
+        Ok(())
+    }
+
+    /// Links the previous copy of an unchanged file into the new backup instead of copying it again.
+    fn reuse_unchanged(&self, name: &Path, metadata: &fs::Metadata) -> Option<ManifestEntry> {
+        let BackupTarget::Directory(root) = &self.target else {
+            return None;
+        };
+        let previous = self.previous.as_ref()?;
+        let entry = previous.manifest.get(name)?;
+        if !entry.matches(metadata) {
+            return None;
+        }
+
+        let previous_file = previous.dir.join(name);
+        let dest_file = root.join(name);
+        if let Some(parent) = dest_file.parent() {
+            fs::create_dir_all(parent).ok()?;
+        }
+
+        if !self.link_files || !hard_link(&previous_file, &dest_file) {
+            fs::copy(&previous_file, &dest_file).ok()?;
+        }
+
+        Some(entry.clone())
+    }
+}
+
+/// Links `dest` to `source`, returning whether it did; the caller copies the file otherwise.
+fn hard_link(source: &Path, dest: &Path) -> bool {
+    match fs::hard_link(source, dest) {
+        Ok(()) => true,
+        Err(e) => {
+            log::debug!(
+                "Could not hard link {}, copying instead: {}",
+                source.display(),
+                e
+            );
+            false
+        }
+    }
+}
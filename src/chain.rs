@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::GameBackup;
+use crate::durable;
+use crate::manifest::{ChainLink, Manifest};
+use crate::snapshot::snapshot_id;
+
+/// Append-only record of the snapshots of a game deleted by cartridge itself, kept in its
+/// snapshots directory, so the gaps they leave in the chain are accounted for.
+pub const REMOVED_FILE: &str = "removed.jsonl";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Removal {
+    id: String,
+    hash: String,
+    #[serde(default)]
+    previous: Option<ChainLink>,
+    removed_at: DateTime<Utc>,
+}
+
+/// A break in the chain of snapshots of a game.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "problem", rename_all = "kebab-case")]
+pub enum ChainProblem {
+    /// The snapshot links to one that is gone without having been deleted by cartridge
+    Missing { snapshot: String, missing: String },
+    /// The snapshot links to one whose manifest has changed since
+    Replaced { snapshot: String, replaced: String },
+    /// The snapshot is not linked from any newer one
+    Unlinked { snapshot: String },
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChainReport {
+    pub snapshots: usize,
+    /// Snapshots whose link to the next older one checked out
+    pub linked: usize,
+    pub problems: Vec<ChainProblem>,
+}
+
+impl ChainReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+impl GameBackup {
+    /// Link to a backup, recorded in the backup that replaces it.
+    pub(crate) fn chain_link(&self, backup_dir: &Path) -> Option<ChainLink> {
+        let manifest = Manifest::load(backup_dir).ok().flatten()?;
+        Some(ChainLink {
+            id: snapshot_id(&manifest),
+            hash: manifest.chain_hash(),
+        })
+    }
+
+    /// Records a backup that is deleted on purpose. Failing to do so only makes `verify --chain`
+    /// report the gap, so it is only reported.
+    pub(crate) fn record_removal(&self, game_name: &str, backup_dir: &Path) {
+        let Some(manifest) = Manifest::load(backup_dir).ok().flatten() else {
+            return;
+        };
+        let removal = Removal {
+            id: snapshot_id(&manifest),
+            hash: manifest.chain_hash(),
+            previous: manifest.previous,
+            removed_at: Utc::now(),
+        };
+        if let Err(e) = append(&self.snapshots_dir(game_name), &removal) {
+            log::error!(
+                "Failed to record deletion of snapshot {} of '{}': {:#}",
+                removal.id,
+                game_name,
+                e
+            );
+        }
+    }
+
+    /// Follows the links from each snapshot of a game to the next older one, across snapshots
+    /// deleted by cartridge, to find snapshots that were deleted or replaced behind its back.
+    pub fn verify_chain(&self, game_name: &str) -> Result<ChainReport> {
+        log::info!("Verifying snapshot chain for game: {}", game_name);
+
+        let game = self.find_game(game_name)?;
+        let snapshots = self.list_snapshots(&game.name)?;
+        let manifests: Vec<Option<Manifest>> = snapshots
+            .iter()
+            .map(|snapshot| Manifest::load(&snapshot.path).ok().flatten())
+            .collect();
+        let removed = load(&self.snapshots_dir(&game.name))?;
+
+        let mut report = ChainReport {
+            snapshots: snapshots.len(),
+            linked: 0,
+            problems: Vec::new(),
+        };
+        // The newest snapshot has nothing to be linked from
+        let mut reached = true;
+        for (i, snapshot) in snapshots.iter().enumerate() {
+            if !reached {
+                report.problems.push(ChainProblem::Unlinked {
+                    snapshot: snapshot.id.clone(),
+                });
+            }
+            let older = snapshots.get(i + 1);
+            let Some(mut link) = manifests[i]
+                .as_ref()
+                .and_then(|manifest| manifest.previous.clone())
+            else {
+                // Backups made before links were recorded start the chain
+                log::debug!("Snapshot {} does not link to an older one", snapshot.id);
+                reached = true;
+                continue;
+            };
+
+            reached = loop {
+                if let Some(older) = older
+                    && link.id == older.id
+                {
+                    let hash = manifests[i + 1].as_ref().map(Manifest::chain_hash);
+                    if hash.as_deref() == Some(link.hash.as_str()) {
+                        report.linked += 1;
+                    } else {
+                        report.problems.push(ChainProblem::Replaced {
+                            snapshot: snapshot.id.clone(),
+                            replaced: older.id.clone(),
+                        });
+                    }
+                    break true;
+                }
+                match removed.get(&link.id) {
+                    Some(removal) if removal.hash == link.hash => match &removal.previous {
+                        Some(previous) => link = previous.clone(),
+                        None => break false,
+                    },
+                    _ => {
+                        report.problems.push(ChainProblem::Missing {
+                            snapshot: snapshot.id.clone(),
+                            missing: link.id.clone(),
+                        });
+                        break true;
+                    }
+                }
+            };
+        }
+
+        log::info!(
+            "Verified chain of {} snapshots for '{}': {} linked, {} problems",
+            report.snapshots,
+            game.name,
+            report.linked,
+            report.problems.len()
+        );
+        Ok(report)
+    }
+}
+
+fn append(snapshots_dir: &Path, removal: &Removal) -> Result<()> {
+    fs::create_dir_all(snapshots_dir).with_context(|| {
+        format!(
+            "Failed to create snapshots directory: {}",
+            snapshots_dir.display()
+        )
+    })?;
+    let path = snapshots_dir.join(REMOVED_FILE);
+    let line = serde_json::to_string(removal).with_context(|| "Failed to serialize removal")?;
+
+    durable::append_line(&path, &line)
+        .with_context(|| format!("Failed to write removal log: {}", path.display()))
+}
+
+/// Reads the removal log by snapshot id, skipping lines that cannot be parsed.
+fn load(snapshots_dir: &Path) -> Result<HashMap<String, Removal>> {
+    let path = snapshots_dir.join(REMOVED_FILE);
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read removal log: {}", path.display()))?;
+
+    let mut removals = HashMap::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Removal>(line) {
+            Ok(removal) => {
+                removals.insert(removal.id.clone(), removal);
+            }
+            Err(e) => log::warn!(
+                "Skipping unreadable line {} of {}: {}",
+                i + 1,
+                path.display(),
+                e
+            ),
+        }
+    }
+    Ok(removals)
+}
@@ -0,0 +1,56 @@
+use std::path::Path;
+
+use crate::{Game, GameBackup};
+
+/// Lowercase folder names that sync clients keep their files in, with the service's name.
+const SYNC_FOLDERS: &[(&str, &str)] = &[
+    ("dropbox", "Dropbox"),
+    ("google drive", "Google Drive"),
+    ("googledrive", "Google Drive"),
+    ("my drive", "Google Drive"),
+    ("icloud drive", "iCloud Drive"),
+    ("mobile documents", "iCloud Drive"),
+    ("cloudstorage", "a cloud storage provider"),
+];
+
+/// Name of the cloud service whose sync folder `path` is in, guessed from its directory
+/// names.
+pub(crate) fn sync_service(path: &Path) -> Option<&'static str> {
+    path.iter().find_map(|component| {
+        let name = component.to_string_lossy().to_lowercase();
+        // Business accounts sync to `OneDrive - <organization>`
+        if name.starts_with("onedrive") {
+            return Some("OneDrive");
+        }
+        SYNC_FOLDERS
+            .iter()
+            .find(|(folder, _)| name == *folder)
+            .map(|(_, service)| *service)
+    })
+}
+
+impl GameBackup {
+    /// Warns about save locations of the game inside a cloud sync folder, unless the game
+    /// has `cloud_sync_ok`. A restore there races the sync client, which then keeps both
+    /// versions as conflicted copies.
+    pub(crate) fn warn_cloud_synced(&self, game: &Game) {
+        if game.cloud_sync_ok {
+            return;
+        }
+        for save_location in &game.saves {
+            let Ok(Some(path)) = self.save_path(save_location) else {
+                continue;
+            };
+            if let Some(service) = sync_service(&path) {
+                log::warn!(
+                    "Saves of '{}' are in a folder synced by {}: {}. Pause syncing while \
+                     restoring to avoid conflicted copies, or set `cloud_sync_ok = true` for \
+                     the game to silence this",
+                    game.name,
+                    service,
+                    path.display()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::doctor::LocationStatus;
+use crate::manifest::Manifest;
+use crate::{GameBackup, real_path};
+
+/// Result of `cartridge audit`: what backing up every game would find, without writing
+/// anything.
+#[derive(Debug, Serialize)]
+pub struct ConfigAudit {
+    pub games: Vec<GameEstimate>,
+    pub findings: Vec<Finding>,
+}
+
+impl ConfigAudit {
+    /// Findings that would make a backup fail or copy the wrong data.
+    pub fn errors(&self) -> usize {
+        self.findings.iter().filter(|f| f.is_error()).count()
+    }
+}
+
+/// What a backup of a game would read right now.
+#[derive(Debug, Serialize)]
+pub struct GameEstimate {
+    pub game: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// Newest modification time of its save files
+    pub last_modified: Option<DateTime<Utc>>,
+    /// When its current backup was taken
+    pub last_backup: Option<DateTime<Utc>>,
+}
+
+/// A problem found by `cartridge audit`. Locations are numbered from 1, as in the config.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Finding {
+    /// The save path could not be expanded, e.g. because of an undefined variable
+    InvalidPath {
+        game: String,
+        location: usize,
+        error: String,
+    },
+    MissingPath {
+        game: String,
+        location: usize,
+        path: PathBuf,
+    },
+    CloudSynced {
+        game: String,
+        location: usize,
+        path: PathBuf,
+        service: &'static str,
+    },
+    /// Two save locations hold each other's files, so they are backed up twice and restores
+    /// of one overwrite the other
+    OverlappingSaves {
+        game: String,
+        location: usize,
+        other_game: String,
+        other_location: usize,
+        path: PathBuf,
+    },
+    /// The save path holds or is inside the backup root or cold storage
+    OverlapsBackups {
+        game: String,
+        location: usize,
+        path: PathBuf,
+        backups: PathBuf,
+    },
+    /// More files than the backup accepts, see `max_files`
+    TooManyFiles {
+        game: String,
+        files: usize,
+        limit: usize,
+    },
+    /// The saves could not be read
+    Unreadable {
+        game: String,
+        error: String,
+    },
+    NeverBackedUp {
+        game: String,
+    },
+    /// Saves changed after the current backup was taken
+    OutdatedBackup {
+        game: String,
+        backed_up_at: DateTime<Utc>,
+        modified_at: DateTime<Utc>,
+    },
+    /// Saves have not changed for longer than `stale_after`, or are gone
+    Stale {
+        game: String,
+        last_modified: Option<DateTime<Utc>>,
+    },
+}
+
+impl Finding {
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Finding::InvalidPath { .. }
+                | Finding::OverlapsBackups { .. }
+                | Finding::TooManyFiles { .. }
+                | Finding::Unreadable { .. }
+        )
+    }
+
+    pub fn game(&self) -> &str {
+        match self {
+            Finding::InvalidPath { game, .. }
+            | Finding::MissingPath { game, .. }
+            | Finding::CloudSynced { game, .. }
+            | Finding::OverlappingSaves { game, .. }
+            | Finding::OverlapsBackups { game, .. }
+            | Finding::TooManyFiles { game, .. }
+            | Finding::Unreadable { game, .. }
+            | Finding::NeverBackedUp { game }
+            | Finding::OutdatedBackup { game, .. }
+            | Finding::Stale { game, .. } => game,
+        }
+    }
+}
+
+impl GameBackup {
+    /// Checks every enabled, unarchived game the way a backup would see it: save paths as in
+    /// `doctor`, the files and bytes it would copy, save paths that overlap each other or the
+    /// backups, and backups that are missing, older than the saves or of abandoned games.
+    /// Nothing is written.
+    pub fn audit_config(&self) -> ConfigAudit {
+        let games: Vec<_> = self
+            .list_games()
+            .into_iter()
+            .filter(|game| !game.archived)
+            .collect();
+        let mut findings = Vec::new();
+
+        // Resolved save paths of every location, for the overlap checks
+        let mut paths: Vec<(&str, usize, bool, PathBuf)> = Vec::new();
+        for check in self.doctor() {
+            let Some(game) = games.iter().find(|game| game.name == check.name) else {
+                continue;
+            };
+            for (i, location) in check.locations.into_iter().enumerate() {
+                let number = i + 1;
+                let path = match (location.status, location.path) {
+                    (LocationStatus::Invalid { error }, _) => {
+                        findings.push(Finding::InvalidPath {
+                            game: game.name.clone(),
+                            location: number,
+                            error,
+                        });
+                        continue;
+                    }
+                    (LocationStatus::Missing, Some(path)) => {
+                        findings.push(Finding::MissingPath {
+                            game: game.name.clone(),
+                            location: number,
+                            path: path.clone(),
+                        });
+                        path
+                    }
+                    (_, Some(path)) => path,
+                    (_, None) => continue,
+                };
+                if let Some(service) = location.synced_by {
+                    findings.push(Finding::CloudSynced {
+                        game: game.name.clone(),
+                        location: number,
+                        path: path.clone(),
+                        service,
+                    });
+                }
+                if let Some(backups) = self.overlapping_backups(&path) {
+                    findings.push(Finding::OverlapsBackups {
+                        game: game.name.clone(),
+                        location: number,
+                        path: path.clone(),
+                        backups,
+                    });
+                }
+                let has_patterns = !game.saves[i].files.is_empty();
+                paths.push((&game.name, number, has_patterns, real_path(&path)));
+            }
+        }
+
+        for (i, (game, location, has_patterns, path)) in paths.iter().enumerate() {
+            for (other_game, other_location, other_has_patterns, other_path) in &paths[i + 1..] {
+                // Locations picking files by pattern often share a directory on purpose
+                if *has_patterns && *other_has_patterns {
+                    continue;
+                }
+                if path.starts_with(other_path) || other_path.starts_with(path) {
+                    findings.push(Finding::OverlappingSaves {
+                        game: game.to_string(),
+                        location: *location,
+                        other_game: other_game.to_string(),
+                        other_location: *other_location,
+                        path: path.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut estimates = Vec::new();
+        for game in games {
+            let activity = match self.save_activity(game) {
+                Ok(activity) => activity,
+                Err(e) => {
+                    // Unexpandable paths were reported above
+                    if findings.iter().any(|finding| {
+                        matches!(finding, Finding::InvalidPath { .. })
+                            && finding.game() == game.name
+                    }) {
+                        continue;
+                    }
+                    findings.push(Finding::Unreadable {
+                        game: game.name.clone(),
+                        error: format!("{:#}", e),
+                    });
+                    continue;
+                }
+            };
+            let limit = self.game_max_files(game);
+            if activity.files > limit {
+                findings.push(Finding::TooManyFiles {
+                    game: game.name.clone(),
+                    files: activity.files,
+                    limit,
+                });
+            }
+
+            let last_backup = Manifest::load(&self.backup_root.join(&game.name))
+                .unwrap_or_else(|e| {
+                    log::warn!("{:#}", e);
+                    None
+                })
+                .map(|manifest| manifest.created_at);
+            match (last_backup, activity.last_modified) {
+                (None, _) => findings.push(Finding::NeverBackedUp {
+                    game: game.name.clone(),
+                }),
+                (Some(backed_up_at), Some(modified_at)) if modified_at > backed_up_at => findings
+                    .push(Finding::OutdatedBackup {
+                        game: game.name.clone(),
+                        backed_up_at,
+                        modified_at,
+                    }),
+                _ => {}
+            }
+            if self.is_stale(&activity) {
+                findings.push(Finding::Stale {
+                    game: game.name.clone(),
+                    last_modified: activity.last_modified,
+                });
+            }
+
+            estimates.push(GameEstimate {
+                game: game.name.clone(),
+                files: activity.files,
+                bytes: activity.bytes,
+                last_modified: activity.last_modified,
+                last_backup,
+            });
+        }
+
+        findings.sort_by(|a, b| a.game().cmp(b.game()));
+        ConfigAudit {
+            games: estimates,
+            findings,
+        }
+    }
+}
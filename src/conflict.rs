@@ -0,0 +1,253 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::durable;
+use crate::manifest::{self, Manifest, escaped_path};
+use crate::plan::{ActionKind, ConflictPolicy, FileAction, RestoreOptions};
+use crate::snapshot::snapshot_id;
+use crate::{GameBackup, SaveKind};
+
+/// Live files that restores left in place because they differed from the backup, one
+/// `<game>.json` per game, kept in the backup root until they are resolved.
+pub const CONFLICTS_DIR: &str = ".conflicts";
+
+/// A live file a restore skipped, and the backup copy it would have been replaced with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conflict {
+    /// Live file that was kept
+    #[serde(with = "escaped_path")]
+    pub path: PathBuf,
+    /// Path of the file inside its save location
+    #[serde(with = "escaped_path")]
+    pub relative: PathBuf,
+    pub kind: SaveKind,
+    /// Snapshot the backup copy belongs to
+    pub snapshot: String,
+    pub recorded_at: DateTime<Utc>,
+    pub live: ConflictSide,
+    pub backup: ConflictSide,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictSide {
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+    pub hash: String,
+}
+
+impl Conflict {
+    /// Whether the live file was changed since the conflict was recorded.
+    pub fn live_changed(&self) -> bool {
+        manifest::hash_file(&self.path).map_or(true, |hash| hash != self.live.hash)
+    }
+}
+
+/// How to settle a conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Keep the live file and forget the conflict
+    KeepLive,
+    /// Replace the live file with the backup copy that was skipped
+    UseBackup,
+}
+
+impl GameBackup {
+    /// Adds the files of a save location that a restore skipped to the game's conflict
+    /// journal. Failing to do so does not undo the restore, so it is only reported.
+    pub(crate) fn record_conflicts(
+        &self,
+        game_name: &str,
+        location: &Path,
+        kind: SaveKind,
+        prefix: &Path,
+        manifest: Option<&Manifest>,
+        actions: &[FileAction],
+    ) {
+        let skipped: Vec<&FileAction> = actions
+            .iter()
+            .filter(|action| action.kind == ActionKind::Skip)
+            .collect();
+        if skipped.is_empty() {
+            return;
+        }
+        let Some(manifest) = manifest else {
+            log::warn!(
+                "Backup of '{}' has no manifest, not recording the {} skipped file(s)",
+                game_name,
+                skipped.len()
+            );
+            return;
+        };
+
+        let result = self.read_conflicts(game_name).and_then(|mut conflicts| {
+            for action in skipped {
+                let Ok(relative) = action.dest.strip_prefix(location) else {
+                    continue;
+                };
+                let Some(entry) = manifest.get(&prefix.join(relative)) else {
+                    log::debug!("No manifest entry for: {}", relative.display());
+                    continue;
+                };
+                let conflict = Conflict {
+                    path: action.dest.clone(),
+                    relative: relative.to_path_buf(),
+                    kind,
+                    snapshot: snapshot_id(manifest),
+                    recorded_at: Utc::now(),
+                    live: ConflictSide {
+                        size: action.dest_state.as_ref().map_or(0, |state| state.size),
+                        modified: action
+                            .dest_state
+                            .as_ref()
+                            .and_then(|state| state.modified)
+                            .map(DateTime::from),
+                        hash: manifest::hash_file(&action.dest)?,
+                    },
+                    backup: ConflictSide {
+                        size: entry.size,
+                        modified: Some(entry.modified.into()),
+                        hash: entry.hash.clone(),
+                    },
+                };
+                conflicts.retain(|known| known.path != conflict.path);
+                conflicts.push(conflict);
+            }
+            self.write_conflicts(game_name, &conflicts)
+        });
+        match result {
+            Ok(()) => log::info!(
+                "Recorded skipped files of '{}', review them with `cartridge conflicts {}`",
+                game_name,
+                game_name
+            ),
+            Err(e) => log::error!(
+                "Failed to record skipped files of '{}' in the conflict journal: {:#}",
+                game_name,
+                e
+            ),
+        }
+    }
+
+    /// Conflicts of a game that are still open. Those whose live file was since replaced by
+    /// the backup copy, or deleted, are dropped from the journal.
+    pub fn list_conflicts(&self, game_name: &str) -> Result<Vec<Conflict>> {
+        let game = self.find_game(game_name)?;
+        let conflicts = self.read_conflicts(&game.name)?;
+        let count = conflicts.len();
+        let open: Vec<Conflict> = conflicts
+            .into_iter()
+            .filter(|conflict| {
+                conflict.path.exists()
+                    && manifest::hash_file(&conflict.path)
+                        .map_or(true, |hash| hash != conflict.backup.hash)
+            })
+            .collect();
+        if open.len() != count {
+            self.write_conflicts(&game.name, &open)?;
+        }
+        Ok(open)
+    }
+
+    /// Settles the conflict of a live file, given by its full path or a trailing part of it.
+    pub fn resolve_conflict(
+        &self,
+        game_name: &str,
+        path: &Path,
+        resolution: Resolution,
+    ) -> Result<Conflict> {
+        let game = self.find_game(game_name)?;
+        let mut conflicts = self.list_conflicts(&game.name)?;
+        let matching: Vec<usize> = conflicts
+            .iter()
+            .enumerate()
+            .filter(|(_, conflict)| conflict.path == path || conflict.path.ends_with(path))
+            .map(|(i, _)| i)
+            .collect();
+        let index = match matching.as_slice() {
+            [index] => *index,
+            [] => {
+                return Err(anyhow!(
+                    "No conflict recorded for '{}' in '{}'. Run `cartridge conflicts {}` to list them",
+                    path.display(),
+                    game.name,
+                    game.name
+                ));
+            }
+            _ => {
+                return Err(anyhow!(
+                    "'{}' matches {} conflicts of '{}', give more of its path",
+                    path.display(),
+                    matching.len(),
+                    game.name
+                ));
+            }
+        };
+
+        let conflict = conflicts[index].clone();
+        if resolution == Resolution::UseBackup {
+            let options = RestoreOptions {
+                on_conflict: ConflictPolicy::Overwrite,
+                files: vec![glob::Pattern::escape(&manifest::entry_key(
+                    &conflict.relative,
+                ))],
+                snapshot: Some(conflict.snapshot.clone()),
+                kind: Some(conflict.kind),
+                ..RestoreOptions::default()
+            };
+            let report = self.restore_game(&game.name, &options)?;
+            let restored = report.actions.iter().any(|action| {
+                action.dest == conflict.path
+                    && matches!(action.kind, ActionKind::Overwrite | ActionKind::Create)
+            });
+            if !restored {
+                return Err(anyhow!(
+                    "Snapshot {} no longer holds {}",
+                    conflict.snapshot,
+                    conflict.path.display()
+                ));
+            }
+        }
+        conflicts.remove(index);
+        self.write_conflicts(&game.name, &conflicts)?;
+        Ok(conflict)
+    }
+
+    fn conflicts_path(&self, game_name: &str) -> PathBuf {
+        self.backup_root
+            .join(CONFLICTS_DIR)
+            .join(format!("{}.json", game_name))
+    }
+
+    fn read_conflicts(&self, game_name: &str) -> Result<Vec<Conflict>> {
+        let path = self.conflicts_path(game_name);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read conflict journal: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse conflict journal: {}", path.display()))
+    }
+
+    fn write_conflicts(&self, game_name: &str, conflicts: &[Conflict]) -> Result<()> {
+        let path = self.conflicts_path(game_name);
+        if conflicts.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path).with_context(|| {
+                    format!("Failed to remove conflict journal: {}", path.display())
+                })?;
+            }
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+        }
+        let content = serde_json::to_string_pretty(conflicts)?;
+        durable::write_atomic(&path, content)
+            .with_context(|| format!("Failed to write conflict journal: {}", path.display()))
+    }
+}
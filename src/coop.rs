@@ -0,0 +1,363 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::archive::{ArchiveFormat, ArchiveWriter};
+use crate::delta::{archive_key_path, verify_extracted};
+use crate::durable;
+use crate::manifest;
+use crate::plan::{ConflictPolicy, FileAction, RestoreOptions};
+use crate::{Game, GameBackup, STAGING_DIR, SaveItem, process};
+
+/// Last version of each game's saves that was shared or received, as `<game>.json` in the
+/// backup root. It is the common ancestor that tells whether both players changed the saves.
+pub const SHARES_DIR: &str = ".shares";
+/// Description of the shared saves, stored next to the files in the archive.
+pub const SHARE_FILE: &str = "share.json";
+
+/// A version of a game's live saves passed between players with `share` and `receive`.
+/// Paths are `<location>/<path>`, as in `export-diff` archives.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedSaves {
+    pub game: String,
+    pub id: String,
+    /// Version the sender last shared or received before this one
+    pub parent: Option<String>,
+    pub shared_at: DateTime<Utc>,
+    /// Hashes of the files by path
+    pub files: BTreeMap<String, String>,
+}
+
+/// How received saves relate to the live ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiveOutcome {
+    /// The live saves are already the received ones
+    UpToDate,
+    /// Only the other player changed the saves since the last exchange
+    FastForward,
+    /// Both players changed the saves, the received ones replaced the local changes
+    Diverged,
+}
+
+#[derive(Debug)]
+pub struct ReceivedSaves {
+    pub shared: SharedSaves,
+    pub outcome: ReceiveOutcome,
+    /// Received files copied over the live ones
+    pub actions: Vec<FileAction>,
+    /// Live files deleted because the received saves no longer have them
+    pub removed: Vec<PathBuf>,
+}
+
+impl GameBackup {
+    /// Writes the live saves of a game into an archive for another player to `receive`, and
+    /// remembers them as the last version exchanged.
+    pub fn share_saves(&self, game_name: &str, output: &Path) -> Result<SharedSaves> {
+        let format = ArchiveFormat::from_path(output).ok_or_else(|| {
+            anyhow!(
+                "Unsupported export file extension: {} (expected .zip or .tar.zst)",
+                output.display()
+            )
+        })?;
+        let game = self.find_game(game_name)?;
+        let running = process::running(&game.processes);
+        if !running.is_empty() {
+            log::warn!(
+                "Game '{}' is running ({}), the shared saves may be incomplete",
+                game.name,
+                running.join(", ")
+            );
+        }
+
+        let live = self.live_files(game)?;
+        if live.is_empty() {
+            return Err(anyhow!("'{}' has no saves to share", game.name));
+        }
+        let files = hash_live_files(&live)?;
+        let last = self.read_share_state(&game.name)?;
+        let shared = match last {
+            // Sharing the same saves again keeps their version
+            Some(last) if last.files == files => last,
+            last => {
+                let shared_at = Utc::now();
+                SharedSaves {
+                    game: game.name.clone(),
+                    id: version_id(shared_at, &files),
+                    parent: last.map(|last| last.id),
+                    shared_at,
+                    files,
+                }
+            }
+        };
+
+        let staging = self.backup_root.join(STAGING_DIR);
+        fs::create_dir_all(&staging).with_context(|| {
+            format!("Failed to create staging directory: {}", staging.display())
+        })?;
+        let share_file = staging.join(format!("{}.{}", game.name, SHARE_FILE));
+        let game_dir = PathBuf::from(&game.name);
+        let result = fs::write(&share_file, serde_json::to_string_pretty(&shared)?)
+            .with_context(|| format!("Failed to write file: {}", share_file.display()))
+            .and_then(|()| {
+                let mut writer = ArchiveWriter::create(output, format, None, None)?;
+                for (path, source) in &live {
+                    writer.add_file(source, &game_dir.join(manifest::key_path(path)))?;
+                }
+                writer.add_file(&share_file, &game_dir.join(SHARE_FILE))?;
+                writer.finish()
+            });
+        let _ = fs::remove_file(&share_file);
+        if let Err(e) = result {
+            let _ = fs::remove_file(output);
+            return Err(e);
+        }
+
+        self.write_share_state(&shared)?;
+        log::info!(
+            "Shared version {} of '{}' ({} files) to: {}",
+            shared.id,
+            shared.game,
+            shared.files.len(),
+            output.display()
+        );
+        Ok(shared)
+    }
+
+    /// Replaces the live saves of a game with ones another player shared. When both players
+    /// changed the saves since they last exchanged them, nothing is written unless `force` is
+    /// set. The live saves are backed up before they are replaced.
+    pub fn receive_saves(&self, input: &Path, force: bool, dry_run: bool) -> Result<ReceivedSaves> {
+        log::info!("Receiving shared saves from: {}", input.display());
+        self.with_extracted_archive(input, SHARE_FILE, "share", |staging| {
+            self.apply_shared_saves(staging, force, dry_run)
+        })
+    }
+
+    fn apply_shared_saves(
+        &self,
+        staging: &Path,
+        force: bool,
+        dry_run: bool,
+    ) -> Result<ReceivedSaves> {
+        let share_file = staging.join(SHARE_FILE);
+        let content = fs::read_to_string(&share_file)
+            .with_context(|| format!("Failed to read file: {}", share_file.display()))?;
+        let shared: SharedSaves = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse: {}", share_file.display()))?;
+        let game = self.find_game(&shared.game)?;
+        for (path, hash) in &shared.files {
+            self.delta_live_path(game, path)?;
+            verify_extracted(staging, path, hash)?;
+        }
+
+        let running = process::running(&game.processes);
+        if !running.is_empty() {
+            if force || dry_run {
+                log::warn!(
+                    "Game '{}' is running ({}), received saves may be overwritten or corrupted",
+                    game.name,
+                    running.join(", ")
+                );
+            } else {
+                return Err(anyhow!(
+                    "Game '{}' is running ({}). Close it before receiving saves, or pass --force",
+                    game.name,
+                    running.join(", ")
+                ));
+            }
+        }
+
+        let live = self.live_files(game)?;
+        let live_hashes = hash_live_files(&live)?;
+        let last = self.read_share_state(&game.name)?;
+        let mut received = ReceivedSaves {
+            outcome: ReceiveOutcome::UpToDate,
+            actions: Vec::new(),
+            removed: Vec::new(),
+            shared,
+        };
+        if live_hashes == received.shared.files {
+            log::info!(
+                "Saves of '{}' are already version {}",
+                game.name,
+                received.shared.id
+            );
+            if !dry_run {
+                self.write_share_state(&received.shared)?;
+            }
+            return Ok(received);
+        }
+
+        // Local files changed since the last exchange, all of them when there was none
+        let changed: BTreeSet<&str> = match &last {
+            Some(last) => live_hashes
+                .keys()
+                .chain(last.files.keys())
+                .filter(|path| live_hashes.get(*path) != last.files.get(*path))
+                .map(String::as_str)
+                .collect(),
+            None => live_hashes.keys().map(String::as_str).collect(),
+        };
+        // Received saves that do not build on the last exchange lack the changes made since
+        let unrelated = last.as_ref().is_some_and(|last| {
+            received.shared.parent.as_ref() != Some(&last.id) && received.shared.id != last.id
+        });
+        if changed.is_empty() && !unrelated {
+            received.outcome = ReceiveOutcome::FastForward;
+        } else {
+            received.outcome = ReceiveOutcome::Diverged;
+            let base = last
+                .as_ref()
+                .map_or_else(|| "the first exchange".to_string(), |last| last.id.clone());
+            let reason = if changed.is_empty() {
+                format!(
+                    "version {} was not made from {}, which you have",
+                    received.shared.id, base
+                )
+            } else {
+                format!(
+                    "your saves changed since {}: {}",
+                    base,
+                    changed.into_iter().collect::<Vec<_>>().join(", ")
+                )
+            };
+            if !force {
+                return Err(anyhow!(
+                    "Both players changed the saves of '{}', {}. Pass --force to take the \
+                     received saves anyway (yours are backed up first), or `share` yours instead",
+                    game.name,
+                    reason
+                ));
+            }
+            log::warn!(
+                "Both players changed the saves of '{}', taking the received ones: {}",
+                game.name,
+                reason
+            );
+        }
+
+        if !dry_run && !live.is_empty() {
+            log::info!(
+                "Backing up the saves of '{}' before replacing them",
+                game.name
+            );
+            self.backup_game(&game.name, None)?;
+        }
+        let options = RestoreOptions {
+            on_conflict: ConflictPolicy::Overwrite,
+            dry_run,
+            ..RestoreOptions::default()
+        };
+        for path in received.shared.files.keys() {
+            let dest = self.delta_live_path(game, path)?;
+            if !dry_run && let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            }
+            received.actions.push(self.restore_file(
+                &staging.join(archive_key_path(path)?),
+                &dest,
+                &options,
+            )?);
+        }
+        for (path, source) in &live {
+            if received.shared.files.contains_key(path) {
+                continue;
+            }
+            if !dry_run {
+                log::debug!("Removing file: {}", source.display());
+                fs::remove_file(source)
+                    .with_context(|| format!("Failed to remove file: {}", source.display()))?;
+            }
+            received.removed.push(source.clone());
+        }
+
+        if !dry_run {
+            self.write_share_state(&received.shared)?;
+            log::info!("Received version {} of '{}'", received.shared.id, game.name);
+        }
+        Ok(received)
+    }
+
+    /// Live save files of a game by `<location>/<path>`.
+    fn live_files(&self, game: &Game) -> Result<BTreeMap<String, PathBuf>> {
+        let mut files = BTreeMap::new();
+        for (i, save_location) in game.saves.iter().enumerate() {
+            let Some(save_path) = self.save_path(save_location)? else {
+                continue;
+            };
+            if !save_path.exists() {
+                continue;
+            }
+            let rules = self.ignore_rules(i, save_location, None);
+            self.walk_save_location(
+                save_location,
+                &save_path,
+                Path::new(""),
+                rules,
+                &mut |item| {
+                    if let SaveItem::File { source, name } = item {
+                        files.insert(
+                            format!("{}/{}", i, manifest::entry_key(name)),
+                            source.to_path_buf(),
+                        );
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        Ok(files)
+    }
+
+    fn share_state_path(&self, game_name: &str) -> PathBuf {
+        self.backup_root
+            .join(SHARES_DIR)
+            .join(format!("{}.json", game_name))
+    }
+
+    fn read_share_state(&self, game_name: &str) -> Result<Option<SharedSaves>> {
+        let path = self.share_state_path(game_name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read share state: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .with_context(|| format!("Failed to parse share state: {}", path.display()))
+    }
+
+    fn write_share_state(&self, shared: &SharedSaves) -> Result<()> {
+        let path = self.share_state_path(&shared.game);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+        }
+        durable::write_atomic(&path, serde_json::to_string_pretty(shared)?)
+            .with_context(|| format!("Failed to write share state: {}", path.display()))
+    }
+}
+
+fn hash_live_files(files: &BTreeMap<String, PathBuf>) -> Result<BTreeMap<String, String>> {
+    files
+        .iter()
+        .map(|(path, source)| Ok((path.clone(), manifest::hash_file(source)?)))
+        .collect()
+}
+
+/// Version of shared saves: when they were shared, and a short hash of their content.
+fn version_id(shared_at: DateTime<Utc>, files: &BTreeMap<String, String>) -> String {
+    let mut hasher = blake3::Hasher::new();
+    for (path, hash) in files {
+        hasher.update(path.as_bytes());
+        hasher.update(hash.as_bytes());
+    }
+    format!(
+        "{}-{}",
+        shared_at.format("%Y%m%dT%H%M%SZ"),
+        &hasher.finalize().to_hex()[..8]
+    )
+}
@@ -0,0 +1,340 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Local, NaiveTime, TimeDelta, Utc};
+use serde::{Deserialize, Deserializer, Serialize, de};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::durable;
+use crate::period::Period;
+use crate::queue::QueuedBackup;
+use crate::status::RunState;
+use crate::verify::{self, VerifyReport};
+use crate::{GameBackup, process};
+
+/// When each snapshot was last verified by the daemon, in the backup root.
+pub const VERIFY_STATE_FILE: &str = "verify-state.json";
+/// How often the daemon checks whether it may verify the next snapshot, and whether the
+/// saves of watched games changed.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+/// How often the daemon looks for backups queued with `cartridge queue backup`.
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_EVERY: TimeDelta = TimeDelta::days(30);
+const DEFAULT_PAUSE: TimeDelta = TimeDelta::minutes(1);
+
+/// `[idle_verify]`: how `cartridge daemon` spreads verifying old snapshots over idle time.
+#[derive(Debug, Deserialize)]
+pub struct IdleVerify {
+    /// Local time of day snapshots are verified in, e.g. `"02:00-06:00"`
+    pub window: IdleWindow,
+    /// How long a verified snapshot is trusted before it is verified again (default 30 days)
+    pub every: Option<Period>,
+    /// Rest between two snapshots, so verifying never keeps the disk busy (default 1 minute)
+    pub pause: Option<Period>,
+}
+
+impl IdleVerify {
+    fn every(&self) -> TimeDelta {
+        self.every.map(|period| period.0).unwrap_or(DEFAULT_EVERY)
+    }
+
+    fn pause(&self) -> Duration {
+        self.pause
+            .map(|period| period.0)
+            .unwrap_or(DEFAULT_PAUSE)
+            .to_std()
+            .unwrap_or(POLL_INTERVAL)
+    }
+}
+
+/// A time of day window such as `"02:00-06:00"`. It wraps past midnight when it ends before
+/// it starts, and covers the whole day when both are the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl IdleWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else if self.start > self.end {
+            time >= self.start || time < self.end
+        } else {
+            true
+        }
+    }
+}
+
+impl FromStr for IdleWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid time window '{}', expected two times of day such as \"02:00-06:00\"",
+                s
+            )
+        };
+        let (start, end) = s.split_once('-').ok_or_else(invalid)?;
+        let time =
+            |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| invalid());
+        Ok(Self {
+            start: time(start)?,
+            end: time(end)?,
+        })
+    }
+}
+
+impl fmt::Display for IdleWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for IdleWindow {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// A snapshot the daemon verified.
+#[derive(Debug)]
+pub struct IdleVerification {
+    pub snapshot: String,
+    pub report: VerifyReport,
+}
+
+/// Something the daemon did.
+#[derive(Debug)]
+pub enum DaemonEvent {
+    Verified(IdleVerification),
+    /// A backup from the queue finished, with the error it failed with
+    BackedUp {
+        queued: QueuedBackup,
+        error: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VerifyRecord {
+    verified_at: DateTime<Utc>,
+    ok: bool,
+}
+
+/// Last verification of each snapshot, by game and snapshot id.
+type VerifyState = BTreeMap<String, BTreeMap<String, VerifyRecord>>;
+
+impl GameBackup {
+    pub fn idle_verify(&self) -> Option<&IdleVerify> {
+        self.config.idle_verify.as_ref()
+    }
+
+    /// Runs until stopped. Backups queued with [`GameBackup::enqueue_backup`] are run one at a
+    /// time, and games with `watch = true` are queued when their saves change. In between, one
+    /// old snapshot at a time is verified during the `[idle_verify]` window, least recently
+    /// verified first. Nothing is verified while a game is running or a backup or restore is in
+    /// progress.
+    pub fn run_daemon(&self, on_event: impl FnMut(&DaemonEvent)) -> Result<()> {
+        self.run_daemon_until(&AtomicBool::new(false), on_event)
+    }
+
+    /// Runs the daemon until `stop` is set, e.g. by a service control handler. It is checked
+    /// between two steps, so a backup or verification in progress is finished first.
+    pub fn run_daemon_until(
+        &self,
+        stop: &AtomicBool,
+        mut on_event: impl FnMut(&DaemonEvent),
+    ) -> Result<()> {
+        let settings = self.idle_verify();
+        let watching = self.has_watched_games();
+        if settings.is_none() && !watching {
+            return Err(anyhow!(
+                "Nothing for the daemon to do. Add an [idle_verify] table to the config, or set \
+                 `watch = true` for a game"
+            ));
+        }
+        if let Some(settings) = settings {
+            log::info!(
+                "Verifying snapshots between {}, each one every {}",
+                settings.window,
+                Period(settings.every())
+            );
+        }
+
+        let mut next_check = Instant::now();
+        loop {
+            if stop.load(Ordering::Relaxed) {
+                log::info!("Daemon stopped");
+                return Ok(());
+            }
+            match self.run_next_queued() {
+                Ok(Some((queued, result))) => {
+                    let error = result.err().map(|e| {
+                        log::error!("Queued backup of '{}' failed: {:#}", queued.game, e);
+                        format!("{:#}", e)
+                    });
+                    on_event(&DaemonEvent::BackedUp { queued, error });
+                    continue;
+                }
+                Ok(None) => {}
+                Err(e) => log::error!("{:#}", e),
+            }
+            if Instant::now() < next_check {
+                thread::sleep(QUEUE_POLL_INTERVAL);
+                continue;
+            }
+
+            next_check = Instant::now() + POLL_INTERVAL;
+            if watching {
+                self.queue_watched_games();
+            }
+            let Some(settings) = settings else {
+                continue;
+            };
+            if !settings.window.contains(Local::now().time()) || self.is_busy() {
+                continue;
+            }
+            match self.verify_next_snapshot(settings.every()) {
+                Ok(Some(verification)) => {
+                    on_event(&DaemonEvent::Verified(verification));
+                    next_check = Instant::now() + settings.pause();
+                }
+                Ok(None) => {}
+                // A snapshot that cannot be read is recorded as failed and retried next round
+                Err(e) => {
+                    log::error!("{:#}", e);
+                    next_check = Instant::now() + settings.pause();
+                }
+            }
+        }
+    }
+
+    /// Verifies the snapshot that is due and was verified least recently, if any.
+    fn verify_next_snapshot(&self, every: TimeDelta) -> Result<Option<IdleVerification>> {
+        let previous = read_state(&self.backup_root)?;
+        let now = Utc::now();
+
+        let mut due = Vec::new();
+        let mut known = VerifyState::new();
+        for game in self.list_games() {
+            for snapshot in self.list_snapshots(&game.name)? {
+                if snapshot.current {
+                    continue;
+                }
+                let record = previous
+                    .get(&game.name)
+                    .and_then(|records| records.get(&snapshot.id))
+                    .cloned();
+                let verified_at = record.as_ref().map(|record| record.verified_at);
+                if let Some(record) = record {
+                    known
+                        .entry(game.name.clone())
+                        .or_default()
+                        .insert(snapshot.id.clone(), record);
+                }
+                if verified_at.is_none_or(|verified_at| now - verified_at >= every) {
+                    due.push((verified_at, game.name.clone(), snapshot));
+                }
+            }
+        }
+        // Snapshots pruned since are forgotten
+        let mut state = known;
+
+        // Never verified snapshots sort first, then the ones verified longest ago
+        let Some((_, game_name, snapshot)) = due
+            .into_iter()
+            .min_by_key(|(verified_at, _, snapshot)| (*verified_at, snapshot.id.clone()))
+        else {
+            log::debug!(
+                "All snapshots were verified within the last {}",
+                Period(every)
+            );
+            return Ok(None);
+        };
+
+        log::info!("Verifying snapshot {} of '{}'", snapshot.id, game_name);
+        let result = verify::verify_dir(&game_name, &snapshot.path);
+        let ok = result.as_ref().is_ok_and(VerifyReport::is_ok);
+        state.entry(game_name.clone()).or_default().insert(
+            snapshot.id.clone(),
+            VerifyRecord {
+                verified_at: now,
+                ok,
+            },
+        );
+        write_state(&self.backup_root, &state)?;
+
+        let report = result.with_context(|| {
+            format!(
+                "Failed to verify snapshot {} of '{}'",
+                snapshot.id, game_name
+            )
+        })?;
+        if !ok {
+            log::error!(
+                "Snapshot {} of '{}' failed verification: {} missing, {} corrupted",
+                snapshot.id,
+                game_name,
+                report.missing.len(),
+                report.corrupted.len()
+            );
+        }
+        Ok(Some(IdleVerification {
+            snapshot: snapshot.id,
+            report,
+        }))
+    }
+
+    /// Whether verifying now would compete with a game or a backup or restore for the disk.
+    fn is_busy(&self) -> bool {
+        if let Ok(Some(status)) = self.read_status()
+            && status.state == RunState::Running
+            && !status.is_stale()
+        {
+            log::debug!("A backup or restore is running, not verifying");
+            return true;
+        }
+        let running: Vec<String> = self
+            .list_games()
+            .iter()
+            .flat_map(|game| process::running(&game.processes))
+            .collect();
+        if !running.is_empty() {
+            log::debug!("Game running ({}), not verifying", running.join(", "));
+            return true;
+        }
+        false
+    }
+}
+
+fn read_state(backup_root: &Path) -> Result<VerifyState> {
+    let path = backup_root.join(VERIFY_STATE_FILE);
+    if !path.exists() {
+        return Ok(VerifyState::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read verification state: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse verification state: {}", path.display()))
+}
+
+fn write_state(backup_root: &Path, state: &VerifyState) -> Result<()> {
+    let path = backup_root.join(VERIFY_STATE_FILE);
+    let content = serde_json::to_string_pretty(state)?;
+    durable::write_atomic(&path, content)
+        .with_context(|| format!("Failed to write verification state: {}", path.display()))
+}
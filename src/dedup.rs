@@ -0,0 +1,98 @@
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::GameBackup;
+use crate::manifest::Manifest;
+
+/// Files with identical content across the backups and snapshots of all games.
+#[derive(Debug, Default)]
+pub struct DedupReport {
+    pub files: usize,
+    pub total_bytes: u64,
+    /// Size of the content if every distinct file were stored once
+    pub unique_bytes: u64,
+    /// Contents stored more than once, largest savings first
+    pub groups: Vec<DuplicateGroup>,
+}
+
+impl DedupReport {
+    pub fn savings(&self) -> u64 {
+        self.total_bytes - self.unique_bytes
+    }
+}
+
+#[derive(Debug)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub size: u64,
+    pub copies: Vec<StoredFile>,
+}
+
+impl DuplicateGroup {
+    pub fn games(&self) -> BTreeSet<&str> {
+        self.copies.iter().map(|copy| copy.game.as_str()).collect()
+    }
+
+    /// Whether the content is shared by different games rather than by snapshots of one game.
+    pub fn is_cross_game(&self) -> bool {
+        self.games().len() > 1
+    }
+
+    pub fn savings(&self) -> u64 {
+        self.size * (self.copies.len() as u64 - 1)
+    }
+}
+
+#[derive(Debug)]
+pub struct StoredFile {
+    pub game: String,
+    pub snapshot: String,
+    pub path: String,
+}
+
+impl GameBackup {
+    /// Groups the files recorded in every backup manifest by content hash.
+    pub fn dedup_report(&self) -> Result<DedupReport> {
+        log::info!("Looking for duplicate files across backups");
+
+        let mut by_hash: BTreeMap<String, (u64, Vec<StoredFile>)> = BTreeMap::new();
+        let mut report = DedupReport::default();
+        for game in &self.config.games {
+            for snapshot in self.list_snapshots(&game.name)? {
+                let Some(manifest) = Manifest::load(&snapshot.path)? else {
+                    continue;
+                };
+                for (path, entry) in manifest.files {
+                    report.files += 1;
+                    report.total_bytes += entry.size;
+                    by_hash
+                        .entry(entry.hash)
+                        .or_insert_with(|| (entry.size, Vec::new()))
+                        .1
+                        .push(StoredFile {
+                            game: game.name.clone(),
+                            snapshot: snapshot.id.clone(),
+                            path,
+                        });
+                }
+            }
+        }
+
+        for (hash, (size, copies)) in by_hash {
+            report.unique_bytes += size;
+            if copies.len() > 1 {
+                report.groups.push(DuplicateGroup { hash, size, copies });
+            }
+        }
+        report
+            .groups
+            .sort_by(|a, b| b.savings().cmp(&a.savings()).then(a.hash.cmp(&b.hash)));
+
+        log::info!(
+            "Found {} duplicated contents in {} files",
+            report.groups.len(),
+            report.files
+        );
+        Ok(report)
+    }
+}
@@ -0,0 +1,461 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::archive::{self, ArchiveFormat, ArchiveWriter};
+use crate::manifest::{self, Manifest};
+use crate::plan::{ConflictPolicy, FileAction, RestoreOptions};
+use crate::snapshot::snapshot_id;
+use crate::{Game, GameBackup, STAGING_DIR, process, unprotect_backup};
+
+/// Description of the delta, stored next to the files in the archive.
+pub const DELTA_FILE: &str = "delta.json";
+
+/// Files that differ between two snapshots of a game. Paths are `<location>/<path>`, with
+/// the save locations numbered in the order of the configuration, as in the archive.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotDelta {
+    pub game: String,
+    pub from: String,
+    pub to: String,
+    /// Files added or changed in `to`, included in the archive
+    pub changed: Vec<String>,
+    /// Files of `from` that are gone in `to`
+    pub removed: Vec<String>,
+    /// Hashes in `to` of the changed files, which the files in the archive must match
+    #[serde(default)]
+    pub hashes: BTreeMap<String, String>,
+    /// Hashes in `from` of the changed and removed files that it has, which the saves a delta
+    /// is applied to must match
+    #[serde(default)]
+    pub base: BTreeMap<String, String>,
+}
+
+/// What applying a delta to the live saves did, or would do in a dry run.
+#[derive(Debug)]
+pub struct AppliedDelta {
+    pub delta: SnapshotDelta,
+    /// Changed files of the delta, copied over the live ones
+    pub actions: Vec<FileAction>,
+    /// Live files deleted because the delta removes them
+    pub removed: Vec<PathBuf>,
+}
+
+impl GameBackup {
+    /// Writes the files that were added or changed between snapshot `from` and snapshot `to`
+    /// (the latest backup if `None`) of a game into an archive, with a [`DELTA_FILE`] listing
+    /// them and the files that were removed.
+    pub fn export_diff(
+        &self,
+        game_name: &str,
+        from: &str,
+        to: Option<&str>,
+        output: &Path,
+    ) -> Result<SnapshotDelta> {
+        let format = ArchiveFormat::from_path(output).ok_or_else(|| {
+            anyhow!(
+                "Unsupported export file extension: {} (expected .zip or .tar.zst)",
+                output.display()
+            )
+        })?;
+        let game = self.find_game(game_name)?;
+        let from_dir = self.snapshot_dir(&game.name, Some(from))?;
+        let to_dir = self.snapshot_dir(&game.name, to)?;
+        let from_manifest = snapshot_manifest(&game.name, &from_dir)?;
+        let to_manifest = snapshot_manifest(&game.name, &to_dir)?;
+
+        let from_files = location_files(game, &from_manifest);
+        let to_files = location_files(game, &to_manifest);
+        let changed: Vec<String> = to_files
+            .iter()
+            .filter(|(path, hash)| from_files.get(*path) != Some(*hash))
+            .map(|(path, _)| path.clone())
+            .collect();
+        let removed: Vec<String> = from_files
+            .keys()
+            .filter(|path| !to_files.contains_key(*path))
+            .cloned()
+            .collect();
+        let delta = SnapshotDelta {
+            game: game.name.clone(),
+            from: snapshot_id(&from_manifest),
+            to: snapshot_id(&to_manifest),
+            base: changed
+                .iter()
+                .chain(&removed)
+                .filter_map(|path| Some((path.clone(), from_files.get(path)?.clone())))
+                .collect(),
+            hashes: changed
+                .iter()
+                .filter_map(|path| Some((path.clone(), to_files.get(path)?.clone())))
+                .collect(),
+            changed,
+            removed,
+        };
+        log::info!(
+            "Exporting {} changed and {} removed files of '{}' between {} and {} to: {}",
+            delta.changed.len(),
+            delta.removed.len(),
+            delta.game,
+            delta.from,
+            delta.to,
+            output.display()
+        );
+
+        let sandbox = self
+            .backup_root
+            .join(STAGING_DIR)
+            .join(format!("{}.delta", game.name));
+        if sandbox.exists() {
+            fs::remove_dir_all(&sandbox).with_context(|| {
+                format!("Failed to clean staging directory: {}", sandbox.display())
+            })?;
+        }
+        fs::create_dir_all(&sandbox).with_context(|| {
+            format!("Failed to create staging directory: {}", sandbox.display())
+        })?;
+
+        let options = RestoreOptions {
+            on_conflict: ConflictPolicy::Overwrite,
+            ..RestoreOptions::default()
+        };
+        let fetched = self.fetch_from_cold(&game.name, &to_dir, &options)?;
+        let result = self
+            .restore_locations(
+                game,
+                fetched.as_deref().unwrap_or(&to_dir),
+                &options,
+                Some(&sandbox),
+                &mut Vec::new(),
+            )
+            .and_then(|()| write_delta(&delta, &sandbox, output, format));
+        if let Err(e) = fs::remove_dir_all(&sandbox) {
+            log::warn!(
+                "Failed to remove staging directory {}: {}",
+                sandbox.display(),
+                e
+            );
+        }
+        if let Some(fetched) = &fetched
+            && let Err(e) = unprotect_backup(fetched).and_then(|()| {
+                fs::remove_dir_all(fetched).with_context(|| {
+                    format!("Failed to remove fetched snapshot: {}", fetched.display())
+                })
+            })
+        {
+            log::warn!("{:#}", e);
+        }
+        if let Err(e) = result {
+            let _ = fs::remove_file(output);
+            return Err(e);
+        }
+        Ok(delta)
+    }
+
+    /// Applies a delta archive written by `export_diff` to the live saves of its game. Every
+    /// file it touches must still be as in the snapshot the delta was made from, or already as
+    /// in the one it leads to; otherwise nothing is written unless `force` is set.
+    pub fn import_diff(&self, input: &Path, force: bool, dry_run: bool) -> Result<AppliedDelta> {
+        log::info!("Applying delta from: {}", input.display());
+        self.with_extracted_archive(input, DELTA_FILE, "export-diff", |staging| {
+            self.apply_delta(staging, force, dry_run)
+        })
+    }
+
+    /// Extracts an archive holding a single game directory, described by the file
+    /// `description` in it, into a staging directory for `apply`, and removes it afterwards.
+    /// `made_by` names the command that writes such archives.
+    pub(crate) fn with_extracted_archive<T>(
+        &self,
+        input: &Path,
+        description: &str,
+        made_by: &str,
+        apply: impl FnOnce(&Path) -> Result<T>,
+    ) -> Result<T> {
+        let format = ArchiveFormat::from_path(input).ok_or_else(|| {
+            anyhow!(
+                "Unsupported import file extension: {} (expected .zip or .tar.zst)",
+                input.display()
+            )
+        })?;
+        let game_dir = archive::list(input, format, None)?
+            .into_iter()
+            .find(|name| name.components().count() == 2 && name.ends_with(description))
+            .and_then(|name| name.parent().map(Path::to_path_buf))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Not an archive made by `{}`, it has no {}: {}",
+                    made_by,
+                    description,
+                    input.display()
+                )
+            })?;
+        let staging = self.backup_root.join(STAGING_DIR).join(format!(
+            "{}.{}",
+            game_dir.to_string_lossy(),
+            made_by
+        ));
+        if staging.exists() {
+            fs::remove_dir_all(&staging).with_context(|| {
+                format!("Failed to clean staging directory: {}", staging.display())
+            })?;
+        }
+        fs::create_dir_all(&staging).with_context(|| {
+            format!("Failed to create staging directory: {}", staging.display())
+        })?;
+
+        let result = archive::extract(input, format, None, &game_dir, &staging)
+            .and_then(|_| apply(&staging));
+        if let Err(e) = fs::remove_dir_all(&staging) {
+            log::warn!(
+                "Failed to remove staging directory {}: {}",
+                staging.display(),
+                e
+            );
+        }
+        result
+    }
+
+    fn apply_delta(&self, staging: &Path, force: bool, dry_run: bool) -> Result<AppliedDelta> {
+        let delta_file = staging.join(DELTA_FILE);
+        let content = fs::read_to_string(&delta_file)
+            .with_context(|| format!("Failed to read file: {}", delta_file.display()))?;
+        let delta: SnapshotDelta = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse: {}", delta_file.display()))?;
+        let game = self.find_game(&delta.game)?;
+        for path in &delta.changed {
+            let hash = delta.hashes.get(path).ok_or_else(|| {
+                anyhow!(
+                    "Delta has no hash for {}, export it again with this version",
+                    path
+                )
+            })?;
+            verify_extracted(staging, path, hash)?;
+        }
+
+        let running = process::running(&game.processes);
+        if !running.is_empty() {
+            if force || dry_run {
+                log::warn!(
+                    "Game '{}' is running ({}), changed saves may be overwritten or corrupted",
+                    game.name,
+                    running.join(", ")
+                );
+            } else {
+                return Err(anyhow!(
+                    "Game '{}' is running ({}). Close it before applying a delta, or pass --force",
+                    game.name,
+                    running.join(", ")
+                ));
+            }
+        }
+
+        let mut mismatched = Vec::new();
+        for path in delta.changed.iter().chain(&delta.removed) {
+            let live = self.delta_live_path(game, path)?;
+            let live_hash = if live.exists() {
+                Some(manifest::hash_file(&live)?)
+            } else {
+                None
+            };
+            let matches_base = live_hash.as_ref() == delta.base.get(path);
+            let already_applied = if delta.removed.contains(path) {
+                live_hash.is_none()
+            } else {
+                live_hash.as_ref() == delta.hashes.get(path)
+            };
+            if !matches_base && !already_applied {
+                mismatched.push(path.as_str());
+            }
+        }
+        if !mismatched.is_empty() {
+            if !force {
+                return Err(anyhow!(
+                    "The saves of '{}' differ from snapshot {} the delta was made from: {}. Pass \
+                     --force to apply it anyway",
+                    game.name,
+                    delta.from,
+                    mismatched.join(", ")
+                ));
+            }
+            log::warn!(
+                "Applying delta although the saves of '{}' differ from snapshot {}: {}",
+                game.name,
+                delta.from,
+                mismatched.join(", ")
+            );
+        }
+
+        let options = RestoreOptions {
+            on_conflict: ConflictPolicy::Overwrite,
+            dry_run,
+            ..RestoreOptions::default()
+        };
+        let mut actions = Vec::new();
+        for path in &delta.changed {
+            let live = self.delta_live_path(game, path)?;
+            if !dry_run && let Some(parent) = live.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            }
+            actions.push(self.restore_file(
+                &staging.join(archive_key_path(path)?),
+                &live,
+                &options,
+            )?);
+        }
+        let mut removed = Vec::new();
+        for path in &delta.removed {
+            let live = self.delta_live_path(game, path)?;
+            if !live.exists() {
+                continue;
+            }
+            if !dry_run {
+                log::debug!("Removing file: {}", live.display());
+                fs::remove_file(&live)
+                    .with_context(|| format!("Failed to remove file: {}", live.display()))?;
+            }
+            removed.push(live);
+        }
+
+        log::info!(
+            "{} delta of '{}' from {} to {}",
+            if dry_run { "Dry run of" } else { "Applied" },
+            game.name,
+            delta.from,
+            delta.to
+        );
+        Ok(AppliedDelta {
+            delta,
+            actions,
+            removed,
+        })
+    }
+
+    /// Live path of a `<location>/<path>` file of a delta or a shared save. The path comes from
+    /// an archive, so it must stay inside its save location.
+    pub(crate) fn delta_live_path(&self, game: &Game, path: &str) -> Result<PathBuf> {
+        let (index, relative) = path
+            .split_once('/')
+            .ok_or_else(|| anyhow!("Invalid path in delta: {}", path))?;
+        let save_location = index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| game.saves.get(index))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Delta refers to save location {} of '{}', which is not configured",
+                    index,
+                    game.name
+                )
+            })?;
+        let save_path = self.save_path(save_location)?.ok_or_else(|| {
+            anyhow!(
+                "Save location {} of '{}' has no path on {}",
+                index,
+                game.name,
+                std::env::consts::OS
+            )
+        })?;
+        let live = save_path.join(archive_key_path(relative)?);
+        if !live.starts_with(&save_path) {
+            return Err(anyhow!(
+                "Path in archive leaves its save location: {}",
+                path
+            ));
+        }
+        Ok(live)
+    }
+}
+
+/// Relative path of a file listed in a delta or share archive. Such archives may come from
+/// someone else, so only plain names are accepted; `..`, `.`, empty names and absolute paths
+/// could reach outside the save location.
+pub(crate) fn archive_key_path(key: &str) -> Result<PathBuf> {
+    let path = manifest::key_path(key);
+    let plain = key
+        .split('/')
+        .all(|part| !part.is_empty() && part != "." && part != "..")
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if !plain {
+        return Err(anyhow!("Invalid path in archive: {}", key));
+    }
+    Ok(path)
+}
+
+/// Checks that a file extracted from a delta or share archive has the hash it is listed with.
+pub(crate) fn verify_extracted(staging: &Path, key: &str, hash: &str) -> Result<()> {
+    let extracted = staging.join(archive_key_path(key)?);
+    if !extracted.is_file() {
+        return Err(anyhow!("File listed in the archive is missing: {}", key));
+    }
+    if manifest::hash_file(&extracted)? != hash {
+        return Err(anyhow!(
+            "{} does not match its hash, the archive is damaged or was altered",
+            key
+        ));
+    }
+    Ok(())
+}
+
+fn snapshot_manifest(game_name: &str, snapshot_dir: &Path) -> Result<Manifest> {
+    Manifest::load(snapshot_dir)?.ok_or_else(|| {
+        anyhow!(
+            "Backup of '{}' has no manifest, its files cannot be compared: {}",
+            game_name,
+            snapshot_dir.display()
+        )
+    })
+}
+
+/// Hashes of the files of a backup by `<location>/<path>`, so that backups taken while a save
+/// location was stored under a different prefix still compare.
+fn location_files(game: &Game, manifest: &Manifest) -> BTreeMap<String, String> {
+    let mut files = BTreeMap::new();
+    for i in 0..game.saves.len() {
+        let Some(prefix) = manifest.location(i) else {
+            continue;
+        };
+        let prefix = format!("{}/", manifest::entry_key(&prefix));
+        for (key, entry) in &manifest.files {
+            if let Some(relative) = key.strip_prefix(&prefix) {
+                files.insert(format!("{}/{}", i, relative), entry.hash.clone());
+            }
+        }
+    }
+    files
+}
+
+/// Archives the changed files, restored into `sandbox`, under the game's name.
+fn write_delta(
+    delta: &SnapshotDelta,
+    sandbox: &Path,
+    output: &Path,
+    format: ArchiveFormat,
+) -> Result<()> {
+    let game_dir = PathBuf::from(&delta.game);
+    let mut writer = ArchiveWriter::create(output, format, None, None)?;
+    for path in &delta.changed {
+        let relative = manifest::key_path(path);
+        let source = sandbox.join(&relative);
+        if !source.exists() {
+            return Err(anyhow!(
+                "{} of snapshot {} could not be extracted, its save location has no path on {}",
+                path,
+                delta.to,
+                std::env::consts::OS
+            ));
+        }
+        writer.add_file(&source, &game_dir.join(&relative))?;
+    }
+
+    let delta_file = sandbox.join(DELTA_FILE);
+    fs::write(&delta_file, serde_json::to_string_pretty(delta)?)
+        .with_context(|| format!("Failed to write file: {}", delta_file.display()))?;
+    writer.add_file(&delta_file, &game_dir.join(DELTA_FILE))?;
+    writer.finish()
+}
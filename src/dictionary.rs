@@ -0,0 +1,95 @@
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::archive::{self, ArchiveFormat};
+use crate::durable;
+use crate::{BackupFormat, GameBackup, SaveItem};
+
+/// Trained dictionaries, as `.dictionaries/<game>.dict`. Each backup keeps a copy of the
+/// dictionary it was compressed with, so retraining never breaks older backups.
+pub const DICTIONARIES_DIR: &str = ".dictionaries";
+
+/// Default dictionary size, the one recommended by zstd.
+pub const DEFAULT_DICTIONARY_SIZE: usize = 110 * 1024;
+
+impl GameBackup {
+    /// Trains a zstd dictionary from a game's live save files, to be used by its `tar.zst`
+    /// backups from then on. Returns the dictionary size and the number of sample files.
+    pub fn train_dictionary(&self, game_name: &str, max_size: usize) -> Result<(usize, usize)> {
+        let game = self.find_game(game_name)?;
+        ArchiveFormat::TarZst.ensure_available()?;
+        if self.game_format(game) != BackupFormat::TarZst {
+            log::warn!(
+                "Game '{}' is not backed up as tar.zst, the dictionary is only used once it is",
+                game.name
+            );
+        }
+
+        let mut samples: Vec<PathBuf> = Vec::new();
+        for (i, save_location) in game.saves.iter().enumerate() {
+            let Some(source_path) = self.save_path(save_location)? else {
+                continue;
+            };
+            if !source_path.exists() {
+                continue;
+            }
+            let rules = self.ignore_rules(i, save_location, None);
+            self.walk_save_location(
+                save_location,
+                &source_path,
+                Path::new(""),
+                rules,
+                &mut |item| {
+                    if let SaveItem::File { source, .. } = item {
+                        samples.push(source.to_path_buf());
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+
+        if samples.len() < 2 {
+            return Err(anyhow!(
+                "Game '{}' has {} save files, too few to train a dictionary from",
+                game.name,
+                samples.len()
+            ));
+        }
+
+        log::info!(
+            "Training dictionary for '{}' from {} files",
+            game.name,
+            samples.len()
+        );
+        let dictionary = archive::train_dictionary(&samples, max_size)?;
+
+        let path = self.dictionary_path(&game.name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        durable::write_atomic(&path, &dictionary)
+            .with_context(|| format!("Failed to write dictionary: {}", path.display()))?;
+        log::info!("Saved dictionary: {}", path.display());
+
+        Ok((dictionary.len(), samples.len()))
+    }
+
+    /// The trained dictionary of a game, if there is one.
+    pub(crate) fn trained_dictionary(&self, game_name: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.dictionary_path(game_name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        fs::read(&path)
+            .map(Some)
+            .with_context(|| format!("Failed to read dictionary: {}", path.display()))
+    }
+
+    fn dictionary_path(&self, game_name: &str) -> PathBuf {
+        self.backup_root
+            .join(DICTIONARIES_DIR)
+            .join(format!("{}.dict", game_name))
+    }
+}
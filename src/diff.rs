@@ -0,0 +1,136 @@
+use anyhow::{Result, anyhow};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::{self, Manifest};
+use crate::{GameBackup, SaveItem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffStatus {
+    /// The file exists in the live saves but not in the backup
+    Added,
+    /// The file differs between the live saves and the backup
+    Modified { live_newer: bool },
+    /// The file exists in the backup but not in the live saves
+    Removed,
+}
+
+#[derive(Debug)]
+pub struct DiffEntry {
+    pub path: String,
+    pub live_path: Option<PathBuf>,
+    pub status: DiffStatus,
+}
+
+#[derive(Debug)]
+pub struct DiffReport {
+    pub game: String,
+    pub entries: Vec<DiffEntry>,
+    pub unchanged: usize,
+}
+
+impl DiffReport {
+    /// Live files that a restore would overwrite with older content from the backup.
+    pub fn newer_live_files(&self) -> impl Iterator<Item = &DiffEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status == DiffStatus::Modified { live_newer: true })
+    }
+}
+
+impl GameBackup {
+    /// Compares the live save files of a game against the manifest of its backup.
+    pub fn diff_game(&self, game_name: &str) -> Result<DiffReport> {
+        log::info!("Comparing live saves with backup for game: {}", game_name);
+
+        let game = self.find_game(game_name)?;
+        let game_backup_dir = self.backup_root.join(&game.name);
+        let manifest = Manifest::load(&game_backup_dir)?.ok_or_else(|| {
+            anyhow!(
+                "No manifest found for game '{}'. Run a backup to create one",
+                game_name
+            )
+        })?;
+
+        let mut report = DiffReport {
+            game: game.name.clone(),
+            entries: Vec::new(),
+            unchanged: 0,
+        };
+        let mut seen = HashSet::new();
+
+        for (i, save_location) in game.saves.iter().enumerate() {
+            let Some(source_path) = self.save_path(save_location)? else {
+                continue;
+            };
+            if !source_path.exists() {
+                log::debug!("Save path does not exist: {}", source_path.display());
+                continue;
+            }
+
+            let prefix = self.backup_prefix(i, &source_path, Some(&manifest))?;
+            let rules = self.ignore_rules(i, save_location, Some(&manifest));
+            self.walk_save_location(save_location, &source_path, &prefix, rules, &mut |item| {
+                let SaveItem::File { source, name } = item else {
+                    return Ok(());
+                };
+
+                let key = manifest::entry_key(name);
+                let status = compare_file(&manifest, source, name)?;
+                seen.insert(key.clone());
+
+                match status {
+                    Some(status) => report.entries.push(DiffEntry {
+                        path: key,
+                        live_path: Some(source.to_path_buf()),
+                        status,
+                    }),
+                    None => report.unchanged += 1,
+                }
+                Ok(())
+            })?;
+        }
+
+        for key in manifest.files.keys() {
+            if !seen.contains(key) {
+                report.entries.push(DiffEntry {
+                    path: key.clone(),
+                    live_path: None,
+                    status: DiffStatus::Removed,
+                });
+            }
+        }
+
+        report.entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        log::info!(
+            "Found {} differences and {} unchanged files for '{}'",
+            report.entries.len(),
+            report.unchanged,
+            report.game
+        );
+        Ok(report)
+    }
+}
+
+fn compare_file(manifest: &Manifest, source: &Path, name: &Path) -> Result<Option<DiffStatus>> {
+    let Some(entry) = manifest.get(name) else {
+        return Ok(Some(DiffStatus::Added));
+    };
+
+    let metadata = fs::metadata(source)?;
+    if entry.matches(&metadata) {
+        return Ok(None);
+    }
+
+    if metadata.len() == entry.size && manifest::hash_file(source)? == entry.hash {
+        return Ok(None);
+    }
+
+    let live_newer = metadata
+        .modified()
+        .map(|modified| modified > entry.modified)
+        .unwrap_or(false);
+    Ok(Some(DiffStatus::Modified { live_newer }))
+}
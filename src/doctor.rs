@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use crate::{GameBackup, cloud};
+
+#[derive(Debug)]
+pub enum LocationStatus {
+    Exists,
+    Missing,
+    /// The location only has paths for other platforms
+    NotConfigured {
+        platforms: Vec<String>,
+    },
+    /// The path could not be resolved, e.g. because of an undefined variable
+    Invalid {
+        error: String,
+    },
+}
+
+#[derive(Debug)]
+pub struct LocationCheck {
+    pub path: Option<PathBuf>,
+    pub status: LocationStatus,
+    /// Cloud service syncing the location, unless the game has `cloud_sync_ok`
+    pub synced_by: Option<&'static str>,
+}
+
+#[derive(Debug)]
+pub struct GameCheck {
+    pub name: String,
+    pub enabled: bool,
+    pub locations: Vec<LocationCheck>,
+}
+
+impl GameCheck {
+    pub fn is_ok(&self) -> bool {
+        self.locations.iter().all(|location| {
+            matches!(
+                location.status,
+                LocationStatus::Exists | LocationStatus::NotConfigured { .. }
+            )
+        })
+    }
+}
+
+impl GameBackup {
+    /// Reports, for every configured game, which save paths resolve and exist on this platform.
+    pub fn doctor(&self) -> Vec<GameCheck> {
+        log::info!(
+            "Checking configured save paths for platform: {}",
+            std::env::consts::OS
+        );
+
+        self.config
+            .games
+            .iter()
+            .map(|game| GameCheck {
+                name: game.name.clone(),
+                enabled: game.enabled,
+                locations: game
+                    .saves
+                    .iter()
+                    .map(|save_location| match self.save_path(save_location) {
+                        Ok(Some(path)) => LocationCheck {
+                            status: if path.exists() {
+                                LocationStatus::Exists
+                            } else {
+                                LocationStatus::Missing
+                            },
+                            synced_by: if game.cloud_sync_ok {
+                                None
+                            } else {
+                                cloud::sync_service(&path)
+                            },
+                            path: Some(path),
+                        },
+                        Ok(None) => LocationCheck {
+                            path: None,
+                            synced_by: None,
+                            status: LocationStatus::NotConfigured {
+                                platforms: save_location
+                                    .path
+                                    .platforms()
+                                    .into_iter()
+                                    .map(String::from)
+                                    .collect(),
+                            },
+                        },
+                        Err(e) => LocationCheck {
+                            path: None,
+                            synced_by: None,
+                            status: LocationStatus::Invalid {
+                                error: format!("{:#}", e),
+                            },
+                        },
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,348 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::{Game, GameBackup};
+
+/// What to do when a `sensitive` game is backed up to an unencrypted removable drive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnencryptedPolicy {
+    /// Back up anyway and log a warning
+    #[default]
+    Warn,
+    /// Refuse the backup unless it is run with `--allow-unencrypted`
+    Refuse,
+}
+
+/// The drive a path is stored on, as far as it could be determined.
+#[derive(Debug, Clone)]
+pub struct Drive {
+    pub device: String,
+    pub mount_point: PathBuf,
+    /// Whether the drive can be unplugged and carried away, e.g. a USB stick or SD card
+    pub removable: bool,
+    /// Whether the drive is encrypted at rest; `None` when it could not be determined
+    pub encrypted: Option<bool>,
+}
+
+impl Drive {
+    pub fn is_exposed(&self) -> bool {
+        self.removable && self.encrypted == Some(false)
+    }
+}
+
+impl GameBackup {
+    /// Drive the backup root is stored on, detected once per run. `None` when the platform
+    /// gives no way to tell.
+    pub fn backup_drive(&self) -> Option<&Drive> {
+        self.backup_drive
+            .get_or_init(|| match detect(&self.backup_root) {
+                Ok(drive) => {
+                    log::debug!("Backup root is on {:?}", drive);
+                    Some(drive)
+                }
+                Err(e) => {
+                    log::debug!("Could not inspect the drive of the backup root: {:#}", e);
+                    None
+                }
+            })
+            .as_ref()
+    }
+
+    /// Warns about, or refuses, backing up a sensitive game to an unencrypted removable drive.
+    pub(crate) fn check_backup_drive(&self, game: &Game) -> Result<()> {
+        if !game.sensitive {
+            return Ok(());
+        }
+        let Some(drive) = self.backup_drive().filter(|drive| drive.is_exposed()) else {
+            return Ok(());
+        };
+
+        if self.config.on_unencrypted == UnencryptedPolicy::Refuse && !self.allow_unencrypted {
+            return Err(anyhow!(
+                "Refusing to back up sensitive game '{}' to an unencrypted removable drive ({} at {}). \
+                 Encrypt the drive (LUKS, BitLocker or FileVault) or pass --allow-unencrypted",
+                game.name,
+                drive.device,
+                drive.mount_point.display()
+            ));
+        }
+        log::warn!(
+            "Backing up sensitive game '{}' to an unencrypted removable drive ({} at {}). \
+             Anyone holding the drive can read the saves; consider encrypting it \
+             (LUKS, BitLocker or FileVault)",
+            game.name,
+            drive.device,
+            drive.mount_point.display()
+        );
+        Ok(())
+    }
+}
+
+/// Inspects the drive of `path`, or of its nearest existing parent when it is not created yet.
+pub(crate) fn detect(path: &Path) -> Result<Drive> {
+    let existing = path
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .ok_or_else(|| anyhow!("No existing parent of: {}", path.display()))?;
+    let path = existing
+        .canonicalize()
+        .map_err(|e| anyhow!("Failed to resolve {}: {}", existing.display(), e))?;
+    platform::detect(&path)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use anyhow::{Context, Result, anyhow};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::Drive;
+
+    /// Filesystems that encrypt the files they store themselves.
+    const ENCRYPTED_FILESYSTEMS: &[&str] = &[
+        "ecryptfs",
+        "fuse.gocryptfs",
+        "fuse.cryfs",
+        "fuse.encfs",
+        "fuse.securefs",
+    ];
+
+    pub(super) fn detect(path: &Path) -> Result<Drive> {
+        let mountinfo = fs::read_to_string("/proc/self/mountinfo")
+            .with_context(|| "Failed to read /proc/self/mountinfo")?;
+
+        // The longest mount point containing the path is the one it is stored on
+        let mut best: Option<Mount> = None;
+        for mount in mountinfo.lines().filter_map(Mount::parse) {
+            if path.starts_with(&mount.mount_point)
+                && best.as_ref().is_none_or(|best| {
+                    mount.mount_point.as_os_str().len() >= best.mount_point.as_os_str().len()
+                })
+            {
+                best = Some(mount);
+            }
+        }
+        let mount = best.ok_or_else(|| anyhow!("No mount found for: {}", path.display()))?;
+
+        let (removable, encrypted) = if ENCRYPTED_FILESYSTEMS.contains(&mount.fstype.as_str()) {
+            (false, true)
+        } else {
+            let block = Path::new("/sys/dev/block").join(&mount.device_number);
+            match fs::canonicalize(&block) {
+                Ok(block) => inspect_block(&block),
+                // Not backed by a block device, such as tmpfs or a network share
+                Err(_) => (false, false),
+            }
+        };
+        Ok(Drive {
+            device: mount.source,
+            mount_point: mount.mount_point,
+            removable,
+            encrypted: Some(encrypted),
+        })
+    }
+
+    struct Mount {
+        device_number: String,
+        mount_point: PathBuf,
+        fstype: String,
+        source: String,
+    }
+
+    impl Mount {
+        /// Parses a line of `/proc/self/mountinfo`, see proc(5).
+        fn parse(line: &str) -> Option<Self> {
+            let (mount, filesystem) = line.split_once(" - ")?;
+            let mount: Vec<&str> = mount.split(' ').collect();
+            let mut filesystem = filesystem.split(' ');
+            Some(Self {
+                device_number: mount.get(2)?.to_string(),
+                mount_point: PathBuf::from(unescape(mount.get(4)?)),
+                fstype: filesystem.next()?.to_string(),
+                source: unescape(filesystem.next()?),
+            })
+        }
+    }
+
+    /// Undoes the octal escapes mountinfo uses for spaces and other special characters.
+    fn unescape(field: &str) -> String {
+        let mut result = String::new();
+        let mut rest = field;
+        while let Some(position) = rest.find('\\') {
+            result.push_str(&rest[..position]);
+            let code = rest.get(position + 1..position + 4);
+            match code.and_then(|code| u8::from_str_radix(code, 8).ok()) {
+                Some(byte) => {
+                    result.push(byte as char);
+                    rest = &rest[position + 4..];
+                }
+                None => {
+                    result.push('\\');
+                    rest = &rest[position + 1..];
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    /// Whether a block device in sysfs sits on a removable or USB disk, and whether it or
+    /// any device under it is a dm-crypt mapping.
+    fn inspect_block(block: &Path) -> (bool, bool) {
+        let mut encrypted =
+            fs::read_to_string(block.join("dm/uuid")).is_ok_and(|uuid| uuid.starts_with("CRYPT-"));
+        let disk = if block.join("partition").exists() {
+            block.parent().unwrap_or(block)
+        } else {
+            block
+        };
+        let mut removable = fs::read_to_string(disk.join("removable"))
+            .is_ok_and(|removable| removable.trim() == "1")
+            || disk
+                .components()
+                .any(|component| component.as_os_str().to_string_lossy().starts_with("usb"));
+
+        // Device mapper and RAID devices are stacked on the devices listed in `slaves`
+        if let Ok(slaves) = fs::read_dir(block.join("slaves")) {
+            for slave in slaves.flatten() {
+                if let Ok(slave) = fs::canonicalize(slave.path()) {
+                    let (slave_removable, slave_encrypted) = inspect_block(&slave);
+                    removable |= slave_removable;
+                    encrypted |= slave_encrypted;
+                }
+            }
+        }
+        (removable, encrypted)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use anyhow::{Context, Result, anyhow};
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    use super::Drive;
+
+    pub(super) fn detect(path: &Path) -> Result<Drive> {
+        let df = run(Command::new("df").arg("-P").arg(path))?;
+        let line = df
+            .lines()
+            .nth(1)
+            .ok_or_else(|| anyhow!("Unexpected df output: {}", df))?;
+        let device = line
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        // The mount point is the last column and may contain spaces
+        let mount_point = line
+            .find(" /")
+            .map(|position| line[position + 1..].to_string())
+            .ok_or_else(|| anyhow!("Unexpected df output: {}", df))?;
+
+        let info = run(Command::new("diskutil").arg("info").arg(&mount_point))?;
+        let field = |name: &str| {
+            info.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                (key.trim() == name).then(|| value.trim().to_string())
+            })
+        };
+        let removable = field("Removable Media").is_some_and(|value| value == "Removable")
+            || field("Device Location").is_some_and(|value| value == "External")
+            || field("Protocol").is_some_and(|value| value == "USB");
+        let encrypted = match (field("FileVault"), field("Encrypted")) {
+            (Some(value), _) | (None, Some(value)) if value == "Yes" => Some(true),
+            (Some(_), _) | (None, Some(_)) => Some(false),
+            (None, None) => None,
+        };
+        Ok(Drive {
+            device,
+            mount_point: PathBuf::from(mount_point),
+            removable,
+            encrypted,
+        })
+    }
+
+    fn run(command: &mut Command) -> Result<String> {
+        let output = command
+            .output()
+            .with_context(|| format!("Failed to run {:?}", command))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "{:?} failed: {}",
+                command,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use anyhow::{Context, Result, anyhow};
+    use std::path::{Component, Path, PathBuf, Prefix};
+    use std::process::Command;
+
+    use super::Drive;
+
+    pub(super) fn detect(path: &Path) -> Result<Drive> {
+        let letter = match path.components().next() {
+            Some(Component::Prefix(prefix)) => match prefix.kind() {
+                Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => letter as char,
+                _ => return Err(anyhow!("Not on a drive letter: {}", path.display())),
+            },
+            _ => return Err(anyhow!("Not on a drive letter: {}", path.display())),
+        };
+
+        // BitLockerProtection: 1 on, 2 off, 3 encrypting, 4 decrypting, 5 suspended, 6 locked
+        let script = format!(
+            "$v = Get-Volume -DriveLetter {0}; \
+             $b = (Get-Partition -DriveLetter {0} | Get-Disk).BusType; \
+             $p = (New-Object -ComObject Shell.Application).NameSpace('{0}:').Self.ExtendedProperty('System.Volume.BitLockerProtection'); \
+             \"$($v.DriveType);$b;$p\"",
+            letter
+        );
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .output()
+            .with_context(|| "Failed to run powershell")?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "powershell failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut fields = stdout.trim().split(';');
+        let drive_type = fields.next().unwrap_or_default();
+        let bus_type = fields.next().unwrap_or_default();
+        let removable = drive_type == "Removable" || bus_type == "USB" || bus_type == "SD";
+        let encrypted = match fields.next().unwrap_or_default() {
+            "1" | "3" | "6" => Some(true),
+            "2" | "4" | "5" => Some(false),
+            _ => None,
+        };
+        Ok(Drive {
+            device: format!("{}:", letter),
+            mount_point: PathBuf::from(format!("{}:\\", letter)),
+            removable,
+            encrypted,
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use anyhow::{Result, anyhow};
+    use std::path::Path;
+
+    use super::Drive;
+
+    pub(super) fn detect(_path: &Path) -> Result<Drive> {
+        Err(anyhow!("Drive detection is not supported on this platform"))
+    }
+}
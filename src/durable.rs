@@ -0,0 +1,87 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Replaces `path` with `contents` through a temporary file next to it, flushed to disk before
+/// it is renamed into place, so a crash leaves either the old content or the new one.
+pub(crate) fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    // A symlinked file, e.g. a config managed as a dotfile, is replaced where it really is
+    let real = fs::canonicalize(path);
+    let path = real.as_deref().unwrap_or(path);
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp = path.with_file_name(temp_name);
+    let result = File::create(&temp).and_then(|mut file| {
+        file.write_all(contents.as_ref())?;
+        file.sync_all()
+    });
+    if let Err(e) = result.and_then(|()| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => sync_dir(dir),
+        _ => sync_dir(Path::new(".")),
+    }
+}
+
+/// Appends a line to a JSON Lines journal and flushes it to disk. A line left without its end
+/// by a crash is ended first, so readers lose only that entry and not the new one too.
+pub(crate) fn append_line(path: &Path, line: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+    let mut entry = String::with_capacity(line.len() + 2);
+    if file.seek(SeekFrom::End(0))? > 0 {
+        let mut last = [0; 1];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            log::warn!(
+                "Ending a line torn by an earlier crash in: {}",
+                path.display()
+            );
+            entry.push('\n');
+        }
+    }
+    entry.push_str(line);
+    entry.push('\n');
+    file.write_all(entry.as_bytes())?;
+    file.sync_data()
+}
+
+/// Flushes every file and directory below `dir` to disk, so a rename that publishes them
+/// never outlives their content in a power loss.
+pub(crate) fn sync_tree(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            sync_tree(&path)?;
+        } else {
+            sync_file(&path)?;
+        }
+    }
+    sync_dir(dir)
+}
+
+/// Flushes the content of a file to disk.
+pub(crate) fn sync_file(path: &Path) -> io::Result<()> {
+    // Windows only flushes handles opened for writing
+    #[cfg(windows)]
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    #[cfg(not(windows))]
+    let file = File::open(path)?;
+    file.sync_all()
+}
+
+/// Flushes the entries of a directory, e.g. after a rename in it. Windows has no way to open a
+/// directory for this, its renames are flushed with the volume.
+pub(crate) fn sync_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
@@ -0,0 +1,279 @@
+//! C ABI for launcher frontends that embed cartridge, declared in `include/cartridge.h`.
+//!
+//! Functions returning `int` return 0 on success and -1 on failure, with the error message
+//! available from `cartridge_last_error`. Strings returned by cartridge are freed with
+//! `cartridge_string_free`.
+
+use anyhow::{Result, anyhow};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+use std::sync::Mutex;
+
+use crate::GameBackup;
+use crate::plan::RestoreOptions;
+use crate::progress::{Operation, Progress};
+
+pub const CARTRIDGE_BACKUP_STARTED: c_int = 0;
+pub const CARTRIDGE_BACKUP_FILE: c_int = 1;
+pub const CARTRIDGE_BACKUP_FINISHED: c_int = 2;
+pub const CARTRIDGE_BACKUP_FAILED: c_int = 3;
+pub const CARTRIDGE_RESTORE_STARTED: c_int = 10;
+pub const CARTRIDGE_RESTORE_FILE: c_int = 11;
+pub const CARTRIDGE_RESTORE_FINISHED: c_int = 12;
+pub const CARTRIDGE_RESTORE_FAILED: c_int = 13;
+
+/// Called with the event, the game, the file (null for events other than `*_FILE`) and its
+/// size in bytes. The strings are only valid during the call.
+pub type ProgressFn = extern "C" fn(
+    user_data: *mut c_void,
+    event: c_int,
+    game: *const c_char,
+    path: *const c_char,
+    bytes: u64,
+);
+
+/// A loaded configuration.
+pub struct Cartridge {
+    game_backup: GameBackup,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Pointer passed back to the progress callback. The caller is responsible for it being
+/// usable from the thread that runs the backup or restore.
+struct UserData(*mut c_void);
+
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+impl UserData {
+    // A method rather than the field, so closures capture the whole wrapper
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+/// Loads a configuration file. Returns null on failure.
+///
+/// # Safety
+///
+/// `config_path` must be a valid NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cartridge_open(config_path: *const c_char) -> *mut Cartridge {
+    let result = guard(|| {
+        let config_path = unsafe { to_str(config_path, "config_path") }?;
+        let game_backup = GameBackup::new(Path::new(config_path))?;
+        Ok(Box::into_raw(Box::new(Cartridge { game_backup })))
+    });
+    result.unwrap_or(ptr::null_mut())
+}
+
+/// Frees a configuration returned by `cartridge_open`.
+///
+/// # Safety
+///
+/// `cartridge` must be null or returned by `cartridge_open`, and not used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cartridge_close(cartridge: *mut Cartridge) {
+    if !cartridge.is_null() {
+        drop(unsafe { Box::from_raw(cartridge) });
+    }
+}
+
+/// Message of the last error on this thread, or null. Valid until the next call into
+/// cartridge on this thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn cartridge_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+/// Frees a string returned by cartridge.
+///
+/// # Safety
+///
+/// `string` must be null or returned by cartridge, and not used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cartridge_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+/// Lists the enabled games as a JSON array of objects with `name`, `has_backup` and
+/// `save_locations`. Returns null on failure.
+///
+/// # Safety
+///
+/// `cartridge` must be returned by `cartridge_open`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cartridge_list_games(cartridge: *const Cartridge) -> *mut c_char {
+    let result = guard(|| {
+        let game_backup = unsafe { &to_ref(cartridge)?.game_backup };
+        let games: Vec<serde_json::Value> = game_backup
+            .list_games()
+            .iter()
+            .map(|game| {
+                serde_json::json!({
+                    "name": game.name,
+                    "has_backup": game_backup.has_backup(&game.name),
+                    "save_locations": game.saves.len(),
+                })
+            })
+            .collect();
+        Ok(CString::new(serde_json::to_string(&games)?)?.into_raw())
+    });
+    result.unwrap_or(ptr::null_mut())
+}
+
+/// Sets the function called as games are backed up or restored, or removes it when
+/// `callback` is null.
+///
+/// # Safety
+///
+/// `cartridge` must be returned by `cartridge_open`. `user_data` is passed to `callback`
+/// as is and must stay valid while it is set.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cartridge_set_progress(
+    cartridge: *mut Cartridge,
+    callback: Option<ProgressFn>,
+    user_data: *mut c_void,
+) -> c_int {
+    status(guard(|| {
+        let cartridge =
+            unsafe { cartridge.as_mut() }.ok_or_else(|| anyhow!("cartridge must not be null"))?;
+        let Some(callback) = callback else {
+            cartridge.game_backup.clear_progress();
+            return Ok(());
+        };
+        let user_data = UserData(user_data);
+        // File events belong to the game last started
+        let current = Mutex::new(CString::default());
+        cartridge.game_backup.set_progress(move |progress| {
+            let mut current = current.lock().unwrap_or_else(|e| e.into_inner());
+            let (event, path, bytes) = match progress {
+                Progress::Started {
+                    operation, game, ..
+                } => {
+                    *current = CString::new(*game).unwrap_or_default();
+                    (event(*operation, 0), None, 0)
+                }
+                Progress::File {
+                    operation,
+                    path,
+                    bytes,
+                } => (event(*operation, 1), Some(*path), *bytes),
+                Progress::Finished { operation, ok, .. } => {
+                    (event(*operation, if *ok { 2 } else { 3 }), None, 0)
+                }
+            };
+            let game = current.clone();
+            let path = path
+                .map(|path| CString::new(path.to_string_lossy().into_owned()).unwrap_or_default());
+            callback(
+                user_data.get(),
+                event,
+                game.as_ptr(),
+                path.as_ref().map_or(ptr::null(), |path| path.as_ptr()),
+                bytes,
+            );
+        });
+        Ok(())
+    }))
+}
+
+/// Backs up a game, or every enabled game when `game_name` is null.
+///
+/// # Safety
+///
+/// `cartridge` must be returned by `cartridge_open`, and `game_name` must be null or a
+/// valid NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cartridge_backup(
+    cartridge: *const Cartridge,
+    game_name: *const c_char,
+) -> c_int {
+    status(guard(|| {
+        let game_backup = unsafe { &to_ref(cartridge)?.game_backup };
+        if game_name.is_null() {
+            game_backup.backup_all_games(None)
+        } else {
+            game_backup.backup_game(unsafe { to_str(game_name, "game_name") }?, None)
+        }
+    }))
+}
+
+/// Restores the latest backup of a game, or of every enabled game when `game_name` is null.
+/// Live files that differ are overwritten, and games that are running are refused.
+///
+/// # Safety
+///
+/// `cartridge` must be returned by `cartridge_open`, and `game_name` must be null or a
+/// valid NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cartridge_restore(
+    cartridge: *const Cartridge,
+    game_name: *const c_char,
+) -> c_int {
+    status(guard(|| {
+        let game_backup = unsafe { &to_ref(cartridge)?.game_backup };
+        let options = RestoreOptions::default();
+        if game_name.is_null() {
+            game_backup.restore_all_games(&options)?;
+        } else {
+            game_backup.restore_game(unsafe { to_str(game_name, "game_name") }?, &options)?;
+        }
+        Ok(())
+    }))
+}
+
+fn event(operation: Operation, offset: c_int) -> c_int {
+    match operation {
+        Operation::Backup => CARTRIDGE_BACKUP_STARTED + offset,
+        Operation::Restore => CARTRIDGE_RESTORE_STARTED + offset,
+    }
+}
+
+/// Runs `f`, recording its error and keeping panics from unwinding into the caller.
+fn guard<T>(f: impl FnOnce() -> Result<T>) -> Option<T> {
+    LAST_ERROR.with(|error| error.borrow_mut().take());
+    let result = panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| Err(anyhow!("cartridge panicked")));
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            let message = format!("{:#}", e).replace('\0', " ");
+            LAST_ERROR.with(|error| *error.borrow_mut() = CString::new(message).ok());
+            None
+        }
+    }
+}
+
+fn status(result: Option<()>) -> c_int {
+    match result {
+        Some(()) => 0,
+        None => -1,
+    }
+}
+
+unsafe fn to_ref<'a>(cartridge: *const Cartridge) -> Result<&'a Cartridge> {
+    unsafe { cartridge.as_ref() }.ok_or_else(|| anyhow!("cartridge must not be null"))
+}
+
+unsafe fn to_str<'a>(string: *const c_char, name: &str) -> Result<&'a str> {
+    if string.is_null() {
+        return Err(anyhow!("{} must not be null", name));
+    }
+    unsafe { CStr::from_ptr(string) }
+        .to_str()
+        .map_err(|_| anyhow!("{} is not valid UTF-8", name))
+}
@@ -0,0 +1,249 @@
+use anyhow::{Context, Result, anyhow};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::manifest;
+use crate::plan::{ConflictPolicy, RestoreOptions};
+use crate::{BackupFormat, GameBackup};
+
+/// Shape of a synthetic save tree. The same seed always generates the same tree.
+#[derive(Debug, Clone)]
+pub struct FixtureSpec {
+    pub files: usize,
+    /// Most directory levels below the save path
+    pub depth: usize,
+    /// Largest file size in bytes, files are spread between empty and this
+    pub max_size: usize,
+    /// Add files whose names are known to trip up copying and archiving: spaces, glob
+    /// characters, non-ASCII and long names, and dotfiles
+    pub special_names: bool,
+    /// Add a file whose name is not UTF-8, on Unix, which zip archives cannot store
+    pub non_utf8_names: bool,
+    pub seed: u64,
+}
+
+impl Default for FixtureSpec {
+    fn default() -> Self {
+        Self {
+            files: 50,
+            depth: 3,
+            max_size: 64 * 1024,
+            special_names: true,
+            non_utf8_names: true,
+            seed: 1,
+        }
+    }
+}
+
+/// A game with a generated save tree and a configuration backing it up, all inside one
+/// directory: `saves/` is the save path, `backup/` the backup root, and `cartridge.toml` the
+/// configuration.
+#[derive(Debug)]
+pub struct GameFixture {
+    pub name: String,
+    pub root: PathBuf,
+    pub save_dir: PathBuf,
+    pub config_path: PathBuf,
+}
+
+impl GameFixture {
+    /// Generates the save tree of a game in `root`, which must not exist or be empty, and a
+    /// configuration storing its backups in `format`.
+    pub fn create(
+        root: &Path,
+        name: &str,
+        spec: &FixtureSpec,
+        format: BackupFormat,
+    ) -> Result<Self> {
+        if root.exists()
+            && fs::read_dir(root)
+                .with_context(|| format!("Failed to read directory: {}", root.display()))?
+                .next()
+                .is_some()
+        {
+            return Err(anyhow!(
+                "Fixture directory is not empty: {}",
+                root.display()
+            ));
+        }
+        fs::create_dir_all(root)
+            .with_context(|| format!("Failed to create directory: {}", root.display()))?;
+        let root = fs::canonicalize(root)
+            .with_context(|| format!("Failed to resolve: {}", root.display()))?;
+        let save_dir = root.join("saves");
+        fs::create_dir_all(&save_dir)
+            .with_context(|| format!("Failed to create directory: {}", save_dir.display()))?;
+        generate_tree(&save_dir, spec)?;
+
+        let config_path = root.join("cartridge.toml");
+        let config = format!(
+            "backup_root = \"backup\"\ninclude_hidden = true\nformat = \"{}\"\n\n\
+             [[game]]\nname = {}\n\n[[game.save]]\npath = {}\n",
+            format.name(),
+            toml::Value::from(name),
+            toml::Value::from(save_dir.to_string_lossy().as_ref())
+        );
+        fs::write(&config_path, config)
+            .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
+        log::debug!("Created fixture '{}' in: {}", name, root.display());
+        Ok(Self {
+            name: name.to_string(),
+            root,
+            save_dir,
+            config_path,
+        })
+    }
+
+    pub fn game_backup(&self) -> Result<GameBackup> {
+        GameBackup::new(&self.config_path)
+    }
+
+    /// Backs the saves up, deletes them, restores them and checks that every file came back
+    /// with the same path and content.
+    pub fn assert_round_trip(&self) -> Result<()> {
+        let game_backup = self.game_backup()?;
+        let before = hash_tree(&self.save_dir)?;
+        game_backup.backup_game(&self.name, None)?;
+
+        fs::remove_dir_all(&self.save_dir)
+            .with_context(|| format!("Failed to remove saves: {}", self.save_dir.display()))?;
+        let options = RestoreOptions {
+            on_conflict: ConflictPolicy::Overwrite,
+            ..RestoreOptions::default()
+        };
+        game_backup.restore_game(&self.name, &options)?;
+
+        let after = hash_tree(&self.save_dir)?;
+        let differences = compare_trees(&before, &after);
+        if !differences.is_empty() {
+            return Err(anyhow!(
+                "Restored saves of '{}' differ from the backed up ones:\n{}",
+                self.name,
+                differences.join("\n")
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Content hash of every file below `dir`, by path relative to it.
+pub fn hash_tree(dir: &Path) -> Result<BTreeMap<PathBuf, String>> {
+    fn walk(dir: &Path, relative: &Path, hashes: &mut BTreeMap<PathBuf, String>) -> Result<()> {
+        for entry in fs::read_dir(dir)
+            .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        {
+            let entry =
+                entry.with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+            let path = entry.path();
+            let relative = relative.join(entry.file_name());
+            if path.is_dir() {
+                walk(&path, &relative, hashes)?;
+            } else {
+                hashes.insert(relative, manifest::hash_file(&path)?);
+            }
+        }
+        Ok(())
+    }
+    let mut hashes = BTreeMap::new();
+    walk(dir, Path::new(""), &mut hashes)?;
+    Ok(hashes)
+}
+
+/// Describes how two trees from [`hash_tree`] differ, one line per file.
+pub fn compare_trees(
+    expected: &BTreeMap<PathBuf, String>,
+    actual: &BTreeMap<PathBuf, String>,
+) -> Vec<String> {
+    let mut differences = Vec::new();
+    for (path, hash) in expected {
+        match actual.get(path) {
+            None => differences.push(format!("missing: {}", path.display())),
+            Some(other) if other != hash => {
+                differences.push(format!("changed: {}", path.display()))
+            }
+            Some(_) => {}
+        }
+    }
+    for path in actual.keys().filter(|path| !expected.contains_key(*path)) {
+        differences.push(format!("unexpected: {}", path.display()));
+    }
+    differences
+}
+
+fn generate_tree(save_dir: &Path, spec: &FixtureSpec) -> Result<()> {
+    let mut rng = Rng(spec.seed.max(1));
+    let mut dirs = vec![PathBuf::new()];
+    for i in 0..spec.files {
+        // Now and then go a level deeper than an existing directory
+        let parent = dirs[rng.below(dirs.len())].clone();
+        let dir = if parent.components().count() < spec.depth && rng.below(3) == 0 {
+            let dir = parent.join(format!("dir{}", dirs.len()));
+            dirs.push(dir.clone());
+            dir
+        } else {
+            parent
+        };
+        let size = rng.below(spec.max_size + 1);
+        write_file(
+            &save_dir.join(dir).join(format!("save{i}.dat")),
+            &mut rng,
+            size,
+        )?;
+    }
+
+    if spec.special_names {
+        let mut names: Vec<OsString> = [
+            "with space.sav",
+            "[1] *glob?.sav",
+            "ümlaut-セーブ.sav",
+            ".hidden.sav",
+            "empty.sav",
+        ]
+        .into_iter()
+        .map(OsString::from)
+        .collect();
+        names.push(OsString::from(format!("{}.sav", "long".repeat(60))));
+        #[cfg(unix)]
+        if spec.non_utf8_names {
+            use std::os::unix::ffi::OsStringExt;
+            names.push(OsString::from_vec(b"latin1-\xe9t\xe9.sav".to_vec()));
+        }
+        for name in names {
+            let dir = &dirs[rng.below(dirs.len())];
+            let size = if name == "empty.sav" {
+                0
+            } else {
+                rng.below(1024)
+            };
+            write_file(&save_dir.join(dir).join(name), &mut rng, size)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_file(path: &Path, rng: &mut Rng, size: usize) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    let content: Vec<u8> = (0..size).map(|_| rng.next() as u8).collect();
+    fs::write(path, content).with_context(|| format!("Failed to write file: {}", path.display()))
+}
+
+/// xorshift64, enough to make varied but reproducible trees without a dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound.max(1) as u64) as usize
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// A file that could not be copied or moved, for the causes the user can do something
+/// about. Other failures keep the generic "Failed to copy file" message.
+#[derive(Debug)]
+pub enum FileError {
+    PermissionDenied {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// A rename between two filesystems, which has to be done as a copy instead
+    CrossDevice {
+        from: PathBuf,
+        to: PathBuf,
+        source: io::Error,
+    },
+    PathTooLong {
+        path: PathBuf,
+        source: io::Error,
+    },
+    DiskFull {
+        path: PathBuf,
+        source: io::Error,
+    },
+}
+
+impl FileError {
+    /// What the user can do about the error.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            FileError::PermissionDenied { .. } => {
+                "check that the file and its directory belong to you and are not read-only, \
+                 or that no other program holds it open"
+            }
+            FileError::CrossDevice { .. } => {
+                "keep the backup root and its .staging directory on the same filesystem"
+            }
+            FileError::PathTooLong { .. } => {
+                "move the backup root to a shorter path, or enable long paths on Windows"
+            }
+            FileError::DiskFull { .. } => {
+                "free up space or raise the quota, e.g. with `cartridge prune` or `cartridge trash purge`"
+            }
+        }
+    }
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::PermissionDenied { path, .. } => {
+                write!(f, "Permission denied: {}", path.display())?
+            }
+            FileError::CrossDevice { from, to, .. } => write!(
+                f,
+                "Cannot move across filesystems: {} -> {}",
+                from.display(),
+                to.display()
+            )?,
+            FileError::PathTooLong { path, .. } => write!(f, "Path too long: {}", path.display())?,
+            FileError::DiskFull { path, .. } => write!(f, "No space left for: {}", path.display())?,
+        }
+        write!(f, " (hint: {})", self.hint())
+    }
+}
+
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FileError::PermissionDenied { source, .. }
+            | FileError::CrossDevice { source, .. }
+            | FileError::PathTooLong { source, .. }
+            | FileError::DiskFull { source, .. } => Some(source),
+        }
+    }
+}
+
+/// A configured save path that is not on disk, e.g. because the game was uninstalled or its
+/// saves moved.
+#[derive(Debug)]
+pub struct MissingSavePath {
+    pub path: PathBuf,
+}
+
+impl fmt::Display for MissingSavePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Save path does not exist: {}", self.path.display())
+    }
+}
+
+impl std::error::Error for MissingSavePath {}
+
+/// Turns an error copying `source` to `dest` into a [`FileError`] where its cause is known.
+pub(crate) fn copy_error(error: io::Error, source: &Path, dest: &Path) -> anyhow::Error {
+    classify(error, source, dest).unwrap_or_else(|error| {
+        anyhow::Error::new(error).context(format!("Failed to copy file: {}", source.display()))
+    })
+}
+
+/// Turns an error moving `source` to `dest` into a [`FileError`] where its cause is known.
+pub(crate) fn rename_error(error: io::Error, source: &Path, dest: &Path) -> anyhow::Error {
+    classify(error, source, dest).unwrap_or_else(|error| {
+        anyhow::Error::new(error).context(format!(
+            "Failed to move {} -> {}",
+            source.display(),
+            dest.display()
+        ))
+    })
+}
+
+fn classify(error: io::Error, source: &Path, dest: &Path) -> Result<anyhow::Error, io::Error> {
+    // The error does not say which side failed. If the source can be read, it was the
+    // destination
+    let path = || match fs::File::open(source) {
+        Err(e) if e.kind() != ErrorKind::IsADirectory => source.to_path_buf(),
+        _ => dest.to_path_buf(),
+    };
+    let error = match error.kind() {
+        ErrorKind::PermissionDenied | ErrorKind::ReadOnlyFilesystem => {
+            FileError::PermissionDenied {
+                path: path(),
+                source: error,
+            }
+        }
+        ErrorKind::CrossesDevices => FileError::CrossDevice {
+            from: source.to_path_buf(),
+            to: dest.to_path_buf(),
+            source: error,
+        },
+        ErrorKind::InvalidFilename => FileError::PathTooLong {
+            path: path(),
+            source: error,
+        },
+        ErrorKind::StorageFull | ErrorKind::QuotaExceeded => FileError::DiskFull {
+            path: dest.to_path_buf(),
+            source: error,
+        },
+        _ => return Err(error),
+    };
+    Ok(error.into())
+}
+
+/// Whether the error, or any error that caused it, is the destination running out of space.
+pub fn is_disk_full(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if let Some(FileError::DiskFull { .. }) = cause.downcast_ref::<FileError>() {
+            return true;
+        }
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|e| matches!(e.kind(), ErrorKind::StorageFull | ErrorKind::QuotaExceeded))
+    })
+}
@@ -0,0 +1,146 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::durable;
+use crate::manifest::Manifest;
+use crate::{Game, GameBackup, format_size};
+
+/// Append-only record of the size of every backup, one JSON object per line, kept in the
+/// backup root.
+pub const SIZES_FILE: &str = "backup-sizes.jsonl";
+const DEFAULT_SIZE_ALERT_FACTOR: f64 = 4.0;
+/// Saves smaller than this before and after are never flagged, small saves jump around.
+const MIN_ALERT_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SizeRecord {
+    pub time: DateTime<Utc>,
+    pub game: String,
+    pub files: usize,
+    /// Size of the saves in the backup, before compression
+    pub bytes: u64,
+}
+
+impl GameBackup {
+    /// Records the size of the backup just made and warns when it grew or shrank by more
+    /// than `size_alert_factor` since the previous one, which usually means a save path
+    /// points at the wrong directory or the saves were reset.
+    pub(crate) fn check_backup_size(&self, game: &Game) {
+        let manifest = match Manifest::load(&self.backup_root.join(&game.name)) {
+            Ok(Some(manifest)) => manifest,
+            Ok(None) => return,
+            Err(e) => {
+                log::warn!("Could not read the manifest of '{}': {:#}", game.name, e);
+                return;
+            }
+        };
+        let record = SizeRecord {
+            time: Utc::now(),
+            game: game.name.clone(),
+            files: manifest.files.len(),
+            bytes: manifest.files.values().map(|entry| entry.size).sum(),
+        };
+
+        match self.backup_sizes(&game.name) {
+            Ok(sizes) => {
+                if let Some(previous) = sizes.last() {
+                    self.compare_sizes(game, previous, &record);
+                }
+            }
+            Err(e) => log::warn!("Could not read earlier backup sizes: {:#}", e),
+        }
+        if let Err(e) = append(&self.backup_root, &record) {
+            log::error!(
+                "Failed to record the backup size of '{}': {:#}",
+                game.name,
+                e
+            );
+        }
+    }
+
+    /// Recorded sizes of a game's backups, oldest first.
+    pub fn backup_sizes(&self, game_name: &str) -> Result<Vec<SizeRecord>> {
+        Ok(load(&self.backup_root)?
+            .into_iter()
+            .filter(|record| record.game == game_name)
+            .collect())
+    }
+
+    fn compare_sizes(&self, game: &Game, previous: &SizeRecord, current: &SizeRecord) {
+        let factor = game
+            .size_alert_factor
+            .or(self.config.size_alert_factor)
+            .unwrap_or(DEFAULT_SIZE_ALERT_FACTOR);
+        // A factor of 1 or less turns the check off
+        if factor <= 1.0 || previous.bytes.max(current.bytes) < MIN_ALERT_SIZE {
+            return;
+        }
+
+        let (smaller, larger) = (
+            previous.bytes.min(current.bytes).max(1) as f64,
+            previous.bytes.max(current.bytes) as f64,
+        );
+        if larger / smaller < factor {
+            return;
+        }
+        if current.bytes > previous.bytes {
+            log::warn!(
+                "Saves of '{}' grew from {} to {} ({} files) since the last backup. \
+                 Check that its save paths do not include more than the saves",
+                game.name,
+                format_size(previous.bytes),
+                format_size(current.bytes),
+                current.files
+            );
+        } else {
+            log::warn!(
+                "Saves of '{}' shrank from {} to {} ({} files) since the last backup. \
+                 They may have been reset or corrupted, see `cartridge snapshots {}` for \
+                 older backups",
+                game.name,
+                format_size(previous.bytes),
+                format_size(current.bytes),
+                current.files,
+                game.name
+            );
+        }
+    }
+}
+
+fn append(backup_root: &Path, record: &SizeRecord) -> Result<()> {
+    let path = backup_root.join(SIZES_FILE);
+    let line = serde_json::to_string(record).with_context(|| "Failed to serialize backup size")?;
+
+    durable::append_line(&path, &line)
+        .with_context(|| format!("Failed to write backup size log: {}", path.display()))
+}
+
+/// Reads the backup size log, skipping lines that cannot be parsed.
+fn load(backup_root: &Path) -> Result<Vec<SizeRecord>> {
+    let path = backup_root.join(SIZES_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read backup size log: {}", path.display()))?;
+
+    let mut records = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(e) => log::warn!(
+                "Skipping unreadable line {} of {}: {}",
+                i + 1,
+                path.display(),
+                e
+            ),
+        }
+    }
+    Ok(records)
+}
@@ -0,0 +1,136 @@
+use fluent::FluentResource;
+use fluent::concurrent::FluentBundle;
+use std::sync::OnceLock;
+use unic_langid::LanguageIdentifier;
+
+pub use fluent::FluentArgs;
+
+/// Languages the CLI output is translated into, with their Fluent messages. The first one
+/// is the fallback for messages missing from a translation.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+];
+
+/// Environment variables consulted for the language, in order.
+const LANGUAGE_VARIABLES: &[&str] = &["CARTRIDGE_LANG", "LC_ALL", "LC_MESSAGES", "LANG"];
+
+static LOCALIZER: OnceLock<Localizer> = OnceLock::new();
+
+struct Localizer {
+    language: &'static str,
+    bundle: FluentBundle<FluentResource>,
+    fallback: Option<FluentBundle<FluentResource>>,
+}
+
+impl Localizer {
+    fn new(language: &'static str) -> Self {
+        let (fallback_language, _) = LANGUAGES[0];
+        Self {
+            language,
+            bundle: bundle(language),
+            fallback: (language != fallback_language).then(|| bundle(fallback_language)),
+        }
+    }
+
+    fn translate(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in std::iter::once(&self.bundle).chain(&self.fallback) {
+            let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+                continue;
+            };
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, args, &mut errors);
+            if !errors.is_empty() {
+                log::debug!("Failed to format message '{}': {:?}", id, errors);
+            }
+            return text.into_owned();
+        }
+        log::debug!("No translation for message '{}'", id);
+        id.to_string()
+    }
+}
+
+/// Selects the language of the CLI output: the requested one, or the one from
+/// `CARTRIDGE_LANG` or the usual locale variables. Falls back to English for languages
+/// without a translation. Only the first call has an effect.
+pub fn init(requested: Option<&str>) {
+    LOCALIZER.get_or_init(|| Localizer::new(detect_language(requested)));
+}
+
+/// Language the CLI output is shown in.
+pub fn language() -> &'static str {
+    localizer().language
+}
+
+/// Text of a message in the selected language. Use the [`t!`](crate::t) macro to pass
+/// arguments.
+pub fn translate(id: &str, args: Option<&FluentArgs>) -> String {
+    localizer().translate(id, args)
+}
+
+fn localizer() -> &'static Localizer {
+    LOCALIZER.get_or_init(|| Localizer::new(detect_language(None)))
+}
+
+fn detect_language(requested: Option<&str>) -> &'static str {
+    if let Some(requested) = requested
+        && supported_language(requested).is_none()
+    {
+        log::warn!("No translation for language '{}', using English", requested);
+    }
+    requested
+        .map(str::to_string)
+        .or_else(|| {
+            LANGUAGE_VARIABLES
+                .iter()
+                .filter_map(|name| std::env::var(name).ok())
+                .find(|value| !value.is_empty())
+        })
+        .and_then(|value| supported_language(&value))
+        .unwrap_or(LANGUAGES[0].0)
+}
+
+/// Supported language of a locale such as `de_DE.UTF-8`.
+fn supported_language(locale: &str) -> Option<&'static str> {
+    let language = locale
+        .split(['_', '-', '.', '@'])
+        .next()?
+        .to_ascii_lowercase();
+    LANGUAGES
+        .iter()
+        .map(|(supported, _)| *supported)
+        .find(|supported| *supported == language)
+}
+
+fn bundle(language: &str) -> FluentBundle<FluentResource> {
+    let (_, source) = LANGUAGES
+        .iter()
+        .find(|(supported, _)| *supported == language)
+        .unwrap_or(&LANGUAGES[0]);
+    let id: LanguageIdentifier = language.parse().unwrap_or_default();
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // Unicode isolation marks around arguments show up as garbage in many terminals
+    bundle.set_use_isolating(false);
+    let resource =
+        FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+            log::warn!("Errors in the {} translation: {:?}", language, errors);
+            resource
+        });
+    if let Err(errors) = bundle.add_resource(resource) {
+        log::warn!("Errors in the {} translation: {:?}", language, errors);
+    }
+    bundle
+}
+
+/// Translates a message, with named arguments: `t!("snapshots-title", game = name)`.
+#[macro_export]
+macro_rules! t {
+    ($id:literal) => {
+        $crate::i18n::translate($id, None)
+    };
+    ($id:literal, $($key:ident = $value:expr),+ $(,)?) => {{
+        let mut args = $crate::i18n::FluentArgs::new();
+        $(args.set(stringify!($key), $value);)+
+        $crate::i18n::translate($id, Some(&args))
+    }};
+}
@@ -0,0 +1,277 @@
+use anyhow::{Context, Result, anyhow};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table, Value};
+
+use crate::durable;
+use crate::{GameBackup, SavePath};
+
+/// Prefixes shallower than this many components (`/home`, `C:/Users`) are too generic to suggest.
+const MIN_COMPONENTS: usize = 2;
+
+/// A path prefix shared by configured save paths that could be written as a variable.
+#[derive(Debug)]
+pub struct VariableSuggestion {
+    pub name: String,
+    /// The prefix as written in the configuration
+    pub prefix: String,
+    /// Value of the `[[var]]` entry to add, or `None` when an existing variable already holds
+    /// the prefix
+    pub value: Option<String>,
+    /// Configured paths that start with the prefix
+    pub paths: Vec<String>,
+}
+
+impl GameBackup {
+    /// Looks for directories shared by the absolute save paths of the configuration and
+    /// suggests variables for them, reusing existing variables where their value matches.
+    pub fn infer_variables(&self) -> Vec<VariableSuggestion> {
+        let paths: BTreeSet<&str> = self
+            .config
+            .games
+            .iter()
+            .flat_map(|game| &game.saves)
+            .flat_map(|save_location| match &save_location.path {
+                SavePath::Any(path) => vec![path.as_str()],
+                SavePath::PerPlatform(paths) => paths.values().map(String::as_str).collect(),
+            })
+            .filter(|path| is_absolute(path))
+            .collect();
+
+        let mut candidates: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for path in &paths {
+            for prefix in prefixes(path) {
+                candidates.entry(prefix).or_default().insert(path);
+            }
+        }
+
+        let existing: BTreeMap<&str, &str> = self
+            .variables
+            .iter()
+            .filter_map(|(name, value)| Some((trim_separators(value.to_str()?), name.as_str())))
+            .collect();
+
+        let qualifying: Vec<(&str, &BTreeSet<&str>)> = candidates
+            .iter()
+            .filter(|(prefix, covered)| {
+                let threshold = if existing.contains_key(*prefix) { 1 } else { 2 };
+                covered.len() >= threshold
+            })
+            .map(|(prefix, covered)| (*prefix, covered))
+            .collect();
+
+        // Existing variables are always worth using, new ones only for the deepest prefix
+        // shared by a group of paths
+        let mut chosen: Vec<(&str, &BTreeSet<&str>)> = qualifying
+            .iter()
+            .filter(|(prefix, covered)| {
+                existing.contains_key(prefix)
+                    || !qualifying.iter().any(|(other, other_covered)| {
+                        other.len() > prefix.len()
+                            && is_parent(prefix, other)
+                            && other_covered == covered
+                    })
+            })
+            .copied()
+            .collect();
+
+        let mut taken: BTreeSet<String> = self.variables.keys().cloned().collect();
+        let mut suggestions: Vec<VariableSuggestion> = Vec::new();
+        // Shorter prefixes first, so longer ones can be written in terms of them
+        chosen.sort_by_key(|(prefix, _)| prefix.len());
+        for (prefix, covered) in chosen {
+            let (name, value) = match existing.get(prefix) {
+                Some(name) => (name.to_string(), None),
+                None => {
+                    let name = unique_name(variable_name(prefix), &taken);
+                    let value = substitute(prefix, &suggestions).unwrap_or(prefix.to_string());
+                    (name, Some(value))
+                }
+            };
+            taken.insert(name.clone());
+            suggestions.push(VariableSuggestion {
+                name,
+                prefix: prefix.to_string(),
+                value,
+                paths: covered.iter().map(|path| path.to_string()).collect(),
+            });
+        }
+
+        suggestions
+    }
+
+    /// Adds the suggested variables to the config file and rewrites save paths to use them,
+    /// keeping the rest of the file as it is. Returns the number of rewritten paths.
+    pub fn apply_variable_suggestions(
+        &self,
+        config_path: &Path,
+        suggestions: &[VariableSuggestion],
+    ) -> Result<usize> {
+        self.ensure_unlocked("rewrite the configuration")?;
+
+        let content = fs::read_to_string(config_path)
+            .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+        let mut document: DocumentMut = content
+            .parse()
+            .with_context(|| "Failed to parse TOML configuration")?;
+
+        let new_variables: Vec<&VariableSuggestion> = suggestions
+            .iter()
+            .filter(|suggestion| suggestion.value.is_some())
+            .collect();
+        if !new_variables.is_empty() {
+            let variables = document
+                .entry("var")
+                .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+                .as_array_of_tables_mut()
+                .ok_or_else(|| anyhow!("Expected `var` to be an array of tables"))?;
+            for suggestion in new_variables {
+                let mut table = Table::new();
+                table["name"] = toml_edit::value(&suggestion.name);
+                table["value"] = toml_edit::value(suggestion.value.as_deref().unwrap_or_default());
+                variables.push(table);
+            }
+        }
+
+        let mut rewritten = 0;
+        if let Some(games) = document
+            .get_mut("game")
+            .and_then(Item::as_array_of_tables_mut)
+        {
+            for game in games.iter_mut() {
+                let Some(saves) = game.get_mut("save").and_then(Item::as_array_of_tables_mut)
+                else {
+                    continue;
+                };
+                for save in saves.iter_mut() {
+                    if let Some(path) = save.get_mut("path") {
+                        rewritten += rewrite_paths(path, suggestions);
+                    }
+                }
+            }
+        }
+
+        durable::write_atomic(config_path, document.to_string())
+            .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
+        log::info!(
+            "Rewrote {} save paths in: {}",
+            rewritten,
+            config_path.display()
+        );
+        Ok(rewritten)
+    }
+}
+
+/// Rewrites a `path` entry, which is either a string or a table of per-platform strings.
+fn rewrite_paths(item: &mut Item, suggestions: &[VariableSuggestion]) -> usize {
+    if let Some(table) = item.as_table_like_mut() {
+        return table
+            .iter_mut()
+            .map(|(_, item)| rewrite_paths(item, suggestions))
+            .sum();
+    }
+
+    let Some(value) = item.as_value_mut() else {
+        return 0;
+    };
+    let Some(path) = value
+        .as_str()
+        .and_then(|path| substitute(path, suggestions))
+    else {
+        return 0;
+    };
+    let decor = value.decor().clone();
+    *value = Value::from(path);
+    *value.decor_mut() = decor;
+    1
+}
+
+/// Writes a path in terms of the longest suggested prefix it starts with.
+fn substitute(path: &str, suggestions: &[VariableSuggestion]) -> Option<String> {
+    suggestions
+        .iter()
+        .filter(|suggestion| is_parent(&suggestion.prefix, path))
+        .max_by_key(|suggestion| suggestion.prefix.len())
+        .map(|suggestion| {
+            format!(
+                "${{{}}}{}",
+                suggestion.name,
+                &path[suggestion.prefix.len()..]
+            )
+        })
+}
+
+pub(crate) fn is_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with('/')
+        || path.starts_with("\\\\")
+        || (bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && is_separator(bytes[2] as char))
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+fn trim_separators(path: &str) -> &str {
+    path.trim_end_matches(is_separator)
+}
+
+/// Whether `path` is `prefix` itself or lies below it.
+fn is_parent(prefix: &str, path: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(is_separator))
+}
+
+/// Every ancestor directory of a path deep enough to be suggested, and the path itself.
+fn prefixes(path: &str) -> Vec<&str> {
+    let path = trim_separators(path);
+    let mut prefixes = Vec::new();
+    let mut components = 0;
+    let mut in_component = false;
+    for (i, c) in path.char_indices() {
+        if is_separator(c) {
+            if in_component && components >= MIN_COMPONENTS {
+                prefixes.push(&path[..i]);
+            }
+            in_component = false;
+        } else if !in_component {
+            in_component = true;
+            components += 1;
+        }
+    }
+    if components >= MIN_COMPONENTS {
+        prefixes.push(path);
+    }
+    prefixes
+}
+
+fn variable_name(prefix: &str) -> String {
+    let last = prefix.rsplit(is_separator).next().unwrap_or_default();
+    let name: String = last
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let name = name.trim_matches('_').to_string();
+    if name.is_empty() {
+        "path".to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("dir_{}", name)
+    } else {
+        name
+    }
+}
+
+fn unique_name(name: String, taken: &BTreeSet<String>) -> String {
+    if !taken.contains(&name) {
+        return name;
+    }
+    (2..)
+        .map(|i| format!("{}_{}", name, i))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or(name)
+}
@@ -0,0 +1,75 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::archive::ArchiveFormat;
+
+/// Capabilities of this build, reported by `cartridge info` for wrapper scripts.
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub platform: Platform,
+    /// Storage backends backups can be written to
+    pub backends: BTreeMap<&'static str, bool>,
+    /// Optional functionality compiled into this binary
+    pub features: BTreeMap<&'static str, bool>,
+    pub formats: Vec<&'static str>,
+    pub paths: DefaultPaths,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Platform {
+    pub os: &'static str,
+    pub family: &'static str,
+    pub arch: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DefaultPaths {
+    pub home: Option<PathBuf>,
+    pub config_file: Option<PathBuf>,
+    pub backup_root: Option<PathBuf>,
+}
+
+impl BuildInfo {
+    /// Collects build information, using `config_path` to derive the backup location if known.
+    pub fn new(config_path: Option<&Path>) -> Self {
+        let backends = BTreeMap::from([("local", true), ("s3", false), ("sftp", false)]);
+        let features = BTreeMap::from([
+            ("encryption", false),
+            ("ffi", cfg!(feature = "ffi")),
+            ("python", cfg!(feature = "python")),
+            ("self-update", cfg!(feature = "self-update")),
+            ("sqlite", cfg!(feature = "sqlite")),
+            ("split-volumes", true),
+            ("zip", cfg!(feature = "zip")),
+            ("tar-zst", cfg!(feature = "tar-zst")),
+        ]);
+
+        Self {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            platform: Platform {
+                os: std::env::consts::OS,
+                family: std::env::consts::FAMILY,
+                arch: std::env::consts::ARCH,
+            },
+            backends,
+            features,
+            formats: std::iter::once("dir")
+                .chain(
+                    [ArchiveFormat::Zip, ArchiveFormat::TarZst]
+                        .into_iter()
+                        .filter(ArchiveFormat::is_available)
+                        .map(|format| format.extension()),
+                )
+                .collect(),
+            paths: DefaultPaths {
+                home: dirs::home_dir(),
+                config_file: config_path.map(Path::to_path_buf),
+                backup_root: config_path.map(crate::backup_root),
+            },
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::backup::BackupWriter;
+use crate::manifest::Manifest;
+use crate::{Game, GameBackup, STAGING_DIR, archive, archive_path, stored_format};
+
+/// What a save location holds. Kinds can be backed up, restored and retained separately, so
+/// settings can be restored on a new install without bringing back old saves.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SaveKind {
+    #[default]
+    Save,
+    Config,
+    /// Installed mods; only their names and versions are kept unless `files` selects some
+    Mods,
+}
+
+impl SaveKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SaveKind::Save => "save",
+            SaveKind::Config => "config",
+            SaveKind::Mods => "mods",
+        }
+    }
+}
+
+impl FromStr for SaveKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "save" => Ok(SaveKind::Save),
+            "config" => Ok(SaveKind::Config),
+            "mods" => Ok(SaveKind::Mods),
+            _ => Err(anyhow!(
+                "Invalid kind '{}' (expected save, config or mods)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SaveKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl GameBackup {
+    /// Copies a save location from the current backup into a new one unchanged, so backing up
+    /// one kind keeps the locations of other kinds as they were.
+    pub(crate) fn carry_over_location(
+        &self,
+        game: &Game,
+        index: usize,
+        game_backup_dir: &Path,
+        manifest: &Manifest,
+        writer: &mut BackupWriter,
+    ) -> Result<()> {
+        let Some(prefix) = manifest.location(index) else {
+            log::warn!(
+                "Save location {} of '{}' is not in the current backup, leaving it out",
+                index + 1,
+                game.name
+            );
+            return Ok(());
+        };
+        log::info!("Keeping {} from the current backup", prefix.display());
+        let rules = self.ignore_rules(index, &game.saves[index], Some(manifest));
+        writer.record_location(index, &prefix, rules);
+        if let Some(mods) = manifest.mods(index) {
+            writer.record_mods(index, mods);
+        }
+        if game.saves[index].is_mod_list_only() {
+            return Ok(());
+        }
+
+        let Some(archive_format) = stored_format(game_backup_dir, Some(manifest)).archive() else {
+            return carry_over_dir(&game_backup_dir.join(&prefix), &prefix, manifest, writer);
+        };
+
+        let extract_dir = self
+            .backup_root
+            .join(STAGING_DIR)
+            .join(format!("{}.carry", game.name));
+        if extract_dir.exists() {
+            fs::remove_dir_all(&extract_dir).with_context(|| {
+                format!(
+                    "Failed to clean extraction directory: {}",
+                    extract_dir.display()
+                )
+            })?;
+        }
+        fs::create_dir_all(&extract_dir).with_context(|| {
+            format!(
+                "Failed to create extraction directory: {}",
+                extract_dir.display()
+            )
+        })?;
+
+        let dictionary = archive::read_dictionary(game_backup_dir)?;
+        let result = archive::extract(
+            &archive_path(game_backup_dir, archive_format),
+            archive_format,
+            dictionary.as_deref(),
+            &prefix,
+            &extract_dir,
+        )
+        .and_then(|_| carry_over_dir(&extract_dir, &prefix, manifest, writer));
+        if let Err(e) = fs::remove_dir_all(&extract_dir) {
+            log::warn!(
+                "Failed to remove extraction directory {}: {}",
+                extract_dir.display(),
+                e
+            );
+        }
+        result
+    }
+}
+
+fn carry_over_dir(
+    source: &Path,
+    name: &Path,
+    manifest: &Manifest,
+    writer: &mut BackupWriter,
+) -> Result<()> {
+    writer.add_dir(name)?;
+    if !source.exists() {
+        return Ok(());
+    }
+
+    let entries = fs::read_dir(source)
+        .with_context(|| format!("Failed to read directory: {}", source.display()))?;
+    for entry in entries {
+        let entry = entry
+            .with_context(|| format!("Failed to read directory entry in: {}", source.display()))?;
+        let path = entry.path();
+        let entry_name = name.join(entry.file_name());
+
+        if path.is_dir() {
+            carry_over_dir(&path, &entry_name, manifest, writer)?;
+        } else {
+            match manifest.get(&entry_name) {
+                Some(manifest_entry) => {
+                    writer.carry_file(&path, &entry_name, manifest_entry.clone())?
+                }
+                None => writer.add_file(&path, &entry_name)?,
+            }
+        }
+    }
+    Ok(())
+}
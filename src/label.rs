@@ -0,0 +1,75 @@
+use anyhow::{Context, Result, anyhow};
+use std::process::Stdio;
+use std::time::Duration;
+
+use crate::process;
+use crate::{Game, GameBackup};
+
+/// How long a `label_command` may run before it is stopped and the backup goes on unlabeled.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+/// Labels are cut to this many characters so they fit a snapshot listing.
+const MAX_LABEL_LENGTH: usize = 120;
+
+/// Where the label of the backups taken in this run comes from.
+#[derive(Debug, Clone)]
+pub enum BackupLabel {
+    /// The same text for every backup
+    Message(String),
+    /// The first line printed by each game's `label_command`
+    Hook,
+}
+
+impl GameBackup {
+    /// Labels the backups taken from now on, shown by `snapshots`.
+    pub fn label_backups(&mut self, label: BackupLabel) {
+        self.backup_label = Some(label);
+    }
+
+    /// Label for a backup of the game that is being taken. A failing hook only costs the
+    /// label, not the backup.
+    pub(crate) fn backup_label(&self, game: &Game) -> Option<String> {
+        let label = match self.backup_label.as_ref()? {
+            BackupLabel::Message(message) => Some(message.clone()),
+            BackupLabel::Hook => match self.run_label_hook(game) {
+                Ok(label) => label,
+                Err(e) => {
+                    log::warn!("Could not label the backup of '{}': {:#}", game.name, e);
+                    None
+                }
+            },
+        }?;
+        let label = label.lines().next().unwrap_or_default().trim();
+        (!label.is_empty()).then(|| label.chars().take(MAX_LABEL_LENGTH).collect())
+    }
+
+    /// Runs the game's `label_command` with `CARTRIDGE_GAME` and `CARTRIDGE_SAVE_PATHS` set,
+    /// and returns what it printed.
+    fn run_label_hook(&self, game: &Game) -> Result<Option<String>> {
+        let Some(label_command) = &game.label_command else {
+            log::debug!("No label_command for '{}', not labeling it", game.name);
+            return Ok(None);
+        };
+
+        let mut save_paths = Vec::new();
+        for save_location in &game.saves {
+            save_paths.extend(self.save_path(save_location)?);
+        }
+        let mut command = self.command(label_command)?;
+        command
+            .env("CARTRIDGE_GAME", &game.name)
+            .env(
+                "CARTRIDGE_SAVE_PATHS",
+                std::env::join_paths(&save_paths)
+                    .with_context(|| "Save path cannot be passed to label_command")?,
+            )
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit());
+        log::debug!("Running label hook of '{}': {:?}", game.name, command);
+
+        let (status, output) = process::run_hook(&mut command, "label_command", HOOK_TIMEOUT)?;
+        if !status.success() {
+            return Err(anyhow!("label_command exited with {}", status));
+        }
+        Ok(Some(String::from_utf8_lossy(&output).to_string()))
+    }
+}
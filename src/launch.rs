@@ -0,0 +1,276 @@
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::manifest::{self, Manifest};
+use crate::plan::{ActionKind, RestoreReport};
+use crate::validate::ValidationIssue;
+use crate::{GameBackup, process};
+
+/// How often `run` checks whether the game's processes are still running.
+const PROCESS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// How long `run` waits for the game's processes to show up after its launcher returned.
+const PROCESS_START_GRACE: Duration = Duration::from_secs(30);
+
+/// External command from the config: a command line such as `"heroic launch xyz"`, or the
+/// program and its arguments as a list.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum CommandLine {
+    Line(String),
+    Args(Vec<String>),
+}
+
+impl CommandLine {
+    fn args(&self) -> Result<Vec<String>> {
+        match self {
+            CommandLine::Line(line) => split_command_line(line),
+            CommandLine::Args(args) => Ok(args.clone()),
+        }
+    }
+}
+
+/// Result of checking the saves a restore wrote before the game is started on them.
+#[derive(Debug, Default)]
+pub struct RestoreCheck {
+    pub game: String,
+    /// Restored files whose content matches the backup
+    pub verified: usize,
+    /// Restored files that are missing or differ from the backup
+    pub mismatched: Vec<PathBuf>,
+    /// Problems the game's validators found in the live saves
+    pub invalid: Vec<ValidationIssue>,
+}
+
+impl RestoreCheck {
+    pub fn is_ok(&self) -> bool {
+        self.mismatched.is_empty() && self.invalid.is_empty()
+    }
+}
+
+impl GameBackup {
+    /// Checks the files written or kept by a restore against the manifest of the backup they
+    /// came from, and runs the game's validators on its live saves.
+    pub fn check_restore(
+        &self,
+        report: &RestoreReport,
+        snapshot: Option<&str>,
+    ) -> Result<RestoreCheck> {
+        let game = self.find_game(&report.game)?;
+        let backup_dir = self.snapshot_dir(&game.name, snapshot)?;
+        let manifest = Manifest::load(&backup_dir)?.ok_or_else(|| {
+            anyhow!(
+                "No manifest found for the backup of '{}', cannot verify the restore",
+                game.name
+            )
+        })?;
+
+        // Live path of each save location and the path it is stored under in the backup,
+        // deepest first so nested locations are matched before their parents
+        let mut locations = Vec::new();
+        for (i, save_location) in game.saves.iter().enumerate() {
+            if let Some(path) = self.save_path(save_location)? {
+                let prefix = self.backup_prefix(i, &path, Some(&manifest))?;
+                locations.push((path, prefix));
+            }
+        }
+        locations.sort_by_key(|(path, _)| std::cmp::Reverse(path.components().count()));
+
+        let mut check = RestoreCheck {
+            game: game.name.clone(),
+            ..RestoreCheck::default()
+        };
+        for action in &report.actions {
+            if !matches!(
+                action.kind,
+                ActionKind::Create
+                    | ActionKind::Overwrite
+                    | ActionKind::BackupExisting { .. }
+                    | ActionKind::Unchanged
+            ) {
+                continue;
+            }
+            let entry = locations.iter().find_map(|(path, prefix)| {
+                let relative = action.dest.strip_prefix(path).ok()?;
+                manifest.get(&prefix.join(relative))
+            });
+            let Some(entry) = entry else {
+                log::debug!(
+                    "No manifest entry for restored file, not verifying it: {}",
+                    action.dest.display()
+                );
+                continue;
+            };
+
+            let matches = fs::metadata(&action.dest).is_ok_and(|metadata| {
+                metadata.len() == entry.size
+                    && manifest::hash_file(&action.dest).is_ok_and(|hash| hash == entry.hash)
+            });
+            if matches {
+                check.verified += 1;
+            } else {
+                log::warn!(
+                    "Restored file does not match the backup: {}",
+                    action.dest.display()
+                );
+                check.mismatched.push(action.dest.clone());
+            }
+        }
+
+        check.invalid = self.validate_saves(game)?;
+        Ok(check)
+    }
+
+    /// Fails if the game has no way to be launched, e.g. before restoring it for launch.
+    pub fn check_launch(&self, game_name: &str) -> Result<()> {
+        self.launch_command(game_name).map(|_| ())
+    }
+
+    /// Starts a game with its `launch` command, or through Steam with its `steam_app_id`,
+    /// without waiting for it to exit.
+    pub fn launch_game(&self, game_name: &str) -> Result<()> {
+        let mut command = self.launch_command(game_name)?;
+        log::info!("Launching '{}': {:?}", game_name, command);
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to launch '{}'", game_name))?;
+        Ok(())
+    }
+
+    /// Starts a game and waits until it exits: first its launch command, then any of its
+    /// `process` names, since launchers such as Steam or Heroic return while the game runs.
+    pub fn run_game(&self, game_name: &str) -> Result<()> {
+        let game = self.find_game(game_name)?;
+        let mut command = self.launch_command(game_name)?;
+        log::info!("Running '{}': {:?}", game.name, command);
+        let status = command
+            .status()
+            .with_context(|| format!("Failed to launch '{}'", game.name))?;
+        if !status.success() {
+            log::warn!("Launch command of '{}' exited with {}", game.name, status);
+        }
+
+        let launched = Instant::now();
+        let mut seen = false;
+        loop {
+            let running = process::running(&game.processes);
+            if !running.is_empty() {
+                seen = true;
+                log::debug!("Waiting for {} to exit", running.join(", "));
+            } else if seen || game.processes.is_empty() || launched.elapsed() >= PROCESS_START_GRACE
+            {
+                break;
+            }
+            thread::sleep(PROCESS_POLL_INTERVAL);
+        }
+        log::info!("'{}' exited", game.name);
+        Ok(())
+    }
+
+    fn launch_command(&self, game_name: &str) -> Result<Command> {
+        let game = self.find_game(game_name)?;
+        if let Some(launch) = &game.launch {
+            let mut command = self
+                .command(launch)
+                .with_context(|| format!("Invalid launch command of '{}'", game.name))?;
+            if let Some(dir) = &game.launch_dir {
+                command.current_dir(self.expand_path(dir)?);
+            }
+            for (name, value) in &game.launch_env {
+                command.env(name, self.expand_variables_os(value)?);
+            }
+            Ok(command)
+        } else if let Some(app_id) = game.steam_app_id {
+            Ok(steam_command(app_id))
+        } else {
+            Err(anyhow!(
+                "Don't know how to start '{}'. Set `launch` or `steam_app_id` for it in the config",
+                game.name
+            ))
+        }
+    }
+
+    /// Builds a command from the config, with variables expanded in each of its words.
+    pub(crate) fn command(&self, line: &CommandLine) -> Result<Command> {
+        let args = line.args()?;
+        let (program, args) = args
+            .split_first()
+            .ok_or_else(|| anyhow!("The command is empty"))?;
+        let mut command = Command::new(self.expand_variables_os(program)?);
+        for arg in args {
+            command.arg(self.expand_variables_os(arg)?);
+        }
+        Ok(command)
+    }
+}
+
+/// Asks the Steam client to start a game, through the handler of `steam://` links.
+fn steam_command(app_id: u32) -> Command {
+    let url = format!("steam://rungameid/{}", app_id);
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]).arg(url);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg(url);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(url);
+        command
+    }
+}
+
+/// Splits a command line into words like a POSIX shell does, honouring single and double
+/// quotes and, outside Windows, backslash escapes. Nothing else, such as `$VAR` or globs, is interpreted.
+fn split_command_line(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow!("Unterminated quote in: {}", line)),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(anyhow!("Unterminated quote in: {}", line)),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow!("Unterminated quote in: {}", line)),
+                    }
+                }
+            }
+            // Backslashes separate paths on Windows
+            '\\' if !cfg!(windows) => word.get_or_insert_default().extend(chars.next()),
+            c => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
@@ -1,13 +1,176 @@
 use anyhow::{Context, Result, anyhow};
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{OsStr, OsString};
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+pub mod archive;
+pub mod audit;
+mod backup;
+pub mod chain;
+mod cloud;
+pub mod config_audit;
+pub mod conflict;
+pub mod coop;
+mod daemon;
+pub mod dedup;
+pub mod delta;
+mod dictionary;
+mod diff;
+mod doctor;
+pub mod drive;
+mod durable;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "test-util")]
+pub mod fixture;
+pub mod fserror;
+pub mod growth;
+pub mod i18n;
+pub mod infer;
+pub mod info;
+mod kind;
+mod label;
+mod launch;
+pub mod machine;
+#[cfg(target_os = "macos")]
+mod macos;
+pub mod manifest;
+pub mod mods;
+pub mod mount;
+mod overrides;
+mod owner;
+pub mod paths;
+mod period;
+pub mod plan;
+pub mod probe;
+mod process;
+pub mod progress;
+pub mod protect;
+pub mod prune;
+#[cfg(feature = "python")]
+mod python;
+pub mod quarantine;
+pub mod queue;
+mod recent;
+mod rehearse;
+#[cfg(all(windows, feature = "service"))]
+pub mod service;
+pub mod session;
+mod share;
+mod size;
+pub mod snapshot;
+mod stale;
+pub mod status;
+pub mod summary;
+mod template;
+mod tier;
+pub mod transfer;
+pub mod trash;
+#[cfg(feature = "self-update")]
+pub mod update;
+pub mod validate;
+mod verify;
+mod volume;
+
+use archive::{ArchiveFormat, ArchiveWriter};
+use backup::{BackupTarget, BackupWriter, PreviousBackup};
+use drive::{Drive, UnencryptedPolicy};
+use fserror::MissingSavePath;
+use manifest::{IgnoreRules, Manifest};
+use owner::Owner;
+use plan::{ActionKind, FileAction, FileState, ReadOnlyPolicy, RestoreOptions, RestoreReport};
+use progress::{Operation, Progress, ProgressCallback};
+use protect::Protection;
+use quarantine::QuarantineEntry;
+use status::Heartbeat;
+use validate::{InvalidSavePolicy, ValidationIssue, Validator};
+
+pub use daemon::{DaemonEvent, IdleVerification, IdleVerify, IdleWindow};
+pub use dictionary::DEFAULT_DICTIONARY_SIZE;
+pub use diff::{DiffEntry, DiffReport, DiffStatus};
+pub use doctor::{GameCheck, LocationCheck, LocationStatus};
+pub use kind::SaveKind;
+pub use label::BackupLabel;
+pub use launch::{CommandLine, RestoreCheck};
+pub use overrides::ConfigOverride;
+pub use period::Period;
+pub use recent::RecentGame;
+pub use size::ByteSize;
+pub use stale::StaleGame;
+pub use template::SaveTemplate;
+pub use tier::ColdStorage;
+pub use verify::{VerifyReport, prometheus_metrics, write_metrics};
+
+const STAGING_DIR: &str = ".staging";
+/// Most files one game may back up when `max_files` is not set.
+const DEFAULT_MAX_FILES: usize = 1_000_000;
+
+/// Table of `[system_vars]` whose values are only used when detection fails.
+const FALLBACK_SYSTEM_VARS: &str = "fallback";
+
+#[cfg(not(target_os = "macos"))]
+const RESERVED_VARIABLES: &[&str] = &["home", "config"];
+#[cfg(target_os = "macos")]
+const RESERVED_VARIABLES: &[&str] = &["home", "config", "app_support", "containers"];
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
+    /// Refuse commands that change the configuration or delete backups
+    #[serde(default)]
+    pub locked: bool,
+    /// Where backups are stored, relative to the config file; defaults to `backup` next to it
+    pub backup_root: Option<String>,
+    /// Name recorded in each backup as the machine it was taken on (default: the host name),
+    /// used by `restore --from-machine` when several machines share a backup root
+    pub machine: Option<String>,
+    /// Where snapshots beyond the newest few are moved
+    pub cold_storage: Option<ColdStorage>,
+    /// Keep replaced backups as snapshots; `prune` deletes all but this many per game
+    pub keep_snapshots: Option<usize>,
+    /// Per-kind retention for `prune`: how many distinct versions of each kind's files to keep
+    #[serde(default)]
+    pub keep_kind: BTreeMap<SaveKind, usize>,
+    /// How long snapshots deleted by `prune` or `forget` stay in the trash (default 7 days)
+    pub purge_after: Option<Period>,
+    #[serde(default)]
+    pub format: BackupFormat,
+    /// Split archive backups into volumes of at most this size, e.g. `"4GB"` for FAT32 drives
+    pub volume_size: Option<ByteSize>,
+    #[serde(default)]
+    pub protect: Protection,
+    #[serde(default)]
+    pub on_read_only: ReadOnlyPolicy,
+    /// What to do when a `sensitive` game is backed up to an unencrypted removable drive
+    #[serde(default)]
+    pub on_unencrypted: UnencryptedPolicy,
+    /// Warn when a backup is this many times larger or smaller than the previous one
+    /// (default 4, 1 turns the check off)
+    pub size_alert_factor: Option<f64>,
+    /// Refuse to back up a game with more files than this (default 1,000,000). Save folders
+    /// are walked one directory at a time, but the manifest holds an entry per file in memory
+    pub max_files: Option<usize>,
+    /// `status` flags games whose saves have not changed for this long (default 365 days)
+    pub stale_after: Option<Period>,
+    /// Verify old snapshots a few at a time while `cartridge daemon` runs
+    pub idle_verify: Option<IdleVerify>,
+    /// Make the user who runs a restore, or who called sudo, own the restored files, as with
+    /// `restore --as-user`
+    #[serde(default)]
+    pub restore_as_user: bool,
+    /// Whether recursive copies pick up dotfiles and OS junk such as `Thumbs.db`
+    #[serde(default)]
+    pub include_hidden: bool,
+    #[serde(default)]
+    pub system_vars: HashMap<String, SystemVarOverride>,
     #[serde(rename = "var", default)]
     pub variables: Vec<Variable>,
+    /// Save locations shared by several games, used with `template = "<name>"`
+    #[serde(rename = "save_template", default)]
+    pub save_templates: BTreeMap<String, SaveTemplate>,
     #[serde(rename = "game", default)]
     pub games: Vec<Game>,
 }
@@ -18,42 +181,334 @@ pub struct Variable {
     pub value: String,
 }
 
+/// Entry of the `[system_vars]` table: either an override that applies on every platform,
+/// or a table of overrides that only apply on the named OS (`[system_vars.linux]`). Values in
+/// `[system_vars.fallback]` are only used where the OS does not tell, e.g. in containers
+/// without a home directory.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SystemVarOverride {
+    Value(String),
+    PerOs(HashMap<String, String>),
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Game {
     pub name: String,
     #[serde(default = "default_enabled")]
     pub enabled: bool,
+    /// Stop backing up the game but keep its backups, which `prune`, `forget` and the trash
+    /// leave alone; set by `archive-game`
+    #[serde(default)]
+    pub archived: bool,
+    pub format: Option<BackupFormat>,
+    pub volume_size: Option<ByteSize>,
+    pub keep_snapshots: Option<usize>,
+    #[serde(default)]
+    pub keep_kind: BTreeMap<SaveKind, usize>,
+    pub protect: Option<Protection>,
+    pub size_alert_factor: Option<f64>,
+    pub max_files: Option<usize>,
     #[serde(rename = "save", default)]
     pub saves: Vec<SaveLocation>,
+    #[serde(rename = "validate", default)]
+    pub validators: Vec<Validator>,
+    #[serde(default)]
+    pub on_invalid: InvalidSavePolicy,
+    /// Executable names of the game, used to detect whether it is running
+    #[serde(rename = "process", default)]
+    pub processes: Vec<String>,
+    /// Whether the saves hold personal data, so backing them up to an unencrypted removable
+    /// drive is warned about or refused (see `on_unencrypted`)
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Whether the saves are known to be in a cloud sync folder such as Dropbox or OneDrive,
+    /// which silences the warning about it
+    #[serde(default)]
+    pub cloud_sync_ok: bool,
+    /// Whether `cartridge daemon` backs the game up whenever its saves change while it is not
+    /// running
+    #[serde(default)]
+    pub watch: bool,
+    /// Command that starts the game, for `run` and `restore --then-launch`
+    #[serde(alias = "launch_cmd")]
+    pub launch: Option<CommandLine>,
+    /// Working directory of the `launch` command
+    pub launch_dir: Option<String>,
+    /// Environment variables set for the `launch` command
+    #[serde(default)]
+    pub launch_env: BTreeMap<String, String>,
+    /// Command whose first line of output labels backups taken with `--message-from-hook`,
+    /// e.g. a script reading the character name from the save
+    pub label_command: Option<CommandLine>,
+    /// Steam AppID the game is started with when it has no `launch` command
+    pub steam_app_id: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct SaveLocation {
-    pub path: String,
+    #[serde(default)]
+    pub path: SavePath,
+    /// Name of the `[save_template]` the path and patterns come from
+    pub template: Option<String>,
+    /// Built-in layout of a game engine the path comes from: unity, unreal, renpy,
+    /// rpgmaker-mv, rpgmaker-mz or rpgmaker-vx
+    pub engine: Option<String>,
+    /// Values of the placeholders of the template or engine
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+    /// Whether the location holds saves, settings or mods
+    #[serde(default)]
+    pub kind: SaveKind,
+    /// Glob patterns of the files to back up, relative to `path`; variables are expanded
+    /// like in `path`, and their values are matched literally
     #[serde(default)]
     pub files: Vec<String>,
+    pub include_hidden: Option<bool>,
+    /// Command run against the backed-up copy of the location before the backup is kept, with
+    /// `CARTRIDGE_BACKUP_PATH` set to it; a non-zero exit is handled per `on_invalid`
+    pub validate_command: Option<CommandLine>,
+}
+
+/// Location of a save directory, either shared by every platform or given per platform
+/// (`path.windows = "..."`, `path.linux = "..."`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SavePath {
+    Any(String),
+    PerPlatform(BTreeMap<String, String>),
+}
+
+impl SavePath {
+    /// Unexpanded path to use on the given OS; `unix` acts as a fallback for Linux and macOS.
+    pub fn for_platform(&self, os: &str) -> Option<&str> {
+        match self {
+            SavePath::Any(path) => Some(path),
+            SavePath::PerPlatform(paths) => paths
+                .get(os)
+                .or_else(|| {
+                    if os == "windows" {
+                        None
+                    } else {
+                        paths.get("unix")
+                    }
+                })
+                .map(String::as_str),
+        }
+    }
+
+    pub fn platforms(&self) -> Vec<&str> {
+        match self {
+            SavePath::Any(_) => Vec::new(),
+            SavePath::PerPlatform(paths) => paths.keys().map(String::as_str).collect(),
+        }
+    }
+
+    /// Whether no path was given, as for locations that use a template.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            SavePath::Any(path) => path.is_empty(),
+            SavePath::PerPlatform(paths) => paths.is_empty(),
+        }
+    }
+}
+
+impl Default for SavePath {
+    fn default() -> Self {
+        SavePath::PerPlatform(BTreeMap::new())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackupFormat {
+    #[default]
+    #[serde(rename = "dir")]
+    Directory,
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar.zst")]
+    TarZst,
+}
+
+impl BackupFormat {
+    /// Name of the format as written in the configuration.
+    pub fn name(&self) -> &'static str {
+        match self.archive() {
+            None => "dir",
+            Some(format) => format.extension(),
+        }
+    }
+
+    pub fn archive(&self) -> Option<ArchiveFormat> {
+        match self {
+            BackupFormat::Directory => None,
+            BackupFormat::Zip => Some(ArchiveFormat::Zip),
+            BackupFormat::TarZst => Some(ArchiveFormat::TarZst),
+        }
+    }
+}
+
+enum SaveItem<'a> {
+    Dir { name: &'a Path },
+    File { source: &'a Path, name: &'a Path },
+}
+
+/// The part of a backup that is restored to one save location.
+struct RestoreSelection {
+    /// How the backup being restored is stored
+    format: BackupFormat,
+    /// Path the save location is stored under inside the backup
+    prefix: PathBuf,
+    /// Patterns given with `--files`; empty restores every file
+    patterns: Vec<glob::Pattern>,
+    /// Rules the location was backed up with
+    rules: IgnoreRules,
 }
 
 fn default_enabled() -> bool {
     true
 }
 
+/// Copies a file out of a backup. Backups may be read-only, but restored saves must stay
+/// writable for the game. The copy is written next to `dest` and renamed over it, so the live
+/// file is always either the old save or the complete restored one.
+///
+/// Where the filesystem supports it (Btrfs, XFS, APFS, ReFS) the file is cloned instead of
+/// copied, which is instant and shares storage with the backup until either side changes.
+fn copy_restored_file(source: &Path, dest: &Path) -> Result<()> {
+    let file_name = dest
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid file name: {}", dest.display()))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(".cartridge-restore");
+    // Names near the 255 byte limit of most filesystems get a short one of their own
+    if temp_name.len() > 255 {
+        let hash = blake3::hash(file_name.as_encoded_bytes()).to_hex();
+        temp_name = format!(".{}.cartridge-restore", &hash[..16]).into();
+    }
+    let temp = dest.with_file_name(temp_name);
+    if temp.exists() {
+        fs::remove_file(&temp).with_context(|| {
+            format!("Failed to remove stale temporary file: {}", temp.display())
+        })?;
+    }
+
+    let result = reflink_copy::reflink_or_copy(source, &temp)
+        .map_err(|e| fserror::copy_error(e, source, dest))
+        .and_then(|copied| {
+            if copied.is_none() {
+                log::debug!("Cloned file: {} -> {}", source.display(), dest.display());
+                // Clones start out with default permissions
+                let permissions = fs::metadata(source)
+                    .with_context(|| format!("Failed to read metadata: {}", source.display()))?
+                    .permissions();
+                fs::set_permissions(&temp, permissions)
+                    .with_context(|| format!("Failed to change permissions: {}", temp.display()))?;
+            }
+            if fs::metadata(&temp)?.permissions().readonly() {
+                protect::make_writable(&temp)?;
+            }
+            // Flushed before the rename, so a crash never leaves a truncated save behind
+            durable::sync_file(&temp)
+                .with_context(|| format!("Failed to flush file: {}", temp.display()))?;
+            fs::rename(&temp, dest).map_err(|e| fserror::rename_error(e, &temp, dest))?;
+            if let Some(dir) = dest.parent() {
+                durable::sync_dir(dir)
+                    .with_context(|| format!("Failed to flush directory: {}", dir.display()))?;
+            }
+            Ok(())
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn set_read_only(path: &Path) -> Result<()> {
+    let mut permissions = fs::metadata(path)
+        .with_context(|| format!("Failed to read metadata: {}", path.display()))?
+        .permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(path, permissions)
+        .with_context(|| format!("Failed to change permissions: {}", path.display()))
+}
+
+/// Makes a game backup writable again, using the protection recorded in its manifest.
+fn unprotect_backup(game_backup_dir: &Path) -> Result<()> {
+    let protection = Manifest::load(game_backup_dir)
+        .ok()
+        .flatten()
+        .map(|manifest| manifest.protection)
+        .unwrap_or_default();
+    // Read-only permissions are cleared regardless, in case the manifest is out of date
+    protect::unprotect(game_backup_dir, protection.max(Protection::ReadOnly))
+}
+
+fn archive_path(game_backup_dir: &Path, format: ArchiveFormat) -> PathBuf {
+    game_backup_dir.join(format!("saves.{}", format.extension()))
+}
+
+/// Format a backup was written in, which may differ from the one currently configured for
+/// the game. Backups from before the format was recorded are recognized by their archive.
+fn stored_format(game_backup_dir: &Path, manifest: Option<&Manifest>) -> BackupFormat {
+    if let Some(format) = manifest.and_then(|manifest| manifest.format) {
+        return format;
+    }
+    [BackupFormat::Zip, BackupFormat::TarZst]
+        .into_iter()
+        .find(|format| {
+            format
+                .archive()
+                .is_some_and(|archive| volume::exists(&archive_path(game_backup_dir, archive)))
+        })
+        .unwrap_or(BackupFormat::Directory)
+}
+
 pub struct GameBackup {
     config: Config,
-    variables: HashMap<String, String>,
+    variables: HashMap<String, OsString>,
+    /// System variables that could not be detected, and variables using them, each with the
+    /// system variable it is missing
+    unavailable_variables: HashMap<String, String>,
     backup_root: PathBuf,
+    cold_root: Option<PathBuf>,
+    progress: Option<ProgressCallback>,
+    heartbeat: Mutex<Option<Heartbeat>>,
+    backup_drive: OnceLock<Option<Drive>>,
+    allow_unencrypted: bool,
+    adopt_existing: bool,
+    skip_missing: bool,
+    backup_label: Option<BackupLabel>,
+    machine: OnceLock<Option<String>>,
 }
 
 impl GameBackup {
     pub fn new(config_path: &Path) -> Result<Self> {
+        Self::with_overrides(config_path, &[])
+    }
+
+    /// Loads the configuration with the given settings applied on top of the config file.
+    pub fn with_overrides(config_path: &Path, overrides: &[ConfigOverride]) -> Result<Self> {
         log::info!("Loading configuration from: {}", config_path.display());
 
         let config_content = fs::read_to_string(config_path)
             .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
 
         log::debug!("Parsing TOML configuration");
-        let config: Config = toml::from_str(&config_content)
+        let mut table: toml::Table = toml::from_str(&config_content)
             .with_context(|| "Failed to parse TOML configuration")?;
+        for setting in overrides {
+            setting.apply(&mut table)?;
+        }
+        let mut config: Config = table.try_into().with_context(|| {
+            if overrides.is_empty() {
+                "Failed to parse TOML configuration".to_string()
+            } else {
+                "Failed to parse TOML configuration with the --set overrides".to_string()
+            }
+        })?;
+        config.apply_save_templates()?;
 
         log::info!(
             "Successfully loaded {} games and {} variables",
@@ -61,38 +516,101 @@ impl GameBackup {
             config.variables.len()
         );
 
-        let backup_root = config_path
-            .parent()
-            .unwrap_or_else(|| Path::new("."))
-            .join("backup");
-
-        log::info!("Backup root directory: {}", backup_root.display());
-
         let mut game_backup = Self {
             config,
             variables: HashMap::new(),
-            backup_root,
+            unavailable_variables: HashMap::new(),
+            backup_root: backup_root(config_path),
+            cold_root: None,
+            progress: None,
+            heartbeat: Mutex::new(None),
+            backup_drive: OnceLock::new(),
+            machine: OnceLock::new(),
+            allow_unencrypted: false,
+            adopt_existing: false,
+            skip_missing: false,
+            backup_label: None,
         };
 
         game_backup.resolve_variables()?;
+        let config_dir = config_dir(config_path);
+        if let Some(root) = &game_backup.config.backup_root {
+            let root = game_backup.expand_path(root)?;
+            game_backup.backup_root = config_dir.join(root);
+        }
+        log::info!(
+            "Backup root directory: {}",
+            game_backup.backup_root.display()
+        );
+        if let Some(cold_storage) = &game_backup.config.cold_storage {
+            let root = game_backup.expand_path(&cold_storage.path)?;
+            let cold_root = config_dir.join(root);
+            log::info!(
+                "Cold storage directory: {} (keeping {} backups per game hot)",
+                cold_root.display(),
+                cold_storage.keep_hot
+            );
+            game_backup.cold_root = Some(cold_root);
+        }
         Ok(game_backup)
     }
 
+    /// Directory the backups of this configuration are stored in.
+    pub fn backup_dir(&self) -> &Path {
+        &self.backup_root
+    }
+
+    /// Locks the configuration for this run, as if it had `locked = true`.
+    pub fn freeze(&mut self) {
+        self.config.locked = true;
+    }
+
+    /// Lets sensitive games be backed up to an unencrypted removable drive even when
+    /// `on_unencrypted = "refuse"`.
+    pub fn allow_unencrypted(&mut self) {
+        self.allow_unencrypted = true;
+    }
+
+    /// Lets backups go ahead when save paths are missing, keeping those locations as they are
+    /// in the current backup.
+    pub fn skip_missing_saves(&mut self) {
+        self.skip_missing = true;
+    }
+
+    /// Lets backups take over a game's backup directory that cartridge did not create, moving
+    /// what it holds to the quarantine first.
+    pub fn adopt_existing(&mut self) {
+        self.adopt_existing = true;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.config.locked
+    }
+
+    /// Fails if the configuration is locked, naming the refused operation.
+    pub fn ensure_unlocked(&self, operation: &str) -> Result<()> {
+        if self.config.locked {
+            return Err(anyhow!(
+                "Refusing to {}: the configuration is locked (`locked = true` or --frozen)",
+                operation
+            ));
+        }
+        Ok(())
+    }
+
     fn resolve_variables(&mut self) -> Result<()> {
         log::info!("Resolving variables");
 
         // Add built-in system variables
         self.add_system_variables()?;
+        self.apply_system_var_overrides()?;
 
         // Check for reserved variable names
         for var in &self.config.variables {
-            if var.name == "home" {
-                return Err(anyhow!(
-                    "Variable name 'home' is reserved and cannot be used in configuration"
-                ));
-            } else if var.name == "config" {
+            if RESERVED_VARIABLES.contains(&var.name.as_str()) {
                 return Err(anyhow!(
-                    "Variable name 'config' is reserved and cannot be used in configuration"
+                    "Variable name '{}' is reserved and cannot be used in configuration. Use the [system_vars] table to override it",
+                    var.name
                 ));
             }
         }
@@ -100,111 +618,236 @@ impl GameBackup {
         // Resolve user-defined variables in order (top to bottom)
         for var in &self.config.variables {
             log::debug!("Resolving variable: {} = {}", var.name, var.value);
-            let resolved_value = self.expand_variables(&var.value)?;
-            self.variables.insert(var.name.clone(), resolved_value);
+            if let Some(missing) = self.unavailable_variable_in(&var.value) {
+                log::debug!(
+                    "Variable '{}' uses '{}', which is unavailable",
+                    var.name,
+                    missing
+                );
+                let root = self.unavailable_variables[missing].clone();
+                self.unavailable_variables.insert(var.name.clone(), root);
+                continue;
+            }
+            let resolved_value = self.expand_variables_os(&var.value)?;
             log::debug!(
                 "Variable '{}' resolved to: {}",
                 var.name,
-                self.variables[&var.name]
+                Path::new(&resolved_value).display()
             );
+            self.variables.insert(var.name.clone(), resolved_value);
         }
 
         log::info!("Successfully resolved {} variables", self.variables.len());
+        self.warn_unavailable_variables();
         Ok(())
     }
 
+    /// Name of an unavailable variable used in `value`, if any.
+    fn unavailable_variable_in(&self, value: &str) -> Option<&str> {
+        self.unavailable_variables
+            .keys()
+            .find(|name| value.contains(&format!("${{{}}}", name)))
+            .map(String::as_str)
+    }
+
+    /// Names the games that cannot be backed up because a system directory could not be
+    /// detected, once when the config is loaded rather than as a failure per path later.
+    fn warn_unavailable_variables(&self) {
+        let mut affected: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for game in self.config.games.iter().filter(|game| game.enabled) {
+            let paths = game
+                .saves
+                .iter()
+                .filter_map(|location| location.path.for_platform(std::env::consts::OS));
+            for path in paths {
+                if let Some(missing) = self.unavailable_variable_in(path) {
+                    let games = affected
+                        .entry(self.unavailable_variables[missing].as_str())
+                        .or_default();
+                    if !games.contains(&game.name.as_str()) {
+                        games.push(&game.name);
+                    }
+                }
+            }
+        }
+        for (root, games) in affected {
+            log::warn!(
+                "Could not determine system variable '{}', so these games cannot be backed up \
+                 or restored: {}. Set `{}` under [system_vars.{}] in the config",
+                root,
+                games.join(", "),
+                root,
+                FALLBACK_SYSTEM_VARS
+            );
+        }
+    }
+
     fn add_system_variables(&mut self) -> Result<()> {
         log::debug!("Adding system variables");
 
-        #[cfg(windows)]
+        self.add_system_variable("home", dirs::home_dir());
+        self.add_system_variable("config", dirs::config_dir());
+        #[cfg(target_os = "macos")]
         {
-            if let Some(home_dir) = dirs::home_dir() {
-                self.variables
-                    .insert("home".to_string(), home_dir.to_string_lossy().to_string());
-                log::debug!("Added system variable 'home': {}", home_dir.display());
-            } else {
-                log::warn!("Could not determine home directory");
-            }
-            if let Some(appdata) = dirs::config_dir() {
-                self.variables
-                    .insert("config".to_string(), appdata.to_string_lossy().to_string());
-                log::debug!("Added system variable 'config': {}", appdata.display());
-            } else {
-                log::warn!("Could not determine config directory");
-            }
+            self.add_system_variable("app_support", macos::application_support_dir());
+            self.add_system_variable("containers", macos::containers_dir());
         }
 
-        #[cfg(unix)]
-        {
-            if let Some(home_dir) = dirs::home_dir() {
+        Ok(())
+    }
+
+    /// Adds a detected system directory, or records it as unavailable so that only the paths
+    /// using it fail, with an error saying how to set it.
+    fn add_system_variable(&mut self, name: &str, value: Option<PathBuf>) {
+        match value {
+            Some(value) => {
+                log::debug!("Added system variable '{}': {}", name, value.display());
                 self.variables
-                    .insert("home".to_string(), home_dir.to_string_lossy().to_string());
-                log::debug!("Added system variable 'home': {}", home_dir.display());
-            } else {
-                log::warn!("Could not determine home directory");
+                    .insert(name.to_string(), value.into_os_string());
             }
-            if let Some(config_dir) = dirs::config_dir() {
-                self.variables.insert(
-                    "config".to_string(),
-                    config_dir.to_string_lossy().to_string(),
-                );
-                log::debug!("Added system variable 'config': {}", config_dir.display());
-            } else {
-                log::warn!("Could not determine config directory");
+            None => {
+                log::debug!("Could not determine system variable '{}'", name);
+                self.unavailable_variables
+                    .insert(name.to_string(), name.to_string());
             }
         }
-
-        Ok(())
     }
 
-    fn expand_variables(&self, value: &str) -> Result<String> {
-        let mut result = value.to_string();
-        let mut iterations = 0;
-        const MAX_ITERATIONS: usize = 10;
-
-        while result.contains("${") && iterations < MAX_ITERATIONS {
-            let mut changed = false;
-            let mut new_result = String::new();
-            let mut chars = result.chars().peekable();
-
-            while let Some(ch) = chars.next() {
-                if ch == '$' && chars.peek() == Some(&'{') {
-                    chars.next(); // consume '{'
-                    let mut var_name = String::new();
+    fn apply_system_var_overrides(&mut self) -> Result<()> {
+        let mut overrides: Vec<(&String, &String)> = Vec::new();
+        let mut os_overrides: Vec<(&String, &String)> = Vec::new();
 
-                    while let Some(ch) = chars.next() {
-                        if ch == '}' {
-                            break;
-                        }
-                        var_name.push(ch);
-                    }
+        let mut fallbacks: Vec<(&String, &String)> = Vec::new();
 
-                    if let Some(var_value) = self.variables.get(&var_name) {
-                        new_result.push_str(var_value);
-                        changed = true;
-                    } else {
-                        return Err(anyhow!("Undefined variable: {}", var_name));
-                    }
-                } else {
-                    new_result.push(ch);
+        for (key, value) in &self.config.system_vars {
+            match value {
+                SystemVarOverride::Value(value) => overrides.push((key, value)),
+                SystemVarOverride::PerOs(values) if key == FALLBACK_SYSTEM_VARS => {
+                    fallbacks.extend(values.iter())
+                }
+                SystemVarOverride::PerOs(values) if key == std::env::consts::OS => {
+                    os_overrides.extend(values.iter())
+                }
+                SystemVarOverride::PerOs(_) => {
+                    log::debug!("Ignoring system variable overrides for OS '{}'", key);
                 }
             }
+        }
 
-            result = new_result;
-            iterations += 1;
+        // OS-specific overrides win over the ones that apply everywhere, and `home` goes
+        // first so the others can be written relative to it
+        let mut resolved: HashMap<&String, &String> = overrides.into_iter().collect();
+        resolved.extend(os_overrides);
+        let mut resolved: Vec<(&String, &String)> = resolved.into_iter().collect();
+        resolved.sort_by_key(|(name, _)| (name.as_str() != "home", name.as_str()));
+        // Fallbacks only fill in what could not be detected
+        fallbacks.sort_by_key(|(name, _)| (name.as_str() != "home", name.as_str()));
+        let resolved = resolved
+            .into_iter()
+            .map(|(name, value)| (name, value, false))
+            .chain(
+                fallbacks
+                    .into_iter()
+                    .map(|(name, value)| (name, value, true)),
+            );
 
-            if !changed {
-                break;
+        for (name, value, fallback) in resolved {
+            if !RESERVED_VARIABLES.contains(&name.as_str()) {
+                return Err(anyhow!(
+                    "Unknown system variable '{}' in [system_vars] (expected one of: {})",
+                    name,
+                    RESERVED_VARIABLES.join(", ")
+                ));
+            }
+            if fallback && self.variables.contains_key(name) {
+                log::debug!(
+                    "Not using the fallback of detected system variable '{}'",
+                    name
+                );
+                continue;
             }
-        }
 
-        if iterations >= MAX_ITERATIONS {
-            return Err(anyhow!(
-                "Variable resolution exceeded maximum iterations (possible circular reference)"
-            ));
+            let expanded = self.expand_variables_os(value)?;
+            log::info!(
+                "{} system variable '{}': {}",
+                if fallback {
+                    "Falling back to"
+                } else {
+                    "Overriding"
+                },
+                name,
+                Path::new(&expanded).display()
+            );
+            self.variables.insert(name.clone(), expanded);
+            self.unavailable_variables.remove(name);
         }
 
-        Ok(result)
+        Ok(())
+    }
+
+    /// Expands variables in a path from the config, keeping the values of system variables
+    /// such as `home` as the OS gave them, even when they are not valid UTF-8.
+    fn expand_path(&self, value: &str) -> Result<PathBuf> {
+        self.expand_variables_os(value).map(PathBuf::from)
+    }
+
+    fn expand_variables_os(&self, value: &str) -> Result<OsString> {
+        self.substitute_variables(value, |var_value| Ok(var_value.to_os_string()))
+    }
+
+    /// Expands variables in a glob pattern from the config. Their values are escaped, so only
+    /// the pattern as written is glob syntax, not a `[` or `*` in a directory name.
+    fn expand_pattern(&self, pattern: &str) -> Result<String> {
+        let expanded = self.substitute_variables(pattern, |var_value| {
+            let var_value = var_value.to_str().ok_or_else(|| {
+                anyhow!(
+                    "Variable in pattern '{}' is not valid UTF-8: {}",
+                    pattern,
+                    Path::new(var_value).display()
+                )
+            })?;
+            Ok(glob::Pattern::escape(var_value).into())
+        })?;
+        expanded.into_string().map_err(|expanded| {
+            anyhow!(
+                "Pattern is not valid UTF-8: {}",
+                Path::new(&expanded).display()
+            )
+        })
+    }
+
+    /// Replaces every `${name}` in `value` with the variable's value passed through
+    /// `convert`. Values of variables are stored expanded, so a single pass is enough.
+    fn substitute_variables(
+        &self,
+        value: &str,
+        convert: impl Fn(&OsStr) -> Result<OsString>,
+    ) -> Result<OsString> {
+        paths::substitute_variables(
+            value,
+            |name| self.variables.get(name).map(OsString::as_os_str),
+            convert,
+        )
+        .map_err(|e| match self.unavailable_variable_in(value) {
+            Some(missing) => {
+                let root = &self.unavailable_variables[missing];
+                let uses = if root == missing {
+                    String::new()
+                } else {
+                    format!(", which uses '{}',", root)
+                };
+                anyhow!(
+                    "Cannot expand '{}': variable '{}'{} could not be determined on this \
+                     system. Set `{}` under [system_vars.{}] in the config",
+                    value,
+                    missing,
+                    uses,
+                    root,
+                    FALLBACK_SYSTEM_VARS
+                )
+            }
+            None => e,
+        })
     }
 
     pub fn list_games(&self) -> Vec<&Game> {
@@ -220,6 +863,11 @@ impl GameBackup {
         enabled_games
     }
 
+    /// Every configured game, including disabled and archived ones.
+    pub fn list_all_games(&self) -> &[Game] {
+        &self.config.games
+    }
+
     pub fn has_backup(&self, game_name: &str) -> bool {
         let game_backup_dir = self.backup_root.join(game_name);
         let has_backup = game_backup_dir.exists();
@@ -227,111 +875,831 @@ impl GameBackup {
         has_backup
     }
 
-    pub fn backup_game(&self, game_name: &str) -> Result<()> {
-        log::info!("Starting backup for game: {}", game_name);
-
-        let game = self
-            .config
+    fn find_game(&self, game_name: &str) -> Result<&Game> {
+        self.config
             .games
             .iter()
             .find(|g| g.name == game_name)
-            .ok_or_else(|| anyhow!("Game '{}' not found in configuration", game_name))?;
+            .ok_or_else(|| anyhow!("Game '{}' not found in configuration", game_name))
+    }
+
+    /// Backs up a game. With a `kind`, only save locations of that kind are read; the others
+    /// are kept from the current backup.
+    pub fn backup_game(&self, game_name: &str, kind: Option<SaveKind>) -> Result<()> {
+        let own_status = self.begin_status(Operation::Backup, 1);
+        self.report_progress(Progress::Started {
+            operation: Operation::Backup,
+            game: game_name,
+            files: self.count_backup_files(game_name, kind),
+        });
+        let result = self.run_backup(game_name, kind);
+        self.report_progress(Progress::Finished {
+            operation: Operation::Backup,
+            game: game_name,
+            ok: result.is_ok(),
+            error: result.as_ref().err(),
+        });
+        if own_status {
+            self.end_status(result.is_ok());
+        }
+        result
+    }
+
+    fn run_backup(&self, game_name: &str, kind: Option<SaveKind>) -> Result<()> {
+        log::info!("Starting backup for game: {}", game_name);
+
+        let game = self.find_game(game_name)?;
 
         if !game.enabled {
             log::warn!("Game '{}' is disabled, skipping backup", game_name);
             return Ok(());
         }
+        if game.archived {
+            log::warn!(
+                "Game '{}' is archived, skipping backup. Run `cartridge archive-game {} --undo` \
+                 to back it up again",
+                game_name,
+                game_name
+            );
+            return Ok(());
+        }
+        self.warn_cloud_synced(game);
+
+        if let Some(kind) = kind {
+            if !game.saves.iter().any(|save| save.kind == kind) {
+                log::info!("Game '{}' has no {} locations, skipping", game.name, kind);
+                return Ok(());
+            }
+            log::info!("Backing up only {} locations of '{}'", kind, game.name);
+        }
+
+        if let Some(format) = self.game_format(game).archive() {
+            format
+                .ensure_available()
+                .with_context(|| format!("Cannot back up game '{}'", game.name))?;
+        }
+        self.check_backup_drive(game)?;
+
+        let issues = self.validate_saves(game)?;
+        if !issues.is_empty() {
+            match game.on_invalid {
+                InvalidSavePolicy::Fail => {
+                    return Err(anyhow!(
+                        "{} save file(s) of '{}' failed validation, keeping the previous backup \
+                         (set on_invalid = \"tag\" to back up anyway)",
+                        issues.len(),
+                        game.name
+                    ));
+                }
+                InvalidSavePolicy::Tag => log::warn!(
+                    "Backing up '{}' despite {} failed validation(s), tagging the backup",
+                    game.name,
+                    issues.len()
+                ),
+            }
+        }
+
+        self.check_outside_backups(game)?;
 
         let game_backup_dir = self.backup_root.join(&game.name);
-        log::info!("Creating backup directory: {}", game_backup_dir.display());
-        fs::create_dir_all(&game_backup_dir).with_context(|| {
+        let staging_dir = self.backup_root.join(STAGING_DIR).join(&game.name);
+
+        self.recover_replaced_backup(&game.name)?;
+        self.check_foreign_backup_dir(game, &game_backup_dir)?;
+        if staging_dir.exists() {
+            // Leftovers from an interrupted run are kept for inspection rather than deleted
+            let error = anyhow!("Backup was interrupted before it completed");
+            quarantine::quarantine(&self.backup_root, &game.name, &staging_dir, &error)?;
+        }
+
+        log::info!("Creating staging directory: {}", staging_dir.display());
+        fs::create_dir_all(&staging_dir).with_context(|| {
             format!(
-                "Failed to create backup directory: {}",
-                game_backup_dir.display()
+                "Failed to create staging directory: {}",
+                staging_dir.display()
             )
         })?;
 
-        for (i, save_location) in game.saves.iter().enumerate() {
-            log::info!(
-                "Processing save location {}/{} for game '{}'",
-                i + 1,
-                game.saves.len(),
-                game.name
+        let uploaded = match self
+            .write_backup(game, kind, &staging_dir, &game_backup_dir, issues)
+            .and_then(|uploaded| {
+                self.validate_backup(game, kind, &staging_dir)?;
+                Ok(uploaded)
+            }) {
+            Ok(uploaded) => uploaded,
+            Err(e) if fserror::is_disk_full(&e) => {
+                // Quarantined data would go on holding the space the next backup needs
+                log::error!(
+                    "Backup destination is full, removing partial backup data for '{}'",
+                    game.name
+                );
+                if let Err(re) = fs::remove_dir_all(&staging_dir) {
+                    log::error!(
+                        "Failed to remove partial backup data: {}: {}",
+                        staging_dir.display(),
+                        re
+                    );
+                }
+                return Err(e);
+            }
+            Err(e) => {
+                match quarantine::quarantine(&self.backup_root, &game.name, &staging_dir, &e) {
+                    Ok(entry_dir) => log::error!(
+                        "Partial backup data for '{}' moved to quarantine: {}",
+                        game.name,
+                        entry_dir.display()
+                    ),
+                    Err(qe) => log::error!(
+                        "Failed to quarantine partial backup data for '{}': {:#}",
+                        game.name,
+                        qe
+                    ),
+                }
+                return Err(e);
+            }
+        };
+
+        self.replace_backup(&game.name, &staging_dir, &game_backup_dir)?;
+        protect::protect(&game_backup_dir, self.game_protection(game))?;
+        self.record_transfer(&game.name, &self.backup_root, uploaded, 0);
+        self.check_backup_size(game);
+
+        self.purge_expired_trash();
+        // The backup itself succeeded, so failing to move older snapshots is only reported
+        if let Err(e) = self.migrate_to_cold(&game.name) {
+            log::error!(
+                "Failed to move old snapshots of '{}' to cold storage: {:#}",
+                game.name,
+                e
             );
-            self.backup_save_location(save_location, &game_backup_dir)?;
         }
 
         log::info!("Successfully completed backup for game: {}", game_name);
         Ok(())
     }
 
-    fn backup_save_location(
+    /// Writes a backup of the game into `backup_dir` and returns the number of bytes written.
+    fn write_backup(
         &self,
-        save_location: &SaveLocation,
+        game: &Game,
+        kind: Option<SaveKind>,
+        backup_dir: &Path,
         game_backup_dir: &Path,
-    ) -> Result<()> {
-        let source_path = self.expand_variables(&save_location.path)?;
-        let source_path = Path::new(&source_path);
+        issues: Vec<ValidationIssue>,
+    ) -> Result<u64> {
+        let (summary, written) = match self.game_format(game).archive() {
+            None => {
+                let previous = self.previous_backup(game_backup_dir);
+                let mut writer = BackupWriter::new(BackupTarget::Directory(backup_dir), previous);
+                if self.game_protection(game) != Protection::None {
+                    writer.copy_unchanged();
+                }
+                self.backup_save_locations(game, kind, &mut writer)?;
+                let linked = writer.links_files();
+                let summary = writer.finish();
+                // Unchanged files are linked from the previous backup rather than written
+                let written = if linked {
+                    summary.bytes_copied
+                } else {
+                    summary.bytes_copied + summary.bytes_unchanged
+                };
+                (summary, written)
+            }
+            Some(archive_format) => {
+                let archive_path = archive_path(backup_dir, archive_format);
+                log::info!("Writing backup archive: {}", archive_path.display());
+
+                let dictionary = match archive_format {
+                    ArchiveFormat::TarZst => self.trained_dictionary(&game.name)?,
+                    ArchiveFormat::Zip => None,
+                };
+                if dictionary.is_some() {
+                    log::info!("Compressing with the trained dictionary of '{}'", game.name);
+                }
 
-        log::info!("Backing up from: {}", source_path.display());
+                let volume_size = self.game_volume_size(game);
+                if let Some(volume_size) = volume_size {
+                    log::info!("Splitting archive into volumes of {}", volume_size);
+                }
 
-        if !source_path.exists() {
-            return Err(anyhow!(
-                "Save path does not exist: {}",
-                source_path.display()
-            ));
-        }
+                let mut archive = ArchiveWriter::create(
+                    &archive_path,
+                    archive_format,
+                    dictionary.as_deref(),
+                    volume_size.map(|size| size.bytes()),
+                )?;
+                let mut writer = BackupWriter::new(BackupTarget::Archive(&mut archive), None);
+                self.backup_save_locations(game, kind, &mut writer)?;
+                let summary = writer.finish();
+                archive.finish()?;
+
+                // Keep the dictionary with the backup, it is needed to read the archive
+                if let Some(dictionary) = &dictionary {
+                    let dictionary_path = backup_dir.join(archive::DICTIONARY_FILE);
+                    fs::write(&dictionary_path, dictionary).with_context(|| {
+                        format!("Failed to write dictionary: {}", dictionary_path.display())
+                    })?;
+                }
+                let (_, written) = tier::dir_size(backup_dir)?;
+                (summary, written)
+            }
+        };
+
+        log::info!(
+            "Backed up {} files, {} ({} files / {} copied, {} files / {} unchanged since last backup) in {:.1}s ({}/s)",
+            summary.copied + summary.unchanged,
+            format_size(summary.bytes_copied + summary.bytes_unchanged),
+            summary.copied,
+            format_size(summary.bytes_copied),
+            summary.unchanged,
+            format_size(summary.bytes_unchanged),
+            summary.elapsed.as_secs_f64(),
+            format_size(throughput(summary.bytes_copied, summary.elapsed))
+        );
+        let mut manifest = summary.manifest;
+        manifest.previous = self.chain_link(game_backup_dir);
+        manifest.format = Some(self.game_format(game));
+        manifest.protection = self.game_protection(game);
+        manifest.validation_issues = issues;
+        manifest.label = self.backup_label(game);
+        manifest.machine = self.machine_name().map(String::from);
+        manifest.save(backup_dir)?;
+        Ok(written)
+    }
 
-        let backup_subdir = self.create_backup_path(source_path, game_backup_dir)?;
-        log::debug!("Backup destination: {}", backup_subdir.display());
+    fn previous_backup(&self, game_backup_dir: &Path) -> Option<PreviousBackup> {
+        match Manifest::load(game_backup_dir) {
+            // Files of an archive backup cannot be linked into a directory backup
+            Ok(Some(manifest))
+                if stored_format(game_backup_dir, Some(&manifest)) != BackupFormat::Directory =>
+            {
+                None
+            }
+            Ok(Some(manifest)) => Some(PreviousBackup {
+                dir: game_backup_dir.to_path_buf(),
+                manifest,
+            }),
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Ignoring unreadable manifest of previous backup: {:#}", e);
+                None
+            }
+        }
+    }
 
-        fs::create_dir_all(&backup_subdir).with_context(|| {
+    fn replace_backup(
+        &self,
+        game_name: &str,
+        staging_dir: &Path,
+        game_backup_dir: &Path,
+    ) -> Result<()> {
+        let previous_dir = previous_backup_dir(staging_dir);
+        self.recover_replaced_backup(game_name)?;
+        // Flushed first, so a power loss after the renames cannot leave the published backup
+        // with files its manifest does not match
+        durable::sync_tree(staging_dir).with_context(|| {
             format!(
-                "Failed to create backup subdirectory: {}",
-                backup_subdir.display()
+                "Failed to flush staged backup to disk: {}",
+                staging_dir.display()
             )
         })?;
 
-        if save_location.files.is_empty() {
-            log::info!("No specific files specified, backing up all files recursively");
-            self.copy_all_files(source_path, &backup_subdir)?;
-        } else {
-            log::info!(
-                "Backing up {} specific file patterns",
-                save_location.files.len()
-            );
-            for pattern in &save_location.files {
-                self.copy_files_by_pattern(source_path, &backup_subdir, pattern)?;
+        let had_previous = game_backup_dir.exists();
+        if had_previous {
+            unprotect_backup(game_backup_dir)?;
+            fs::rename(game_backup_dir, &previous_dir)
+                .map_err(|e| fserror::rename_error(e, game_backup_dir, &previous_dir))
+                .with_context(|| {
+                    format!(
+                        "Failed to move previous backup aside: {}",
+                        game_backup_dir.display()
+                    )
+                })?;
+        }
+
+        log::debug!(
+            "Moving staged backup into place: {} -> {}",
+            staging_dir.display(),
+            game_backup_dir.display()
+        );
+        if let Err(e) = fs::rename(staging_dir, game_backup_dir) {
+            if had_previous {
+                let _ = fs::rename(&previous_dir, game_backup_dir);
+            }
+            return Err(
+                fserror::rename_error(e, staging_dir, game_backup_dir).context(format!(
+                    "Failed to move staged backup into place: {}",
+                    game_backup_dir.display()
+                )),
+            );
+        }
+
+        if let Some(parent) = game_backup_dir.parent() {
+            durable::sync_dir(parent).with_context(|| {
+                format!("Failed to flush backup directory: {}", parent.display())
+            })?;
+        }
+
+        if had_previous {
+            let new_is_suspect = Manifest::load(game_backup_dir)?
+                .is_some_and(|manifest| !manifest.validation_issues.is_empty());
+            self.retire_previous_backup(game_name, &previous_dir, new_is_suspect)?;
+        }
+
+        Ok(())
+    }
+
+    /// Finishes or undoes replacing a game's backup that a crash interrupted, going by what is
+    /// left of it: the previous backup moved aside is retired as usual when the new one was
+    /// put in its place, or put back when it was not, leaving the staged one to quarantine.
+    fn recover_replaced_backup(&self, game_name: &str) -> Result<()> {
+        let game_backup_dir = self.backup_root.join(game_name);
+        let previous_dir = previous_backup_dir(&self.backup_root.join(STAGING_DIR).join(game_name));
+        if !previous_dir.exists() {
+            return Ok(());
+        }
+        if game_backup_dir.exists() {
+            log::warn!(
+                "Retiring the previous backup of '{}' left aside by an interrupted run",
+                game_name
+            );
+            let new_is_suspect = Manifest::load(&game_backup_dir)?
+                .is_some_and(|manifest| !manifest.validation_issues.is_empty());
+            return self.retire_previous_backup(game_name, &previous_dir, new_is_suspect);
+        }
+        log::warn!(
+            "Putting back the backup of '{}' that an interrupted run moved aside",
+            game_name
+        );
+        fs::rename(&previous_dir, &game_backup_dir)
+            .map_err(|e| fserror::rename_error(e, &previous_dir, &game_backup_dir))
+            .with_context(|| {
+                format!(
+                    "Failed to put back previous backup: {}",
+                    game_backup_dir.display()
+                )
+            })
+    }
+
+    /// Works out what a backup of the game would copy, without writing anything.
+    pub fn plan_backup(&self, game_name: &str, kind: Option<SaveKind>) -> Result<Vec<FileAction>> {
+        log::info!("Planning backup for game: {}", game_name);
+
+        let game = self.find_game(game_name)?;
+        let game_backup_dir = self.backup_root.join(&game.name);
+        let previous = Manifest::load(&game_backup_dir)?;
+        let archive = self
+            .game_format(game)
+            .archive()
+            .map(|format| archive_path(&game_backup_dir, format));
+
+        let mut actions = Vec::new();
+        for (i, save_location) in game.saves.iter().enumerate() {
+            if kind.is_some_and(|kind| save_location.kind != kind) {
+                continue;
+            }
+            let Some(source_path) = self.save_path(save_location)? else {
+                continue;
+            };
+            if !source_path.exists() {
+                if self.skip_missing {
+                    log::warn!(
+                        "Save path does not exist, skipping: {}",
+                        source_path.display()
+                    );
+                    continue;
+                }
+                return Err(MissingSavePath { path: source_path }.into());
+            }
+
+            let prefix = self.create_backup_path(&source_path, Path::new(""))?;
+            let rules = self.ignore_rules(i, save_location, None);
+            self.walk_save_location(save_location, &source_path, &prefix, rules, &mut |item| {
+                let SaveItem::File { source, name } = item else {
+                    return Ok(());
+                };
+
+                let metadata = fs::metadata(source)
+                    .with_context(|| format!("Failed to read metadata: {}", source.display()))?;
+                let entry = previous.as_ref().and_then(|manifest| manifest.get(name));
+                let kind = match entry {
+                    None => ActionKind::Create,
+                    Some(entry) if entry.matches(&metadata) => ActionKind::Unchanged,
+                    Some(_) => ActionKind::Overwrite,
+                };
+                let dest = match &archive {
+                    Some(archive_path) => archive_path.join(name),
+                    None => game_backup_dir.join(name),
+                };
+
+                actions.push(FileAction {
+                    source: source.to_path_buf(),
+                    dest,
+                    kind,
+                    source_state: FileState::from_metadata(&metadata),
+                    dest_state: entry.map(|entry| FileState {
+                        size: entry.size,
+                        modified: Some(entry.modified),
+                        read_only: false,
+                    }),
+                });
+                Ok(())
+            })?;
+        }
+
+        Ok(actions)
+    }
+
+    fn game_format(&self, game: &Game) -> BackupFormat {
+        game.format.unwrap_or(self.config.format)
+    }
+
+    fn game_volume_size(&self, game: &Game) -> Option<ByteSize> {
+        game.volume_size.or(self.config.volume_size)
+    }
+
+    fn game_protection(&self, game: &Game) -> Protection {
+        game.protect.unwrap_or(self.config.protect)
+    }
+
+    fn game_max_files(&self, game: &Game) -> usize {
+        game.max_files
+            .or(self.config.max_files)
+            .unwrap_or(DEFAULT_MAX_FILES)
+    }
+
+    fn backup_save_locations(
+        &self,
+        game: &Game,
+        kind: Option<SaveKind>,
+        writer: &mut BackupWriter,
+    ) -> Result<()> {
+        let game_backup_dir = self.backup_root.join(&game.name);
+        let current = if kind.is_some() || self.skip_missing {
+            Manifest::load(&game_backup_dir)?
+        } else {
+            None
+        };
+        let keep_current = |i: usize, writer: &mut BackupWriter| match &current {
+            Some(manifest) => self.carry_over_location(game, i, &game_backup_dir, manifest, writer),
+            None => {
+                log::warn!(
+                    "No current backup to keep {} location {} of '{}' from, leaving it out",
+                    game.saves[i].kind,
+                    i + 1,
+                    game.name
+                );
+                Ok(())
+            }
+        };
+
+        for (i, save_location) in game.saves.iter().enumerate() {
+            log::info!(
+                "Processing save location {}/{} for game '{}'",
+                i + 1,
+                game.saves.len(),
+                game.name
+            );
+
+            if kind.is_some_and(|kind| save_location.kind != kind) {
+                keep_current(i, writer)?;
+                continue;
+            }
+
+            let Some(source_path) = self.save_path(save_location)? else {
+                log::info!(
+                    "No save path configured for {} (available: {}), skipping",
+                    std::env::consts::OS,
+                    save_location.path.platforms().join(", ")
+                );
+                continue;
+            };
+            if self.skip_missing && !source_path.exists() {
+                log::warn!(
+                    "Save path does not exist, keeping it as it is in the current backup: {}",
+                    source_path.display()
+                );
+                keep_current(i, writer)?;
+                continue;
+            }
+
+            let prefix = self.create_backup_path(&source_path, Path::new(""))?;
+            let rules = self.ignore_rules(i, save_location, None);
+            writer.record_location(i, &prefix, rules);
+            if save_location.kind == SaveKind::Mods {
+                let mods = mods::scan_mods(&source_path)?;
+                log::info!("Recorded {} installed mods", mods.len());
+                writer.record_mods(i, mods);
+            }
+            self.backup_save_location(
+                save_location,
+                &source_path,
+                &prefix,
+                rules,
+                self.game_max_files(game),
+                writer,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn backup_save_location(
+        &self,
+        save_location: &SaveLocation,
+        source_path: &Path,
+        prefix: &Path,
+        rules: IgnoreRules,
+        max_files: usize,
+        writer: &mut BackupWriter,
+    ) -> Result<()> {
+        log::info!("Backing up from: {}", source_path.display());
+
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(container) = macos::cloud_synced_container(source_path) {
+                log::warn!(
+                    "Save path is synced by iCloud ({}); the backup may capture a sync in progress",
+                    container.display()
+                );
+            }
+        }
+
+        if !source_path.exists() {
+            return Err(MissingSavePath {
+                path: source_path.to_path_buf(),
+            }
+            .into());
+        }
+
+        if save_location.files.is_empty() {
+            log::info!("No specific files specified, backing up all files recursively");
+        } else {
+            log::info!(
+                "Backing up {} specific file patterns",
+                save_location.files.len()
+            );
+        }
+
+        self.walk_save_location(
+            save_location,
+            source_path,
+            prefix,
+            rules,
+            &mut |item| match item {
+                SaveItem::Dir { name } => writer.add_dir(name),
+                SaveItem::File { source, name } => {
+                    // Checked as files are added, before a runaway save path fills memory
+                    if writer.file_count() >= max_files {
+                        return Err(anyhow!(
+                            "More than {} files to back up below {}, raise `max_files` if \
+                             this is expected",
+                            max_files,
+                            source_path.display()
+                        ));
+                    }
+                    writer.add_file(source, name)?;
+                    self.report_file(Operation::Backup, source);
+                    Ok(())
+                }
+            },
+        )
+    }
+
+    /// Refuses save paths that are or hold the backup root or cold storage, which a backup
+    /// would otherwise copy into itself without end.
+    fn check_outside_backups(&self, game: &Game) -> Result<()> {
+        for save_location in &game.saves {
+            let Some(save_path) = self.save_path(save_location)? else {
+                continue;
+            };
+            if let Some(root) = self.overlapping_backups(&save_path)
+                && root.starts_with(real_path(&save_path))
+            {
+                return Err(anyhow!(
+                    "Save path {} of '{}' contains the backups in {}, which would be copied \
+                     into themselves. Move the backup root or narrow the save path",
+                    save_path.display(),
+                    game.name,
+                    root.display()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Refuses to replace a game's backup directory holding data that cartridge did not write,
+    /// which would be deleted with the previous backup. With `adopt_existing` the data is moved
+    /// to the quarantine instead.
+    fn check_foreign_backup_dir(&self, game: &Game, game_backup_dir: &Path) -> Result<()> {
+        if !game_backup_dir.exists() || game_backup_dir.join(manifest::MANIFEST_FILE).exists() {
+            return Ok(());
+        }
+        if game_backup_dir.is_dir()
+            && fs::read_dir(game_backup_dir)
+                .with_context(|| {
+                    format!("Failed to read directory: {}", game_backup_dir.display())
+                })?
+                .next()
+                .is_none()
+        {
+            return Ok(());
+        }
+        if !self.adopt_existing {
+            return Err(anyhow!(
+                "{} already exists but was not created by cartridge (it has no {}). Move it \
+                 away, or pass --adopt to move its content to the quarantine and back up '{}' \
+                 there",
+                game_backup_dir.display(),
+                manifest::MANIFEST_FILE,
+                game.name
+            ));
+        }
+        let error = anyhow!(
+            "Adopted {}, which was not created by cartridge",
+            game_backup_dir.display()
+        );
+        quarantine::quarantine(&self.backup_root, &game.name, game_backup_dir, &error)?;
+        Ok(())
+    }
+
+    /// The backup root or cold storage, resolved, when `path` is inside it or holds it.
+    fn overlapping_backups(&self, path: &Path) -> Option<PathBuf> {
+        let path = real_path(path);
+        std::iter::once(&self.backup_root)
+            .chain(&self.cold_root)
+            .map(|root| real_path(root))
+            .find(|root| root.starts_with(&path) || path.starts_with(root))
+    }
+
+    /// Expanded save path of a location on the current platform, or `None` when the
+    /// location has no path configured for it.
+    fn save_path(&self, save_location: &SaveLocation) -> Result<Option<PathBuf>> {
+        match save_location.path.for_platform(std::env::consts::OS) {
+            Some(path) => Ok(Some(self.expand_path(path)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Path inside the game's backup that a save location is stored under. Backups record
+    /// this per location, so a backup made on another OS maps onto this platform's path.
+    fn backup_prefix(
+        &self,
+        index: usize,
+        save_path: &Path,
+        manifest: Option<&Manifest>,
+    ) -> Result<PathBuf> {
+        if let Some(prefix) = manifest.and_then(|manifest| manifest.location(index)) {
+            return Ok(prefix);
+        }
+        self.create_backup_path(save_path, Path::new(""))
+    }
+
+    /// Ignore rules of a save location: the ones recorded in a backup's manifest when known,
+    /// so restores and comparisons treat files the same way the backup did, or the configured
+    /// ones otherwise.
+    fn ignore_rules(
+        &self,
+        index: usize,
+        save_location: &SaveLocation,
+        manifest: Option<&Manifest>,
+    ) -> IgnoreRules {
+        if let Some(rules) = manifest.and_then(|manifest| manifest.ignore_rules(index)) {
+            return rules;
+        }
+        IgnoreRules {
+            include_hidden: save_location
+                .include_hidden
+                .unwrap_or(self.config.include_hidden),
+        }
+    }
+
+    /// Visits every directory and file a save location covers, along with the
+    /// path it is stored under inside the game's backup. Symlinked files and directories are
+    /// followed, except links back into a directory being walked, but the stored path is
+    /// always the one below the configured save path, never a link's target.
+    fn walk_save_location(
+        &self,
+        save_location: &SaveLocation,
+        source_path: &Path,
+        prefix: &Path,
+        rules: IgnoreRules,
+        visit: &mut dyn FnMut(SaveItem) -> Result<()>,
+    ) -> Result<()> {
+        log::debug!("Backup destination: {}", prefix.display());
+
+        if save_location.is_mod_list_only() {
+            log::debug!("Only the mod list is kept for: {}", source_path.display());
+            return Ok(());
+        }
+
+        let mut walked = vec![fs::canonicalize(source_path).unwrap_or(source_path.to_path_buf())];
+        if save_location.files.is_empty() {
+            return self.walk_dir(
+                source_path,
+                prefix,
+                rules.include_hidden,
+                &mut walked,
+                visit,
+            );
+        }
+
+        visit(SaveItem::Dir { name: prefix })?;
+        for pattern in &save_location.files {
+            let pattern = &self.expand_pattern(pattern)?;
+            let mut found = 0;
+            self.visit_files_by_pattern(source_path, pattern, &mut walked, &mut |path| {
+                let file_name = path
+                    .file_name()
+                    .ok_or_else(|| anyhow!("Invalid file name: {}", path.display()))?;
+                found += 1;
+                visit(SaveItem::File {
+                    source: path,
+                    name: &prefix.join(file_name),
+                })
+            })?;
+            log::info!("Found {} files matching pattern: {}", found, pattern);
+        }
+
+        Ok(())
+    }
+
+    /// `walked` holds the real paths of the directories entered so far, `source`'s last.
+    fn walk_dir(
+        &self,
+        source: &Path,
+        name: &Path,
+        include_hidden: bool,
+        walked: &mut Vec<PathBuf>,
+        visit: &mut dyn FnMut(SaveItem) -> Result<()>,
+    ) -> Result<()> {
+        visit(SaveItem::Dir { name })?;
+
+        if source.is_file() {
+            let file_name = source
+                .file_name()
+                .ok_or_else(|| anyhow!("Invalid file name: {}", source.display()))?;
+            return visit(SaveItem::File {
+                source,
+                name: &name.join(file_name),
+            });
+        }
+
+        let entries = fs::read_dir(source)
+            .with_context(|| format!("Failed to read directory: {}", source.display()))?;
+
+        for entry in entries {
+            let entry = entry.with_context(|| {
+                format!("Failed to read directory entry in: {}", source.display())
+            })?;
+            let path = entry.path();
+            let entry_name = name.join(entry.file_name());
+
+            if !include_hidden && is_hidden(&entry) {
+                log::debug!("Skipping hidden file: {}", path.display());
+                continue;
+            }
+
+            if path.is_dir() {
+                let Some(real) = enter_dir(walked, &path)? else {
+                    continue;
+                };
+                walked.push(real);
+                let result = self.walk_dir(&path, &entry_name, include_hidden, walked, visit);
+                walked.pop();
+                result?;
+            } else {
+                visit(SaveItem::File {
+                    source: &path,
+                    name: &entry_name,
+                })?;
             }
         }
 
         Ok(())
     }
 
+    /// Path a save location is stored under, anonymized from the save path as configured
+    /// rather than where its symlinks lead, so relinking a save folder does not move it
+    /// inside the backup.
     fn create_backup_path(&self, source_path: &Path, game_backup_dir: &Path) -> Result<PathBuf> {
         let mut backup_path = game_backup_dir.to_path_buf();
 
         #[cfg(windows)]
         {
-            if let Some(prefix) = source_path.components().next() {
-                if let std::path::Component::Prefix(prefix_component) = prefix {
-                    if let std::path::Prefix::Disk(drive_letter) = prefix_component.kind() {
-                        let drive_name =
-                            format!("drive_{}", (drive_letter as char).to_ascii_lowercase());
-                        backup_path.push(drive_name);
-
-                        // Process the rest of the path components
-                        let remaining_components: Vec<_> =
-                            source_path.components().skip(1).collect();
-                        let anonymized_path = self.anonymize_windows_path(&remaining_components)?;
-
-                        for component in anonymized_path.components() {
-                            if let std::path::Component::Normal(name) = component {
-                                backup_path.push(name);
-                            }
-                        }
-                    }
-                }
+            // Honor a `home` override like on Unix, it is the user profile on Windows
+            let user_profile = self
+                .variables
+                .get("home")
+                .map(PathBuf::from)
+                .or_else(dirs::home_dir);
+            if let Some(anonymized_path) = paths::anonymize_windows_path(
+                &source_path.to_string_lossy(),
+                user_profile
+                    .as_deref()
+                    .map(Path::to_string_lossy)
+                    .as_deref(),
+            ) {
+                backup_path.push(anonymized_path);
             }
         }
 
@@ -348,89 +1716,573 @@ impl GameBackup {
         Ok(backup_path)
     }
 
-    #[cfg(windows)]
-    fn anonymize_windows_path(&self, components: &[std::path::Component]) -> Result<PathBuf> {
-        let mut result = PathBuf::new();
-        let mut i = 0;
-
-        while i < components.len() {
-            match &components[i] {
-                std::path::Component::Normal(name) => {
-                    let name_str = name.to_string_lossy();
-
-                    // Check if we're at Users/[username] pattern
-                    if name_str.eq_ignore_ascii_case("Users") && i + 1 < components.len() {
-                        if let std::path::Component::Normal(_username) = &components[i + 1] {
-                            // Replace Users/[username] with Users/user_home
-                            result.push("Users");
-                            result.push("user_home");
-                            i += 2; // Skip both Users and username components
-                            continue;
-                        }
-                    }
+    #[cfg(unix)]
+    fn anonymize_unix_path(&self, path: &Path) -> Result<PathBuf> {
+        // Honor a `home` override so saves from a mounted foreign disk map to user_home too
+        let home_dir = self
+            .variables
+            .get("home")
+            .map(PathBuf::from)
+            .or_else(dirs::home_dir);
+        if !home_dir.as_ref().is_some_and(|home| path.starts_with(home)) {
+            // A path written with the physical home, e.g. /var/home on Fedora Atomic where
+            // /home is a symlink, is only recognized once both are resolved
+            if let Some(home_dir) = &home_dir
+                && let (Ok(real_home), Ok(real_path)) =
+                    (fs::canonicalize(home_dir), fs::canonicalize(path))
+                && real_path.starts_with(&real_home)
+            {
+                return Ok(paths::anonymize_unix_path(&real_path, Some(&real_home)));
+            }
+
+            // Other users' homes are anonymized the same way as the current one
+            #[cfg(target_os = "macos")]
+            {
+                if let Some(anonymized) = macos::anonymize_users_path(path) {
+                    return Ok(anonymized);
+                }
+            }
+        }
+
+        Ok(paths::anonymize_unix_path(path, home_dir.as_deref()))
+    }
+
+    /// Visits the files below `source_dir` whose relative path matches `pattern`, in path
+    /// order. Only the relative path is matched as text, so file names that are not valid
+    /// UTF-8 still match wildcards and are visited as they are on disk.
+    fn visit_files_by_pattern(
+        &self,
+        source_dir: &Path,
+        pattern: &str,
+        walked: &mut Vec<PathBuf>,
+        visit: &mut dyn FnMut(&Path) -> Result<()>,
+    ) -> Result<()> {
+        log::debug!(
+            "Searching for files matching pattern: {}",
+            source_dir.join(pattern).display()
+        );
+
+        let pattern = pattern.trim_start_matches("./");
+        let glob_pattern = glob::Pattern::new(pattern)
+            .with_context(|| format!("Invalid glob pattern: {}", pattern))?;
+        // Without `**`, no file deeper than the pattern's own components can match
+        let depth = if pattern.contains("**") {
+            usize::MAX
+        } else {
+            pattern.split('/').count()
+        };
+
+        visit_matching_files(
+            source_dir,
+            Path::new(""),
+            depth,
+            &glob_pattern,
+            walked,
+            visit,
+        )
+    }
+
+    pub fn restore_game(&self, game_name: &str, options: &RestoreOptions) -> Result<RestoreReport> {
+        // Dry runs write nothing, not even the status file
+        let own_status = !options.dry_run && self.begin_status(Operation::Restore, 1);
+        self.report_progress(Progress::Started {
+            operation: Operation::Restore,
+            game: game_name,
+            files: None,
+        });
+        let result = self.run_restore(game_name, options);
+        self.report_progress(Progress::Finished {
+            operation: Operation::Restore,
+            game: game_name,
+            ok: result.is_ok(),
+            error: result.as_ref().err(),
+        });
+        if own_status {
+            self.end_status(result.is_ok());
+        }
+        result
+    }
+
+    fn run_restore(&self, game_name: &str, options: &RestoreOptions) -> Result<RestoreReport> {
+        log::info!("Starting restore for game: {}", game_name);
+        let started = std::time::Instant::now();
+
+        let game = self.find_game(game_name)?;
+        if !options.dry_run {
+            self.recover_replaced_backup(&game.name)?;
+        }
+        let mut report = RestoreReport {
+            game: game.name.clone(),
+            ..RestoreReport::default()
+        };
+
+        if !game.enabled {
+            log::warn!("Game '{}' is disabled, skipping restore", game_name);
+            return Ok(report);
+        }
+        self.warn_cloud_synced(game);
+
+        let running = process::running(&game.processes);
+        if !running.is_empty() {
+            if options.force || options.dry_run {
+                log::warn!(
+                    "Game '{}' is running ({}), restored saves may be overwritten or corrupted",
+                    game.name,
+                    running.join(", ")
+                );
+            } else {
+                return Err(anyhow!(
+                    "Game '{}' is running ({}). Close it before restoring, or pass --force",
+                    game.name,
+                    running.join(", ")
+                ));
+            }
+        }
+
+        let snapshot = match (&options.snapshot, &options.from_machine) {
+            (None, Some(machine)) => Some(self.machine_snapshot(&game.name, machine)?),
+            (snapshot, _) => snapshot.clone(),
+        };
+        let game_backup_dir = self.snapshot_dir(&game.name, snapshot.as_deref())?;
+        if let Ok(Some(manifest)) = Manifest::load(&game_backup_dir) {
+            report.machine = manifest.machine;
+            report.created_at = Some(manifest.created_at);
+        }
+        if let Some(snapshot) = &snapshot {
+            log::info!("Restoring snapshot {} of '{}'", snapshot, game.name);
+        } else {
+            self.warn_about_suspect_backup(game)?;
+            self.warn_about_newer_live_files(game_name, &game_backup_dir, options)?;
+        }
+        let fetched = self.fetch_from_cold(&game.name, &game_backup_dir, options)?;
+        let result = self.restore_locations(
+            game,
+            fetched.as_deref().unwrap_or(&game_backup_dir),
+            options,
+            None,
+            &mut report.actions,
+        );
+        if let Some(fetched) = &fetched
+            && let Err(e) = unprotect_backup(fetched).and_then(|()| {
+                fs::remove_dir_all(fetched).with_context(|| {
+                    format!("Failed to remove fetched snapshot: {}", fetched.display())
+                })
+            })
+        {
+            log::warn!("{:#}", e);
+        }
+        if !options.dry_run {
+            self.audit_restore(
+                game,
+                &game_backup_dir,
+                &report.actions,
+                result.as_ref().err(),
+            );
+        }
+        result?;
+
+        let (written, bytes) = report
+            .actions
+            .iter()
+            .filter(|action| {
+                matches!(
+                    action.kind,
+                    ActionKind::Create | ActionKind::Overwrite | ActionKind::BackupExisting { .. }
+                )
+            })
+            .fold((0, 0), |(count, bytes), action| {
+                (count + 1, bytes + action.source_state.size)
+            });
+        if options.dry_run {
+            log::info!(
+                "Dry run completed for game: {} ({} files, {} would be written)",
+                game_name,
+                written,
+                format_size(bytes)
+            );
+        } else {
+            let elapsed = started.elapsed();
+            log::info!(
+                "Restored {} files, {} in {:.1}s ({}/s)",
+                written,
+                format_size(bytes),
+                elapsed.as_secs_f64(),
+                format_size(throughput(bytes, elapsed))
+            );
+            log::info!("Successfully completed restore for game: {}", game_name);
+            // Snapshots fetched from cold storage were accounted for when they were fetched
+            if fetched.is_none() {
+                self.record_transfer(&game.name, &self.backup_root, 0, bytes);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Restores every save location of a game. With a `sandbox`, location `i` is restored into
+    /// `sandbox/i` instead of its live save path.
+    fn restore_locations(
+        &self,
+        game: &Game,
+        game_backup_dir: &Path,
+        options: &RestoreOptions,
+        sandbox: Option<&Path>,
+        actions: &mut Vec<FileAction>,
+    ) -> Result<()> {
+        let manifest = Manifest::load(game_backup_dir).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable backup manifest: {:#}", e);
+            None
+        });
+        let patterns = options.file_patterns()?;
+        let owner = if sandbox.is_none()
+            && !options.dry_run
+            && (options.as_user || self.config.restore_as_user)
+        {
+            Some(Owner::invoking(game.sensitive)?)
+        } else {
+            None
+        };
+        let format = stored_format(game_backup_dir, manifest.as_ref());
+        if format != self.game_format(game) {
+            log::info!(
+                "Backup of '{}' is stored as {}, not as currently configured",
+                game.name,
+                format.name()
+            );
+        }
 
-                    // Regular component, add as-is
-                    result.push(name);
-                    i += 1;
+        // Checked for every location before any is written
+        for save_location in &game.saves {
+            if sandbox.is_none()
+                && let Some(dest_path) = self.save_path(save_location)?
+                && let Some(root) = self.overlapping_backups(&dest_path)
+            {
+                return Err(anyhow!(
+                    "Refusing to restore '{}' to {}, it overlaps the backups in {}. Check the \
+                     save path and the variables it uses",
+                    game.name,
+                    dest_path.display(),
+                    root.display()
+                ));
+            }
+        }
+
+        for (i, save_location) in game.saves.iter().enumerate() {
+            log::info!(
+                "Processing restore location {}/{} for game '{}'",
+                i + 1,
+                game.saves.len(),
+                game.name
+            );
+
+            if let Some(kind) = options.kind
+                && save_location.kind != kind
+            {
+                log::info!("Not restoring {} location", save_location.kind);
+                continue;
+            }
+
+            let Some(dest_path) = self.save_path(save_location)? else {
+                log::info!(
+                    "No save path configured for {} (available: {}), skipping",
+                    std::env::consts::OS,
+                    save_location.path.platforms().join(", ")
+                );
+                continue;
+            };
+
+            if save_location.kind == SaveKind::Mods {
+                self.warn_about_mod_changes(i, &dest_path, manifest.as_ref());
+                if save_location.is_mod_list_only() {
+                    log::info!("Only the mod list was backed up, nothing to restore");
+                    continue;
                 }
-                _ => {
-                    // Should not happen in the remaining components, but handle gracefully
-                    i += 1;
+            }
+
+            let selection = RestoreSelection {
+                format,
+                prefix: self.backup_prefix(i, &dest_path, manifest.as_ref())?,
+                patterns: patterns.clone(),
+                rules: self.ignore_rules(i, save_location, manifest.as_ref()),
+            };
+            let dest_path = match sandbox {
+                Some(sandbox) => sandbox.join(i.to_string()),
+                None => dest_path,
+            };
+            // Topmost directory the restore is going to create
+            let created = dest_path
+                .ancestors()
+                .take_while(|dir| !dir.exists())
+                .last()
+                .map(Path::to_path_buf);
+            let first_action = actions.len();
+            self.restore_save_location(
+                game,
+                &dest_path,
+                game_backup_dir,
+                &selection,
+                options,
+                actions,
+            )?;
+            if let Some(owner) = &owner {
+                owner.apply(&dest_path, created.as_deref(), &actions[first_action..])?;
+            }
+            if sandbox.is_none() && !options.dry_run {
+                self.record_conflicts(
+                    &game.name,
+                    &dest_path,
+                    save_location.kind,
+                    &selection.prefix,
+                    manifest.as_ref(),
+                    &actions[first_action..],
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records a restore in the audit log. Failing to do so does not undo the restore,
+    /// so it is only reported.
+    fn audit_restore(
+        &self,
+        game: &Game,
+        game_backup_dir: &Path,
+        actions: &[FileAction],
+        error: Option<&anyhow::Error>,
+    ) {
+        let snapshot = Manifest::load(game_backup_dir)
+            .ok()
+            .flatten()
+            .map(|manifest| snapshot::snapshot_id(&manifest));
+        let mut entry = audit::AuditEntry::new(&game.name, snapshot, game_backup_dir, actions);
+        entry.error = error.map(|e| format!("{:#}", e));
+
+        if let Err(e) = audit::append(&self.backup_root, &entry) {
+            log::error!(
+                "Failed to record restore of '{}' in the audit log: {:#}",
+                game.name,
+                e
+            );
+        }
+    }
+
+    fn warn_about_suspect_backup(&self, game: &Game) -> Result<()> {
+        let snapshots = self.list_snapshots(&game.name)?;
+        let Some(current) = snapshots.iter().find(|snapshot| snapshot.current) else {
+            return Ok(());
+        };
+        if !current.is_suspect() {
+            return Ok(());
+        }
+
+        log::warn!(
+            "The latest backup of '{}' failed validation for {} file(s)",
+            game.name,
+            current.validation_issues
+        );
+        if let Some(good) = snapshots
+            .iter()
+            .find(|snapshot| snapshot.is_last_known_good())
+        {
+            log::warn!(
+                "Last known good snapshot is {}; restore it with --snapshot {}",
+                good.id,
+                snapshot::LAST_KNOWN_GOOD
+            );
+        }
+        Ok(())
+    }
+
+    fn warn_about_newer_live_files(
+        &self,
+        game_name: &str,
+        game_backup_dir: &Path,
+        options: &RestoreOptions,
+    ) -> Result<()> {
+        if !game_backup_dir.join(manifest::MANIFEST_FILE).exists() {
+            return Ok(());
+        }
+
+        let patterns = options.file_patterns()?;
+        let mut kind_paths = Vec::new();
+        if let Some(kind) = options.kind {
+            for save_location in &self.find_game(game_name)?.saves {
+                if save_location.kind == kind
+                    && let Some(path) = self.save_path(save_location)?
+                {
+                    kind_paths.push(path);
                 }
             }
         }
+        match self.diff_game(game_name) {
+            Ok(diff) => {
+                let newer: Vec<&DiffEntry> = diff
+                    .newer_live_files()
+                    .filter(|entry| {
+                        patterns.is_empty() || plan::matches_any(&patterns, Path::new(&entry.path))
+                    })
+                    .filter(|entry| {
+                        options.kind.is_none()
+                            || entry.live_path.as_ref().is_some_and(|live_path| {
+                                kind_paths.iter().any(|path| live_path.starts_with(path))
+                            })
+                    })
+                    .collect();
+                if !newer.is_empty() {
+                    log::warn!(
+                        "Restore will overwrite {} local files that are newer than the backup:",
+                        newer.len()
+                    );
+                    for entry in newer {
+                        let path = entry
+                            .live_path
+                            .as_ref()
+                            .map(|p| p.display().to_string())
+                            .unwrap_or_else(|| entry.path.clone());
+                        log::warn!("  {}", path);
+                    }
+                }
+            }
+            Err(e) => log::debug!("Could not compare live saves with backup: {:#}", e),
+        }
+        Ok(())
+    }
+
+    fn restore_save_location(
+        &self,
+        game: &Game,
+        dest_path: &Path,
+        game_backup_dir: &Path,
+        selection: &RestoreSelection,
+        options: &RestoreOptions,
+        actions: &mut Vec<FileAction>,
+    ) -> Result<()> {
+        log::info!("Restoring to: {}", dest_path.display());
+
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(container) = macos::cloud_synced_container(dest_path) {
+                log::warn!(
+                    "Restore destination is synced by iCloud ({}); the next sync may overwrite restored files",
+                    container.display()
+                );
+            }
+        }
+
+        if let Some(archive_format) = selection.format.archive() {
+            let archive_path = archive_path(game_backup_dir, archive_format);
+            if !volume::exists(&archive_path) {
+                return Err(anyhow!(
+                    "Backup archive does not exist: {}",
+                    archive_path.display()
+                ));
+            }
+
+            log::debug!(
+                "Restore source: {} in {}",
+                selection.prefix.display(),
+                archive_path.display()
+            );
+
+            // Extract into the staging area first so conflicts are handled exactly like
+            // for directory backups
+            let extract_dir = self
+                .backup_root
+                .join(STAGING_DIR)
+                .join(format!("{}.restore", game.name));
+            if extract_dir.exists() {
+                fs::remove_dir_all(&extract_dir).with_context(|| {
+                    format!(
+                        "Failed to clean extraction directory: {}",
+                        extract_dir.display()
+                    )
+                })?;
+            }
+            fs::create_dir_all(&extract_dir).with_context(|| {
+                format!(
+                    "Failed to create extraction directory: {}",
+                    extract_dir.display()
+                )
+            })?;
+
+            let dictionary = archive::read_dictionary(game_backup_dir)?;
+            let result = archive::extract(
+                &archive_path,
+                archive_format,
+                dictionary.as_deref(),
+                &selection.prefix,
+                &extract_dir,
+            )
+            .and_then(|file_count| {
+                log::info!("Extracted {} files from backup archive", file_count);
+                self.restore_into(&extract_dir, dest_path, selection, options, actions)
+            });
+            if let Err(e) = fs::remove_dir_all(&extract_dir) {
+                log::warn!(
+                    "Failed to remove extraction directory {}: {}",
+                    extract_dir.display(),
+                    e
+                );
+            }
+            return result;
+        }
 
-        Ok(result)
-    }
+        let backup_subdir = game_backup_dir.join(&selection.prefix);
+        log::debug!("Restore source: {}", backup_subdir.display());
 
-    #[cfg(unix)]
-    fn anonymize_unix_path(&self, path: &Path) -> Result<PathBuf> {
-        if let Some(home_dir) = dirs::home_dir() {
-            if let Ok(relative_path) = path.strip_prefix(&home_dir) {
-                // Path is under home directory, replace with user_home
-                let mut anonymized = PathBuf::from("user_home");
-                anonymized.push(relative_path);
-                return Ok(anonymized);
-            }
+        if !backup_subdir.exists() {
+            return Err(anyhow!(
+                "Backup directory does not exist: {}",
+                backup_subdir.display()
+            ));
         }
 
-        // Path is not under home directory, keep as is but remove leading slash
-        if path.is_absolute() {
-            let mut result = PathBuf::new();
-            for component in path.components().skip(1) {
-                // Skip root component
-                if let std::path::Component::Normal(name) = component {
-                    result.push(name);
-                }
-            }
-            return Ok(result);
+        self.restore_into(&backup_subdir, dest_path, selection, options, actions)
+    }
+
+    fn restore_into(
+        &self,
+        source: &Path,
+        dest_path: &Path,
+        selection: &RestoreSelection,
+        options: &RestoreOptions,
+        actions: &mut Vec<FileAction>,
+    ) -> Result<()> {
+        if !options.dry_run {
+            // Create destination directory if it doesn't exist
+            fs::create_dir_all(dest_path).with_context(|| {
+                format!(
+                    "Failed to create destination directory: {}",
+                    dest_path.display()
+                )
+            })?;
         }
 
-        Ok(path.to_path_buf())
+        self.restore_files(
+            source,
+            dest_path,
+            Path::new(""),
+            selection,
+            options,
+            actions,
+        )
     }
 
-    fn copy_all_files(&self, source: &Path, dest: &Path) -> Result<()> {
+    fn restore_files(
+        &self,
+        source: &Path,
+        dest: &Path,
+        relative: &Path,
+        selection: &RestoreSelection,
+        options: &RestoreOptions,
+        actions: &mut Vec<FileAction>,
+    ) -> Result<()> {
         log::debug!(
-            "Copying all files from {} to {}",
+            "Restoring all files from {} to {}",
             source.display(),
             dest.display()
         );
 
-        if source.is_file() {
-            let file_name = source
-                .file_name()
-                .ok_or_else(|| anyhow!("Invalid file name: {}", source.display()))?;
-            let dest_file = dest.join(file_name);
-            log::debug!(
-                "Copying file: {} -> {}",
-                source.display(),
-                dest_file.display()
-            );
-            fs::copy(source, dest_file)
-                .with_context(|| format!("Failed to copy file: {}", source.display()))?;
-            return Ok(());
-        }
-
         let entries = fs::read_dir(source)
             .with_context(|| format!("Failed to read directory: {}", source.display()))?;
 
@@ -439,136 +2291,148 @@ impl GameBackup {
                 format!("Failed to read directory entry in: {}", source.display())
             })?;
             let path = entry.path();
-            let file_name = entry.file_name();
-            let dest_path = dest.join(&file_name);
+            let dest_path = dest.join(entry.file_name());
+            let entry_relative = relative.join(entry.file_name());
+
+            if !selection.rules.include_hidden && is_hidden(&entry) {
+                log::debug!("Not restoring ignored file: {}", entry_relative.display());
+                continue;
+            }
 
             if path.is_dir() {
-                log::debug!("Creating directory: {}", dest_path.display());
-                fs::create_dir_all(&dest_path).with_context(|| {
-                    format!("Failed to create directory: {}", dest_path.display())
-                })?;
-                self.copy_all_files(&path, &dest_path)?;
+                // With a file filter, directories are only created once a matching file needs them
+                if !options.dry_run && selection.patterns.is_empty() {
+                    log::debug!("Creating directory: {}", dest_path.display());
+                    fs::create_dir_all(&dest_path).with_context(|| {
+                        format!("Failed to create directory: {}", dest_path.display())
+                    })?;
+                }
+                self.restore_files(
+                    &path,
+                    &dest_path,
+                    &entry_relative,
+                    selection,
+                    options,
+                    actions,
+                )?;
             } else {
-                log::debug!(
-                    "Copying file: {} -> {}",
-                    path.display(),
-                    dest_path.display()
-                );
-                fs::copy(&path, &dest_path)
-                    .with_context(|| format!("Failed to copy file: {}", path.display()))?;
+                if !selection.patterns.is_empty() {
+                    if !plan::matches_any(&selection.patterns, &entry_relative) {
+                        log::debug!("Not restoring filtered file: {}", entry_relative.display());
+                        continue;
+                    }
+                    if !options.dry_run {
+                        fs::create_dir_all(dest).with_context(|| {
+                            format!("Failed to create directory: {}", dest.display())
+                        })?;
+                    }
+                }
+                actions.push(self.restore_file(&path, &dest_path, options)?);
             }
         }
 
         Ok(())
     }
 
-    fn copy_files_by_pattern(
+    fn restore_file(
         &self,
-        source_dir: &Path,
-        dest_dir: &Path,
-        pattern: &str,
-    ) -> Result<()> {
-        let full_pattern = source_dir.join(pattern);
-        let pattern_str = full_pattern.to_string_lossy();
-
-        log::debug!("Searching for files matching pattern: {}", pattern_str);
-
-        let paths = glob::glob(&pattern_str)
-            .with_context(|| format!("Invalid glob pattern: {}", pattern_str))?;
-
-        let mut file_count = 0;
-        for path_result in paths {
-            let path = path_result
-                .with_context(|| format!("Error processing glob pattern: {}", pattern_str))?;
-
-            if path.is_file() {
-                let file_name = path
-                    .file_name()
-                    .ok_or_else(|| anyhow!("Invalid file name: {}", path.display()))?;
-                let dest_file = dest_dir.join(file_name);
-
-                log::debug!(
-                    "Copying file: {} -> {}",
-                    path.display(),
-                    dest_file.display()
-                );
-                fs::copy(&path, &dest_file)
-                    .with_context(|| format!("Failed to copy file: {}", path.display()))?;
-                file_count += 1;
+        source: &Path,
+        dest: &Path,
+        options: &RestoreOptions,
+    ) -> Result<FileAction> {
+        let source_metadata = fs::metadata(source)
+            .with_context(|| format!("Failed to read metadata: {}", source.display()))?;
+        let dest_metadata = fs::metadata(dest).ok();
+
+        let kind = match &dest_metadata {
+            None => ActionKind::Create,
+            Some(dest_metadata) => {
+                if plan::files_identical(source, dest, &source_metadata, dest_metadata)? {
+                    ActionKind::Unchanged
+                } else {
+                    plan::resolve_conflict(dest, options)?
+                }
             }
-        }
-
-        log::info!("Copied {} files matching pattern: {}", file_count, pattern);
-        Ok(())
-    }
-
-    pub fn restore_game(&self, game_name: &str) -> Result<()> {
-        log::info!("Starting restore for game: {}", game_name);
-
-        let game = self
-            .config
-            .games
-            .iter()
-            .find(|g| g.name == game_name)
-            .ok_or_else(|| anyhow!("Game '{}' not found in configuration", game_name))?;
-
-        if !game.enabled {
-            log::warn!("Game '{}' is disabled, skipping restore", game_name);
-            return Ok(());
-        }
-
-        let game_backup_dir = self.backup_root.join(&game.name);
-        if !game_backup_dir.exists() {
-            return Err(anyhow!("No backup found for game: {}", game_name));
-        }
+        };
 
-        for (i, save_location) in game.saves.iter().enumerate() {
-            log::info!(
-                "Processing restore location {}/{} for game '{}'",
-                i + 1,
-                game.saves.len(),
-                game.name
-            );
-            self.restore_save_location(save_location, &game_backup_dir)?;
+        let read_only = dest_metadata
+            .as_ref()
+            .is_some_and(|metadata| metadata.permissions().readonly());
+        let read_only_policy = options.on_read_only.unwrap_or(self.config.on_read_only);
+        if read_only
+            && read_only_policy == ReadOnlyPolicy::Fail
+            && matches!(
+                kind,
+                ActionKind::Overwrite | ActionKind::BackupExisting { .. }
+            )
+        {
+            if !options.dry_run {
+                return Err(anyhow!(
+                    "Refusing to overwrite read-only file: {} (use --on-read-only clear or reapply)",
+                    dest.display()
+                ));
+            }
+            log::warn!("Restore would fail on read-only file: {}", dest.display());
         }
 
-        log::info!("Successfully completed restore for game: {}", game_name);
-        Ok(())
-    }
-
-    fn restore_save_location(
-        &self,
-        save_location: &SaveLocation,
-        game_backup_dir: &Path,
-    ) -> Result<()> {
-        let dest_path = self.expand_variables(&save_location.path)?;
-        let dest_path = Path::new(&dest_path);
-
-        log::info!("Restoring to: {}", dest_path.display());
-
-        let backup_subdir = self.create_backup_path(dest_path, game_backup_dir)?;
-        log::debug!("Restore source: {}", backup_subdir.display());
-
-        if !backup_subdir.exists() {
-            return Err(anyhow!(
-                "Backup directory does not exist: {}",
-                backup_subdir.display()
-            ));
+        if !options.dry_run {
+            match &kind {
+                ActionKind::Create => {
+                    log::debug!("Copying file: {} -> {}", source.display(), dest.display());
+                    copy_restored_file(source, dest)?;
+                }
+                ActionKind::Overwrite => {
+                    log::debug!("Copying file: {} -> {}", source.display(), dest.display());
+                    if read_only {
+                        log::debug!("Clearing read-only attribute: {}", dest.display());
+                        protect::make_writable(dest)?;
+                    }
+                    copy_restored_file(source, dest)?;
+                    if read_only && read_only_policy == ReadOnlyPolicy::Reapply {
+                        set_read_only(dest)?;
+                    }
+                }
+                ActionKind::BackupExisting { stash } => {
+                    log::info!(
+                        "Moving existing file aside: {} -> {}",
+                        dest.display(),
+                        stash.display()
+                    );
+                    fs::rename(dest, stash).with_context(|| {
+                        format!("Failed to move existing file aside: {}", dest.display())
+                    })?;
+                    copy_restored_file(source, dest)?;
+                    if read_only && read_only_policy == ReadOnlyPolicy::Reapply {
+                        set_read_only(dest)?;
+                    }
+                }
+                ActionKind::Skip => {
+                    log::info!("Keeping existing file: {}", dest.display());
+                }
+                ActionKind::Unchanged | ActionKind::Conflict => {}
+            }
+            if matches!(
+                kind,
+                ActionKind::Create | ActionKind::Overwrite | ActionKind::BackupExisting { .. }
+            ) {
+                self.report_file(Operation::Restore, dest);
+            }
         }
 
-        // Create destination directory if it doesn't exist
-        fs::create_dir_all(dest_path).with_context(|| {
-            format!(
-                "Failed to create destination directory: {}",
-                dest_path.display()
-            )
-        })?;
-
-        self.copy_all_files(&backup_subdir, dest_path)?;
-        Ok(())
+        Ok(FileAction {
+            source: source.to_path_buf(),
+            dest: dest.to_path_buf(),
+            kind,
+            // Restored copies are always writable, whatever the protection of the backup
+            source_state: FileState {
+                read_only: false,
+                ..FileState::from_metadata(&source_metadata)
+            },
+            dest_state: dest_metadata.as_ref().map(FileState::from_metadata),
+        })
     }
 
-    pub fn backup_all_games(&self) -> Result<()> {
+    pub fn backup_all_games(&self, kind: Option<SaveKind>) -> Result<()> {
         log::info!("Starting backup for all enabled games");
 
         let enabled_games: Vec<&Game> = self
@@ -576,25 +2440,45 @@ impl GameBackup {
             .games
             .iter()
             .filter(|game| game.enabled)
+            .filter(|game| {
+                if game.archived {
+                    log::info!("Game '{}' is archived, skipping backup", game.name);
+                }
+                !game.archived
+            })
             .collect();
 
         if enabled_games.is_empty() {
             log::warn!("No enabled games found in configuration");
             return Ok(());
         }
+        self.backup_games(&enabled_games, kind)
+    }
 
+    /// Backs up the given games one after the other, stopping early only when the backup
+    /// destination is full.
+    fn backup_games(&self, enabled_games: &[&Game], kind: Option<SaveKind>) -> Result<()> {
         let mut success_count = 0;
         let mut error_count = 0;
+        let mut completed = Vec::new();
+        // Index of the first game not attempted after the destination filled up
+        let mut stopped_at = None;
 
-        for game in enabled_games {
-            match self.backup_game(&game.name) {
+        let own_status = self.begin_status(Operation::Backup, enabled_games.len());
+        for (i, game) in enabled_games.iter().enumerate() {
+            match self.backup_game(&game.name, kind) {
                 Ok(()) => {
                     success_count += 1;
+                    completed.push(game.name.as_str());
                     log::info!("✓ Successfully backed up: {}", game.name);
                 }
                 Err(e) => {
                     error_count += 1;
                     log::error!("✗ Failed to backup '{}': {}", game.name, e);
+                    if fserror::is_disk_full(&e) {
+                        stopped_at = Some(i + 1);
+                        break;
+                    }
                 }
             }
         }
@@ -604,6 +2488,26 @@ impl GameBackup {
             success_count,
             error_count
         );
+        if own_status {
+            self.end_status(error_count == 0);
+        }
+
+        if let Some(stopped_at) = stopped_at {
+            let names = |names: &[&str]| match names {
+                [] => "none".to_string(),
+                names => names.join(", "),
+            };
+            let skipped: Vec<&str> = enabled_games[stopped_at..]
+                .iter()
+                .map(|game| game.name.as_str())
+                .collect();
+            return Err(anyhow!(
+                "Backup stopped because the backup destination is full. \
+                 Backed up before that: {}. Not attempted: {}",
+                names(&completed),
+                names(&skipped)
+            ));
+        }
 
         if error_count > 0 {
             return Err(anyhow!(
@@ -614,7 +2518,7 @@ impl GameBackup {
         Ok(())
     }
 
-    pub fn restore_all_games(&self) -> Result<()> {
+    pub fn restore_all_games(&self, options: &RestoreOptions) -> Result<Vec<RestoreReport>> {
         log::info!("Starting restore for all enabled games");
 
         let enabled_games: Vec<&Game> = self
@@ -622,19 +2526,29 @@ impl GameBackup {
             .games
             .iter()
             .filter(|game| game.enabled)
+            .filter(|game| {
+                if game.archived {
+                    log::info!("Game '{}' is archived, skipping restore", game.name);
+                }
+                !game.archived
+            })
             .collect();
 
         if enabled_games.is_empty() {
             log::warn!("No enabled games found in configuration");
-            return Ok(());
+            return Ok(Vec::new());
         }
 
         let mut success_count = 0;
         let mut error_count = 0;
+        let mut reports = Vec::new();
 
+        let own_status =
+            !options.dry_run && self.begin_status(Operation::Restore, enabled_games.len());
         for game in enabled_games {
-            match self.restore_game(&game.name) {
-                Ok(()) => {
+            match self.restore_game(&game.name, options) {
+                Ok(report) => {
+                    reports.push(report);
                     success_count += 1;
                     log::info!("✓ Successfully restored: {}", game.name);
                 }
@@ -650,6 +2564,9 @@ impl GameBackup {
             success_count,
             error_count
         );
+        if own_status {
+            self.end_status(error_count == 0);
+        }
 
         if error_count > 0 {
             return Err(anyhow!(
@@ -657,8 +2574,344 @@ impl GameBackup {
             ));
         }
 
+        Ok(reports)
+    }
+
+    pub fn export_game(&self, game_name: &str, output: &Path) -> Result<()> {
+        log::info!(
+            "Exporting backups for game '{}' to: {}",
+            game_name,
+            output.display()
+        );
+
+        let format = ArchiveFormat::from_path(output).ok_or_else(|| {
+            anyhow!(
+                "Unsupported export file extension: {} (expected .zip or .tar.zst)",
+                output.display()
+            )
+        })?;
+
+        let game_backup_dir = self.backup_root.join(game_name);
+        if !game_backup_dir.exists() {
+            return Err(anyhow!("No backup found for game: {}", game_name));
+        }
+
+        let mut writer = ArchiveWriter::create(output, format, None, None)?;
+        let result = writer
+            .add_dir_all(&game_backup_dir, Path::new(game_name))
+            .and_then(|()| writer.finish());
+        if let Err(e) = result {
+            let _ = fs::remove_file(output);
+            return Err(e);
+        }
+
+        log::info!("Successfully exported game: {}", game_name);
         Ok(())
     }
+
+    pub fn import_game(&self, input: &Path, force: bool) -> Result<String> {
+        log::info!("Importing backups from: {}", input.display());
+
+        let format = ArchiveFormat::from_path(input).ok_or_else(|| {
+            anyhow!(
+                "Unsupported import file extension: {} (expected .zip or .tar.zst)",
+                input.display()
+            )
+        })?;
+
+        // An export holds exactly one game directory at its top level
+        let mut game_names: Vec<String> = archive::list(input, format, None)?
+            .iter()
+            .filter_map(|name| name.components().next())
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect();
+        game_names.sort();
+        game_names.dedup();
+
+        let game_name = match game_names.as_slice() {
+            [name] => name.clone(),
+            [] => return Err(anyhow!("Archive is empty: {}", input.display())),
+            _ => {
+                return Err(anyhow!(
+                    "Archive contains backups for multiple games ({}): {}",
+                    game_names.join(", "),
+                    input.display()
+                ));
+            }
+        };
+
+        if !self.config.games.iter().any(|g| g.name == game_name) {
+            log::warn!("Game '{}' is not defined in configuration", game_name);
+        }
+
+        let game_backup_dir = self.backup_root.join(&game_name);
+        if game_backup_dir.exists() {
+            if !force {
+                return Err(anyhow!(
+                    "Backup for game '{}' already exists: {}. Use --force to replace it",
+                    game_name,
+                    game_backup_dir.display()
+                ));
+            }
+            self.ensure_unlocked("replace an existing backup")?;
+            log::info!("Removing existing backup: {}", game_backup_dir.display());
+            unprotect_backup(&game_backup_dir)?;
+            fs::remove_dir_all(&game_backup_dir).with_context(|| {
+                format!(
+                    "Failed to remove existing backup: {}",
+                    game_backup_dir.display()
+                )
+            })?;
+        }
+
+        fs::create_dir_all(&self.backup_root).with_context(|| {
+            format!(
+                "Failed to create backup directory: {}",
+                self.backup_root.display()
+            )
+        })?;
+
+        let file_count = archive::extract(input, format, None, Path::new(""), &self.backup_root)?;
+
+        let protection = self
+            .config
+            .games
+            .iter()
+            .find(|g| g.name == game_name)
+            .map(|game| self.game_protection(game))
+            .unwrap_or(self.config.protect);
+        if let Some(mut manifest) = Manifest::load(&game_backup_dir)? {
+            manifest.protection = protection;
+            manifest.save(&game_backup_dir)?;
+        }
+        protect::protect(&game_backup_dir, protection)?;
+
+        log::info!(
+            "Successfully imported {} files for game: {}",
+            file_count,
+            game_name
+        );
+        Ok(game_name)
+    }
+
+    pub fn list_quarantine(&self) -> Result<Vec<QuarantineEntry>> {
+        quarantine::list(&self.backup_root)
+    }
+
+    pub fn quarantine_entry(&self, id: &str) -> Result<QuarantineEntry> {
+        quarantine::get(&self.backup_root, id)
+    }
+
+    pub fn purge_quarantine(&self, id: Option<&str>) -> Result<usize> {
+        self.ensure_unlocked("purge quarantined backups")?;
+        let entries = match id {
+            Some(id) => vec![self.quarantine_entry(id)?],
+            None => self.list_quarantine()?,
+        };
+
+        for entry in &entries {
+            quarantine::purge(entry)?;
+        }
+
+        log::info!("Purged {} quarantine entries", entries.len());
+        Ok(entries.len())
+    }
+}
+
+/// Human-readable size using binary units, e.g. `1.5 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Bytes per second for a transfer.
+fn throughput(bytes: u64, elapsed: std::time::Duration) -> u64 {
+    // Avoid dividing by zero for runs that finish within the timer resolution
+    let seconds = elapsed.as_secs_f64().max(0.001);
+    (bytes as f64 / seconds) as u64
+}
+
+/// Visits the files below `dir`, at most `depth` levels down, whose path relative to the save
+/// location matches `pattern`. Only the names of one directory are held at a time, sorted so
+/// the files come in the same order on every platform.
+fn visit_matching_files(
+    dir: &Path,
+    relative: &Path,
+    depth: usize,
+    pattern: &glob::Pattern,
+    walked: &mut Vec<PathBuf>,
+    visit: &mut dyn FnMut(&Path) -> Result<()>,
+) -> Result<()> {
+    if depth == 0 || !dir.is_dir() {
+        return Ok(());
+    }
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..glob::MatchOptions::new()
+    };
+    let mut names = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<io::Result<Vec<_>>>()
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    names.sort();
+    for name in names {
+        let path = dir.join(&name);
+        let relative = relative.join(&name);
+        if path.is_dir() {
+            if depth == 1 {
+                continue;
+            }
+            let Some(real) = enter_dir(walked, &path)? else {
+                continue;
+            };
+            walked.push(real);
+            let result = visit_matching_files(&path, &relative, depth - 1, pattern, walked, visit);
+            walked.pop();
+            result?;
+        } else if path.is_file() && pattern.matches_with(&manifest::entry_key(&relative), options) {
+            visit(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Real path of a directory met while walking the last of `walked`, or `None` when it is a
+/// symlink to one of the directories being walked, or above them, which would never end.
+fn enter_dir(walked: &[PathBuf], path: &Path) -> Result<Option<PathBuf>> {
+    let metadata = fs::symlink_metadata(path)
+        .with_context(|| format!("Failed to read metadata: {}", path.display()))?;
+    let parent = walked.last().map(PathBuf::as_path).unwrap_or(Path::new(""));
+    if !metadata.file_type().is_symlink()
+        && let Some(name) = path.file_name()
+    {
+        return Ok(Some(parent.join(name)));
+    }
+    let real = fs::canonicalize(path)
+        .with_context(|| format!("Failed to resolve symlink: {}", path.display()))?;
+    if walked.iter().any(|dir| dir.starts_with(&real)) {
+        log::warn!(
+            "Skipping {}, it links back to {}",
+            path.display(),
+            real.display()
+        );
+        return Ok(None);
+    }
+    log::debug!("Following symlink {} to {}", path.display(), real.display());
+    Ok(Some(real))
+}
+
+/// Where the backup a staged one replaces is moved while they are swapped.
+fn previous_backup_dir(staging_dir: &Path) -> PathBuf {
+    let mut previous_dir = staging_dir.as_os_str().to_os_string();
+    previous_dir.push(".old");
+    PathBuf::from(previous_dir)
+}
+
+/// `path` with symlinks resolved as far as it exists, the rest appended as it is.
+fn real_path(path: &Path) -> PathBuf {
+    for existing in path.ancestors() {
+        if let Ok(real) = fs::canonicalize(existing) {
+            return match path.strip_prefix(existing) {
+                Ok(rest) if !rest.as_os_str().is_empty() => real.join(rest),
+                _ => real,
+            };
+        }
+    }
+    path.to_path_buf()
+}
+
+/// OS metadata files that are never part of a save.
+const JUNK_FILES: &[&str] = &["thumbs.db", "desktop.ini", ".ds_store"];
+
+/// Whether a directory entry is a dotfile, OS junk, or flagged hidden or system on Windows.
+fn is_hidden(entry: &fs::DirEntry) -> bool {
+    let file_name = entry.file_name();
+    let file_name = file_name.to_string_lossy();
+    if file_name.starts_with('.') || JUNK_FILES.contains(&file_name.to_lowercase().as_str()) {
+        return true;
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+        if let Ok(metadata) = entry.metadata()
+            && metadata.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0
+        {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Configuration files of a workspace directory, in name order.
+pub fn find_config_files(config_dir: &Path) -> Result<Vec<PathBuf>> {
+    log::info!("Loading config files from: {}", config_dir.display());
+    let entries = fs::read_dir(config_dir)
+        .with_context(|| format!("Failed to read config directory: {}", config_dir.display()))?;
+
+    let mut toml_files = Vec::new();
+    for entry in entries {
+        let entry = entry.with_context(|| "Failed to read directory entry")?;
+        let path = entry.path();
+
+        if path.is_file()
+            && let Some(extension) = path.extension()
+            && extension == "toml"
+        {
+            toml_files.push(path);
+        }
+    }
+
+    if toml_files.is_empty() {
+        return Err(anyhow!(
+            "No TOML configuration files found in: {}",
+            config_dir.display()
+        ));
+    }
+    toml_files.sort();
+    Ok(toml_files)
+}
+
+/// Directory backups are stored in for the given config file.
+pub fn backup_root(config_path: &Path) -> PathBuf {
+    config_dir(config_path).join("backup")
+}
+
+/// Directory relative paths in a config file are resolved against: the one the file really
+/// is in, so a config symlinked into place by a dotfile manager finds its backups next to
+/// itself whichever link it was loaded through.
+fn config_dir(config_path: &Path) -> PathBuf {
+    let config_path = match fs::canonicalize(config_path) {
+        Ok(real) => {
+            if real != config_path {
+                log::debug!(
+                    "Config {} resolves to {}",
+                    config_path.display(),
+                    real.display()
+                );
+            }
+            real
+        }
+        Err(_) => config_path.to_path_buf(),
+    };
+    config_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf()
 }
 
 pub fn find_config_file(config_path: Option<&str>) -> Result<PathBuf> {
@@ -691,12 +2944,11 @@ pub fn find_config_file(config_path: Option<&str>) -> Result<PathBuf> {
         let entry = entry.with_context(|| "Failed to read directory entry")?;
         let path = entry.path();
 
-        if path.is_file() {
-            if let Some(extension) = path.extension() {
-                if extension == "toml" {
-                    toml_files.push(path);
-                }
-            }
+        if path.is_file()
+            && let Some(extension) = path.extension()
+            && extension == "toml"
+        {
+            toml_files.push(path);
         }
     }
 
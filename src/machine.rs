@@ -0,0 +1,97 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use crate::GameBackup;
+
+/// A machine that backed up a game into this backup root, e.g. through a synced folder.
+#[derive(Debug, Serialize)]
+pub struct SourceMachine {
+    /// Name recorded in its backups, `None` for backups taken before machines were recorded
+    pub name: Option<String>,
+    /// Id of its newest snapshot of the game
+    pub latest: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub snapshots: usize,
+}
+
+impl GameBackup {
+    /// Name recorded in new backups as the machine they were taken on: `machine` from the
+    /// config, or the host name.
+    pub fn machine_name(&self) -> Option<&str> {
+        self.machine
+            .get_or_init(|| {
+                if let Some(machine) = &self.config.machine {
+                    return Some(machine.clone());
+                }
+                let name = host_name();
+                if name.is_none() {
+                    log::debug!("Could not find the host name, backups will not record it");
+                }
+                name
+            })
+            .as_deref()
+    }
+
+    /// Machines with snapshots of a game, the one with the newest snapshot first.
+    pub fn source_machines(&self, game_name: &str) -> Result<Vec<SourceMachine>> {
+        let mut machines: BTreeMap<Option<String>, SourceMachine> = BTreeMap::new();
+        for snapshot in self.list_snapshots(game_name)? {
+            let machine =
+                machines
+                    .entry(snapshot.machine.clone())
+                    .or_insert_with(|| SourceMachine {
+                        name: snapshot.machine.clone(),
+                        latest: snapshot.id.clone(),
+                        created_at: snapshot.created_at,
+                        snapshots: 0,
+                    });
+            machine.snapshots += 1;
+            if snapshot.created_at > machine.created_at {
+                machine.latest = snapshot.id;
+                machine.created_at = snapshot.created_at;
+            }
+        }
+        let mut machines: Vec<SourceMachine> = machines.into_values().collect();
+        machines.sort_by_key(|machine| Reverse(machine.created_at));
+        Ok(machines)
+    }
+
+    /// Id of the newest snapshot of a game taken on `machine`.
+    pub(crate) fn machine_snapshot(&self, game_name: &str, machine: &str) -> Result<String> {
+        self.list_snapshots(game_name)?
+            .into_iter()
+            .filter(|snapshot| snapshot.machine.as_deref() == Some(machine))
+            .max_by_key(|snapshot| snapshot.created_at)
+            .map(|snapshot| snapshot.id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No backup of '{}' was taken on '{}'. Run `cartridge restore {} --list-machines` to list the machines that have one",
+                    game_name,
+                    machine,
+                    game_name
+                )
+            })
+    }
+}
+
+/// Host name of this machine, without any domain.
+fn host_name() -> Option<String> {
+    #[cfg(windows)]
+    let name = std::env::var("COMPUTERNAME").ok();
+    #[cfg(not(windows))]
+    let name = std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| {
+            let output = std::process::Command::new("hostname").output().ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+        });
+    let name = name?;
+    let name = name.trim().split('.').next().unwrap_or_default();
+    (!name.is_empty()).then(|| name.to_string())
+}
@@ -0,0 +1,62 @@
+use std::path::{Component, Path, PathBuf};
+
+pub fn application_support_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join("Library").join("Application Support"))
+}
+
+pub fn containers_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join("Library").join("Containers"))
+}
+
+/// Maps `/Users/<name>/...` to `user_home/...` for any user, not just the current one.
+pub fn anonymize_users_path(path: &Path) -> Option<PathBuf> {
+    let mut components = path.components();
+    if components.next() != Some(Component::RootDir) {
+        return None;
+    }
+
+    let (Some(Component::Normal(users)), Some(Component::Normal(user))) =
+        (components.next(), components.next())
+    else {
+        return None;
+    };
+
+    // /Users/Shared is a common folder rather than somebody's home directory
+    if users != "Users" || user == "Shared" {
+        return None;
+    }
+
+    let mut anonymized = PathBuf::from("user_home");
+    anonymized.push(components.as_path());
+    Some(anonymized)
+}
+
+/// Returns the iCloud container a path belongs to, if its contents are kept in sync by
+/// iCloud Drive or CloudKit. Restoring into such a location can be undone by the next sync.
+pub fn cloud_synced_container(path: &Path) -> Option<PathBuf> {
+    let library = dirs::home_dir()?.join("Library");
+
+    // iCloud Drive ubiquity containers
+    let mobile_documents = library.join("Mobile Documents");
+    if let Ok(relative_path) = path.strip_prefix(&mobile_documents) {
+        let container = relative_path.components().next()?;
+        return Some(mobile_documents.join(container));
+    }
+
+    // Sandboxed app containers that keep CloudKit sync state
+    let containers = library.join("Containers");
+    if let Ok(relative_path) = path.strip_prefix(&containers) {
+        let container = containers.join(relative_path.components().next()?);
+        let data_library = container.join("Data").join("Library");
+        if data_library
+            .join("Application Support")
+            .join("CloudKit")
+            .exists()
+            || data_library.join("Caches").join("CloudKit").exists()
+        {
+            return Some(container);
+        }
+    }
+
+    None
+}
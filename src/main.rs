@@ -1,43 +1,556 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use cartridge::chain::ChainProblem;
+use cartridge::config_audit::{ConfigAudit, Finding};
+use cartridge::conflict::{Conflict, ConflictSide, Resolution};
+use cartridge::coop::ReceiveOutcome;
+use cartridge::dedup::{DedupReport, DuplicateGroup};
+use cartridge::info::BuildInfo;
+use cartridge::machine::SourceMachine;
+use cartridge::mods::ModStatus;
+use cartridge::plan::{
+    ActionKind, ConflictPolicy, ConflictPrompt, FileAction, FileState, ReadOnlyPolicy,
+    RestoreOptions, RestoreReport,
+};
+use cartridge::progress::Operation;
+use cartridge::prune::PruneCandidate;
+use cartridge::queue::Priority;
+use cartridge::session::Session;
+use cartridge::status::{RunState, Status};
+use cartridge::summary::{NextStep, RunSummary};
+use cartridge::t;
+use cartridge::{
+    BackupLabel, ByteSize, DEFAULT_DICTIONARY_SIZE, Period, RecentGame, SaveKind, StaleGame,
+};
+use cartridge::{
+    ConfigOverride, DaemonEvent, DiffReport, DiffStatus, Game, GameBackup, LocationStatus,
+    RestoreCheck, VerifyReport, find_config_file, find_config_files, format_size, write_metrics,
+};
 use clap::{Parser, Subcommand};
-use cartridge::{GameBackup, find_config_file};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 #[derive(Parser)]
 #[command(name = "cartridge")]
 #[command(about = "A CLI tool for backing up and restoring game save files")]
-#[command(version = "0.1.0")]
+#[command(version)]
 struct Cli {
     /// Path to the TOML configuration file
     #[arg(short, long)]
     config: Option<String>,
 
+    /// Directory of independent configuration files to run the command against, one by one
+    #[arg(long, value_name = "DIR", conflicts_with = "config")]
+    config_dir: Option<PathBuf>,
+
     /// Enable verbose logging
     #[arg(short, long)]
     verbose: bool,
 
+    /// Refuse commands that change the configuration or delete backups
+    #[arg(long)]
+    frozen: bool,
+
+    /// Back up games marked `sensitive` even to an unencrypted removable drive
+    #[arg(long)]
+    allow_unencrypted: bool,
+
+    /// Override a setting for this run, e.g. `--set backup_root=/mnt/usb` or
+    /// `--set game.skyrim.enabled=false` (can be repeated)
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    set: Vec<ConfigOverride>,
+
+    /// Language of the output, e.g. `de` (default from CARTRIDGE_LANG or the locale)
+    #[arg(long, value_name = "LANG", global = true)]
+    lang: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
-#[derive(Subcommand)]
+#[derive(Clone, Subcommand)]
 enum Commands {
     /// Backup game saves
     Backup {
         /// Name of the game to backup (if not specified, backup all games)
         game_name: Option<String>,
+        /// Show which files would be copied without writing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Only back up save locations of this kind (save, config or mods), keeping the
+        /// others from the current backup
+        #[arg(long, value_name = "KIND")]
+        kind: Option<SaveKind>,
+        /// Only back up games played within this period (e.g. `7d`), most recently played
+        /// first
+        #[arg(long, value_name = "PERIOD", conflicts_with = "game_name")]
+        recent: Option<Period>,
+        /// Label the backup with this description, shown by `snapshots`
+        #[arg(long, value_name = "TEXT", conflicts_with = "message_from_hook")]
+        message: Option<String>,
+        /// Label the backup with the first line printed by the game's `label_command`
+        #[arg(long)]
+        message_from_hook: bool,
+        /// Back up into a game's backup directory even if cartridge did not create it, moving
+        /// what it holds to the quarantine
+        #[arg(long)]
+        adopt: bool,
+        /// Back up games whose save paths are missing, keeping those locations as they are in
+        /// the current backup
+        #[arg(long)]
+        skip_missing: bool,
     },
     /// Restore game saves
     Restore {
         /// Name of the game to restore (if not specified, restore all games)
         game_name: Option<String>,
+        /// Show which files would be copied, created or overwritten without writing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// How to handle live files that differ from the backup: overwrite, skip, backup-existing or ask
+        #[arg(long, value_name = "POLICY", default_value = "overwrite")]
+        on_conflict: ConflictPolicy,
+        /// How to overwrite read-only live files: reapply, clear or fail (default from config)
+        #[arg(long, value_name = "POLICY")]
+        on_read_only: Option<ReadOnlyPolicy>,
+        /// Restore this snapshot instead of the latest backup (an id or `last-known-good`)
+        #[arg(long, value_name = "SNAPSHOT", requires = "game_name")]
+        snapshot: Option<String>,
+        /// Restore the newest backup taken on this machine instead of the newest from any
+        /// machine sharing the backup root
+        #[arg(long, value_name = "MACHINE", conflicts_with = "snapshot")]
+        from_machine: Option<String>,
+        /// List the machines that have backups of the game, instead of restoring
+        #[arg(long, requires = "game_name")]
+        list_machines: bool,
+        /// Only restore backed-up files matching this glob (can be repeated)
+        #[arg(long = "files", value_name = "GLOB")]
+        files: Vec<String>,
+        /// Restore even if the game is running
+        #[arg(long)]
+        force: bool,
+        /// Refuse to restore a snapshot that needs more than this much data fetched from
+        /// cold storage (e.g. `500MB`)
+        #[arg(long, value_name = "SIZE")]
+        max_download: Option<ByteSize>,
+        /// Only restore save locations of this kind: save, config or mods
+        #[arg(long, value_name = "KIND")]
+        kind: Option<SaveKind>,
+        /// Restore into a temporary sandbox and show how it differs from the live saves
+        #[arg(long, conflicts_with_all = ["dry_run", "on_conflict", "on_read_only", "files", "snapshot", "from_machine", "list_machines", "force", "max_download", "kind", "as_user", "then_launch"])]
+        rehearse: bool,
+        /// Make the user who runs cartridge, or who called sudo, own the restored files, with
+        /// plain user permissions (private ones for `sensitive` games)
+        #[arg(long)]
+        as_user: bool,
+        /// Verify the restored saves, then start the game if they passed (with its `launch`
+        /// command or `steam_app_id`)
+        #[arg(long, requires = "game_name", conflicts_with = "dry_run")]
+        then_launch: bool,
+    },
+    /// List the games in the configuration that are backed up
+    List {
+        /// Also list disabled and archived games
+        #[arg(long)]
+        all: bool,
+    },
+    /// Check backed-up files against the manifest for missing or corrupted data
+    Verify {
+        /// Name of the game to verify (if not specified, verify all games with a backup)
+        game_name: Option<String>,
+        /// Also check that no snapshot was deleted or replaced outside of cartridge, by
+        /// following the link each backup records to the one before it
+        #[arg(long)]
+        chain: bool,
+        /// Print the results as JSON
+        #[arg(long)]
+        json: bool,
+        /// Write the results as Prometheus metrics to this file, for the node_exporter
+        /// textfile collector
+        #[arg(long, value_name = "FILE")]
+        metrics: Option<PathBuf>,
+    },
+    /// List the backups kept for a game
+    Snapshots {
+        /// Name of the game
+        game_name: String,
+    },
+    /// Delete snapshots beyond the newest ones, keeping pinned snapshots
+    Prune {
+        /// Name of the game (if not specified, prune every game with a retention setting)
+        game_name: Option<String>,
+        /// Number of newest backups to keep per game, the latest one included (default
+        /// from `keep_snapshots`)
+        #[arg(long, value_name = "N")]
+        keep: Option<usize>,
+        /// Only show what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Delete specific snapshots of a game
+    Forget {
+        /// Name of the game
+        game_name: String,
+        /// Ids of the snapshots to delete, as listed by `snapshots`
+        #[arg(required = true)]
+        snapshots: Vec<String>,
+        /// Only show what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Keep a snapshot regardless of retention, until it is unpinned
+    Pin {
+        /// Name of the game
+        game_name: String,
+        /// Id of the snapshot, as listed by `snapshots`
+        snapshot: String,
+        /// Why the snapshot is kept, shown by `snapshots`
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Leave a pinned snapshot to retention again
+    Unpin {
+        /// Name of the game
+        game_name: String,
+        /// Id of the snapshot, as listed by `snapshots`
+        snapshot: String,
+    },
+    /// Extract a snapshot read-only into a directory, to browse old saves with normal tools
+    Mount {
+        /// Name of the game
+        game_name: String,
+        /// Id of the snapshot, as listed by `snapshots`, or `last-known-good`
+        snapshot: String,
+        /// Directory to extract to, created if missing; must be empty
+        dir: PathBuf,
+    },
+    /// Remove a directory a snapshot was mounted at
+    Unmount {
+        /// Directory given to `mount`
+        dir: PathBuf,
+    },
+    /// Compare the mods recorded in a backup with the installed ones
+    Mods {
+        /// Name of the game
+        game_name: String,
+        /// Compare with this snapshot instead of the latest backup
+        #[arg(long, value_name = "SNAPSHOT")]
+        snapshot: Option<String>,
+    },
+    /// Report which configured save paths exist on this platform
+    Doctor,
+    /// Check the whole configuration without writing anything: save paths, what a backup
+    /// would copy, overlapping save paths, and missing, outdated or abandoned backups
+    Audit {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+        /// Fail on warnings too, not only on problems that break backups
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Show which live save files differ from the backup
+    Diff {
+        /// Name of the game to compare
+        game_name: String,
+    },
+    /// Export a game's backups into a single archive file (.zip or .tar.zst)
+    Export {
+        /// Name of the game to export
+        game_name: String,
+        /// Path of the archive file to create
+        file: PathBuf,
+    },
+    /// Export only the files that changed between two snapshots of a game into an archive
+    /// (.zip or .tar.zst)
+    ExportDiff {
+        /// Name of the game
+        game_name: String,
+        /// Id of the older snapshot, as listed by `snapshots`
+        #[arg(long)]
+        from: String,
+        /// Id of the newer snapshot (default: the latest backup)
+        #[arg(long)]
+        to: Option<String>,
+        /// Path of the archive file to create
+        file: PathBuf,
+    },
+    /// Apply an archive created by `export-diff` to the live saves of its game
+    ImportDiff {
+        /// Path of the archive file to apply
+        file: PathBuf,
+        /// Apply even if the saves differ from the snapshot the delta was made from, or the
+        /// game is running
+        #[arg(long)]
+        force: bool,
+        /// Show which files would be changed without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Write the live saves of a game into an archive (.zip or .tar.zst) to pass to another
+    /// player, e.g. a co-op world
+    Share {
+        /// Name of the game
+        game_name: String,
+        /// Path of the archive file to create
+        file: PathBuf,
+    },
+    /// Replace the live saves of a game with an archive another player created with `share`
+    Receive {
+        /// Path of the archive file to receive
+        file: PathBuf,
+        /// Take the received saves even if both players changed them since the last exchange,
+        /// or the game is running
+        #[arg(long)]
+        force: bool,
+        /// Show which files would be changed without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Import a game's backups from an archive created by `export`
+    Import {
+        /// Path of the archive file to import
+        file: PathBuf,
+        /// Replace an existing backup for the same game
+        #[arg(long)]
+        force: bool,
+    },
+    /// Download the latest release from GitHub, check its signature and replace this binary
+    #[cfg(feature = "self-update")]
+    SelfUpdate {
+        /// Only report whether a newer release is available
+        #[arg(long)]
+        check: bool,
+    },
+    /// Show version, platform, supported backends and default paths
+    Info {
+        /// Print the information as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Report files with identical content stored by several games or snapshots
+    Dedup {
+        /// Also list contents only duplicated between snapshots of the same game
+        #[arg(long)]
+        all: bool,
+    },
+    /// Train a zstd dictionary from a game's save files to compress its tar.zst backups
+    TrainDict {
+        /// Name of the game
+        game_name: String,
+        /// Maximum dictionary size in bytes
+        #[arg(long, default_value_t = DEFAULT_DICTIONARY_SIZE)]
+        max_size: usize,
+    },
+    /// Suggest variables for directories shared by absolute save paths
+    InferVars {
+        /// Rewrite the config file with the suggestions without asking
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Watch where a freshly installed game writes its first save when its configured save
+    /// paths do not exist yet
+    Probe {
+        /// Name of the game
+        game_name: String,
+        /// Seconds between checks
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
+        /// Give up after this many seconds
+        #[arg(long, default_value_t = 600)]
+        timeout: u64,
+        /// Rewrite the config file with the found paths without asking
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Review the live files a restore kept because they differed from the backup, and
+    /// resolve them one by one
+    Conflicts {
+        /// Name of the game
+        game_name: String,
+        /// Keep this live file and forget its conflict
+        #[arg(long, value_name = "PATH", conflicts_with = "use_backup")]
+        keep_live: Option<PathBuf>,
+        /// Replace this live file with the backup copy the restore skipped
+        #[arg(long, value_name = "PATH")]
+        use_backup: Option<PathBuf>,
+        /// Print the conflicts as JSON
+        #[arg(long, conflicts_with_all = ["keep_live", "use_backup"])]
+        json: bool,
+    },
+    /// Start a game with its `launch` command, wait for it to exit, then back it up
+    Run {
+        /// Name of the game
+        game_name: String,
+    },
+    /// Show the play sessions started with `run` and how much each changed the saves
+    History {
+        /// Only show sessions of this game
+        game_name: Option<String>,
+        /// Print the sessions as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Mark a game as archived: it is no longer backed up, and its backups are kept out of
+    /// reach of `prune`, `forget` and the trash
+    ArchiveGame {
+        /// Name of the game
+        game_name: String,
+        /// Take the game back into routine backups and restores
+        #[arg(long)]
+        undo: bool,
+    },
+    /// Run in the foreground, running queued backups, backing up games with `watch = true`
+    /// when their saves change, and verifying old snapshots a few at a time during the
+    /// `[idle_verify]` window
+    Daemon {
+        #[cfg(all(windows, feature = "service"))]
+        #[command(subcommand)]
+        service: Option<ServiceCommands>,
+    },
+    /// Show or add to the backups waiting for the daemon
+    Queue {
+        #[command(subcommand)]
+        command: QueueCommands,
+    },
+    /// Show how much data was written to and read from each backup destination
+    Stats {
+        /// Month to report, as YYYY-MM (defaults to the current month)
+        #[arg(long, value_name = "YYYY-MM", conflicts_with = "all")]
+        month: Option<String>,
+        /// Report every transfer ever recorded
+        #[arg(long)]
+        all: bool,
+        /// Print the totals as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the progress of a running backup or restore, or how the last one ended
+    Status {
+        /// Keep showing the progress until the run ends
+        #[arg(long)]
+        live: bool,
+        /// Print the status as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Inspect or purge data from failed backups
+    Quarantine {
+        #[command(subcommand)]
+        command: QuarantineCommands,
+    },
+    /// List, restore or purge snapshots deleted by `prune` and `forget`
+    Trash {
+        #[command(subcommand)]
+        command: TrashCommands,
+    },
+    /// Work with the configuration file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+enum TrashCommands {
+    /// List deleted snapshots and when they are purged
+    List,
+    /// Put a deleted snapshot back
+    Restore {
+        /// Name of the game
+        game_name: String,
+        /// Id of the snapshot
+        snapshot: String,
+    },
+    /// Delete snapshots from the trash now
+    Empty {
+        /// Only delete snapshots whose grace period is over
+        #[arg(long)]
+        expired: bool,
+        /// Delete without asking for confirmation
+        #[arg(long)]
+        yes: bool,
     },
-    /// List all games in configuration
+}
+
+#[derive(Clone, Subcommand)]
+enum QueueCommands {
+    /// List the backups waiting for the daemon, in the order they run
     List,
+    /// Ask the daemon to back up a game, ahead of backups triggered by `watch`
+    Backup {
+        /// Name of the game
+        game_name: String,
+    },
+    /// Remove every waiting backup
+    Clear,
+}
+
+#[cfg(all(windows, feature = "service"))]
+#[derive(Clone, Subcommand)]
+enum ServiceCommands {
+    /// Register the daemon as a Windows service that starts with the system and logs to the
+    /// event log, using this configuration, and start it
+    #[command(name = "install-service")]
+    Install,
+    /// Stop the service and remove it
+    #[command(name = "uninstall-service")]
+    Uninstall,
+    /// Run as the service. Started by the service control manager
+    #[command(name = "run-service", hide = true)]
+    Run,
+}
+
+#[derive(Clone, Subcommand)]
+enum ConfigCommands {
+    /// Print a copy of the configuration
+    Export {
+        /// Remove machine-specific settings, local paths and credentials so the config can
+        /// be posted or synced elsewhere
+        #[arg(long)]
+        shareable: bool,
+        /// Write to this file instead of stdout
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+enum QuarantineCommands {
+    /// List quarantined backup attempts
+    List,
+    /// Show details of a quarantined backup attempt
+    Show {
+        /// Identifier of the quarantine entry
+        id: String,
+    },
+    /// Delete quarantined data
+    Purge {
+        /// Identifier of the quarantine entry to delete
+        id: Option<String>,
+        /// Delete all quarantine entries
+        #[arg(long, conflicts_with = "id")]
+        all: bool,
+    },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    #[cfg(all(windows, feature = "service"))]
+    if let Commands::Daemon {
+        service: Some(ServiceCommands::Run),
+    } = cli.command
+    {
+        return run_service(cli);
+    }
+
     // Initialize logger
     let log_level = if cli.verbose { "debug" } else { "info" };
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level))
@@ -45,49 +558,2228 @@ fn main() -> Result<()> {
         .init();
 
     log::info!("Starting Game Backup CLI v{}", env!("CARGO_PKG_VERSION"));
+    cartridge::i18n::init(cli.lang.as_deref());
+
+    // Info must work without a usable configuration
+    if let Commands::Info { json } = cli.command {
+        let config_path = find_config_file(cli.config.as_deref()).ok();
+        let info = BuildInfo::new(config_path.as_deref());
+        if json {
+            println!("{}", serde_json::to_string_pretty(&info)?);
+        } else {
+            print_info(&info);
+        }
+        return Ok(());
+    }
+
+    #[cfg(feature = "self-update")]
+    if let Commands::SelfUpdate { check } = cli.command {
+        return self_update(check);
+    }
+
+    if let Some(config_dir) = &cli.config_dir {
+        return run_workspace(config_dir, &cli);
+    }
+
+    #[cfg(all(windows, feature = "service"))]
+    if let Commands::Daemon {
+        service: Some(ServiceCommands::Uninstall),
+    } = cli.command
+    {
+        cartridge::service::uninstall_service()?;
+        println!(
+            "{}",
+            t!(
+                "service-uninstalled",
+                name = cartridge::service::SERVICE_NAME
+            )
+        );
+        return Ok(());
+    }
 
     // Find and load configuration
     let config_path = find_config_file(cli.config.as_deref())?;
-    let game_backup = GameBackup::new(&config_path)?;
+    let mut game_backup = load_config(&config_path, &cli)?;
+
+    #[cfg(all(windows, feature = "service"))]
+    if let Commands::Daemon {
+        service: Some(ServiceCommands::Install),
+    } = cli.command
+    {
+        game_backup.install_service(&config_path, &cli.set)?;
+        println!(
+            "{}",
+            t!("service-installed", name = cartridge::service::SERVICE_NAME)
+        );
+        return Ok(());
+    }
+    run_summarized(cli.command, &config_path, &mut game_backup)
+}
+
+fn load_config(config_path: &Path, cli: &Cli) -> Result<GameBackup> {
+    let mut game_backup = GameBackup::with_overrides(config_path, &cli.set)?;
+    if cli.frozen {
+        game_backup.freeze();
+    }
+    if cli.allow_unencrypted {
+        game_backup.allow_unencrypted();
+    }
+    if let Commands::Backup {
+        message,
+        message_from_hook,
+        adopt,
+        skip_missing,
+        ..
+    } = &cli.command
+    {
+        if *adopt {
+            game_backup.adopt_existing();
+        }
+        if *skip_missing {
+            game_backup.skip_missing_saves();
+        }
+        if let Some(message) = message {
+            game_backup.label_backups(BackupLabel::Message(message.clone()));
+        } else if *message_from_hook {
+            game_backup.label_backups(BackupLabel::Hook);
+        }
+    }
+    if game_backup.is_locked() {
+        log::info!("Configuration is locked, commands that delete backups are disabled");
+    }
+    Ok(game_backup)
+}
+
+/// Runs a command against every config of a workspace directory and reports which failed.
+fn run_workspace(config_dir: &Path, cli: &Cli) -> Result<()> {
+    let mut workspace: Vec<(PathBuf, GameBackup)> = Vec::new();
+    for config_path in find_config_files(config_dir)? {
+        let game_backup = load_config(&config_path, cli)?;
+        if let Some((other, _)) = workspace
+            .iter()
+            .find(|(_, other)| other.backup_dir() == game_backup.backup_dir())
+        {
+            return Err(anyhow::anyhow!(
+                "{} and {} share the backup root {}. Set `backup_root` in each config",
+                other.display(),
+                config_path.display(),
+                game_backup.backup_dir().display()
+            ));
+        }
+        workspace.push((config_path, game_backup));
+    }
+
+    let mut failed = Vec::new();
+    for (config_path, game_backup) in &mut workspace {
+        println!(
+            "{}",
+            t!(
+                "workspace-header",
+                config = config_path.display().to_string()
+            )
+        );
+        if let Err(e) = run_summarized(cli.command.clone(), config_path, game_backup) {
+            log::error!("✗ Failed for '{}': {:#}", config_path.display(), e);
+            failed.push(config_path.display().to_string());
+        }
+    }
+
+    println!(
+        "{}",
+        t!(
+            "workspace-summary",
+            total = workspace.len(),
+            succeeded = workspace.len() - failed.len(),
+            failed = failed.len()
+        )
+    );
+    if !failed.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Command failed for {} config(s): {}",
+            failed.len(),
+            failed.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Runs a command, and after a backup or restore summarizes how each game went.
+fn run_summarized(
+    command: Commands,
+    config_path: &Path,
+    game_backup: &mut GameBackup,
+) -> Result<()> {
+    let summarized = matches!(
+        command,
+        Commands::Backup { dry_run: false, .. }
+            | Commands::Restore {
+                dry_run: false,
+                rehearse: false,
+                list_machines: false,
+                ..
+            }
+            | Commands::Run { .. }
+    );
+    if !summarized {
+        return run(command, config_path, game_backup);
+    }
 
-    // Execute command
-    match cli.command {
-        Commands::Backup { game_name } => {
-            if let Some(name) = game_name {
-                game_backup.backup_game(&name)?;
+    let summary = Arc::new(Mutex::new(RunSummary::default()));
+    {
+        let summary = Arc::clone(&summary);
+        game_backup.set_progress(move |progress| {
+            summary
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .record(progress)
+        });
+    }
+    let result = run(command, config_path, game_backup);
+    game_backup.clear_progress();
+    print_run_summary(&summary.lock().unwrap_or_else(|e| e.into_inner()));
+    result
+}
+
+fn run(command: Commands, config_path: &Path, game_backup: &GameBackup) -> Result<()> {
+    match command {
+        Commands::Backup {
+            game_name,
+            dry_run,
+            kind,
+            recent,
+            ..
+        } => {
+            if let Some(period) = recent {
+                let games = if dry_run {
+                    game_backup.recent_games(period)?
+                } else {
+                    game_backup.backup_recent_games(kind, period)?
+                };
+                print_recent_games(&games, period);
+                if dry_run {
+                    for game in games {
+                        let actions = game_backup.plan_backup(&game.name, kind)?;
+                        print_plan(&t!("backup-plan-title", game = game.name), &actions);
+                    }
+                }
+            } else if dry_run {
+                let names: Vec<String> = match game_name {
+                    Some(name) => vec![name],
+                    None => game_backup
+                        .list_games()
+                        .iter()
+                        .filter(|game| !game.archived)
+                        .map(|game| game.name.clone())
+                        .collect(),
+                };
+                for name in names {
+                    let actions = game_backup.plan_backup(&name, kind)?;
+                    print_plan(&t!("backup-plan-title", game = name), &actions);
+                }
+            } else if let Some(name) = game_name {
+                game_backup.backup_game(&name, kind)?;
             } else {
-                game_backup.backup_all_games()?;
+                game_backup.backup_all_games(kind)?;
             }
         }
-        Commands::Restore { game_name } => {
-            if let Some(name) = game_name {
-                game_backup.restore_game(&name)?;
+        Commands::Restore {
+            game_name: Some(name),
+            list_machines: true,
+            ..
+        } => {
+            let machines = game_backup.source_machines(&name)?;
+            print_source_machines(&name, &machines, game_backup.machine_name());
+        }
+        Commands::Restore {
+            game_name,
+            rehearse: true,
+            ..
+        } => {
+            let names: Vec<String> = match game_name {
+                Some(name) => vec![name],
+                None => game_backup
+                    .list_games()
+                    .iter()
+                    .filter(|game| !game.archived && game_backup.has_backup(&game.name))
+                    .map(|game| game.name.clone())
+                    .collect(),
+            };
+            for name in names {
+                let report = game_backup.rehearse_restore(&name)?;
+                print_rehearsal(&report);
+            }
+        }
+        Commands::Restore {
+            game_name,
+            dry_run,
+            on_conflict,
+            on_read_only,
+            snapshot,
+            from_machine,
+            files,
+            force,
+            max_download,
+            kind,
+            as_user,
+            then_launch,
+            ..
+        } => {
+            let options = RestoreOptions {
+                dry_run,
+                on_conflict,
+                on_read_only,
+                files,
+                snapshot,
+                from_machine,
+                force,
+                max_download: max_download.map(|size| size.bytes()),
+                kind,
+                as_user,
+                prompt: Some(ConflictPrompt::new(ask_conflict)),
+            };
+            if then_launch && let Some(name) = &game_name {
+                game_backup.check_launch(name)?;
+            }
+            let reports = if let Some(name) = game_name {
+                vec![game_backup.restore_game(&name, &options)?]
             } else {
-                game_backup.restore_all_games()?;
+                game_backup.restore_all_games(&options)?
+            };
+            if dry_run {
+                for report in &reports {
+                    print_plan(
+                        &t!("restore-plan-title", game = report.game.clone()),
+                        &report.actions,
+                    );
+                }
+            }
+            for report in &reports {
+                print_restore_origin(report);
+            }
+            if then_launch {
+                let report = &reports[0];
+                let check = game_backup.check_restore(report, options.snapshot.as_deref())?;
+                print_restore_check(&check);
+                if !check.is_ok() {
+                    return Err(anyhow::anyhow!(
+                        "Not launching '{}': the restored saves failed verification",
+                        report.game
+                    ));
+                }
+                game_backup.launch_game(&report.game)?;
+                println!("{}", t!("launch-started", game = report.game.clone()));
             }
         }
-        Commands::List => {
-            let games = game_backup.list_games();
+        Commands::List { all } => {
+            let games: Vec<&Game> = if all {
+                game_backup.list_all_games().iter().collect()
+            } else {
+                game_backup
+                    .list_games()
+                    .into_iter()
+                    .filter(|game| !game.archived)
+                    .collect()
+            };
             if games.is_empty() {
-                println!("No enabled games found in configuration.");
+                println!("{}", t!("list-empty"));
             } else {
-                println!("Available games:");
+                println!("{}", t!("list-title"));
                 for game in games {
                     let has_backup = game_backup.has_backup(&game.name);
                     let backup_status = if has_backup {
-                        "Has backup"
+                        t!("list-has-backup")
+                    } else {
+                        t!("list-no-backup")
+                    };
+                    let backup_status = if !game.enabled {
+                        t!("list-disabled", status = backup_status)
+                    } else if game.archived {
+                        t!("list-archived", status = backup_status)
                     } else {
-                        "No backup"
+                        backup_status
+                    };
+                    println!(
+                        "  {}",
+                        t!(
+                            "list-game",
+                            game = game.name.clone(),
+                            status = backup_status,
+                            locations = game.saves.len()
+                        )
+                    );
+                }
+            }
+        }
+        Commands::Verify {
+            game_name,
+            chain,
+            json,
+            metrics,
+        } => {
+            let mut reports = if let Some(name) = game_name {
+                vec![game_backup.verify_game(&name)?]
+            } else {
+                game_backup
+                    .list_games()
+                    .iter()
+                    .filter(|game| game_backup.has_backup(&game.name))
+                    .map(|game| game_backup.verify_game(&game.name))
+                    .collect::<Result<Vec<_>>>()?
+            };
+            if chain {
+                for report in &mut reports {
+                    report.chain = Some(game_backup.verify_chain(&report.game)?);
+                }
+            }
+
+            if let Some(path) = &metrics {
+                write_metrics(path, &reports)?;
+                log::info!("Wrote verification metrics: {}", path.display());
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&reports)?);
+            } else {
+                for report in &reports {
+                    print_verify_report(report);
+                }
+            }
+
+            let failed = reports.iter().filter(|report| !report.is_ok()).count();
+            if failed > 0 {
+                return Err(anyhow::anyhow!(
+                    "Verification failed for {} game(s)",
+                    failed
+                ));
+            }
+        }
+        Commands::Prune {
+            game_name,
+            keep,
+            dry_run,
+            yes,
+        } => {
+            let candidates = match game_name {
+                Some(name) => game_backup.plan_prune(&name, keep)?,
+                None => game_backup.plan_prune_all(keep)?,
+            };
+            delete_snapshots(game_backup, &candidates, dry_run, yes)?;
+        }
+        Commands::Forget {
+            game_name,
+            snapshots,
+            dry_run,
+            yes,
+        } => {
+            let candidates = game_backup.plan_forget(&game_name, &snapshots)?;
+            delete_snapshots(game_backup, &candidates, dry_run, yes)?;
+        }
+        Commands::Pin {
+            game_name,
+            snapshot,
+            reason,
+        } => {
+            game_backup.pin_snapshot(&game_name, &snapshot, reason.as_deref())?;
+            println!("{}", t!("pin-done", snapshot = snapshot, game = game_name));
+        }
+        Commands::Unpin {
+            game_name,
+            snapshot,
+        } => {
+            game_backup.unpin_snapshot(&game_name, &snapshot)?;
+            println!(
+                "{}",
+                t!("unpin-done", snapshot = snapshot, game = game_name)
+            );
+        }
+        Commands::Snapshots { game_name } => {
+            let snapshots = game_backup.list_snapshots(&game_name)?;
+            if snapshots.is_empty() {
+                println!("{}", t!("snapshots-none", game = game_name));
+            } else {
+                println!("{}", t!("snapshots-title", game = game_name.clone()));
+                for snapshot in &snapshots {
+                    let created = snapshot
+                        .created_at
+                        .map(|created| {
+                            created
+                                .with_timezone(&chrono::Local)
+                                .format("%Y-%m-%d %H:%M:%S")
+                                .to_string()
+                        })
+                        .unwrap_or_else(|| t!("unknown"));
+
+                    let mut notes = Vec::new();
+                    if let Some(label) = &snapshot.label {
+                        notes.push(t!("snapshot-label", label = label.clone()));
+                    }
+                    if snapshot.current {
+                        notes.push(t!("snapshot-latest"));
+                    }
+                    if snapshot.cold {
+                        notes.push(t!("snapshot-cold"));
+                    }
+                    if snapshot.is_last_known_good() {
+                        notes.push(t!("snapshot-last-known-good"));
+                    } else if let Some(pin) = &snapshot.pin {
+                        notes.push(match pin.as_str() {
+                            "" => t!("snapshot-pinned-plain"),
+                            pin => t!("snapshot-pinned", reason = pin.to_string()),
+                        });
+                    }
+                    if snapshot.is_suspect() {
+                        notes.push(t!("snapshot-suspect", issues = snapshot.validation_issues));
+                    }
+
+                    let marker = if snapshot.pin.is_some() { "*" } else { " " };
+                    println!(
+                        " {} {}  {}  {}",
+                        marker,
+                        snapshot.id,
+                        created,
+                        notes.join(", ")
+                    );
+                }
+            }
+        }
+        Commands::Mount {
+            game_name,
+            snapshot,
+            dir,
+        } => {
+            let mounted = game_backup.mount_snapshot(&game_name, &snapshot, &dir)?;
+            println!(
+                "{}",
+                t!(
+                    "mount-done",
+                    snapshot = mounted.snapshot,
+                    game = mounted.game,
+                    dir = dir.display().to_string()
+                )
+            );
+            for location in &mounted.locations {
+                println!(
+                    "  {}  {}",
+                    dir.join(&location.dir).display(),
+                    t!("mount-location", path = location.path.display().to_string())
+                );
+            }
+            println!("{}", t!("mount-hint", dir = dir.display().to_string()));
+        }
+        Commands::Unmount { dir } => {
+            let mounted = game_backup.unmount_snapshot(&dir)?;
+            println!(
+                "{}",
+                t!(
+                    "unmount-done",
+                    snapshot = mounted.snapshot,
+                    game = mounted.game,
+                    dir = dir.display().to_string()
+                )
+            );
+        }
+        Commands::Doctor => {
+            let checks = game_backup.doctor();
+            println!("{}", t!("doctor-title", os = std::env::consts::OS));
+            for check in &checks {
+                let name = if !check.enabled {
+                    t!("doctor-disabled", game = check.name.clone())
+                } else if check.is_ok() {
+                    check.name.clone()
+                } else {
+                    t!("doctor-problems", game = check.name.clone())
+                };
+                println!("  {}", name);
+                for location in &check.locations {
+                    let line = match (&location.status, &location.path) {
+                        (LocationStatus::Exists, Some(path)) => {
+                            t!("doctor-ok", path = path.display().to_string())
+                        }
+                        (LocationStatus::Missing, Some(path)) => {
+                            t!("doctor-missing", path = path.display().to_string())
+                        }
+                        (LocationStatus::NotConfigured { platforms }, _) => {
+                            t!("doctor-skipped", platforms = platforms.join(", "))
+                        }
+                        (LocationStatus::Invalid { error }, _) => {
+                            t!("doctor-invalid", error = error.clone())
+                        }
+                        (_, None) => continue,
+                    };
+                    println!("    {}", line);
+                    if let Some(service) = location.synced_by {
+                        println!(
+                            "    {}",
+                            t!("doctor-cloud-synced", service = service.to_string())
+                        );
+                    }
+                }
+            }
+
+            let failing = checks
+                .iter()
+                .filter(|check| check.enabled && !check.is_ok())
+                .count();
+            if failing > 0 {
+                return Err(anyhow::anyhow!(
+                    "{} enabled game(s) have missing or invalid save paths",
+                    failing
+                ));
+            }
+        }
+        Commands::Audit { json, strict } => {
+            let audit = game_backup.audit_config();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&audit)?);
+            } else {
+                print_config_audit(&audit);
+            }
+
+            let errors = audit.errors();
+            let warnings = audit.findings.len() - errors;
+            if errors > 0 || (strict && warnings > 0) {
+                return Err(anyhow::anyhow!(
+                    "Audit found {} problem(s) and {} warning(s)",
+                    errors,
+                    warnings
+                ));
+            }
+        }
+        Commands::Diff { game_name } => {
+            let report = game_backup.diff_game(&game_name)?;
+            if report.entries.is_empty() {
+                println!(
+                    "{}",
+                    t!(
+                        "diff-same",
+                        game = report.game.clone(),
+                        unchanged = report.unchanged
+                    )
+                );
+            } else {
+                println!("{}", t!("diff-title", game = report.game.clone()));
+                for entry in &report.entries {
+                    let (marker, note) = match entry.status {
+                        DiffStatus::Added => ("+", t!("diff-added")),
+                        DiffStatus::Modified { live_newer: true } => ("M", t!("diff-live-newer")),
+                        DiffStatus::Modified { live_newer: false } => {
+                            ("M", t!("diff-backup-newer"))
+                        }
+                        DiffStatus::Removed => ("-", t!("diff-removed")),
+                    };
+                    println!("  {} {} ({})", marker, entry.path, note);
+                }
+                println!("{}", t!("diff-unchanged", unchanged = report.unchanged));
+            }
+        }
+        Commands::Mods {
+            game_name,
+            snapshot,
+        } => {
+            let checks = game_backup.check_mods(&game_name, snapshot.as_deref())?;
+            if checks.is_empty() {
+                println!("{}", t!("mods-none", game = game_name));
+                return Ok(());
+            }
+            println!("{}", t!("mods-title", game = game_name));
+            for check in &checks {
+                let note = match &check.status {
+                    ModStatus::Installed => t!("mod-installed"),
+                    ModStatus::Missing => t!("mod-missing"),
+                    ModStatus::VersionChanged { installed } => t!(
+                        "mod-version-changed",
+                        installed = installed.clone().unwrap_or_else(|| t!("mod-no-version"))
+                    ),
+                    ModStatus::NotInBackup => t!("mod-not-in-backup"),
+                };
+                println!(
+                    "  {}  {}  {}",
+                    check.name,
+                    check.version.as_deref().unwrap_or("-"),
+                    note
+                );
+            }
+            let missing = checks
+                .iter()
+                .filter(|check| {
+                    matches!(
+                        check.status,
+                        ModStatus::Missing | ModStatus::VersionChanged { .. }
+                    )
+                })
+                .count();
+            if missing > 0 {
+                println!("{}", t!("mods-changed", count = missing));
+            }
+        }
+        Commands::Export { game_name, file } => {
+            game_backup.export_game(&game_name, &file)?;
+        }
+        Commands::ExportDiff {
+            game_name,
+            from,
+            to,
+            file,
+        } => {
+            let delta = game_backup.export_diff(&game_name, &from, to.as_deref(), &file)?;
+            println!(
+                "{}",
+                t!(
+                    "export-diff-done",
+                    changed = delta.changed.len(),
+                    removed = delta.removed.len(),
+                    from = delta.from,
+                    to = delta.to,
+                    file = file.display().to_string()
+                )
+            );
+        }
+        Commands::ImportDiff {
+            file,
+            force,
+            dry_run,
+        } => {
+            let applied = game_backup.import_diff(&file, force, dry_run)?;
+            if dry_run {
+                print_plan(
+                    &t!("import-diff-plan-title", game = applied.delta.game.clone()),
+                    &applied.actions,
+                );
+                for path in &applied.removed {
+                    println!(
+                        "  {}",
+                        t!("import-diff-remove", path = path.display().to_string())
+                    );
+                }
+            } else {
+                let written = applied
+                    .actions
+                    .iter()
+                    .filter(|action| action.kind != ActionKind::Unchanged)
+                    .count();
+                println!(
+                    "{}",
+                    t!(
+                        "import-diff-done",
+                        game = applied.delta.game,
+                        from = applied.delta.from,
+                        to = applied.delta.to,
+                        written = written,
+                        removed = applied.removed.len()
+                    )
+                );
+            }
+        }
+        Commands::Share { game_name, file } => {
+            let shared = game_backup.share_saves(&game_name, &file)?;
+            println!(
+                "{}",
+                t!(
+                    "share-done",
+                    game = shared.game,
+                    version = shared.id,
+                    files = shared.files.len(),
+                    file = file.display().to_string()
+                )
+            );
+        }
+        Commands::Receive {
+            file,
+            force,
+            dry_run,
+        } => {
+            let received = game_backup.receive_saves(&file, force, dry_run)?;
+            let game = received.shared.game.clone();
+            if received.outcome == ReceiveOutcome::UpToDate {
+                println!(
+                    "{}",
+                    t!(
+                        "receive-up-to-date",
+                        game = game,
+                        version = received.shared.id
+                    )
+                );
+            } else if dry_run {
+                print_plan(&t!("receive-plan-title", game = game), &received.actions);
+                for path in &received.removed {
+                    println!(
+                        "  {}",
+                        t!("import-diff-remove", path = path.display().to_string())
+                    );
+                }
+            } else {
+                if received.outcome == ReceiveOutcome::Diverged {
+                    println!("{}", t!("receive-diverged", game = game.clone()));
+                }
+                println!(
+                    "{}",
+                    t!(
+                        "receive-done",
+                        game = game,
+                        version = received.shared.id,
+                        removed = received.removed.len()
+                    )
+                );
+            }
+        }
+        Commands::Import { file, force } => {
+            let game_name = game_backup.import_game(&file, force)?;
+            println!("{}", t!("import-done", game = game_name));
+        }
+        Commands::Info { .. } => unreachable!("handled before loading the configuration"),
+        #[cfg(feature = "self-update")]
+        Commands::SelfUpdate { .. } => {
+            unreachable!("handled before loading the configuration")
+        }
+        Commands::Dedup { all } => {
+            let report = game_backup.dedup_report()?;
+            print_dedup_report(&report, all);
+        }
+        Commands::TrainDict {
+            game_name,
+            max_size,
+        } => {
+            let (size, samples) = game_backup.train_dictionary(&game_name, max_size)?;
+            println!(
+                "{}",
+                t!(
+                    "train-done",
+                    size = format_size(size as u64),
+                    game = game_name,
+                    samples = samples
+                )
+            );
+        }
+        Commands::InferVars { yes } => {
+            let suggestions = game_backup.infer_variables();
+            if suggestions.is_empty() {
+                println!("{}", t!("infer-none"));
+                return Ok(());
+            }
+
+            for suggestion in &suggestions {
+                match &suggestion.value {
+                    Some(value) => println!(
+                        "{}",
+                        t!(
+                            "infer-new",
+                            name = suggestion.name.clone(),
+                            value = value.clone()
+                        )
+                    ),
+                    None => println!("{}", t!("infer-existing", name = suggestion.name.clone())),
+                }
+                for path in &suggestion.paths {
+                    println!("  {}", path);
+                }
+            }
+
+            if yes
+                || confirm(&t!(
+                    "rewrite-confirm",
+                    path = config_path.display().to_string()
+                ))?
+            {
+                let rewritten =
+                    game_backup.apply_variable_suggestions(config_path, &suggestions)?;
+                println!("{}", t!("rewrite-done", count = rewritten));
+            }
+        }
+        Commands::Probe {
+            game_name,
+            interval,
+            timeout,
+            yes,
+        } => {
+            let targets = game_backup.probe_targets(&game_name)?;
+            if targets.is_empty() {
+                println!("{}", t!("probe-nothing", game = game_name));
+                return Ok(());
+            }
+
+            for target in &targets {
+                println!(
+                    "{}",
+                    t!(
+                        "probe-watching",
+                        configured = target.configured.display().to_string(),
+                        watched = target.watched.display().to_string()
+                    )
+                );
+            }
+            println!(
+                "{}",
+                t!("probe-start", game = game_name.clone(), timeout = timeout)
+            );
+            let findings = game_backup.probe(
+                &targets,
+                std::time::Duration::from_secs(interval.max(1)),
+                std::time::Duration::from_secs(timeout),
+            )?;
+            if findings.is_empty() {
+                println!("{}", t!("probe-none", timeout = timeout));
+                return Ok(());
+            }
+
+            for finding in &findings {
+                println!("{}", t!("probe-location", index = finding.index + 1));
+                println!(
+                    "  {}",
+                    t!(
+                        "probe-configured",
+                        path = finding.configured.display().to_string()
+                    )
+                );
+                println!(
+                    "  {}",
+                    t!("probe-written", path = finding.file.display().to_string())
+                );
+                println!(
+                    "  {}",
+                    t!("probe-suggested", path = finding.suggestion.clone())
+                );
+            }
+            if yes
+                || confirm(&t!(
+                    "rewrite-confirm",
+                    path = config_path.display().to_string()
+                ))?
+            {
+                let rewritten =
+                    game_backup.apply_probe_findings(config_path, &game_name, &findings)?;
+                println!("{}", t!("rewrite-done", count = rewritten));
+            }
+        }
+        Commands::Conflicts {
+            game_name,
+            keep_live,
+            use_backup,
+            json,
+        } => {
+            let resolution = match (keep_live, use_backup) {
+                (Some(path), _) => Some((path, Resolution::KeepLive)),
+                (None, Some(path)) => Some((path, Resolution::UseBackup)),
+                (None, None) => None,
+            };
+            if let Some((path, resolution)) = resolution {
+                let conflict = game_backup.resolve_conflict(&game_name, &path, resolution)?;
+                let path = conflict.path.display().to_string();
+                let message = match resolution {
+                    Resolution::KeepLive => t!("conflict-kept-live", path = path),
+                    Resolution::UseBackup => t!("conflict-used-backup", path = path),
+                };
+                println!("{}", message);
+                return Ok(());
+            }
+
+            let conflicts = game_backup.list_conflicts(&game_name)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&conflicts)?);
+            } else if conflicts.is_empty() {
+                println!("{}", t!("conflicts-none", game = game_name));
+            } else {
+                println!("{}", t!("conflicts-title", game = game_name.clone()));
+                for conflict in &conflicts {
+                    print_conflict(conflict);
+                }
+                println!("{}", t!("conflicts-hint", game = game_name));
+            }
+        }
+        Commands::Run { game_name } => {
+            println!("{}", t!("run-started", game = game_name.clone()));
+            let session = game_backup.run_session(&game_name)?;
+            println!(
+                "{}",
+                t!(
+                    "run-backed-up",
+                    game = game_name,
+                    duration = format_duration(session.duration()),
+                    changed = format_size(session.bytes_changed)
+                )
+            );
+        }
+        Commands::History { game_name, json } => {
+            let sessions = game_backup.session_history(game_name.as_deref())?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&sessions)?);
+            } else if sessions.is_empty() {
+                println!("{}", t!("history-none"));
+            } else {
+                println!("{}", t!("history-title"));
+                for session in &sessions {
+                    print_session(session);
+                }
+            }
+        }
+        Commands::ArchiveGame { game_name, undo } => {
+            game_backup.archive_game(config_path, &game_name, !undo)?;
+            if undo {
+                println!("{}", t!("archive-game-undone", game = game_name));
+            } else {
+                println!("{}", t!("archive-game-done", game = game_name));
+            }
+        }
+        Commands::Daemon { .. } => {
+            if let Some(settings) = game_backup.idle_verify() {
+                println!(
+                    "{}",
+                    t!("daemon-start", window = settings.window.to_string())
+                );
+            }
+            game_backup.run_daemon(|event| match event {
+                DaemonEvent::Verified(verification) if verification.report.is_ok() => {
+                    println!(
+                        "{}",
+                        t!(
+                            "daemon-verified",
+                            game = verification.report.game.clone(),
+                            snapshot = verification.snapshot.clone(),
+                            checked = verification.report.checked
+                        )
+                    );
+                }
+                DaemonEvent::Verified(verification) => {
+                    println!(
+                        "{}",
+                        t!(
+                            "daemon-failed",
+                            game = verification.report.game.clone(),
+                            snapshot = verification.snapshot.clone()
+                        )
+                    );
+                    print_verify_report(&verification.report);
+                }
+                DaemonEvent::BackedUp {
+                    queued,
+                    error: None,
+                } => {
+                    println!(
+                        "{}",
+                        t!(
+                            "daemon-backed-up",
+                            game = queued.game.clone(),
+                            priority = priority_label(queued.priority)
+                        )
+                    );
+                }
+                DaemonEvent::BackedUp {
+                    queued,
+                    error: Some(error),
+                } => {
+                    println!(
+                        "{}",
+                        t!(
+                            "daemon-backup-failed",
+                            game = queued.game.clone(),
+                            error = error.clone()
+                        )
+                    );
+                }
+            })?;
+        }
+        Commands::Queue { command } => match command {
+            QueueCommands::List => {
+                let queue = game_backup.backup_queue()?;
+                if queue.is_empty() {
+                    println!("{}", t!("queue-empty"));
+                }
+                let width = queue
+                    .iter()
+                    .map(|queued| priority_label(queued.priority).chars().count())
+                    .max()
+                    .unwrap_or(0);
+                for queued in &queue {
+                    let since = queued
+                        .started_at
+                        .unwrap_or(queued.requested_at)
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string();
+                    let state = match queued.started_at {
+                        Some(_) => t!("queue-running", since = since),
+                        None => t!("queue-waiting", since = since, requests = queued.requests),
                     };
                     println!(
-                        "  {} - {} ({} save locations)",
-                        game.name,
-                        backup_status,
-                        game.saves.len()
+                        "  {:<width$} {}  {}",
+                        priority_label(queued.priority),
+                        queued.game,
+                        state
+                    );
+                }
+            }
+            QueueCommands::Backup { game_name } => {
+                let added = game_backup.enqueue_backup(&game_name, Priority::Manual)?;
+                if added {
+                    println!("{}", t!("queue-added", game = game_name));
+                } else {
+                    println!("{}", t!("queue-merged", game = game_name));
+                }
+            }
+            QueueCommands::Clear => {
+                let count = game_backup.clear_queue()?;
+                println!("{}", t!("queue-cleared", count = count));
+            }
+        },
+        Commands::Stats { month, all, json } => {
+            let (label, since, until) = if all {
+                (t!("stats-all-time"), None, None)
+            } else {
+                let (since, until) = month_range(month.as_deref())?;
+                (
+                    since
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m")
+                        .to_string(),
+                    Some(since),
+                    Some(until),
+                )
+            };
+            let stats = game_backup.transfer_stats(since, until)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+            } else if stats.is_empty() {
+                println!("{}", t!("stats-none", period = label));
+            } else {
+                println!("{}", t!("stats-title", period = label));
+                for (destination, totals) in &stats {
+                    println!(
+                        "  {}",
+                        t!(
+                            "stats-destination",
+                            destination = destination.clone(),
+                            uploaded = format_size(totals.uploaded),
+                            downloaded = format_size(totals.downloaded),
+                            transfers = totals.transfers
+                        )
+                    );
+                }
+            }
+        }
+        Commands::Status { live, json } => {
+            let mut shown = None;
+            loop {
+                let status = game_backup.read_status()?;
+                let updated = status.as_ref().map(|status| status.updated_at);
+                if shown != Some(updated) {
+                    match &status {
+                        Some(status) if json => {
+                            println!("{}", serde_json::to_string(status)?)
+                        }
+                        Some(status) => print_status(status),
+                        None if json => println!("null"),
+                        None => println!("{}", t!("status-none")),
+                    }
+                    shown = Some(updated);
+                }
+                let running = status
+                    .as_ref()
+                    .is_some_and(|status| status.state == RunState::Running && !status.is_stale());
+                if !live || !running {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_secs(1));
+            }
+            if !json {
+                print_stale_games(&game_backup.stale_games());
+            }
+        }
+        Commands::Trash { command } => match command {
+            TrashCommands::List => {
+                let trashed = game_backup.list_trash()?;
+                if trashed.is_empty() {
+                    println!("{}", t!("trash-empty"));
+                } else {
+                    println!(
+                        "{}",
+                        t!(
+                            "trash-title",
+                            purge_after = game_backup.purge_after().to_string()
+                        )
                     );
+                    for snapshot in trashed {
+                        let purge_at = snapshot
+                            .purge_at
+                            .with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string();
+                        println!(
+                            "  {}  {}  {}  {}{}",
+                            snapshot.game,
+                            snapshot.id,
+                            format_size(snapshot.size),
+                            t!("trash-purged-at", time = purge_at),
+                            if snapshot.cold {
+                                format!("  {}", t!("trash-cold"))
+                            } else {
+                                String::new()
+                            }
+                        );
+                    }
+                }
+            }
+            TrashCommands::Restore {
+                game_name,
+                snapshot,
+            } => {
+                game_backup.restore_from_trash(&game_name, &snapshot)?;
+                println!(
+                    "{}",
+                    t!("trash-restored", snapshot = snapshot, game = game_name)
+                );
+            }
+            TrashCommands::Empty { expired, yes } => {
+                game_backup.ensure_unlocked("purge the trash")?;
+                if !expired && !yes && !confirm(&t!("trash-confirm"))? {
+                    println!("{}", t!("nothing-deleted"));
+                    return Ok(());
+                }
+                let (purged, reclaimed) = game_backup.purge_trash(!expired)?;
+                println!(
+                    "{}",
+                    t!(
+                        "trash-purged",
+                        count = purged,
+                        size = format_size(reclaimed)
+                    )
+                );
+            }
+        },
+        Commands::Config { command } => match command {
+            ConfigCommands::Export { shareable, output } => {
+                let content = if shareable {
+                    game_backup.shareable_config(config_path)?
+                } else {
+                    std::fs::read_to_string(config_path).with_context(|| {
+                        format!("Failed to read config file: {}", config_path.display())
+                    })?
+                };
+                match output {
+                    Some(output) => {
+                        std::fs::write(&output, content).with_context(|| {
+                            format!("Failed to write config file: {}", output.display())
+                        })?;
+                        println!(
+                            "{}",
+                            t!("config-exported", path = output.display().to_string())
+                        );
+                    }
+                    None => print!("{}", content),
                 }
             }
+        },
+        Commands::Quarantine { command } => match command {
+            QuarantineCommands::List => {
+                let entries = game_backup.list_quarantine()?;
+                if entries.is_empty() {
+                    println!("{}", t!("quarantine-none"));
+                } else {
+                    println!("{}", t!("quarantine-title"));
+                    for entry in entries {
+                        println!(
+                            "  {}",
+                            t!(
+                                "quarantine-entry",
+                                id = entry.id.clone(),
+                                game = entry.reason.game.clone(),
+                                time = entry
+                                    .reason
+                                    .failed_at
+                                    .format("%Y-%m-%d %H:%M:%S UTC")
+                                    .to_string()
+                            )
+                        );
+                    }
+                }
+            }
+            QuarantineCommands::Show { id } => {
+                let entry = game_backup.quarantine_entry(&id)?;
+                println!("{}", t!("quarantine-id", id = entry.id.clone()));
+                println!(
+                    "{}",
+                    t!("quarantine-game", game = entry.reason.game.clone())
+                );
+                println!(
+                    "{}",
+                    t!(
+                        "quarantine-failed-at",
+                        time = entry
+                            .reason
+                            .failed_at
+                            .format("%Y-%m-%d %H:%M:%S UTC")
+                            .to_string()
+                    )
+                );
+                println!(
+                    "{}",
+                    t!("quarantine-error", error = entry.reason.error.clone())
+                );
+                println!(
+                    "{}",
+                    t!(
+                        "quarantine-data",
+                        path = entry.data_dir().display().to_string()
+                    )
+                );
+            }
+            QuarantineCommands::Purge { id, all } => {
+                if id.is_none() && !all {
+                    return Err(anyhow::anyhow!(
+                        "Specify a quarantine entry to purge, or --all to purge everything"
+                    ));
+                }
+                let purged = game_backup.purge_quarantine(id.as_deref())?;
+                println!("{}", t!("quarantine-purged", count = purged));
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Start and end of a local calendar month given as `YYYY-MM`, or of the current month.
+fn month_range(
+    month: Option<&str>,
+) -> Result<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> {
+    use chrono::{Datelike, Local, NaiveDate, TimeZone};
+
+    let first = match month {
+        Some(month) => NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+            .with_context(|| t!("invalid-month", month = month.to_string()))?,
+        None => Local::now().date_naive().with_day(1).unwrap_or_default(),
+    };
+    let next = first
+        .checked_add_months(chrono::Months::new(1))
+        .ok_or_else(|| anyhow::anyhow!("Month out of range: {}", first))?;
+
+    let start_of = |date: NaiveDate| {
+        Local
+            .from_local_datetime(&date.and_time(chrono::NaiveTime::MIN))
+            .earliest()
+            .map(|time| time.with_timezone(&chrono::Utc))
+            .ok_or_else(|| anyhow::anyhow!("Invalid local time for: {}", date))
+    };
+    Ok((start_of(first)?, start_of(next)?))
+}
+
+fn print_dedup_report(report: &DedupReport, all: bool) {
+    println!(
+        "{}",
+        t!(
+            "dedup-summary",
+            files = report.files,
+            stored = format_size(report.total_bytes),
+            unique = format_size(report.unique_bytes),
+            savings = format_size(report.savings())
+        )
+    );
+
+    let (cross_game, same_game): (Vec<_>, Vec<_>) = report
+        .groups
+        .iter()
+        .partition(|group| group.is_cross_game());
+    if !cross_game.is_empty() {
+        println!("{}", t!("dedup-cross-game"));
+        for group in &cross_game {
+            print_duplicate_group(group);
+        }
+    }
+
+    if all {
+        if !same_game.is_empty() {
+            println!("{}", t!("dedup-same-game"));
+            for group in &same_game {
+                print_duplicate_group(group);
+            }
+        }
+    } else if !same_game.is_empty() {
+        println!(
+            "{}",
+            t!(
+                "dedup-more",
+                count = same_game.len(),
+                size = format_size(same_game.iter().map(|group| group.savings()).sum())
+            )
+        );
+    }
+}
+
+fn print_duplicate_group(group: &DuplicateGroup) {
+    println!(
+        "  {}",
+        t!(
+            "dedup-group",
+            hash = &group.hash[..group.hash.len().min(12)],
+            copies = group.copies.len(),
+            size = format_size(group.size),
+            savings = format_size(group.savings()),
+            games = group.games().into_iter().collect::<Vec<_>>().join(", ")
+        )
+    );
+    for copy in &group.copies {
+        println!("    {}@{}: {}", copy.game, copy.snapshot, copy.path);
+    }
+}
+
+/// Shows which snapshots would be deleted and deletes them once confirmed.
+fn delete_snapshots(
+    game_backup: &GameBackup,
+    candidates: &[PruneCandidate],
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    if candidates.is_empty() {
+        println!("{}", t!("delete-none"));
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now();
+    println!("{}", t!("delete-title"));
+    for candidate in candidates {
+        let age = candidate
+            .created_at
+            .map(|created| format_age(now - created))
+            .unwrap_or_else(|| t!("age-unknown"));
+        let labels = if candidate.labels.is_empty() {
+            String::new()
+        } else {
+            format!("  [{}]", candidate.labels.join(", "))
+        };
+        println!(
+            "  {}  {}  {}  {}{}",
+            candidate.game,
+            candidate.id,
+            age,
+            format_size(candidate.size),
+            labels
+        );
+    }
+    let total: u64 = candidates.iter().map(|candidate| candidate.size).sum();
+    println!(
+        "{}",
+        t!(
+            "delete-total",
+            count = candidates.len(),
+            size = format_size(total)
+        )
+    );
+
+    if dry_run {
+        return Ok(());
+    }
+    game_backup.ensure_unlocked("delete snapshots")?;
+    if !yes && !confirm(&t!("delete-confirm"))? {
+        println!("{}", t!("nothing-deleted"));
+        return Ok(());
+    }
+    let reclaimed = game_backup.remove_snapshots(candidates)?;
+    let purge_after = game_backup.purge_after();
+    if purge_after.is_zero() {
+        println!(
+            "{}",
+            t!(
+                "delete-done",
+                count = candidates.len(),
+                size = format_size(reclaimed)
+            )
+        );
+    } else {
+        println!(
+            "{}",
+            t!(
+                "delete-trashed",
+                count = candidates.len(),
+                size = format_size(reclaimed),
+                purge_after = purge_after.to_string()
+            )
+        );
+    }
+    Ok(())
+}
+
+fn format_age(age: chrono::TimeDelta) -> String {
+    let minutes = age.num_minutes().max(0);
+    match minutes {
+        0..60 => t!("age-minutes", age = minutes),
+        60..1440 => t!("age-hours", age = minutes / 60),
+        _ => t!("age-days", age = minutes / 1440),
+    }
+}
+
+fn format_duration(duration: chrono::TimeDelta) -> String {
+    let minutes = duration.num_minutes().max(0);
+    if minutes < 60 {
+        t!("duration-minutes", minutes = minutes)
+    } else {
+        t!(
+            "duration-hours",
+            hours = minutes / 60,
+            minutes = minutes % 60
+        )
+    }
+}
+
+fn print_conflict(conflict: &Conflict) {
+    let side = |side: &ConflictSide| {
+        let modified = side
+            .modified
+            .map(|modified| {
+                modified
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|| t!("age-unknown"));
+        (format_size(side.size), modified)
+    };
+    println!("  {}", conflict.path.display());
+    let (size, modified) = side(&conflict.live);
+    let line = if conflict.live_changed() {
+        t!("conflict-live-changed", size = size, modified = modified)
+    } else {
+        t!("conflict-live", size = size, modified = modified)
+    };
+    println!("    {}", line);
+    let (size, modified) = side(&conflict.backup);
+    println!(
+        "    {}",
+        t!(
+            "conflict-backup",
+            size = size,
+            modified = modified,
+            snapshot = conflict.snapshot.clone()
+        )
+    );
+}
+
+fn print_session(session: &Session) {
+    let started = session
+        .started_at
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string();
+    let line = if session.first {
+        t!(
+            "history-first-session",
+            started = started,
+            game = session.game.clone(),
+            duration = format_duration(session.duration()),
+            total = format_size(session.total_bytes)
+        )
+    } else {
+        t!(
+            "history-session",
+            started = started,
+            game = session.game.clone(),
+            duration = format_duration(session.duration()),
+            files = session.files_changed,
+            changed = format_size(session.bytes_changed),
+            removed = session.files_removed,
+            total = format_size(session.total_bytes)
+        )
+    };
+    println!("  {}", line);
+}
+
+/// Asks a yes/no question on stderr; anything but yes counts as no.
+fn confirm(question: &str) -> Result<bool> {
+    eprint!("{} {} ", question, t!("confirm-choices"));
+    io::stderr().flush().ok();
+
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .with_context(|| "Failed to read answer from stdin")?;
+    let answer = answer.trim().to_lowercase();
+    Ok(t!("confirm-yes").split('|').any(|yes| yes == answer))
+}
+
+/// Asks what to do with a live file that differs from the backup, until the answer is valid.
+fn ask_conflict(dest: &Path) -> Result<ConflictPolicy> {
+    let stdin = io::stdin();
+    loop {
+        eprint!(
+            "{} ",
+            t!("restore-conflict-ask", path = dest.display().to_string())
+        );
+        io::stderr().flush().ok();
+
+        let mut answer = String::new();
+        let read = stdin
+            .lock()
+            .read_line(&mut answer)
+            .with_context(|| "Failed to read answer from stdin")?;
+        if read == 0 {
+            return Err(anyhow::anyhow!(
+                "No answer for conflicting file: {}",
+                dest.display()
+            ));
         }
+
+        let answer = answer.trim().to_lowercase();
+        let is = |choices: String| choices.split('|').any(|choice| choice == answer);
+        if is(t!("restore-conflict-overwrite")) {
+            return Ok(ConflictPolicy::Overwrite);
+        } else if is(t!("restore-conflict-skip")) {
+            return Ok(ConflictPolicy::Skip);
+        } else if is(t!("restore-conflict-backup")) {
+            return Ok(ConflictPolicy::BackupExisting);
+        }
+        eprintln!("{}", t!("restore-conflict-retry"));
+    }
+}
+
+/// Runs the daemon under the service control manager. A service has no console, so it logs
+/// to the event log instead.
+#[cfg(all(windows, feature = "service"))]
+fn run_service(cli: Cli) -> Result<()> {
+    let level = if cli.verbose {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Info
+    };
+    log::set_boxed_logger(Box::new(cartridge::service::EventLog::open(level)?))?;
+    log::set_max_level(level);
+    cartridge::i18n::init(cli.lang.as_deref());
+
+    let config_path = find_config_file(cli.config.as_deref())?;
+    cartridge::service::run_service(&config_path, cli.set)
+}
+
+#[cfg(feature = "self-update")]
+fn self_update(check: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release = cartridge::update::latest_release()?;
+    if !release.is_newer() {
+        println!("{}", t!("self-update-up-to-date", version = current));
+        return Ok(());
     }
+    if check {
+        println!(
+            "{}",
+            t!(
+                "self-update-available",
+                current = current,
+                latest = release.version.as_str()
+            )
+        );
+        return Ok(());
+    }
+    let path = cartridge::update::install(&release)?;
+    println!(
+        "{}",
+        t!(
+            "self-update-done",
+            current = current,
+            latest = release.version.as_str(),
+            path = path.display().to_string()
+        )
+    );
     Ok(())
 }
+
+fn print_info(info: &BuildInfo) {
+    let enabled = |map: &std::collections::BTreeMap<&str, bool>| {
+        map.iter()
+            .filter(|(_, enabled)| **enabled)
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let path = |path: &Option<PathBuf>| {
+        path.as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| t!("info-none"))
+    };
+
+    println!("{} {}", info.name, info.version);
+    println!(
+        "{}",
+        t!(
+            "info-platform",
+            os = info.platform.os,
+            family = info.platform.family,
+            arch = info.platform.arch
+        )
+    );
+    println!(
+        "{}",
+        t!("info-backends", backends = enabled(&info.backends))
+    );
+    println!(
+        "{}",
+        t!("info-features", features = enabled(&info.features))
+    );
+    println!("{}", t!("info-formats", formats = info.formats.join(", ")));
+    println!(
+        "{}",
+        t!("info-language", language = cartridge::i18n::language())
+    );
+    println!("{}", t!("info-home", path = path(&info.paths.home)));
+    println!(
+        "{}",
+        t!("info-config-file", path = path(&info.paths.config_file))
+    );
+    println!(
+        "{}",
+        t!("info-backup-root", path = path(&info.paths.backup_root))
+    );
+}
+
+fn print_stale_games(games: &[StaleGame]) {
+    if games.is_empty() {
+        return;
+    }
+    println!();
+    println!("{}", t!("stale-title"));
+    for game in games {
+        let line = match game.last_modified {
+            Some(modified) => t!(
+                "stale-game",
+                game = game.name.clone(),
+                date = modified
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d")
+                    .to_string(),
+                size = format_size(game.bytes)
+            ),
+            None => t!("stale-game-empty", game = game.name.clone()),
+        };
+        println!("  {}", line);
+    }
+    println!("{}", t!("stale-hint"));
+}
+
+fn print_config_audit(audit: &ConfigAudit) {
+    let time = |time: chrono::DateTime<chrono::Utc>| {
+        time.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+    println!("{}", t!("audit-title"));
+    for estimate in &audit.games {
+        println!(
+            "  {}",
+            t!(
+                "audit-game",
+                game = estimate.game.clone(),
+                files = estimate.files,
+                size = format_size(estimate.bytes)
+            )
+        );
+    }
+    if audit.findings.is_empty() {
+        println!("{}", t!("audit-clean"));
+        return;
+    }
+
+    println!();
+    for finding in &audit.findings {
+        let message = match finding {
+            Finding::InvalidPath {
+                location, error, ..
+            } => t!(
+                "audit-invalid-path",
+                location = *location,
+                error = error.clone()
+            ),
+            Finding::MissingPath { location, path, .. } => t!(
+                "audit-missing-path",
+                location = *location,
+                path = path.display().to_string()
+            ),
+            Finding::CloudSynced {
+                location, service, ..
+            } => t!(
+                "audit-cloud-synced",
+                location = *location,
+                service = service.to_string()
+            ),
+            Finding::OverlappingSaves {
+                location,
+                other_game,
+                other_location,
+                path,
+                ..
+            } => t!(
+                "audit-overlapping-saves",
+                location = *location,
+                other = other_game.clone(),
+                other_location = *other_location,
+                path = path.display().to_string()
+            ),
+            Finding::OverlapsBackups {
+                location, backups, ..
+            } => t!(
+                "audit-overlaps-backups",
+                location = *location,
+                backups = backups.display().to_string()
+            ),
+            Finding::TooManyFiles { files, limit, .. } => {
+                t!("audit-too-many-files", files = *files, limit = *limit)
+            }
+            Finding::Unreadable { error, .. } => t!("audit-unreadable", error = error.clone()),
+            Finding::NeverBackedUp { .. } => t!("audit-never-backed-up"),
+            Finding::OutdatedBackup {
+                backed_up_at,
+                modified_at,
+                ..
+            } => t!(
+                "audit-outdated-backup",
+                backed_up = time(*backed_up_at),
+                modified = time(*modified_at)
+            ),
+            Finding::Stale {
+                last_modified: Some(modified),
+                ..
+            } => t!("audit-stale", modified = time(*modified)),
+            Finding::Stale { .. } => t!("audit-stale-empty"),
+        };
+        let line = if finding.is_error() {
+            t!(
+                "audit-error",
+                game = finding.game().to_string(),
+                message = message
+            )
+        } else {
+            t!(
+                "audit-warning",
+                game = finding.game().to_string(),
+                message = message
+            )
+        };
+        println!("  {}", line);
+    }
+}
+
+fn print_source_machines(game: &str, machines: &[SourceMachine], this_machine: Option<&str>) {
+    if machines.is_empty() {
+        println!("{}", t!("snapshots-none", game = game));
+        return;
+    }
+    println!("{}", t!("machines-title", game = game));
+    for machine in machines {
+        let name = match &machine.name {
+            Some(name) if Some(name.as_str()) == this_machine => {
+                t!("machines-this", machine = name.clone())
+            }
+            Some(name) => name.clone(),
+            None => t!("machines-unknown"),
+        };
+        println!(
+            "  {}",
+            t!(
+                "machines-entry",
+                machine = name,
+                id = machine.latest.clone(),
+                date = machine
+                    .created_at
+                    .map(|created_at| {
+                        created_at
+                            .with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_else(|| t!("unknown")),
+                count = machine.snapshots
+            )
+        );
+    }
+}
+
+/// Says which machine and time the restored backup came from, when the backup recorded it.
+fn print_restore_origin(report: &RestoreReport) {
+    let Some(created_at) = report.created_at else {
+        return;
+    };
+    let date = created_at
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string();
+    let line = match &report.machine {
+        Some(machine) => t!(
+            "restore-origin",
+            game = report.game.clone(),
+            machine = machine.clone(),
+            date = date
+        ),
+        None => t!(
+            "restore-origin-unknown",
+            game = report.game.clone(),
+            date = date
+        ),
+    };
+    println!("{}", line);
+}
+
+fn print_run_summary(summary: &RunSummary) {
+    if summary.is_empty() {
+        return;
+    }
+    let succeeded: Vec<&str> = summary
+        .succeeded()
+        .map(|outcome| outcome.game.as_str())
+        .collect();
+    let failed: Vec<_> = summary.failed().collect();
+    let total = summary.games.len();
+
+    println!();
+    let restore = summary
+        .games
+        .iter()
+        .all(|outcome| outcome.operation == Operation::Restore);
+    let title = if restore {
+        t!(
+            "summary-restore",
+            succeeded = succeeded.len(),
+            total = total
+        )
+    } else {
+        t!("summary-backup", succeeded = succeeded.len(), total = total)
+    };
+    println!("{}", title);
+    if !failed.is_empty() && !succeeded.is_empty() {
+        println!(
+            "  {}",
+            t!("summary-succeeded", games = succeeded.join(", "))
+        );
+    }
+    for outcome in &failed {
+        println!(
+            "  {}",
+            t!(
+                "summary-failed",
+                game = outcome.game.clone(),
+                error = outcome.error.clone().unwrap_or_default()
+            )
+        );
+    }
+
+    let mut steps: Vec<String> = Vec::new();
+    for outcome in &failed {
+        let command = match outcome.operation {
+            Operation::Backup => "backup",
+            Operation::Restore => "restore",
+        };
+        for step in &outcome.next_steps {
+            let game = outcome.game.clone();
+            let line = match step {
+                NextStep::Doctor => t!("summary-doctor"),
+                NextStep::SkipMissing => t!("summary-skip-missing", game = game),
+                NextStep::FreeSpace => t!("summary-free-space"),
+                NextStep::Retry => t!("summary-retry", command = command, game = game),
+            };
+            if !steps.contains(&line) {
+                steps.push(line);
+            }
+        }
+    }
+    if !steps.is_empty() {
+        println!("{}", t!("summary-next-steps"));
+        for step in steps {
+            println!("  {}", step);
+        }
+    }
+}
+
+fn print_recent_games(games: &[RecentGame], period: Period) {
+    if games.is_empty() {
+        println!("{}", t!("backup-no-recent", period = period.to_string()));
+        return;
+    }
+    println!("{}", t!("backup-recent-title", period = period.to_string()));
+    for game in games {
+        println!(
+            "  {}",
+            t!(
+                "backup-recent-game",
+                game = game.name.clone(),
+                date = game
+                    .last_played
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            )
+        );
+    }
+}
+
+fn print_status(status: &Status) {
+    let operation = match status.operation {
+        Operation::Backup => t!("status-backup"),
+        Operation::Restore => t!("status-restore"),
+    };
+    let time = |time: chrono::DateTime<chrono::Utc>| {
+        time.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    };
+
+    match status.state {
+        RunState::Running if status.is_stale() => println!(
+            "{}",
+            t!(
+                "status-stale",
+                operation = operation,
+                game = status.game.clone().unwrap_or_default(),
+                pid = status.pid,
+                time = time(status.updated_at)
+            )
+        ),
+        RunState::Running if status.game.is_none() => println!(
+            "{}",
+            t!(
+                "status-starting",
+                operation = operation,
+                total = status.games_total
+            )
+        ),
+        RunState::Running => {
+            println!(
+                "{}",
+                t!(
+                    "status-running",
+                    operation = operation,
+                    game = status.game.clone().unwrap_or_default(),
+                    done = (status.games_done + 1).min(status.games_total),
+                    total = status.games_total,
+                    percent = format!("{:.0}", status.percent)
+                )
+            );
+            let files = match status.files_total {
+                Some(total) => t!("status-files-of", done = status.files_done, total = total),
+                None => t!("status-files", done = status.files_done),
+            };
+            match &status.file {
+                Some(file) => println!("  {}: {}", files, file.display()),
+                None => println!("  {}", files),
+            }
+        }
+        RunState::Finished => println!(
+            "{}",
+            t!(
+                "status-finished",
+                operation = operation,
+                games = status.games_total,
+                time = time(status.updated_at)
+            )
+        ),
+        RunState::Failed => println!(
+            "{}",
+            t!(
+                "status-failed",
+                operation = operation,
+                done = status.games_done,
+                total = status.games_total,
+                time = time(status.updated_at)
+            )
+        ),
+    }
+}
+
+fn print_restore_check(check: &RestoreCheck) {
+    for path in &check.mismatched {
+        println!(
+            "  {}",
+            t!("restore-check-mismatch", path = path.display().to_string())
+        );
+    }
+    for issue in &check.invalid {
+        println!(
+            "  {}",
+            t!(
+                "restore-check-invalid",
+                path = issue.path.clone(),
+                problem = issue.problem.clone()
+            )
+        );
+    }
+    if check.is_ok() {
+        println!(
+            "{}",
+            t!(
+                "restore-check-ok",
+                game = check.game.clone(),
+                verified = check.verified
+            )
+        );
+    }
+}
+
+fn print_rehearsal(report: &DiffReport) {
+    println!("{}", t!("rehearsal-title", game = report.game.clone()));
+    let labels: Vec<String> = report
+        .entries
+        .iter()
+        .map(|entry| match entry.status {
+            DiffStatus::Removed => t!("rehearsal-create"),
+            DiffStatus::Modified { live_newer: true } => t!("rehearsal-overwrite-newer"),
+            DiffStatus::Modified { live_newer: false } => t!("rehearsal-overwrite"),
+            DiffStatus::Added => t!("rehearsal-keep"),
+        })
+        .collect();
+    let width = labels.iter().map(|label| label.chars().count()).max();
+    for (entry, label) in report.entries.iter().zip(&labels) {
+        println!(
+            "  {:<width$} {}",
+            label,
+            entry.path,
+            width = width.unwrap_or(0)
+        );
+    }
+    let changes = report
+        .entries
+        .iter()
+        .filter(|entry| entry.status != DiffStatus::Added)
+        .count();
+    println!(
+        "  {}",
+        t!(
+            "plan-summary",
+            changed = changes,
+            unchanged = report.unchanged
+        )
+    );
+}
+
+fn print_verify_report(report: &VerifyReport) {
+    if report.is_ok() {
+        match &report.chain {
+            Some(chain) => println!(
+                "{}",
+                t!(
+                    "verify-ok-chain",
+                    game = report.game.clone(),
+                    checked = report.checked,
+                    linked = chain.linked,
+                    links = chain.snapshots.saturating_sub(1)
+                )
+            ),
+            None => println!(
+                "{}",
+                t!(
+                    "verify-ok",
+                    game = report.game.clone(),
+                    checked = report.checked
+                )
+            ),
+        }
+        return;
+    }
+
+    println!(
+        "{}",
+        t!(
+            "verify-failed-game",
+            game = report.game.clone(),
+            checked = report.checked,
+            missing = report.missing.len(),
+            corrupted = report.corrupted.len()
+        )
+    );
+    for path in &report.missing {
+        println!("  {}", t!("verify-missing", path = path.clone()));
+    }
+    for path in &report.corrupted {
+        println!("  {}", t!("verify-corrupted", path = path.clone()));
+    }
+    for problem in report.chain.iter().flat_map(|chain| &chain.problems) {
+        let line = match problem {
+            ChainProblem::Missing { snapshot, missing } => t!(
+                "chain-missing",
+                snapshot = snapshot.clone(),
+                missing = missing.clone()
+            ),
+            ChainProblem::Replaced { snapshot, replaced } => t!(
+                "chain-replaced",
+                snapshot = snapshot.clone(),
+                replaced = replaced.clone()
+            ),
+            ChainProblem::Unlinked { snapshot } => {
+                t!("chain-unlinked", snapshot = snapshot.clone())
+            }
+        };
+        println!("  {}", line);
+    }
+}
+
+fn priority_label(priority: Priority) -> String {
+    match priority {
+        Priority::Manual => t!("priority-manual"),
+        Priority::Watch => t!("priority-watch"),
+    }
+}
+
+fn print_plan(title: &str, actions: &[FileAction]) {
+    println!("{}:", title);
+
+    let label = |kind: &ActionKind| match kind {
+        ActionKind::Create => t!("action-create"),
+        ActionKind::Overwrite => t!("action-overwrite"),
+        ActionKind::Skip => t!("action-skip"),
+        ActionKind::BackupExisting { .. } => t!("action-stash"),
+        ActionKind::Conflict => t!("action-ask"),
+        ActionKind::Unchanged => String::new(),
+    };
+    // Labels differ in length between languages, so align on the longest one shown
+    let width = actions
+        .iter()
+        .map(|action| label(&action.kind).chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut unchanged = 0;
+    for action in actions {
+        if action.kind == ActionKind::Unchanged {
+            unchanged += 1;
+            continue;
+        }
+
+        let state = match &action.dest_state {
+            Some(dest_state) => t!(
+                "plan-replaces",
+                source = describe_state(&action.source_state),
+                dest = describe_state(dest_state)
+            ),
+            None => describe_state(&action.source_state),
+        };
+        println!(
+            "  {:<width$} {} ({})",
+            label(&action.kind),
+            action.dest.display(),
+            state
+        );
+        if let ActionKind::BackupExisting { stash } = &action.kind {
+            println!(
+                "  {:width$} {}",
+                "",
+                t!("plan-stashed", path = stash.display().to_string())
+            );
+        }
+    }
+
+    println!(
+        "  {}",
+        t!(
+            "plan-summary",
+            changed = actions.len() - unchanged,
+            unchanged = unchanged
+        )
+    );
+}
+
+fn describe_state(state: &FileState) -> String {
+    let description = match state.modified {
+        Some(modified) => t!(
+            "state-modified",
+            size = format_size(state.size),
+            modified = chrono::DateTime::<chrono::Local>::from(modified)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        ),
+        None => format_size(state.size),
+    };
+    if state.read_only {
+        t!("state-read-only", state = description)
+    } else {
+        description
+    }
+}
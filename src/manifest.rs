@@ -0,0 +1,363 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::durable;
+use crate::mods::ModEntry;
+use crate::protect::Protection;
+use crate::validate::ValidationIssue;
+use crate::{BackupFormat, fserror};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub platform: String,
+    /// Backup path each save location was stored under, by its position in the game config
+    #[serde(default)]
+    pub locations: Vec<Option<String>>,
+    /// Ignore rules each save location was backed up with, by its position in the game config
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<Option<IgnoreRules>>,
+    /// Installed mods of each `mods` save location, by its position in the game config
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mods: Vec<Option<Vec<ModEntry>>>,
+    /// The backup this one replaced, so deleted or replaced snapshots can be detected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous: Option<ChainLink>,
+    /// How the backup is stored; missing from manifests written before formats were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<BackupFormat>,
+    /// Protection applied to the backup once it was completed
+    #[serde(default)]
+    pub protection: Protection,
+    /// Save files that failed validation when the backup was taken
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validation_issues: Vec<ValidationIssue>,
+    /// Description of the backup, given with `backup --message` or by the game's
+    /// `label_command`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Machine the backup was taken on, see `machine` in the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine: Option<String>,
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+/// Reference from a backup to the one it replaced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainLink {
+    /// Snapshot id of the replaced backup
+    pub id: String,
+    /// Chain hash of the replaced backup's manifest
+    pub hash: String,
+}
+
+/// Rules deciding which files of a save location are left out of backups and restores.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IgnoreRules {
+    pub include_hidden: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub size: u64,
+    pub modified: SystemTime,
+    pub hash: String,
+}
+
+impl ManifestEntry {
+    /// Whether a live file with the given metadata is unchanged since this entry was recorded.
+    pub fn matches(&self, metadata: &fs::Metadata) -> bool {
+        metadata.len() == self.size && metadata.modified().ok() == Some(self.modified)
+    }
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Manifest {
+    pub fn new() -> Self {
+        Self {
+            created_at: Utc::now(),
+            platform: std::env::consts::OS.to_string(),
+            locations: Vec::new(),
+            ignore: Vec::new(),
+            mods: Vec::new(),
+            previous: None,
+            format: None,
+            protection: Protection::None,
+            validation_issues: Vec::new(),
+            label: None,
+            machine: None,
+            files: BTreeMap::new(),
+        }
+    }
+
+    pub fn load(backup_dir: &Path) -> Result<Option<Self>> {
+        let manifest_path = backup_dir.join(MANIFEST_FILE);
+        if !manifest_path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Failed to read manifest: {}", manifest_path.display()))?;
+        let manifest = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse manifest: {}", manifest_path.display()))?;
+        Ok(Some(manifest))
+    }
+
+    pub fn save(&self, backup_dir: &Path) -> Result<()> {
+        let manifest_path = backup_dir.join(MANIFEST_FILE);
+        log::debug!(
+            "Writing manifest with {} files: {}",
+            self.files.len(),
+            manifest_path.display()
+        );
+
+        let content =
+            serde_json::to_string_pretty(self).with_context(|| "Failed to serialize manifest")?;
+        durable::write_atomic(&manifest_path, content)
+            .with_context(|| format!("Failed to write manifest: {}", manifest_path.display()))
+    }
+
+    /// Hash identifying what the backup holds, and through its own link, every backup before
+    /// it. Settings that can change after the backup was made, like its protection, are left out.
+    pub fn chain_hash(&self) -> String {
+        let content = serde_json::to_vec(&(
+            &self.created_at,
+            &self.locations,
+            &self.mods,
+            &self.files,
+            &self.previous,
+        ))
+        .unwrap_or_default();
+        blake3::hash(&content).to_hex().to_string()
+    }
+
+    pub fn location(&self, index: usize) -> Option<PathBuf> {
+        self.locations
+            .get(index)
+            .and_then(Option::as_ref)
+            .map(PathBuf::from)
+    }
+
+    pub fn set_location(&mut self, index: usize, prefix: &Path) {
+        if self.locations.len() <= index {
+            self.locations.resize(index + 1, None);
+        }
+        self.locations[index] = Some(entry_key(prefix));
+    }
+
+    pub fn ignore_rules(&self, index: usize) -> Option<IgnoreRules> {
+        self.ignore.get(index).copied().flatten()
+    }
+
+    pub fn set_ignore_rules(&mut self, index: usize, rules: IgnoreRules) {
+        if self.ignore.len() <= index {
+            self.ignore.resize(index + 1, None);
+        }
+        self.ignore[index] = Some(rules);
+    }
+
+    pub fn mods(&self, index: usize) -> Option<Vec<ModEntry>> {
+        self.mods.get(index).cloned().flatten()
+    }
+
+    pub fn set_mods(&mut self, index: usize, mods: Vec<ModEntry>) {
+        if self.mods.len() <= index {
+            self.mods.resize(index + 1, None);
+        }
+        self.mods[index] = Some(mods);
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&ManifestEntry> {
+        self.files.get(&entry_key(path))
+    }
+
+    pub fn insert(&mut self, path: &Path, entry: ManifestEntry) {
+        self.files.insert(entry_key(path), entry);
+    }
+}
+
+/// Normalized manifest key for a backup-relative path, always using `/` separators. In file
+/// names that are not valid UTF-8, the invalid bytes and backslashes are written as `\xNN`,
+/// so such names keep distinct keys and [`key_path`] can turn them back into paths.
+pub fn entry_key(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(escape_os_str(part)),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `part` as text, with the bytes that are not valid UTF-8 and the backslashes of such names
+/// written as `\\xNN`.
+fn escape_os_str(part: &OsStr) -> String {
+    if let Some(part) = part.to_str() {
+        return part.to_string();
+    }
+    let mut key = String::new();
+    for chunk in part.as_encoded_bytes().utf8_chunks() {
+        key.push_str(&chunk.valid().replace('\\', "\\x5c"));
+        for byte in chunk.invalid() {
+            key.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+    key
+}
+
+/// Backup-relative path of a manifest key, the reverse of [`entry_key`].
+pub fn key_path(key: &str) -> PathBuf {
+    key.split('/').map(unescape_os_str).collect()
+}
+
+/// The reverse of [`escape_os_str`].
+#[cfg(unix)]
+fn unescape_os_str(part: &str) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+
+    if !part.contains("\\x") {
+        return OsString::from(part);
+    }
+    let mut bytes = Vec::new();
+    let mut rest = part;
+    while let Some(start) = rest.find("\\x") {
+        bytes.extend_from_slice(&rest.as_bytes()[..start]);
+        match rest
+            .get(start + 2..start + 4)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            Some(byte) => {
+                bytes.push(byte);
+                rest = &rest[start + 4..];
+            }
+            None => {
+                bytes.extend_from_slice(b"\\x");
+                rest = &rest[start + 2..];
+            }
+        }
+    }
+    bytes.extend_from_slice(rest.as_bytes());
+    // Only names that were not valid UTF-8 were escaped
+    match String::from_utf8(bytes) {
+        Ok(_) => OsString::from(part),
+        Err(e) => OsString::from_vec(e.into_bytes()),
+    }
+}
+
+/// Names that are not valid Unicode cannot be rebuilt safely off Unix, so keys are used as
+/// they are.
+#[cfg(not(unix))]
+fn unescape_os_str(part: &str) -> OsString {
+    OsString::from(part)
+}
+
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open file: {}", path.display()))?;
+    hash_reader(&mut file).with_context(|| format!("Failed to hash file: {}", path.display()))
+}
+
+pub fn hash_reader<R: Read>(reader: &mut R) -> io::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    io::copy(reader, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Copies `source` to `dest`, hashing the content on the way through.
+pub fn copy_and_hash(source: &Path, dest: &Path) -> Result<String> {
+    let copy_error = |e| fserror::copy_error(e, source, dest);
+    let mut input = File::open(source).map_err(copy_error)?;
+    let mut output = File::create(dest).map_err(copy_error)?;
+
+    let mut reader = HashingReader::new(&mut input);
+    io::copy(&mut reader, &mut output).map_err(copy_error)?;
+    output.flush().map_err(copy_error)?;
+    let hash = reader.finish();
+
+    // Keep permissions in line with what fs::copy would have produced
+    if let Ok(metadata) = input.metadata() {
+        let _ = fs::set_permissions(dest, metadata.permissions());
+    }
+
+    Ok(hash)
+}
+
+pub struct HashingReader<R> {
+    inner: R,
+    hasher: blake3::Hasher,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: blake3::Hasher::new(),
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.hasher.finalize().to_hex().to_string()
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+/// Serde helpers for paths in JSON records, which only hold UTF-8. Paths that are not valid
+/// UTF-8 are written escaped like manifest keys instead of failing the whole record.
+pub(crate) mod escaped_path {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::path::{Path, PathBuf};
+
+    pub(crate) fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::escape_os_str(path.as_os_str()))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<PathBuf, D::Error> {
+        let path = String::deserialize(deserializer)?;
+        Ok(PathBuf::from(super::unescape_os_str(&path)))
+    }
+
+    pub(crate) mod option {
+        use super::*;
+
+        pub(crate) fn serialize<S: Serializer>(
+            path: &Option<PathBuf>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match path {
+                Some(path) => super::serialize(path, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<PathBuf>, D::Error> {
+            Ok(Option::<String>::deserialize(deserializer)?
+                .map(|path| PathBuf::from(super::super::unescape_os_str(&path))))
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::manifest::Manifest;
+use crate::{GameBackup, SaveKind, SaveLocation, is_hidden};
+
+/// Metadata files mods commonly carry a version in, checked in the top directory of a mod.
+const JSON_METADATA: &[&str] = &[
+    "manifest.json",
+    "mod.json",
+    "modinfo.json",
+    "info.json",
+    "meta.json",
+];
+/// Paradox-style descriptor with a `version="..."` line.
+const DESCRIPTOR: &str = "descriptor.mod";
+/// Metadata files larger than this are not read.
+const MAX_METADATA_SIZE: u64 = 1024 * 1024;
+
+/// An installed mod as recorded in a backup: the name of its folder or file and, when its
+/// metadata declares one, its version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModEntry {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModStatus {
+    /// Installed with the version recorded in the backup
+    Installed,
+    /// Recorded in the backup but not installed
+    Missing,
+    /// Installed with another version than the one recorded
+    VersionChanged { installed: Option<String> },
+    /// Installed but not recorded in the backup
+    NotInBackup,
+}
+
+#[derive(Debug)]
+pub struct ModCheck {
+    pub name: String,
+    /// Version recorded in the backup, or the installed one for mods not in the backup
+    pub version: Option<String>,
+    pub status: ModStatus,
+}
+
+impl SaveLocation {
+    /// Whether only the list of mods is recorded for this location. Mod files themselves are
+    /// only backed up when `files` selects some, e.g. a load order file.
+    pub(crate) fn is_mod_list_only(&self) -> bool {
+        self.kind == SaveKind::Mods && self.files.is_empty()
+    }
+}
+
+impl GameBackup {
+    /// Compares the mods recorded in a backup of the game with the installed ones. Uses the
+    /// current backup, or the given snapshot.
+    pub fn check_mods(&self, game_name: &str, snapshot: Option<&str>) -> Result<Vec<ModCheck>> {
+        let game = self.find_game(game_name)?;
+        let game_backup_dir = self.snapshot_dir(&game.name, snapshot)?;
+        let manifest = Manifest::load(&game_backup_dir)?;
+
+        let mut checks = Vec::new();
+        for (i, save_location) in game.saves.iter().enumerate() {
+            if save_location.kind != SaveKind::Mods {
+                continue;
+            }
+            let Some(path) = self.save_path(save_location)? else {
+                continue;
+            };
+            let recorded = manifest
+                .as_ref()
+                .and_then(|manifest| manifest.mods(i))
+                .unwrap_or_default();
+            checks.extend(compare_mods(recorded, &scan_mods(&path)?));
+        }
+        Ok(checks)
+    }
+
+    /// Warns about mods a backup was made with that are missing or have another version
+    /// installed, before its saves are restored.
+    pub(crate) fn warn_about_mod_changes(
+        &self,
+        index: usize,
+        dest_path: &Path,
+        manifest: Option<&Manifest>,
+    ) {
+        let Some(recorded) = manifest.and_then(|manifest| manifest.mods(index)) else {
+            return;
+        };
+        let installed = match scan_mods(dest_path) {
+            Ok(installed) => installed,
+            Err(e) => {
+                log::debug!("Could not list installed mods: {:#}", e);
+                return;
+            }
+        };
+
+        for check in compare_mods(recorded, &installed) {
+            match check.status {
+                ModStatus::Missing => log::warn!(
+                    "Backup was made with mod '{}'{}, which is not installed",
+                    check.name,
+                    version_suffix(check.version.as_deref())
+                ),
+                ModStatus::VersionChanged { installed } => log::warn!(
+                    "Backup was made with mod '{}'{}, installed is{}",
+                    check.name,
+                    version_suffix(check.version.as_deref()),
+                    version_suffix(installed.as_deref())
+                ),
+                ModStatus::Installed | ModStatus::NotInBackup => {}
+            }
+        }
+    }
+}
+
+/// Lists the mods in a mod directory: one per folder or file directly inside it.
+pub(crate) fn scan_mods(dir: &Path) -> Result<Vec<ModEntry>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut mods = Vec::new();
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    for entry in entries {
+        let entry = entry
+            .with_context(|| format!("Failed to read directory entry in: {}", dir.display()))?;
+        if is_hidden(&entry) {
+            continue;
+        }
+        let path = entry.path();
+        mods.push(ModEntry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            version: if path.is_dir() {
+                mod_version(&path)
+            } else {
+                None
+            },
+        });
+    }
+    mods.sort_by(|a, b| a.name.cmp(&b.name));
+    log::debug!("Found {} mods in {}", mods.len(), dir.display());
+    Ok(mods)
+}
+
+fn compare_mods(recorded: Vec<ModEntry>, installed: &[ModEntry]) -> Vec<ModCheck> {
+    let mut installed: BTreeMap<&str, &ModEntry> = installed
+        .iter()
+        .map(|entry| (entry.name.as_str(), entry))
+        .collect();
+
+    let mut checks: Vec<ModCheck> = recorded
+        .into_iter()
+        .map(|entry| {
+            let status = match installed.remove(entry.name.as_str()) {
+                None => ModStatus::Missing,
+                Some(current) if current.version != entry.version => ModStatus::VersionChanged {
+                    installed: current.version.clone(),
+                },
+                Some(_) => ModStatus::Installed,
+            };
+            ModCheck {
+                name: entry.name,
+                version: entry.version,
+                status,
+            }
+        })
+        .collect();
+    checks.extend(installed.into_values().map(|entry| ModCheck {
+        name: entry.name.clone(),
+        version: entry.version.clone(),
+        status: ModStatus::NotInBackup,
+    }));
+    checks
+}
+
+/// Version declared by a mod folder's metadata, if any.
+fn mod_version(dir: &Path) -> Option<String> {
+    let entries: Vec<_> = fs::read_dir(dir).ok()?.flatten().collect();
+    let find = |name: &str| {
+        entries
+            .iter()
+            .find(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .eq_ignore_ascii_case(name)
+            })
+            .map(|entry| entry.path())
+            .filter(|path| {
+                fs::metadata(path).is_ok_and(|metadata| metadata.len() <= MAX_METADATA_SIZE)
+            })
+    };
+
+    for name in JSON_METADATA {
+        let Some(path) = find(name) else {
+            continue;
+        };
+        let Ok(value) = fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(serde_json::from_str::<serde_json::Value>(&content)?))
+        else {
+            log::debug!("Ignoring unreadable mod metadata: {}", path.display());
+            continue;
+        };
+        let version = ["version", "Version", "mod_version"]
+            .iter()
+            .find_map(|key| value.get(key))
+            .and_then(|version| match version {
+                serde_json::Value::String(version) => Some(version.clone()),
+                serde_json::Value::Number(version) => Some(version.to_string()),
+                _ => None,
+            });
+        if version.is_some() {
+            return version;
+        }
+    }
+
+    let content = fs::read_to_string(find(DESCRIPTOR)?).ok()?;
+    content.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("version")?.trim_start();
+        let value = value.strip_prefix('=')?.trim();
+        Some(value.trim_matches('"').to_string())
+    })
+}
+
+fn version_suffix(version: Option<&str>) -> String {
+    match version {
+        Some(version) => format!(" {}", version),
+        None => " (no version)".to_string(),
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::{Manifest, escaped_path};
+use crate::plan::{ConflictPolicy, RestoreOptions};
+use crate::protect::{self, Protection};
+use crate::snapshot::snapshot_id;
+use crate::{GameBackup, unprotect_backup};
+
+/// Written into a directory a snapshot was mounted at, so `unmount` only ever removes
+/// directories that `mount` filled.
+pub const MOUNT_MARKER: &str = ".cartridge-mount.json";
+
+/// A snapshot extracted read-only into a directory to be browsed with normal tools. Each
+/// save location is in a numbered subdirectory, in the order of the configuration.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MountedSnapshot {
+    pub game: String,
+    pub snapshot: String,
+    pub mounted_at: DateTime<Utc>,
+    pub locations: Vec<MountedLocation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MountedLocation {
+    /// Subdirectory of the mount holding the location
+    pub dir: String,
+    /// Live save path the files are restored to
+    #[serde(with = "escaped_path")]
+    pub path: PathBuf,
+}
+
+impl GameBackup {
+    /// Extracts a snapshot of a game into `dir`, which must not exist or be empty, and makes
+    /// it read-only. Remove it again with [`GameBackup::unmount_snapshot`].
+    pub fn mount_snapshot(
+        &self,
+        game_name: &str,
+        snapshot: &str,
+        dir: &Path,
+    ) -> Result<MountedSnapshot> {
+        let game = self.find_game(game_name)?;
+        let snapshot_dir = self.snapshot_dir(&game.name, Some(snapshot))?;
+        if dir.exists()
+            && fs::read_dir(dir)
+                .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+                .next()
+                .is_some()
+        {
+            return Err(anyhow!(
+                "Cannot mount a snapshot at {}, the directory is not empty",
+                dir.display()
+            ));
+        }
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+
+        let options = RestoreOptions {
+            on_conflict: ConflictPolicy::Overwrite,
+            ..RestoreOptions::default()
+        };
+        let fetched = self.fetch_from_cold(&game.name, &snapshot_dir, &options)?;
+        let source = fetched.as_deref().unwrap_or(&snapshot_dir);
+        let result = self
+            .restore_locations(game, source, &options, Some(dir), &mut Vec::new())
+            .and_then(|()| {
+                let mut locations = Vec::new();
+                for (i, save_location) in game.saves.iter().enumerate() {
+                    let location_dir = i.to_string();
+                    if let Some(path) = self.save_path(save_location)?
+                        && dir.join(&location_dir).exists()
+                    {
+                        locations.push(MountedLocation {
+                            dir: location_dir,
+                            path,
+                        });
+                    }
+                }
+                let mounted = MountedSnapshot {
+                    game: game.name.clone(),
+                    snapshot: Manifest::load(source)?
+                        .map_or_else(|| snapshot.to_string(), |manifest| snapshot_id(&manifest)),
+                    mounted_at: Utc::now(),
+                    locations,
+                };
+                let marker = dir.join(MOUNT_MARKER);
+                fs::write(&marker, serde_json::to_string_pretty(&mounted)?)
+                    .with_context(|| format!("Failed to write file: {}", marker.display()))?;
+                protect::protect(dir, Protection::ReadOnly)?;
+                Ok(mounted)
+            });
+        if let Some(fetched) = &fetched
+            && let Err(e) = unprotect_backup(fetched).and_then(|()| {
+                fs::remove_dir_all(fetched).with_context(|| {
+                    format!("Failed to remove fetched snapshot: {}", fetched.display())
+                })
+            })
+        {
+            log::warn!("{:#}", e);
+        }
+
+        if result.is_err()
+            && let Err(e) = remove_mount(dir)
+        {
+            log::warn!("Failed to clean up {}: {:#}", dir.display(), e);
+        }
+        let mounted = result?;
+        log::info!(
+            "Mounted snapshot {} of '{}' read-only at {}",
+            mounted.snapshot,
+            mounted.game,
+            dir.display()
+        );
+        Ok(mounted)
+    }
+
+    /// Removes a directory that a snapshot was mounted at. Directories that were not made by
+    /// `mount` are refused.
+    pub fn unmount_snapshot(&self, dir: &Path) -> Result<MountedSnapshot> {
+        let marker = dir.join(MOUNT_MARKER);
+        let content = fs::read_to_string(&marker).map_err(|_| {
+            anyhow!(
+                "{} is not a mounted snapshot (no {} in it)",
+                dir.display(),
+                MOUNT_MARKER
+            )
+        })?;
+        let mounted: MountedSnapshot = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse: {}", marker.display()))?;
+        remove_mount(dir)?;
+        log::info!(
+            "Unmounted snapshot {} of '{}' from {}",
+            mounted.snapshot,
+            mounted.game,
+            dir.display()
+        );
+        Ok(mounted)
+    }
+}
+
+fn remove_mount(dir: &Path) -> Result<()> {
+    protect::unprotect(dir, Protection::ReadOnly)?;
+    fs::remove_dir_all(dir)
+        .with_context(|| format!("Failed to remove directory: {}", dir.display()))
+}
@@ -0,0 +1,169 @@
+use anyhow::{Result, anyhow};
+use std::fmt;
+use std::str::FromStr;
+use toml::{Table, Value};
+
+/// Settings that may be spelled two ways, only one of which may be in a table.
+const ALIASES: &[(&str, &str)] = &[("launch", "launch_cmd")];
+
+/// A setting given on the command line with `--set key=value`, applied on top of the config
+/// file for one run. Keys are dotted paths into the configuration; `game.<name>.<key>` and
+/// `var.<name>` address a game and a variable by name, and parts with dots or spaces can be
+/// quoted: `game."Skyrim SE".enabled=false`. The value is read as a TOML value when it is one
+/// (`true`, `5`, `[1, 2]`) and as a plain string otherwise.
+#[derive(Debug, Clone)]
+pub struct ConfigOverride {
+    key: Vec<String>,
+    value: Value,
+    /// The value as given, for settings that are always strings
+    raw: String,
+}
+
+impl FromStr for ConfigOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut key = Vec::new();
+        let mut part = String::new();
+        let mut quoted = false;
+        let mut rest = None;
+        for (i, c) in s.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                '.' if !quoted => key.push(std::mem::take(&mut part)),
+                '=' if !quoted => {
+                    rest = Some(&s[i + 1..]);
+                    break;
+                }
+                c => part.push(c),
+            }
+        }
+        key.push(part);
+
+        let Some(raw) = rest else {
+            return Err(format!("Invalid setting '{}', expected KEY=VALUE", s));
+        };
+        if quoted || key.iter().any(|part| part.trim().is_empty()) {
+            return Err(format!("Invalid key in setting '{}'", s));
+        }
+        let key = key
+            .into_iter()
+            .map(|part| part.trim().to_string())
+            .collect();
+        let value = toml::from_str::<Table>(&format!("value = {}", raw))
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| Value::String(raw.to_string()));
+        Ok(Self {
+            key,
+            value,
+            raw: raw.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for ConfigOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Quoted again where needed, so that the setting can be passed on and parsed back
+        let key: Vec<String> = self
+            .key
+            .iter()
+            .map(|part| {
+                if part.contains(['.', '=']) {
+                    format!("\"{}\"", part)
+                } else {
+                    part.clone()
+                }
+            })
+            .collect();
+        write!(f, "{}={}", key.join("."), self.raw)
+    }
+}
+
+impl ConfigOverride {
+    /// Sets the value in the parsed config file, creating the tables on its way.
+    pub(crate) fn apply(&self, config: &mut Table) -> Result<()> {
+        log::info!("Overriding setting: {}", self);
+        // `locked` keeps a shared machine's backups safe, a run may only add to it
+        if self.key == ["locked"] && self.value != Value::Boolean(true) {
+            return Err(anyhow!(
+                "Cannot set '{}': `locked` can only be set to true on the command line",
+                self
+            ));
+        }
+        let (table, key) = match self.key.as_slice() {
+            [section, name, key @ ..] if section == "game" && !key.is_empty() => {
+                (named_entry(config, "game", name, self)?, key)
+            }
+            [section, name] if section == "var" => {
+                let variable = named_entry(config, "var", name, self)?;
+                variable.insert("value".to_string(), Value::String(self.raw.clone()));
+                return Ok(());
+            }
+            key => (config, key),
+        };
+
+        let (last, parents) = key.split_last().ok_or_else(|| anyhow!("Empty key"))?;
+        let mut table = table;
+        for part in parents {
+            table = table
+                .entry(part.clone())
+                .or_insert_with(|| Value::Table(Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| anyhow!("Cannot set '{}': '{}' is not a table", self, part))?;
+        }
+        for (name, alias) in ALIASES {
+            if last == name {
+                table.remove(*alias);
+            } else if last == alias {
+                table.remove(*name);
+            }
+        }
+        table.insert(last.clone(), self.value.clone());
+        Ok(())
+    }
+}
+
+/// The entry of an array of tables (`[[game]]`, `[[var]]`) with the given name. Variables
+/// that do not exist yet are added; games are not, they need save locations.
+fn named_entry<'a>(
+    config: &'a mut Table,
+    section: &str,
+    name: &str,
+    setting: &ConfigOverride,
+) -> Result<&'a mut Table> {
+    let entries = config
+        .entry(section.to_string())
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or_else(|| anyhow!("Cannot set '{}': `{}` is not a list", setting, section))?;
+    let position = entries.iter().position(|entry| {
+        entry
+            .get("name")
+            .and_then(Value::as_str)
+            .is_some_and(|entry_name| entry_name == name)
+    });
+    let position = match position {
+        Some(position) => position,
+        None if section == "var" => {
+            let mut variable = Table::new();
+            variable.insert("name".to_string(), Value::String(name.to_string()));
+            entries.push(Value::Table(variable));
+            entries.len() - 1
+        }
+        None => {
+            return Err(anyhow!(
+                "Cannot set '{}': game '{}' not found in configuration",
+                setting,
+                name
+            ));
+        }
+    };
+    entries[position].as_table_mut().ok_or_else(|| {
+        anyhow!(
+            "Cannot set '{}': `{}` is not a list of tables",
+            setting,
+            section
+        )
+    })
+}
@@ -0,0 +1,140 @@
+use anyhow::{Context, Result, anyhow};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::plan::{ActionKind, FileAction};
+
+/// The user restored files are handed to with `restore --as-user`.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(unix), allow(dead_code))]
+pub(crate) struct Owner {
+    uid: u32,
+    gid: u32,
+    /// Keep the files to the user alone, for `sensitive` games
+    private: bool,
+}
+
+impl Owner {
+    /// The user who ran cartridge, or who called `sudo` or `pkexec` to run it.
+    pub(crate) fn invoking(private: bool) -> Result<Self> {
+        if cfg!(not(unix)) {
+            // Nothing to hand over, restored files inherit the permissions of their directory
+            return Ok(Self {
+                uid: 0,
+                gid: 0,
+                private,
+            });
+        }
+        let uid = match env_id("SUDO_UID").or_else(|| env_id("PKEXEC_UID")) {
+            Some(uid) => uid,
+            None => id(&["-u"])?,
+        };
+        let gid = match env_id("SUDO_GID") {
+            Some(gid) => gid,
+            None => id(&["-g", &uid.to_string()])?,
+        };
+        log::debug!("Restoring files as uid {} gid {}", uid, gid);
+        Ok(Self { uid, gid, private })
+    }
+
+    /// Hands the files a restore wrote below `location` to the user, together with the
+    /// directories between them and `location`. `created` is the topmost directory the
+    /// restore had to create, if any, whose subdirectories down to `location` are handed
+    /// over as well.
+    pub(crate) fn apply(
+        &self,
+        location: &Path,
+        created: Option<&Path>,
+        actions: &[FileAction],
+    ) -> Result<()> {
+        let mut dirs = HashSet::new();
+        for action in actions {
+            if !matches!(
+                action.kind,
+                ActionKind::Create | ActionKind::Overwrite | ActionKind::BackupExisting { .. }
+            ) {
+                continue;
+            }
+            self.set(&action.dest, false)?;
+            for dir in action.dest.ancestors().skip(1) {
+                if !dir.starts_with(location) || !dirs.insert(dir.to_path_buf()) {
+                    break;
+                }
+                self.set(dir, true)?;
+            }
+        }
+
+        if let Some(created) = created {
+            let mut dir = Some(location.to_path_buf());
+            while let Some(current) = dir
+                && current.starts_with(created)
+            {
+                self.set(&current, true)?;
+                dir = current.parent().map(Path::to_path_buf);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn set(&self, path: &Path, is_dir: bool) -> Result<()> {
+        use std::fs;
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let metadata = fs::symlink_metadata(path)
+            .with_context(|| format!("Failed to read metadata: {}", path.display()))?;
+        if metadata.file_type().is_symlink() {
+            return Ok(());
+        }
+        if metadata.uid() != self.uid || metadata.gid() != self.gid {
+            std::os::unix::fs::chown(path, Some(self.uid), Some(self.gid))
+                .with_context(|| format!("Failed to change owner of: {}", path.display()))?;
+        }
+
+        let mode = match (is_dir, self.private) {
+            (true, false) => 0o755,
+            (true, true) => 0o700,
+            (false, false) => 0o644,
+            (false, true) => 0o600,
+        };
+        // A read-only file stays read-only, e.g. after `on_read_only = "reapply"`
+        let mode = if !is_dir && metadata.mode() & 0o200 == 0 {
+            mode & !0o222
+        } else {
+            mode
+        };
+        if metadata.mode() & 0o7777 != mode {
+            fs::set_permissions(path, fs::Permissions::from_mode(mode))
+                .with_context(|| format!("Failed to change permissions: {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn set(&self, _path: &Path, _is_dir: bool) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn env_id(name: &str) -> Option<u32> {
+    std::env::var(name).ok()?.trim().parse().ok()
+}
+
+/// Runs `id` with the given arguments and parses the number it prints.
+fn id(args: &[&str]) -> Result<u32> {
+    let output = std::process::Command::new("id")
+        .args(args)
+        .output()
+        .with_context(|| "Failed to run id")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "id {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .with_context(|| "Unexpected output of id")
+}
@@ -0,0 +1,130 @@
+use anyhow::{Result, anyhow};
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
+
+/// Directory that stands for the user's home inside backups.
+pub const USER_HOME: &str = "user_home";
+
+/// Replaces every `${name}` in `value` with the value `lookup` finds for it, passed through
+/// `convert`. An unterminated `${` takes the rest of `value` as the name. Values are not
+/// expanded again, so a value containing `${...}` is kept as it is.
+pub fn substitute_variables<'a>(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<&'a OsStr>,
+    convert: impl Fn(&OsStr) -> Result<OsString>,
+) -> Result<OsString> {
+    let mut result = OsString::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        result.push(&rest[..start]);
+        let name_start = &rest[start + 2..];
+        let end = name_start.find('}').unwrap_or(name_start.len());
+        let var_name = &name_start[..end];
+        let var_value =
+            lookup(var_name).ok_or_else(|| anyhow!("Undefined variable: {}", var_name))?;
+        result.push(convert(var_value)?);
+        rest = name_start.get(end + 1..).unwrap_or_default();
+    }
+    result.push(rest);
+    Ok(result)
+}
+
+/// Resolves `.` and `..` in a path as text, without looking at the filesystem. `..` above
+/// the root stays at the root; leading `..` of a relative path are kept.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.last() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(component),
+            },
+            _ => normalized.push(component),
+        }
+    }
+    normalized.iter().collect()
+}
+
+/// Path a Unix save path is stored under inside a backup: below [`USER_HOME`] when it is in
+/// `home`, otherwise the path itself without its root. Only plain names are kept, so the
+/// result never climbs out of the directory it is joined to.
+///
+/// Two save paths map to the same place only when one is below `home` and the other below a
+/// top-level directory named `user_home`.
+pub fn anonymize_unix_path(path: &Path, home: Option<&Path>) -> PathBuf {
+    let path = normalize(path);
+    if let Some(home) = home
+        && let Ok(relative) = path.strip_prefix(normalize(home))
+    {
+        return Path::new(USER_HOME).join(relative);
+    }
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Path a Windows save path, such as `C:\Users\me\Saves`, is stored under inside a backup:
+/// `drive_<letter>` followed by the rest of the path, with `.` and `..` resolved. The path
+/// is read as text with `\` or `/` separators, so it maps the same on every platform. Paths
+/// without a drive letter, such as network shares, have no place and map to `None`.
+///
+/// The user's folder is replaced by `Users\`[`USER_HOME`], when the path is in
+/// `user_profile` and for any other `Users\<name>`, so the saves of every account on the
+/// machine are stored in the same place.
+pub fn anonymize_windows_path(path: &str, user_profile: Option<&str>) -> Option<PathBuf> {
+    let (drive, names) = windows_names(path)?;
+    let mut anonymized = PathBuf::from(format!("drive_{}", drive));
+    let mut rest = names.as_slice();
+    if let Some((profile_drive, profile)) = user_profile.and_then(windows_names)
+        && profile_drive == drive
+        && rest.len() >= profile.len()
+        && rest
+            .iter()
+            .zip(&profile)
+            .all(|(name, profile)| name.to_lowercase() == profile.to_lowercase())
+    {
+        anonymized.push("Users");
+        anonymized.push(USER_HOME);
+        rest = &rest[profile.len()..];
+    }
+    while let Some((name, after)) = rest.split_first() {
+        if name.eq_ignore_ascii_case("Users")
+            && let [_, after @ ..] = after
+        {
+            anonymized.push("Users");
+            anonymized.push(USER_HOME);
+            rest = after;
+        } else {
+            anonymized.push(name);
+            rest = after;
+        }
+    }
+    Some(anonymized)
+}
+
+/// Lowercase drive letter and names of a Windows path as text, with `.` and `..` resolved.
+fn windows_names(path: &str) -> Option<(char, Vec<&str>)> {
+    let mut chars = path.chars();
+    let drive = chars.next().filter(char::is_ascii_alphabetic)?;
+    if chars.next() != Some(':') {
+        return None;
+    }
+    let mut names = Vec::new();
+    for name in chars.as_str().split(['\\', '/']) {
+        match name {
+            "" | "." => {}
+            ".." => {
+                names.pop();
+            }
+            name => names.push(name),
+        }
+    }
+    Some((drive.to_ascii_lowercase(), names))
+}
@@ -0,0 +1,65 @@
+use chrono::TimeDelta;
+use serde::{Deserialize, Deserializer, de};
+use std::fmt;
+use std::str::FromStr;
+
+/// A length of time, written in the configuration as a number with a unit: `"30m"`, `"12h"`,
+/// `"7d"`, `"2w"`. `"0"` is an empty period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Period(pub TimeDelta);
+
+impl Period {
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: i64 = number
+            .parse()
+            .map_err(|_| format!("Invalid period: '{}'", s))?;
+        let delta = match unit.trim() {
+            "" if number == 0 => Some(TimeDelta::zero()),
+            "s" => TimeDelta::try_seconds(number),
+            "m" => TimeDelta::try_minutes(number),
+            "h" => TimeDelta::try_hours(number),
+            "d" => TimeDelta::try_days(number),
+            "w" => TimeDelta::try_weeks(number),
+            _ => {
+                return Err(format!(
+                    "Invalid period '{}', expected a number followed by s, m, h, d or w",
+                    s
+                ));
+            }
+        };
+        delta
+            .map(Period)
+            .ok_or_else(|| format!("Period out of range: '{}'", s))
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0.num_seconds();
+        let (value, unit) = [(604_800, "w"), (86_400, "d"), (3_600, "h"), (60, "m")]
+            .into_iter()
+            .find(|(size, _)| seconds >= *size && seconds % size == 0)
+            .map(|(size, unit)| (seconds / size, unit))
+            .unwrap_or((seconds, "s"));
+        write!(f, "{}{}", value, unit)
+    }
+}
+
+impl<'de> Deserialize<'de> for Period {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,273 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::SaveKind;
+use crate::manifest;
+
+/// What to do when a restore would replace a live file that differs from the backup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    #[default]
+    Overwrite,
+    Skip,
+    BackupExisting,
+    Ask,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "overwrite" => Ok(ConflictPolicy::Overwrite),
+            "skip" => Ok(ConflictPolicy::Skip),
+            "backup-existing" => Ok(ConflictPolicy::BackupExisting),
+            "ask" => Ok(ConflictPolicy::Ask),
+            _ => Err(anyhow!(
+                "Invalid conflict policy '{}' (expected overwrite, skip, backup-existing or ask)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ConflictPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConflictPolicy::Overwrite => "overwrite",
+            ConflictPolicy::Skip => "skip",
+            ConflictPolicy::BackupExisting => "backup-existing",
+            ConflictPolicy::Ask => "ask",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What to do when a restore has to replace a live file that is marked read-only.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReadOnlyPolicy {
+    /// Clear the attribute for the overwrite and set it again afterwards
+    #[default]
+    Reapply,
+    /// Clear the attribute and leave the restored file writable
+    Clear,
+    /// Refuse to overwrite the file
+    Fail,
+}
+
+impl FromStr for ReadOnlyPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "reapply" => Ok(ReadOnlyPolicy::Reapply),
+            "clear" => Ok(ReadOnlyPolicy::Clear),
+            "fail" => Ok(ReadOnlyPolicy::Fail),
+            _ => Err(anyhow!(
+                "Invalid read-only policy '{}' (expected reapply, clear or fail)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ReadOnlyPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ReadOnlyPolicy::Reapply => "reapply",
+            ReadOnlyPolicy::Clear => "clear",
+            ReadOnlyPolicy::Fail => "fail",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RestoreOptions {
+    pub dry_run: bool,
+    pub on_conflict: ConflictPolicy,
+    /// Overrides the `on_read_only` policy from the configuration
+    pub on_read_only: Option<ReadOnlyPolicy>,
+    /// Glob patterns limiting the restore to matching files; empty restores everything
+    pub files: Vec<String>,
+    /// Snapshot to restore instead of the current backup, by id or `last-known-good`
+    pub snapshot: Option<String>,
+    /// Restore the newest snapshot taken on this machine, see `machine` in the config
+    pub from_machine: Option<String>,
+    /// Restore even while the game is running
+    pub force: bool,
+    /// Refuse to fetch more than this many bytes from cold storage
+    pub max_download: Option<u64>,
+    /// Only restore save locations of this kind
+    pub kind: Option<SaveKind>,
+    /// Hand restored files to the user who ran the restore, also through sudo
+    pub as_user: bool,
+    /// Asks what to do with each conflicting file when `on_conflict` is `ask`
+    pub prompt: Option<ConflictPrompt>,
+}
+
+/// Asks what to do with a live file that differs from the backup. It answers with
+/// `Overwrite`, `Skip` or `BackupExisting`.
+#[derive(Clone)]
+pub struct ConflictPrompt(Arc<PromptFn>);
+
+type PromptFn = dyn Fn(&Path) -> Result<ConflictPolicy> + Send + Sync;
+
+impl ConflictPrompt {
+    pub fn new(prompt: impl Fn(&Path) -> Result<ConflictPolicy> + Send + Sync + 'static) -> Self {
+        ConflictPrompt(Arc::new(prompt))
+    }
+}
+
+impl fmt::Debug for ConflictPrompt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ConflictPrompt")
+    }
+}
+
+impl RestoreOptions {
+    pub(crate) fn file_patterns(&self) -> Result<Vec<glob::Pattern>> {
+        self.files
+            .iter()
+            .map(|pattern| {
+                glob::Pattern::new(pattern)
+                    .with_context(|| format!("Invalid file pattern: {}", pattern))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionKind {
+    /// The destination does not exist yet
+    Create,
+    /// The destination exists and differs, and is replaced
+    Overwrite,
+    /// The destination already holds identical content
+    Unchanged,
+    /// The destination differs but is left untouched
+    Skip,
+    /// The destination differs and is moved aside before being replaced
+    BackupExisting { stash: PathBuf },
+    /// The destination differs and the user would be asked what to do
+    Conflict,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileState {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub read_only: bool,
+}
+
+impl FileState {
+    pub fn from_metadata(metadata: &fs::Metadata) -> Self {
+        Self {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            read_only: metadata.permissions().readonly(),
+        }
+    }
+}
+
+/// A single file copy performed, or planned, by a backup or restore.
+#[derive(Debug, Clone)]
+pub struct FileAction {
+    pub source: PathBuf,
+    pub dest: PathBuf,
+    pub kind: ActionKind,
+    pub source_state: FileState,
+    pub dest_state: Option<FileState>,
+}
+
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    pub game: String,
+    pub actions: Vec<FileAction>,
+    /// Machine the restored backup was taken on, when it was recorded
+    pub machine: Option<String>,
+    /// When the restored backup was taken
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Whether a path inside a save location matches one of the patterns. A pattern may match the
+/// path relative to the save location or any trailing part of it, so `*.sav` and `slot1/*.sav`
+/// both select `profiles/slot1/game.sav`.
+pub(crate) fn matches_any(patterns: &[glob::Pattern], path: &Path) -> bool {
+    let components: Vec<String> = manifest::entry_key(path)
+        .split('/')
+        .map(String::from)
+        .collect();
+    (0..components.len()).any(|start| {
+        let suffix = components[start..].join("/");
+        patterns.iter().any(|pattern| pattern.matches(&suffix))
+    })
+}
+
+pub(crate) fn files_identical(
+    source: &Path,
+    dest: &Path,
+    source_metadata: &fs::Metadata,
+    dest_metadata: &fs::Metadata,
+) -> Result<bool> {
+    if source_metadata.len() != dest_metadata.len() {
+        return Ok(false);
+    }
+    Ok(manifest::hash_file(source)? == manifest::hash_file(dest)?)
+}
+
+/// Decides how to handle a live file that differs from the backup copy about to replace it.
+pub(crate) fn resolve_conflict(dest: &Path, options: &RestoreOptions) -> Result<ActionKind> {
+    match options.on_conflict {
+        ConflictPolicy::Overwrite => Ok(ActionKind::Overwrite),
+        ConflictPolicy::Skip => Ok(ActionKind::Skip),
+        ConflictPolicy::BackupExisting => Ok(ActionKind::BackupExisting {
+            stash: stash_path(dest),
+        }),
+        ConflictPolicy::Ask if options.dry_run => Ok(ActionKind::Conflict),
+        ConflictPolicy::Ask => {
+            let prompt = options.prompt.as_ref().ok_or_else(|| {
+                anyhow!(
+                    "Cannot ask about conflicting file {}: no prompt was given, choose another conflict policy",
+                    dest.display()
+                )
+            })?;
+            match (prompt.0)(dest)? {
+                ConflictPolicy::Overwrite => Ok(ActionKind::Overwrite),
+                ConflictPolicy::Skip => Ok(ActionKind::Skip),
+                ConflictPolicy::BackupExisting => Ok(ActionKind::BackupExisting {
+                    stash: stash_path(dest),
+                }),
+                ConflictPolicy::Ask => Err(anyhow!(
+                    "No answer for conflicting file: {}",
+                    dest.display()
+                )),
+            }
+        }
+    }
+}
+
+/// Sibling path an existing live file is moved to before a restore replaces it.
+fn stash_path(dest: &Path) -> PathBuf {
+    let timestamp = chrono::Local::now().format("%Y%m%dT%H%M%S");
+    let file_name = dest
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut stash = dest.with_file_name(format!("{}.{}.bak", file_name, timestamp));
+    let mut suffix = 1;
+    while stash.exists() {
+        suffix += 1;
+        stash = dest.with_file_name(format!("{}.{}-{}.bak", file_name, timestamp, suffix));
+    }
+    stash
+}
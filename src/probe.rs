@@ -0,0 +1,267 @@
+use anyhow::{Context, Result, anyhow};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use toml_edit::{DocumentMut, Item, Value};
+
+use crate::GameBackup;
+use crate::durable;
+
+/// How deep below a watched directory files are looked for.
+const MAX_DEPTH: usize = 6;
+/// Stop scanning a watched directory after this many entries, so probing a large home
+/// directory stays cheap.
+const MAX_ENTRIES: usize = 100_000;
+
+/// A configured save path that does not exist yet, and the directory watched for it.
+#[derive(Debug)]
+pub struct ProbeTarget {
+    /// Position of the save location in the game config
+    pub index: usize,
+    pub configured: PathBuf,
+    /// Nearest existing ancestor of the configured path
+    pub watched: PathBuf,
+}
+
+/// Where the game wrote its first save for a save location.
+#[derive(Debug)]
+pub struct ProbeFinding {
+    pub index: usize,
+    pub configured: PathBuf,
+    /// First file the game wrote
+    pub file: PathBuf,
+    /// Save directory to configure instead: the topmost directory created for the file,
+    /// or the directory it was written into
+    pub found: PathBuf,
+    /// `found` written with the configured variables, as it would go into the config
+    pub suggestion: String,
+}
+
+#[derive(Default)]
+struct Scan {
+    files: HashMap<PathBuf, SystemTime>,
+    dirs: HashSet<PathBuf>,
+}
+
+impl GameBackup {
+    /// Save locations of a game whose path does not exist on this platform yet.
+    pub fn probe_targets(&self, game_name: &str) -> Result<Vec<ProbeTarget>> {
+        let game = self.find_game(game_name)?;
+        let mut targets = Vec::new();
+        for (i, save_location) in game.saves.iter().enumerate() {
+            let Some(path) = self.save_path(save_location)? else {
+                continue;
+            };
+            if path.exists() {
+                continue;
+            }
+            let Some(watched) = path.ancestors().skip(1).find(|dir| dir.is_dir()) else {
+                log::warn!("No existing parent directory for: {}", path.display());
+                continue;
+            };
+            targets.push(ProbeTarget {
+                index: i,
+                configured: path.clone(),
+                watched: watched.to_path_buf(),
+            });
+        }
+        Ok(targets)
+    }
+
+    /// Watches the parents of missing save paths until the game writes a file into each of
+    /// them or `timeout` runs out, polling every `interval`.
+    pub fn probe(
+        &self,
+        targets: &[ProbeTarget],
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Vec<ProbeFinding>> {
+        let mut baselines: Vec<Option<Scan>> = targets
+            .iter()
+            .map(|target| {
+                log::info!("Watching: {}", target.watched.display());
+                Some(scan(&target.watched))
+            })
+            .collect();
+
+        let started = Instant::now();
+        let mut findings = Vec::new();
+        while baselines.iter().any(Option::is_some) && started.elapsed() < timeout {
+            std::thread::sleep(interval);
+
+            for (target, baseline) in targets.iter().zip(baselines.iter_mut()) {
+                let Some(before) = baseline else {
+                    continue;
+                };
+                let after = scan(&target.watched);
+                let Some(file) = first_write(before, &after) else {
+                    continue;
+                };
+
+                let found = file
+                    .ancestors()
+                    .skip(1)
+                    .take_while(|dir| *dir != target.watched)
+                    .filter(|dir| !before.dirs.contains(*dir))
+                    .last()
+                    .or_else(|| file.parent())
+                    .unwrap_or(&target.watched)
+                    .to_path_buf();
+                log::info!("Game wrote {}", file.display());
+                findings.push(ProbeFinding {
+                    index: target.index,
+                    configured: target.configured.clone(),
+                    suggestion: self.contract_variables(&found),
+                    file,
+                    found,
+                });
+                *baseline = None;
+            }
+        }
+
+        Ok(findings)
+    }
+
+    /// Replaces the paths of the probed save locations in the config file for this platform,
+    /// keeping the rest of the file as it is. Returns the number of rewritten paths.
+    pub fn apply_probe_findings(
+        &self,
+        config_path: &Path,
+        game_name: &str,
+        findings: &[ProbeFinding],
+    ) -> Result<usize> {
+        self.ensure_unlocked("rewrite the configuration")?;
+
+        let content = fs::read_to_string(config_path)
+            .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+        let mut document: DocumentMut = content
+            .parse()
+            .with_context(|| "Failed to parse TOML configuration")?;
+
+        let game = document
+            .get_mut("game")
+            .and_then(Item::as_array_of_tables_mut)
+            .and_then(|games| {
+                games
+                    .iter_mut()
+                    .find(|game| game.get("name").and_then(Item::as_str) == Some(game_name))
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "Game '{}' not found in: {}",
+                    game_name,
+                    config_path.display()
+                )
+            })?;
+        let saves = game
+            .get_mut("save")
+            .and_then(Item::as_array_of_tables_mut)
+            .ok_or_else(|| anyhow!("Game '{}' has no save locations", game_name))?;
+
+        let mut rewritten = 0;
+        for finding in findings {
+            let Some(path) = saves
+                .get_mut(finding.index)
+                .and_then(|save| save.get_mut("path"))
+            else {
+                continue;
+            };
+            let item = match path.as_table_like_mut() {
+                Some(paths) => {
+                    let os = std::env::consts::OS;
+                    let key = if paths.contains_key(os) || os == "windows" {
+                        os
+                    } else if paths.contains_key("unix") {
+                        "unix"
+                    } else {
+                        os
+                    };
+                    paths.entry(key).or_insert(toml_edit::value(""))
+                }
+                None => path,
+            };
+            let decor = item.as_value().map(|value| value.decor().clone());
+            let mut value = Value::from(finding.suggestion.as_str());
+            if let Some(decor) = decor {
+                *value.decor_mut() = decor;
+            }
+            *item = Item::Value(value);
+            rewritten += 1;
+        }
+
+        durable::write_atomic(config_path, document.to_string())
+            .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
+        log::info!(
+            "Rewrote {} save paths of '{}' in: {}",
+            rewritten,
+            game_name,
+            config_path.display()
+        );
+        Ok(rewritten)
+    }
+
+    /// Writes a path in terms of the variable with the longest value it starts with.
+    fn contract_variables(&self, path: &Path) -> String {
+        self.variables
+            .iter()
+            .filter(|(_, value)| !value.is_empty() && path.starts_with(value))
+            .max_by_key(|(_, value)| value.len())
+            .and_then(|(name, value)| {
+                let rest = path.strip_prefix(value).ok()?;
+                Some(if rest.as_os_str().is_empty() {
+                    format!("${{{}}}", name)
+                } else {
+                    format!(
+                        "${{{}}}/{}",
+                        name,
+                        rest.to_string_lossy().replace('\\', "/")
+                    )
+                })
+            })
+            .unwrap_or_else(|| path.display().to_string())
+    }
+}
+
+/// The earliest file written since the baseline, new or modified.
+fn first_write(before: &Scan, after: &Scan) -> Option<PathBuf> {
+    after
+        .files
+        .iter()
+        .filter(|(path, modified)| before.files.get(*path) != Some(modified))
+        .min_by_key(|(path, modified)| (**modified, (*path).clone()))
+        .map(|(path, _)| path.clone())
+}
+
+fn scan(dir: &Path) -> Scan {
+    let mut result = Scan::default();
+    let mut pending = vec![(dir.to_path_buf(), 0)];
+    let mut entries_seen = 0;
+    while let Some((dir, depth)) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            entries_seen += 1;
+            if entries_seen > MAX_ENTRIES {
+                log::debug!("Stopped scanning after {} entries", MAX_ENTRIES);
+                return result;
+            }
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if depth < MAX_DEPTH {
+                    pending.push((path.clone(), depth + 1));
+                }
+                result.dirs.insert(path);
+            } else if file_type.is_file()
+                && let Ok(modified) = entry.metadata().and_then(|metadata| metadata.modified())
+            {
+                result.files.insert(path, modified);
+            }
+        }
+    }
+    result
+}
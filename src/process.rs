@@ -0,0 +1,162 @@
+use anyhow::{Context, Result, anyhow};
+use std::io::Read;
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Linux truncates process names in `/proc/<pid>/comm` to this many bytes.
+#[cfg(target_os = "linux")]
+const COMM_LENGTH: usize = 15;
+
+/// Returns the configured process names of a game that are currently running. Failing to
+/// list processes is only logged, so a broken `ps` never blocks a restore on its own.
+pub(crate) fn running(names: &[String]) -> Vec<String> {
+    if names.is_empty() {
+        return Vec::new();
+    }
+
+    let processes = match process_names() {
+        Ok(processes) => processes,
+        Err(e) => {
+            log::warn!("Could not check for running game processes: {:#}", e);
+            return Vec::new();
+        }
+    };
+
+    names
+        .iter()
+        .filter(|name| processes.iter().any(|process| same_process(name, process)))
+        .cloned()
+        .collect()
+}
+
+/// Runs a hook command from the config and returns how it exited and what it printed. It is
+/// stopped once it runs longer than `timeout`, which counts as an error.
+pub(crate) fn run_hook(
+    command: &mut Command,
+    name: &str,
+    timeout: Duration,
+) -> Result<(ExitStatus, Vec<u8>)> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {} {:?}", name, command))?;
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("Failed to capture the output of {}", name))?;
+    let reader = thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!(
+                "{} did not finish within {}s",
+                name,
+                timeout.as_secs()
+            ));
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+    let output = reader
+        .join()
+        .map_err(|_| anyhow!("Failed to read the output of {}", name))?
+        .with_context(|| format!("Failed to read the output of {}", name))?;
+    Ok((status, output))
+}
+
+fn same_process(name: &str, process: &str) -> bool {
+    if cfg!(windows) {
+        return name.eq_ignore_ascii_case(process);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if process.len() == COMM_LENGTH && name.starts_with(process) {
+            return true;
+        }
+    }
+    name == process
+}
+
+#[cfg(target_os = "linux")]
+fn process_names() -> Result<Vec<String>> {
+    let entries = std::fs::read_dir("/proc").with_context(|| "Failed to read /proc")?;
+
+    let mut names = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .chars()
+            .all(|c| c.is_ascii_digit())
+        {
+            continue;
+        }
+
+        // Processes may exit while being listed, so unreadable entries are skipped
+        if let Ok(comm) = std::fs::read_to_string(path.join("comm")) {
+            names.push(comm.trim_end().to_string());
+        }
+        if let Ok(exe) = std::fs::read_link(path.join("exe"))
+            && let Some(file_name) = exe.file_name()
+        {
+            names.push(file_name.to_string_lossy().to_string());
+        }
+    }
+    Ok(names)
+}
+
+#[cfg(windows)]
+fn process_names() -> Result<Vec<String>> {
+    let output = run("tasklist", &["/FO", "CSV", "/NH"])?;
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split(',').next())
+        .map(|name| name.trim_matches('"').to_string())
+        .filter(|name| !name.is_empty())
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn process_names() -> Result<Vec<String>> {
+    use std::path::Path;
+
+    let output = run("ps", &["-A", "-o", "comm="])?;
+    Ok(output
+        .lines()
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
+        .map(|command| {
+            Path::new(command)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| command.to_string())
+        })
+        .collect())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn run(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::GameBackup;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    Backup,
+    Restore,
+}
+
+/// Progress of a backup or restore, reported to the callback set with
+/// [`GameBackup::set_progress`].
+#[derive(Debug)]
+pub enum Progress<'a> {
+    Started {
+        operation: Operation,
+        game: &'a str,
+        /// Files the operation is expected to process, when known up front
+        files: Option<usize>,
+    },
+    /// A file of the game last started was backed up (`path` is the save file) or restored
+    /// (`path` is where it was written)
+    File {
+        operation: Operation,
+        path: &'a Path,
+        bytes: u64,
+    },
+    Finished {
+        operation: Operation,
+        game: &'a str,
+        ok: bool,
+        /// Why the game failed
+        error: Option<&'a anyhow::Error>,
+    },
+}
+
+pub(crate) type ProgressCallback = Box<dyn Fn(&Progress) + Send + Sync>;
+
+impl GameBackup {
+    /// Calls `callback` as games are backed up or restored, e.g. to show a progress bar.
+    pub fn set_progress(&mut self, callback: impl Fn(&Progress) + Send + Sync + 'static) {
+        self.progress = Some(Box::new(callback));
+    }
+
+    pub fn clear_progress(&mut self) {
+        self.progress = None;
+    }
+
+    pub(crate) fn report_progress(&self, progress: Progress) {
+        self.update_status(&progress);
+        if let Some(callback) = &self.progress {
+            callback(&progress);
+        }
+    }
+
+    pub(crate) fn report_file(&self, operation: Operation, path: &Path) {
+        // The status file only counts files, so their size is only read for a callback
+        let bytes = match &self.progress {
+            Some(_) => fs::metadata(path)
+                .map(|metadata| metadata.len())
+                .unwrap_or(0),
+            None => 0,
+        };
+        self.report_progress(Progress::File {
+            operation,
+            path,
+            bytes,
+        });
+    }
+}
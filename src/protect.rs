@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// How completed backups are guarded against edits made outside of cartridge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Protection {
+    #[default]
+    None,
+    /// Files and directories are made read-only
+    ReadOnly,
+    /// Read-only, plus the immutable attribute where the platform supports it
+    Immutable,
+}
+
+/// Applies the protection to every file and directory of a completed backup.
+pub(crate) fn protect(dir: &Path, protection: Protection) -> Result<()> {
+    if protection == Protection::None {
+        return Ok(());
+    }
+
+    log::debug!("Marking backup read-only: {}", dir.display());
+    set_read_only(dir, true)?;
+
+    if protection == Protection::Immutable {
+        set_immutable(dir, true);
+    }
+    Ok(())
+}
+
+/// Lifts the protection of a backup so it can be replaced or removed.
+pub(crate) fn unprotect(dir: &Path, protection: Protection) -> Result<()> {
+    if protection == Protection::Immutable {
+        set_immutable(dir, false);
+    }
+
+    log::debug!("Making backup writable: {}", dir.display());
+    set_read_only(dir, false)
+}
+
+/// Gives the owner write access to a single file.
+pub(crate) fn make_writable(path: &Path) -> Result<()> {
+    let mut permissions = fs::metadata(path)
+        .with_context(|| format!("Failed to read metadata: {}", path.display()))?
+        .permissions();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    #[cfg(not(unix))]
+    {
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+    }
+
+    fs::set_permissions(path, permissions)
+        .with_context(|| format!("Failed to change permissions: {}", path.display()))
+}
+
+fn set_read_only(path: &Path, read_only: bool) -> Result<()> {
+    let metadata = fs::symlink_metadata(path)
+        .with_context(|| format!("Failed to read metadata: {}", path.display()))?;
+    if metadata.file_type().is_symlink() {
+        return Ok(());
+    }
+
+    // Directories are made writable before and read-only after their contents are changed
+    if metadata.is_dir() && !read_only {
+        make_writable(path)?;
+    }
+
+    if metadata.is_dir() {
+        let entries = fs::read_dir(path)
+            .with_context(|| format!("Failed to read directory: {}", path.display()))?;
+        for entry in entries {
+            let entry = entry.with_context(|| {
+                format!("Failed to read directory entry in: {}", path.display())
+            })?;
+            set_read_only(&entry.path(), read_only)?;
+        }
+    }
+
+    if read_only {
+        let mut permissions = metadata.permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(path, permissions)
+            .with_context(|| format!("Failed to change permissions: {}", path.display()))?;
+    } else if !metadata.is_dir() {
+        make_writable(path)?;
+    }
+
+    Ok(())
+}
+
+/// Sets or clears the immutable attribute recursively. This usually needs elevated privileges,
+/// so failures are only logged and the backup stays merely read-only.
+fn set_immutable(dir: &Path, immutable: bool) {
+    let command = if cfg!(target_os = "linux") {
+        Some(("chattr", if immutable { "+i" } else { "-i" }))
+    } else if cfg!(target_os = "macos") {
+        Some(("chflags", if immutable { "uchg" } else { "nouchg" }))
+    } else {
+        None
+    };
+
+    let Some((program, flag)) = command else {
+        log::warn!(
+            "Immutable backups are not supported on {}, using read-only files only",
+            std::env::consts::OS
+        );
+        return;
+    };
+
+    match Command::new(program).arg("-R").arg(flag).arg(dir).output() {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => log::warn!(
+            "Failed to {} immutable attribute on {}: {}",
+            if immutable { "set" } else { "clear" },
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => log::warn!("Failed to run {}: {}", program, e),
+    }
+}
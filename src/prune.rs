@@ -0,0 +1,256 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use crate::manifest::Manifest;
+use crate::snapshot::Snapshot;
+use crate::tier::dir_size;
+use crate::{Game, GameBackup, SaveKind};
+
+/// A kept snapshot selected for deletion by `prune` or `forget`.
+#[derive(Debug)]
+pub struct PruneCandidate {
+    pub game: String,
+    pub id: String,
+    pub path: PathBuf,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Cold storage and validation notes, as shown by `snapshots`
+    pub labels: Vec<String>,
+    pub size: u64,
+}
+
+impl PruneCandidate {
+    fn new(game_name: &str, snapshot: Snapshot) -> Result<Self> {
+        let mut labels = Vec::new();
+        if snapshot.cold {
+            labels.push("cold storage".to_string());
+        }
+        if snapshot.is_suspect() {
+            labels.push("suspect".to_string());
+        }
+        let (_, size) = dir_size(&snapshot.path)?;
+
+        Ok(Self {
+            game: game_name.to_string(),
+            id: snapshot.id,
+            path: snapshot.path,
+            created_at: snapshot.created_at,
+            labels,
+            size,
+        })
+    }
+}
+
+/// How many snapshots of a game `prune` keeps.
+struct Retention {
+    /// Newest snapshots kept for kinds without a setting of their own
+    keep: Option<usize>,
+    /// Distinct versions of each kind's files to keep
+    by_kind: BTreeMap<SaveKind, usize>,
+}
+
+impl GameBackup {
+    /// Snapshots of a game beyond the newest `keep` backups, the current one included.
+    /// Pinned snapshots and snapshots of archived games are never pruned. Without `keep`, the
+    /// game's `keep_snapshots` and `keep_kind` settings are used: a snapshot is kept while any
+    /// kind of save location still needs it.
+    pub fn plan_prune(&self, game_name: &str, keep: Option<usize>) -> Result<Vec<PruneCandidate>> {
+        let game = self.find_game(game_name)?;
+        if game.archived {
+            log::warn!(
+                "Game '{}' is archived, not pruning its snapshots",
+                game.name
+            );
+            return Ok(Vec::new());
+        }
+        let retention = self.retention(game, keep).ok_or_else(|| {
+            anyhow!(
+                "No retention configured for '{}'. Pass --keep or set keep_snapshots",
+                game.name
+            )
+        })?;
+
+        let snapshots: Vec<Snapshot> = self
+            .list_snapshots(&game.name)?
+            .into_iter()
+            .filter(|snapshot| snapshot.pin.is_none())
+            .collect();
+        let retained = retained(game, &retention, &snapshots);
+        snapshots
+            .into_iter()
+            .zip(retained)
+            .filter(|(snapshot, retained)| !retained && !snapshot.current)
+            .map(|(snapshot, _)| PruneCandidate::new(&game.name, snapshot))
+            .collect()
+    }
+
+    /// Snapshots to prune across every game with a retention setting, or all games when
+    /// `keep` is given.
+    pub fn plan_prune_all(&self, keep: Option<usize>) -> Result<Vec<PruneCandidate>> {
+        let mut candidates = Vec::new();
+        for game in &self.config.games {
+            if game.archived {
+                log::debug!("Game '{}' is archived, skipping", game.name);
+                continue;
+            }
+            if self.retention(game, keep).is_none() {
+                log::debug!("No retention configured for '{}', skipping", game.name);
+                continue;
+            }
+            candidates.extend(self.plan_prune(&game.name, keep)?);
+        }
+        Ok(candidates)
+    }
+
+    /// Kept snapshots of a game selected by id. The current backup and pinned snapshots cannot
+    /// be forgotten.
+    pub fn plan_forget(&self, game_name: &str, ids: &[String]) -> Result<Vec<PruneCandidate>> {
+        let game = self.find_game(game_name)?;
+        if game.archived {
+            return Err(anyhow!(
+                "'{}' is archived, its snapshots are kept. Run `cartridge archive-game {} --undo` \
+                 first",
+                game.name,
+                game.name
+            ));
+        }
+        let mut snapshots = self.list_snapshots(&game.name)?;
+
+        let mut candidates = Vec::new();
+        for id in ids {
+            let Some(position) = snapshots.iter().position(|snapshot| &snapshot.id == id) else {
+                return Err(anyhow!(
+                    "No snapshot '{}' found for game '{}'. Run `cartridge snapshots {}` to list them",
+                    id,
+                    game.name,
+                    game.name
+                ));
+            };
+            if snapshots[position].current {
+                return Err(anyhow!(
+                    "Snapshot '{}' is the latest backup of '{}' and cannot be forgotten",
+                    id,
+                    game.name
+                ));
+            }
+            if snapshots[position].pin.is_some() {
+                return Err(anyhow!(
+                    "Snapshot '{}' of '{}' is pinned. Run `cartridge unpin {} {}` first",
+                    id,
+                    game.name,
+                    game.name,
+                    id
+                ));
+            }
+            candidates.push(PruneCandidate::new(&game.name, snapshots.remove(position))?);
+        }
+        Ok(candidates)
+    }
+
+    /// Moves the given snapshots to the trash, or deletes them when there is no grace period,
+    /// after purging snapshots whose grace period is over. Returns the number of bytes they
+    /// take up.
+    pub fn remove_snapshots(&self, candidates: &[PruneCandidate]) -> Result<u64> {
+        self.ensure_unlocked("delete snapshots")?;
+        self.purge_expired_trash();
+
+        let mut reclaimed = 0;
+        for candidate in candidates {
+            log::info!(
+                "Deleting snapshot {} of '{}': {}",
+                candidate.id,
+                candidate.game,
+                candidate.path.display()
+            );
+            self.trash_snapshot(candidate)?;
+            reclaimed += candidate.size;
+        }
+        Ok(reclaimed)
+    }
+
+    /// Retention of a game; `keep` overrides every setting. `None` when nothing is configured.
+    fn retention(&self, game: &Game, keep: Option<usize>) -> Option<Retention> {
+        if keep.is_some() {
+            return Some(Retention {
+                keep,
+                by_kind: BTreeMap::new(),
+            });
+        }
+        let mut by_kind = self.config.keep_kind.clone();
+        by_kind.extend(&game.keep_kind);
+        let keep = game.keep_snapshots.or(self.config.keep_snapshots);
+        (keep.is_some() || !by_kind.is_empty()).then_some(Retention { keep, by_kind })
+    }
+}
+
+/// Whether each snapshot, newest first, is kept by the retention of any kind of the game.
+fn retained(game: &Game, retention: &Retention, snapshots: &[Snapshot]) -> Vec<bool> {
+    let kinds: BTreeSet<SaveKind> = game.saves.iter().map(|save| save.kind).collect();
+    let mut latest: BTreeMap<SaveKind, Vec<(String, String)>> = BTreeMap::new();
+    let mut versions: BTreeMap<SaveKind, usize> = BTreeMap::new();
+
+    snapshots
+        .iter()
+        .enumerate()
+        .map(|(position, snapshot)| {
+            let within_keep = retention.keep.is_none_or(|keep| position < keep.max(1));
+            if kinds.is_empty() {
+                return within_keep;
+            }
+
+            let manifest = Manifest::load(&snapshot.path).ok().flatten();
+            let mut retained = false;
+            for kind in &kinds {
+                let Some(keep) = retention.by_kind.get(kind) else {
+                    retained |= within_keep;
+                    continue;
+                };
+                // A run of snapshots with the same files of a kind is one version of it
+                let files = kind_files(game, *kind, manifest.as_ref());
+                if latest.get(kind) != Some(&files) {
+                    let count = versions.entry(*kind).or_default();
+                    *count += 1;
+                    retained |= *count <= (*keep).max(1);
+                    latest.insert(*kind, files);
+                }
+            }
+            retained
+        })
+        .collect()
+}
+
+/// Files a backup holds for the save locations of a kind, with their hashes, and the mods
+/// recorded for them with their versions.
+fn kind_files(game: &Game, kind: SaveKind, manifest: Option<&Manifest>) -> Vec<(String, String)> {
+    let Some(manifest) = manifest else {
+        return Vec::new();
+    };
+    let indices: Vec<usize> = game
+        .saves
+        .iter()
+        .enumerate()
+        .filter(|(_, save)| save.kind == kind)
+        .map(|(i, _)| i)
+        .collect();
+    let prefixes: Vec<&str> = indices
+        .iter()
+        .filter_map(|i| manifest.locations.get(*i)?.as_deref())
+        .collect();
+    let mods = indices
+        .iter()
+        .flat_map(|i| manifest.mods(*i).unwrap_or_default())
+        .map(|entry| (entry.name, entry.version.unwrap_or_default()));
+    manifest
+        .files
+        .iter()
+        .filter(|(name, _)| {
+            prefixes.iter().any(|prefix| {
+                name.strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/'))
+            })
+        })
+        .map(|(name, entry)| (name.clone(), entry.hash.clone()))
+        .chain(mods)
+        .collect()
+}
@@ -0,0 +1,381 @@
+//! Python bindings, so backups can be scripted without parsing the CLI output.
+//!
+//! ```python
+//! import cartridge
+//!
+//! backup = cartridge.GameBackup("config.toml")
+//! for game in backup.config.games:
+//!     backup.backup(game.name)
+//!     print(backup.verify(game.name).ok)
+//! ```
+
+use chrono::{DateTime, Utc};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::path::Path;
+
+use crate::diff::DiffStatus;
+use crate::plan::{ActionKind, ConflictPolicy, RestoreOptions};
+use crate::{SaveKind, snapshot, verify};
+
+/// The loaded configuration.
+#[pyclass(
+    name = "Config",
+    module = "cartridge",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+#[derive(Clone)]
+struct PyConfig {
+    backup_root: String,
+    locked: bool,
+    keep_snapshots: Option<usize>,
+    /// Enabled and disabled games
+    games: Vec<PyGame>,
+}
+
+#[pyclass(
+    name = "Game",
+    module = "cartridge",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+#[derive(Clone)]
+struct PyGame {
+    name: String,
+    enabled: bool,
+    archived: bool,
+    saves: Vec<PySaveLocation>,
+}
+
+#[pyclass(
+    name = "SaveLocation",
+    module = "cartridge",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+#[derive(Clone)]
+struct PySaveLocation {
+    /// Expanded path on this platform, or None when none is configured for it
+    path: Option<String>,
+    kind: String,
+    files: Vec<String>,
+}
+
+#[pyclass(
+    name = "Snapshot",
+    module = "cartridge",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+#[derive(Clone)]
+struct PySnapshot {
+    id: String,
+    path: String,
+    created_at: Option<DateTime<Utc>>,
+    current: bool,
+    cold: bool,
+    pin: Option<String>,
+    validation_issues: usize,
+}
+
+#[pyclass(
+    name = "VerifyReport",
+    module = "cartridge",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+struct PyVerifyReport {
+    game: String,
+    ok: bool,
+    checked: usize,
+    missing: Vec<String>,
+    corrupted: Vec<String>,
+}
+
+#[pyclass(
+    name = "DiffEntry",
+    module = "cartridge",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+#[derive(Clone)]
+struct PyDiffEntry {
+    path: String,
+    /// `added` (only in the live saves), `modified` or `removed` (only in the backup)
+    status: &'static str,
+    live_newer: bool,
+}
+
+#[pyclass(
+    name = "DiffReport",
+    module = "cartridge",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+struct PyDiffReport {
+    game: String,
+    entries: Vec<PyDiffEntry>,
+    unchanged: usize,
+}
+
+#[pyclass(
+    name = "FileAction",
+    module = "cartridge",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+#[derive(Clone)]
+struct PyFileAction {
+    source: String,
+    dest: String,
+    /// `create`, `overwrite`, `unchanged`, `skip`, `stash` or `ask`
+    action: &'static str,
+    size: u64,
+}
+
+#[pyclass(
+    name = "RestoreReport",
+    module = "cartridge",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+struct PyRestoreReport {
+    game: String,
+    actions: Vec<PyFileAction>,
+}
+
+/// Backs up and restores the games of a configuration file.
+#[pyclass(name = "GameBackup", module = "cartridge", frozen)]
+struct PyGameBackup {
+    inner: crate::GameBackup,
+}
+
+#[pymethods]
+impl PyGameBackup {
+    #[new]
+    fn new(config_path: &str) -> PyResult<Self> {
+        Ok(Self {
+            inner: crate::GameBackup::new(Path::new(config_path)).map_err(error)?,
+        })
+    }
+
+    #[getter]
+    fn config(&self) -> PyResult<PyConfig> {
+        let config = &self.inner.config;
+        let games = config
+            .games
+            .iter()
+            .map(|game| {
+                let saves = game
+                    .saves
+                    .iter()
+                    .map(|save| {
+                        Ok(PySaveLocation {
+                            path: self
+                                .inner
+                                .save_path(save)?
+                                .map(|path| path.display().to_string()),
+                            kind: save.kind.to_string(),
+                            files: save.files.clone(),
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                Ok(PyGame {
+                    name: game.name.clone(),
+                    enabled: game.enabled,
+                    archived: game.archived,
+                    saves,
+                })
+            })
+            .collect::<anyhow::Result<_>>()
+            .map_err(error)?;
+        Ok(PyConfig {
+            backup_root: self.inner.backup_dir().display().to_string(),
+            locked: config.locked,
+            keep_snapshots: config.keep_snapshots,
+            games,
+        })
+    }
+
+    fn has_backup(&self, game: &str) -> bool {
+        self.inner.has_backup(game)
+    }
+
+    /// Backs up a game, or every enabled game. With a `kind`, only save locations of that
+    /// kind are read.
+    #[pyo3(signature = (game=None, *, kind=None))]
+    fn backup(&self, py: Python<'_>, game: Option<&str>, kind: Option<&str>) -> PyResult<()> {
+        let kind = parse::<SaveKind>(kind)?;
+        py.detach(|| match game {
+            Some(game) => self.inner.backup_game(game, kind),
+            None => self.inner.backup_all_games(kind),
+        })
+        .map_err(error)?;
+        Ok(())
+    }
+
+    /// Restores a game, or every enabled game, and returns what was done.
+    #[pyo3(signature = (
+        game=None, *, dry_run=false, on_conflict="overwrite", snapshot=None, files=Vec::new(),
+        force=false, kind=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn restore(
+        &self,
+        py: Python<'_>,
+        game: Option<&str>,
+        dry_run: bool,
+        on_conflict: &str,
+        snapshot: Option<String>,
+        files: Vec<String>,
+        force: bool,
+        kind: Option<&str>,
+    ) -> PyResult<Vec<PyRestoreReport>> {
+        let options = RestoreOptions {
+            dry_run,
+            on_conflict: on_conflict.parse::<ConflictPolicy>().map_err(invalid)?,
+            snapshot,
+            files,
+            force,
+            kind: parse::<SaveKind>(kind)?,
+            ..RestoreOptions::default()
+        };
+        let reports = py
+            .detach(|| match game {
+                Some(game) => self
+                    .inner
+                    .restore_game(game, &options)
+                    .map(|report| vec![report]),
+                None => self.inner.restore_all_games(&options),
+            })
+            .map_err(error)?;
+        Ok(reports.into_iter().map(restore_report).collect())
+    }
+
+    /// Checks the backup of a game against its manifest.
+    fn verify(&self, py: Python<'_>, game: &str) -> PyResult<PyVerifyReport> {
+        let report = py.detach(|| self.inner.verify_game(game)).map_err(error)?;
+        Ok(verify_report(report))
+    }
+
+    /// Compares the live saves of a game with its backup.
+    fn diff(&self, py: Python<'_>, game: &str) -> PyResult<PyDiffReport> {
+        let report = py.detach(|| self.inner.diff_game(game)).map_err(error)?;
+        Ok(PyDiffReport {
+            game: report.game,
+            entries: report
+                .entries
+                .into_iter()
+                .map(|entry| PyDiffEntry {
+                    path: entry.path,
+                    status: match entry.status {
+                        DiffStatus::Added => "added",
+                        DiffStatus::Modified { .. } => "modified",
+                        DiffStatus::Removed => "removed",
+                    },
+                    live_newer: entry.status == DiffStatus::Modified { live_newer: true },
+                })
+                .collect(),
+            unchanged: report.unchanged,
+        })
+    }
+
+    /// Backups kept for a game, newest first.
+    fn snapshots(&self, game: &str) -> PyResult<Vec<PySnapshot>> {
+        let snapshots = self.inner.list_snapshots(game).map_err(error)?;
+        Ok(snapshots
+            .into_iter()
+            .map(|snapshot: snapshot::Snapshot| PySnapshot {
+                id: snapshot.id,
+                path: snapshot.path.display().to_string(),
+                created_at: snapshot.created_at,
+                current: snapshot.current,
+                cold: snapshot.cold,
+                pin: snapshot.pin,
+                validation_issues: snapshot.validation_issues,
+            })
+            .collect())
+    }
+
+    /// Keeps a snapshot regardless of retention, until it is unpinned.
+    #[pyo3(signature = (game, snapshot, reason=None))]
+    fn pin(&self, game: &str, snapshot: &str, reason: Option<&str>) -> PyResult<()> {
+        self.inner
+            .pin_snapshot(game, snapshot, reason)
+            .map_err(error)
+    }
+
+    fn unpin(&self, game: &str, snapshot: &str) -> PyResult<()> {
+        self.inner.unpin_snapshot(game, snapshot).map_err(error)
+    }
+}
+
+fn verify_report(report: verify::VerifyReport) -> PyVerifyReport {
+    PyVerifyReport {
+        ok: report.is_ok(),
+        game: report.game,
+        checked: report.checked,
+        missing: report.missing,
+        corrupted: report.corrupted,
+    }
+}
+
+fn restore_report(report: crate::plan::RestoreReport) -> PyRestoreReport {
+    PyRestoreReport {
+        game: report.game,
+        actions: report
+            .actions
+            .into_iter()
+            .map(|action| PyFileAction {
+                source: action.source.display().to_string(),
+                dest: action.dest.display().to_string(),
+                action: match action.kind {
+                    ActionKind::Create => "create",
+                    ActionKind::Overwrite => "overwrite",
+                    ActionKind::Unchanged => "unchanged",
+                    ActionKind::Skip => "skip",
+                    ActionKind::BackupExisting { .. } => "stash",
+                    ActionKind::Conflict => "ask",
+                },
+                size: action.source_state.size,
+            })
+            .collect(),
+    }
+}
+
+fn error(e: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{:#}", e))
+}
+
+fn invalid(e: anyhow::Error) -> PyErr {
+    PyValueError::new_err(format!("{:#}", e))
+}
+
+fn parse<T: std::str::FromStr<Err = anyhow::Error>>(value: Option<&str>) -> PyResult<Option<T>> {
+    value.map(str::parse).transpose().map_err(invalid)
+}
+
+#[pymodule]
+fn cartridge(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGameBackup>()?;
+    m.add_class::<PyConfig>()?;
+    m.add_class::<PyGame>()?;
+    m.add_class::<PySaveLocation>()?;
+    m.add_class::<PySnapshot>()?;
+    m.add_class::<PyVerifyReport>()?;
+    m.add_class::<PyDiffEntry>()?;
+    m.add_class::<PyDiffReport>()?;
+    m.add_class::<PyFileAction>()?;
+    m.add_class::<PyRestoreReport>()?;
+    Ok(())
+}
@@ -0,0 +1,217 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::durable;
+
+pub const QUARANTINE_DIR: &str = ".quarantine";
+/// Where older versions kept the quarantine area. A game can be named that too, and then its
+/// backups share the directory with the quarantined data.
+const OLD_QUARANTINE_DIR: &str = "quarantine";
+const REASON_FILE: &str = "reason.toml";
+const DATA_DIR: &str = "data";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuarantineReason {
+    pub game: String,
+    pub failed_at: DateTime<Utc>,
+    pub error: String,
+}
+
+#[derive(Debug)]
+pub struct QuarantineEntry {
+    pub id: String,
+    pub reason: QuarantineReason,
+    pub path: PathBuf,
+}
+
+impl QuarantineEntry {
+    pub fn data_dir(&self) -> PathBuf {
+        self.path.join(DATA_DIR)
+    }
+}
+
+/// Moves the partial data of a failed backup into the quarantine area, recording the error.
+pub fn quarantine(
+    backup_root: &Path,
+    game_name: &str,
+    partial_data: &Path,
+    error: &anyhow::Error,
+) -> Result<PathBuf> {
+    migrate(backup_root)?;
+    let failed_at = Utc::now();
+    let base_id = format!("{}-{}", game_name, failed_at.format("%Y%m%dT%H%M%SZ"));
+    let quarantine_root = backup_root.join(QUARANTINE_DIR);
+
+    let mut id = base_id.clone();
+    let mut suffix = 1;
+    while quarantine_root.join(&id).exists() {
+        suffix += 1;
+        id = format!("{}-{}", base_id, suffix);
+    }
+
+    let entry_dir = quarantine_root.join(&id);
+    log::warn!(
+        "Quarantining backup data for '{}' in: {}",
+        game_name,
+        entry_dir.display()
+    );
+
+    fs::create_dir_all(&entry_dir).with_context(|| {
+        format!(
+            "Failed to create quarantine directory: {}",
+            entry_dir.display()
+        )
+    })?;
+
+    if partial_data.exists() {
+        fs::rename(partial_data, entry_dir.join(DATA_DIR)).with_context(|| {
+            format!(
+                "Failed to move partial backup data to quarantine: {}",
+                partial_data.display()
+            )
+        })?;
+    }
+
+    let reason = QuarantineReason {
+        game: game_name.to_string(),
+        failed_at,
+        error: format!("{:#}", error),
+    };
+    let reason_path = entry_dir.join(REASON_FILE);
+    let content =
+        toml::to_string(&reason).with_context(|| "Failed to serialize quarantine reason")?;
+    durable::write_atomic(&reason_path, content).with_context(|| {
+        format!(
+            "Failed to write quarantine reason: {}",
+            reason_path.display()
+        )
+    })?;
+
+    Ok(entry_dir)
+}
+
+pub fn list(backup_root: &Path) -> Result<Vec<QuarantineEntry>> {
+    migrate(backup_root)?;
+    let quarantine_root = backup_root.join(QUARANTINE_DIR);
+    if !quarantine_root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(&quarantine_root).with_context(|| {
+        format!(
+            "Failed to read quarantine directory: {}",
+            quarantine_root.display()
+        )
+    })?;
+
+    let mut quarantined = Vec::new();
+    for entry in entries {
+        let entry = entry.with_context(|| {
+            format!(
+                "Failed to read directory entry in: {}",
+                quarantine_root.display()
+            )
+        })?;
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        match read_reason(&path) {
+            Ok(reason) => quarantined.push(QuarantineEntry {
+                id: entry.file_name().to_string_lossy().to_string(),
+                reason,
+                path,
+            }),
+            Err(e) => log::warn!(
+                "Skipping unreadable quarantine entry {}: {:#}",
+                path.display(),
+                e
+            ),
+        }
+    }
+
+    quarantined.sort_by_key(|entry| entry.reason.failed_at);
+    Ok(quarantined)
+}
+
+pub fn get(backup_root: &Path, id: &str) -> Result<QuarantineEntry> {
+    list(backup_root)?
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| anyhow!("Quarantine entry '{}' not found", id))
+}
+
+pub fn purge(entry: &QuarantineEntry) -> Result<()> {
+    log::info!("Purging quarantine entry: {}", entry.path.display());
+    fs::remove_dir_all(&entry.path).with_context(|| {
+        format!(
+            "Failed to remove quarantine entry: {}",
+            entry.path.display()
+        )
+    })
+}
+
+/// Moves the entries of a quarantine area left by an older version into [`QUARANTINE_DIR`].
+/// Only directories with a reason file are moved, so the backups of a game named like the old
+/// directory stay where they are.
+fn migrate(backup_root: &Path) -> Result<()> {
+    let old_root = backup_root.join(OLD_QUARANTINE_DIR);
+    let Ok(entries) = fs::read_dir(&old_root) else {
+        return Ok(());
+    };
+    let quarantine_root = backup_root.join(QUARANTINE_DIR);
+    for entry in entries {
+        let entry = entry.with_context(|| {
+            format!("Failed to read directory entry in: {}", old_root.display())
+        })?;
+        let path = entry.path();
+        if !path.join(REASON_FILE).is_file() {
+            continue;
+        }
+        let dest = quarantine_root.join(entry.file_name());
+        if dest.exists() {
+            log::warn!(
+                "Leaving old quarantine entry in place, {} already exists: {}",
+                dest.display(),
+                path.display()
+            );
+            continue;
+        }
+        log::info!(
+            "Moving quarantine entry to {}: {}",
+            quarantine_root.display(),
+            path.display()
+        );
+        fs::create_dir_all(&quarantine_root).with_context(|| {
+            format!(
+                "Failed to create quarantine directory: {}",
+                quarantine_root.display()
+            )
+        })?;
+        fs::rename(&path, &dest)
+            .with_context(|| format!("Failed to move quarantine entry: {}", path.display()))?;
+    }
+    // Only removed when nothing else, such as a game's backups, is in it
+    let _ = fs::remove_dir(&old_root);
+    Ok(())
+}
+
+fn read_reason(entry_dir: &Path) -> Result<QuarantineReason> {
+    let reason_path = entry_dir.join(REASON_FILE);
+    let content = fs::read_to_string(&reason_path).with_context(|| {
+        format!(
+            "Failed to read quarantine reason: {}",
+            reason_path.display()
+        )
+    })?;
+    toml::from_str(&content).with_context(|| {
+        format!(
+            "Failed to parse quarantine reason: {}",
+            reason_path.display()
+        )
+    })
+}
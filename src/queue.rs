@@ -0,0 +1,247 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::durable;
+use crate::manifest::Manifest;
+use crate::{Game, GameBackup, SaveItem, process};
+
+/// Backups waiting for `cartridge daemon`, in the backup root.
+pub const QUEUE_FILE: &str = "queue.json";
+/// Held while the queue is rewritten, so the daemon and `queue backup` never lose each
+/// other's changes.
+const QUEUE_LOCK: &str = "queue.lock";
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Who asked for a queued backup. Manual requests are run before watch-triggered ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Priority {
+    /// The saves of a game with `watch = true` changed
+    Watch,
+    /// Requested with `cartridge queue backup`
+    Manual,
+}
+
+/// A backup waiting in the daemon's queue, or being run by it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedBackup {
+    pub game: String,
+    pub priority: Priority,
+    pub requested_at: DateTime<Utc>,
+    /// How many requests for the game were merged into this one
+    pub requests: usize,
+    /// When the daemon started the backup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+impl GameBackup {
+    /// Asks the daemon to back up a game. A request for a game that is already waiting is
+    /// merged into the waiting one, which keeps the higher priority. Returns whether a new
+    /// entry was added.
+    pub fn enqueue_backup(&self, game_name: &str, priority: Priority) -> Result<bool> {
+        let game = self.find_game(game_name)?;
+        if game.archived {
+            return Err(anyhow!("'{}' is archived and not backed up", game.name));
+        }
+        self.update_queue(|queue| {
+            if let Some(pending) = queue
+                .iter_mut()
+                .find(|queued| queued.game == game.name && queued.started_at.is_none())
+            {
+                pending.requests += 1;
+                pending.priority = pending.priority.max(priority);
+                log::debug!(
+                    "Backup of '{}' is already queued, merged {} requests",
+                    game.name,
+                    pending.requests
+                );
+                return false;
+            }
+            queue.push(QueuedBackup {
+                game: game.name.clone(),
+                priority,
+                requested_at: Utc::now(),
+                requests: 1,
+                started_at: None,
+            });
+            log::info!("Queued {:?} backup of '{}'", priority, game.name);
+            true
+        })
+    }
+
+    /// The queue in the order the daemon works through it, the backup it is running first.
+    pub fn backup_queue(&self) -> Result<Vec<QueuedBackup>> {
+        let mut queue = read_queue(&self.queue_path())?;
+        sort_queue(&mut queue);
+        Ok(queue)
+    }
+
+    /// Removes every waiting backup from the queue, and returns how many there were.
+    pub fn clear_queue(&self) -> Result<usize> {
+        self.update_queue(|queue| {
+            let count = queue.len();
+            queue.retain(|queued| queued.started_at.is_some());
+            count - queue.len()
+        })
+    }
+
+    /// Runs the next queued backup, if any, and takes it off the queue once it is done.
+    pub(crate) fn run_next_queued(&self) -> Result<Option<(QueuedBackup, Result<()>)>> {
+        let next = self.update_queue(|queue| {
+            // A backup left started by a daemon that was stopped is run again, unless the game
+            // was queued since anyway
+            let pending: Vec<String> = queue
+                .iter()
+                .filter(|queued| queued.started_at.is_none())
+                .map(|queued| queued.game.clone())
+                .collect();
+            queue.retain(|queued| queued.started_at.is_none() || !pending.contains(&queued.game));
+            for queued in queue.iter_mut() {
+                queued.started_at = None;
+            }
+            sort_queue(queue);
+            let next = queue.first_mut()?;
+            next.started_at = Some(Utc::now());
+            Some(next.clone())
+        })?;
+        let Some(queued) = next else {
+            return Ok(None);
+        };
+
+        log::info!(
+            "Running {:?} backup of '{}' from the queue",
+            queued.priority,
+            queued.game
+        );
+        let result = self.backup_game(&queued.game, None);
+        self.update_queue(|queue| {
+            queue.retain(|other| !(other.game == queued.game && other.started_at.is_some()))
+        })?;
+        Ok(Some((queued, result)))
+    }
+
+    /// Queues a backup of each game with `watch = true` whose saves changed since its last
+    /// backup, unless the game is running and may still be writing them.
+    pub(crate) fn queue_watched_games(&self) {
+        for game in self.list_games() {
+            if !game.watch || game.archived || !process::running(&game.processes).is_empty() {
+                continue;
+            }
+            let changed = self.saves_changed_since_backup(game).and_then(|changed| {
+                if changed {
+                    self.enqueue_backup(&game.name, Priority::Watch)?;
+                }
+                Ok(())
+            });
+            if let Err(e) = changed {
+                log::warn!("Could not check the saves of '{}': {:#}", game.name, e);
+            }
+        }
+    }
+
+    pub(crate) fn has_watched_games(&self) -> bool {
+        self.list_games().iter().any(|game| game.watch)
+    }
+
+    /// Whether a save file of the game was modified after its latest backup was taken.
+    fn saves_changed_since_backup(&self, game: &Game) -> Result<bool> {
+        let backed_up_at =
+            Manifest::load(&self.backup_root.join(&game.name))?.map(|manifest| manifest.created_at);
+        let mut changed = false;
+        for (i, save_location) in game.saves.iter().enumerate() {
+            let Some(save_path) = self.save_path(save_location)? else {
+                continue;
+            };
+            if changed || !save_path.exists() {
+                continue;
+            }
+            let rules = self.ignore_rules(i, save_location, None);
+            self.walk_save_location(
+                save_location,
+                &save_path,
+                Path::new(""),
+                rules,
+                &mut |item| {
+                    if let SaveItem::File { source, .. } = item
+                        && let Ok(modified) = fs::metadata(source).and_then(|m| m.modified())
+                    {
+                        changed |= backed_up_at.is_none_or(|backed_up_at| {
+                            DateTime::<Utc>::from(modified) > backed_up_at
+                        });
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        Ok(changed)
+    }
+
+    fn queue_path(&self) -> PathBuf {
+        self.backup_root.join(QUEUE_FILE)
+    }
+
+    /// Changes the queue while holding its lock.
+    fn update_queue<T>(&self, change: impl FnOnce(&mut Vec<QueuedBackup>) -> T) -> Result<T> {
+        fs::create_dir_all(&self.backup_root).with_context(|| {
+            format!(
+                "Failed to create backup directory: {}",
+                self.backup_root.display()
+            )
+        })?;
+        let lock = self.backup_root.join(QUEUE_LOCK);
+        let started = Instant::now();
+        while OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock)
+            .is_err()
+        {
+            if started.elapsed() >= LOCK_TIMEOUT {
+                return Err(anyhow!(
+                    "The backup queue is locked, remove {} if no cartridge is running",
+                    lock.display()
+                ));
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        let path = self.queue_path();
+        let result = read_queue(&path).and_then(|mut queue| {
+            let value = change(&mut queue);
+            durable::write_atomic(&path, serde_json::to_string_pretty(&queue)?)
+                .with_context(|| format!("Failed to write backup queue: {}", path.display()))?;
+            Ok(value)
+        });
+        if let Err(e) = fs::remove_file(&lock) {
+            log::warn!("Failed to remove {}: {}", lock.display(), e);
+        }
+        result
+    }
+}
+
+/// Running backups first, then manual requests before watch-triggered ones, oldest first.
+fn sort_queue(queue: &mut [QueuedBackup]) {
+    queue.sort_by_key(|queued| {
+        (
+            queued.started_at.is_none(),
+            std::cmp::Reverse(queued.priority),
+            queued.requested_at,
+        )
+    });
+}
+
+fn read_queue(path: &Path) -> Result<Vec<QueuedBackup>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read backup queue: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse backup queue: {}", path.display()))
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::period::Period;
+use crate::{Game, GameBackup, SaveItem, SaveKind};
+
+/// A game played within the period given to `backup --recent`.
+#[derive(Debug, Serialize)]
+pub struct RecentGame {
+    pub name: String,
+    /// End of its last session run through `cartridge run`, or the newest modification time
+    /// of its save files if that is later
+    pub last_played: DateTime<Utc>,
+}
+
+/// What the save files of a game look like on disk right now.
+#[derive(Debug, Default)]
+pub(crate) struct SaveActivity {
+    /// Newest modification time of its save files, `None` when none were found
+    pub last_modified: Option<DateTime<Utc>>,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+impl GameBackup {
+    /// Enabled, unarchived games played within `period`, most recently played first.
+    pub fn recent_games(&self, period: Period) -> Result<Vec<RecentGame>> {
+        let mut last_sessions: HashMap<String, DateTime<Utc>> = HashMap::new();
+        for session in self.session_history(None)? {
+            let last = last_sessions
+                .entry(session.game)
+                .or_insert(session.ended_at);
+            *last = (*last).max(session.ended_at);
+        }
+
+        let since = Utc::now() - period.0;
+        let mut recent = Vec::new();
+        for game in self.list_games() {
+            if game.archived {
+                continue;
+            }
+            let modified = match self.save_activity(game) {
+                Ok(activity) => activity.last_modified,
+                Err(e) => {
+                    log::warn!("Could not check when '{}' was played: {:#}", game.name, e);
+                    None
+                }
+            };
+            let last_played = modified.max(last_sessions.get(&game.name).copied());
+            match last_played {
+                Some(last_played) if last_played >= since => recent.push(RecentGame {
+                    name: game.name.clone(),
+                    last_played,
+                }),
+                _ => log::info!("'{}' was not played within {}, skipping", game.name, period),
+            }
+        }
+        recent.sort_by_key(|game| Reverse(game.last_played));
+        Ok(recent)
+    }
+
+    /// Backs up the games played within `period`, most recently played first, and returns
+    /// them.
+    pub fn backup_recent_games(
+        &self,
+        kind: Option<SaveKind>,
+        period: Period,
+    ) -> Result<Vec<RecentGame>> {
+        let recent = self.recent_games(period)?;
+        let games = recent
+            .iter()
+            .map(|recent| self.find_game(&recent.name))
+            .collect::<Result<Vec<_>>>()?;
+        if games.is_empty() {
+            log::info!("No games were played within {}", period);
+            return Ok(recent);
+        }
+        log::info!("Backing up {} games played within {}", games.len(), period);
+        self.backup_games(&games, kind)?;
+        Ok(recent)
+    }
+
+    /// Newest modification time and total size of a game's save files. Locations whose path
+    /// does not exist are skipped.
+    pub(crate) fn save_activity(&self, game: &Game) -> Result<SaveActivity> {
+        let mut activity = SaveActivity::default();
+        for (i, location) in game.saves.iter().enumerate() {
+            let Some(source_path) = self.save_path(location)? else {
+                continue;
+            };
+            if !source_path.exists() {
+                continue;
+            }
+            let rules = self.ignore_rules(i, location, None);
+            self.walk_save_location(location, &source_path, Path::new(""), rules, &mut |item| {
+                if let SaveItem::File { source, .. } = item
+                    && let Ok(metadata) = fs::metadata(source)
+                {
+                    activity.files += 1;
+                    activity.bytes += metadata.len();
+                    let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+                    activity.last_modified = activity.last_modified.max(modified);
+                }
+                Ok(())
+            })?;
+        }
+        Ok(activity)
+    }
+}
@@ -0,0 +1,200 @@
+use anyhow::{Context, Result, anyhow};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::diff::{DiffEntry, DiffReport, DiffStatus};
+use crate::manifest::{self, Manifest};
+use crate::plan::{ConflictPolicy, RestoreOptions};
+use crate::{GameBackup, STAGING_DIR, SaveItem};
+
+impl GameBackup {
+    /// Restores a game into a sandbox directory and compares the result with the live saves.
+    ///
+    /// Unlike a dry run this goes through the full restore path, including archive extraction,
+    /// without touching the live save directories.
+    pub fn rehearse_restore(&self, game_name: &str) -> Result<DiffReport> {
+        log::info!("Rehearsing restore for game: {}", game_name);
+
+        let game = self.find_game(game_name)?;
+        let game_backup_dir = self.backup_root.join(&game.name);
+        if !game_backup_dir.exists() {
+            return Err(anyhow!("No backup found for game: {}", game_name));
+        }
+
+        let sandbox = self
+            .backup_root
+            .join(STAGING_DIR)
+            .join(format!("{}.rehearse", game.name));
+        if sandbox.exists() {
+            fs::remove_dir_all(&sandbox).with_context(|| {
+                format!("Failed to clean rehearsal directory: {}", sandbox.display())
+            })?;
+        }
+        fs::create_dir_all(&sandbox).with_context(|| {
+            format!(
+                "Failed to create rehearsal directory: {}",
+                sandbox.display()
+            )
+        })?;
+
+        let options = RestoreOptions {
+            dry_run: false,
+            on_conflict: ConflictPolicy::Overwrite,
+            ..Default::default()
+        };
+        let result = self
+            .restore_locations(
+                game,
+                &game_backup_dir,
+                &options,
+                Some(&sandbox),
+                &mut Vec::new(),
+            )
+            .and_then(|()| {
+                let manifest = Manifest::load(&game_backup_dir)?;
+                let mut report = DiffReport {
+                    game: game.name.clone(),
+                    entries: Vec::new(),
+                    unchanged: 0,
+                };
+                for (i, save_location) in game.saves.iter().enumerate() {
+                    let Some(live_path) = self.save_path(save_location)? else {
+                        continue;
+                    };
+                    let restored = sandbox.join(i.to_string());
+                    if !restored.exists() {
+                        continue;
+                    }
+
+                    let mut live_files = BTreeMap::new();
+                    if live_path.exists() {
+                        self.walk_save_location(
+                            save_location,
+                            &live_path,
+                            Path::new(""),
+                            self.ignore_rules(i, save_location, manifest.as_ref()),
+                            &mut |item| {
+                                if let SaveItem::File { source, name } = item {
+                                    live_files.insert(name.to_path_buf(), source.to_path_buf());
+                                }
+                                Ok(())
+                            },
+                        )?;
+                    }
+
+                    let prefix = self.backup_prefix(i, &live_path, manifest.as_ref())?;
+                    compare_restored(
+                        &restored,
+                        &live_path,
+                        live_files,
+                        |name| {
+                            manifest
+                                .as_ref()
+                                .and_then(|manifest| manifest.get(&prefix.join(name)))
+                                .map(|entry| entry.modified)
+                        },
+                        &mut report,
+                    )?;
+                }
+                Ok(report)
+            });
+
+        if let Err(e) = fs::remove_dir_all(&sandbox) {
+            log::warn!(
+                "Failed to remove rehearsal directory {}: {}",
+                sandbox.display(),
+                e
+            );
+        }
+
+        let mut report = result?;
+        report.entries.sort_by(|a, b| a.path.cmp(&b.path));
+        log::info!(
+            "Rehearsal found {} differences and {} unchanged files for '{}'",
+            report.entries.len(),
+            report.unchanged,
+            report.game
+        );
+        Ok(report)
+    }
+}
+
+/// Compares the files restored into `restored` with the live files of the same save location.
+/// `backed_up_at` gives the modification time a file had when it was backed up, if known.
+fn compare_restored(
+    restored: &Path,
+    live_path: &Path,
+    mut live_files: BTreeMap<PathBuf, PathBuf>,
+    backed_up_at: impl Fn(&Path) -> Option<SystemTime>,
+    report: &mut DiffReport,
+) -> Result<()> {
+    let mut restored_files = Vec::new();
+    collect_files(restored, Path::new(""), &mut restored_files)?;
+
+    for name in restored_files {
+        let restored_file = restored.join(&name);
+        let live_file = live_files
+            .remove(&name)
+            .unwrap_or_else(|| live_path.join(&name));
+
+        let status = match fs::metadata(&live_file) {
+            Err(_) => Some(DiffStatus::Removed),
+            Ok(live_metadata) => {
+                let restored_metadata = fs::metadata(&restored_file).with_context(|| {
+                    format!("Failed to read metadata: {}", restored_file.display())
+                })?;
+                if live_metadata.len() == restored_metadata.len()
+                    && manifest::hash_file(&live_file)? == manifest::hash_file(&restored_file)?
+                {
+                    None
+                } else {
+                    // Restored copies carry the time of the restore, so use the backup's record
+                    let live_newer = match (live_metadata.modified(), backed_up_at(&name)) {
+                        (Ok(live), Some(backed_up)) => live > backed_up,
+                        _ => false,
+                    };
+                    Some(DiffStatus::Modified { live_newer })
+                }
+            }
+        };
+
+        match status {
+            Some(status) => report.entries.push(DiffEntry {
+                path: live_file.display().to_string(),
+                live_path: Some(live_file),
+                status,
+            }),
+            None => report.unchanged += 1,
+        }
+    }
+
+    // Whatever is left only exists in the live saves and is not touched by a restore
+    for live_file in live_files.into_values() {
+        report.entries.push(DiffEntry {
+            path: live_file.display().to_string(),
+            live_path: Some(live_file),
+            status: DiffStatus::Added,
+        });
+    }
+
+    Ok(())
+}
+
+fn collect_files(dir: &Path, name: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    for entry in entries {
+        let entry = entry
+            .with_context(|| format!("Failed to read directory entry in: {}", dir.display()))?;
+        let path = entry.path();
+        let entry_name = name.join(entry.file_name());
+        if path.is_dir() {
+            collect_files(&path, &entry_name, files)?;
+        } else {
+            files.push(entry_name);
+        }
+    }
+    Ok(())
+}
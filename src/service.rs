@@ -0,0 +1,306 @@
+use anyhow::{Context, Result, anyhow};
+use std::ffi::{OsStr, OsString};
+use std::os::windows::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use windows_service::service::{
+    ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+    ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+};
+use windows_service::service_control_handler::{
+    self, ServiceControlHandlerResult, ServiceStatusHandle,
+};
+use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+use windows_service::{define_windows_service, service_dispatcher};
+use windows_sys::Win32::Foundation::HANDLE;
+use windows_sys::Win32::System::EventLog::{
+    DeregisterEventSource, EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE, EVENTLOG_WARNING_TYPE,
+    RegisterEventSourceW, ReportEventW,
+};
+
+use crate::{ConfigOverride, DaemonEvent, GameBackup, RESERVED_VARIABLES};
+
+/// Name of the service, and the source of its entries in the event log.
+pub const SERVICE_NAME: &str = "cartridge";
+const DISPLAY_NAME: &str = "cartridge backup daemon";
+const SERVICE_TYPE: ServiceType = ServiceType::OWN_PROCESS;
+/// How long stopping may take: the backup or verification in progress is finished first.
+const STOP_WAIT_HINT: Duration = Duration::from_secs(120);
+
+/// Configuration the service runs with, handed from [`run_service`] to the thread the service
+/// control manager starts it on.
+static SERVICE_CONFIG: OnceLock<(PathBuf, Vec<ConfigOverride>)> = OnceLock::new();
+
+impl GameBackup {
+    /// Registers `cartridge daemon` as a Windows service that starts with the system, running
+    /// as LocalSystem with the config at `config_path`, and starts it. The system variables
+    /// detected for the installing user are recorded with it, so save paths in their profile
+    /// resolve to the same place under the service account.
+    pub fn install_service(&self, config_path: &Path, overrides: &[ConfigOverride]) -> Result<()> {
+        let config_path = std::path::absolute(config_path)
+            .with_context(|| format!("Failed to resolve: {}", config_path.display()))?;
+        let mut arguments: Vec<OsString> = vec!["--config".into(), config_path.into_os_string()];
+        for setting in overrides {
+            arguments.push("--set".into());
+            arguments.push(setting.to_string().into());
+        }
+        for name in RESERVED_VARIABLES {
+            let Some(value) = self.variables.get(*name) else {
+                continue;
+            };
+            let Some(value) = value.to_str() else {
+                log::warn!(
+                    "System variable '{}' is not valid Unicode, the service detects it itself",
+                    name
+                );
+                continue;
+            };
+            arguments.push("--set".into());
+            arguments.push(format!("system_vars.{}={}", name, value).into());
+        }
+        arguments.extend(["daemon".into(), "run-service".into()]);
+
+        let manager = ServiceManager::local_computer(
+            None::<&str>,
+            ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE,
+        )
+        .context("Failed to connect to the service control manager. Run as administrator")?;
+        let info = ServiceInfo {
+            name: SERVICE_NAME.into(),
+            display_name: DISPLAY_NAME.into(),
+            service_type: SERVICE_TYPE,
+            start_type: ServiceStartType::AutoStart,
+            error_control: ServiceErrorControl::Normal,
+            executable_path: std::env::current_exe()
+                .context("Failed to find the cartridge executable")?,
+            launch_arguments: arguments,
+            dependencies: Vec::new(),
+            account_name: None,
+            account_password: None,
+        };
+        let service = manager
+            .create_service(&info, ServiceAccess::CHANGE_CONFIG | ServiceAccess::START)
+            .with_context(|| format!("Failed to install service '{}'", SERVICE_NAME))?;
+        service
+            .set_description(
+                "Runs queued and watched backups of game saves and verifies old snapshots",
+            )
+            .with_context(|| format!("Failed to describe service '{}'", SERVICE_NAME))?;
+        log::info!("Installed service '{}'", SERVICE_NAME);
+        service
+            .start::<&OsStr>(&[])
+            .with_context(|| format!("Failed to start service '{}'", SERVICE_NAME))
+    }
+}
+
+/// Stops the service and removes it.
+pub fn uninstall_service() -> Result<()> {
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)
+        .context("Failed to connect to the service control manager. Run as administrator")?;
+    let service = manager
+        .open_service(
+            SERVICE_NAME,
+            ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE,
+        )
+        .with_context(|| format!("Failed to open service '{}'", SERVICE_NAME))?;
+    // Deletion takes effect once the service has stopped
+    service
+        .delete()
+        .with_context(|| format!("Failed to remove service '{}'", SERVICE_NAME))?;
+    let state = service
+        .query_status()
+        .with_context(|| format!("Failed to query service '{}'", SERVICE_NAME))?
+        .current_state;
+    if state != ServiceState::Stopped && state != ServiceState::StopPending {
+        service
+            .stop()
+            .with_context(|| format!("Failed to stop service '{}'", SERVICE_NAME))?;
+    }
+    log::info!("Removed service '{}'", SERVICE_NAME);
+    Ok(())
+}
+
+/// Hands the process to the service control manager, which runs the daemon with the given
+/// config until the service is stopped. Only works in a process started as the service.
+pub fn run_service(config_path: &Path, overrides: Vec<ConfigOverride>) -> Result<()> {
+    SERVICE_CONFIG
+        .set((config_path.to_path_buf(), overrides))
+        .map_err(|_| anyhow!("The service is already running in this process"))?;
+    service_dispatcher::start(SERVICE_NAME, ffi_service_main).context(
+        "Failed to connect to the service control manager. `daemon run-service` is started by \
+         the service, use `cartridge daemon` to run the daemon in a console",
+    )
+}
+
+define_windows_service!(ffi_service_main, service_main);
+
+fn service_main(_arguments: Vec<OsString>) {
+    if let Err(e) = serve() {
+        log::error!("Service failed: {:#}", e);
+    }
+}
+
+fn serve() -> Result<()> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let handle: Arc<OnceLock<ServiceStatusHandle>> = Arc::new(OnceLock::new());
+    let handler = {
+        let stop = Arc::clone(&stop);
+        let handle = Arc::clone(&handle);
+        move |control| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                log::info!("Stopping service '{}'", SERVICE_NAME);
+                stop.store(true, Ordering::Relaxed);
+                if let Some(handle) = handle.get() {
+                    set_state(
+                        *handle,
+                        ServiceState::StopPending,
+                        ServiceExitCode::Win32(0),
+                    );
+                }
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        }
+    };
+    let status = service_control_handler::register(SERVICE_NAME, handler)
+        .context("Failed to register the service control handler")?;
+    let _ = handle.set(status);
+    set_state(status, ServiceState::Running, ServiceExitCode::Win32(0));
+
+    let result = SERVICE_CONFIG
+        .get()
+        .ok_or_else(|| anyhow!("The service was started without a configuration"))
+        .and_then(|(config_path, overrides)| GameBackup::with_overrides(config_path, overrides))
+        .and_then(|game_backup| game_backup.run_daemon_until(&stop, log_event));
+    let exit_code = match &result {
+        Ok(()) => ServiceExitCode::Win32(0),
+        Err(_) => ServiceExitCode::ServiceSpecific(1),
+    };
+    set_state(status, ServiceState::Stopped, exit_code);
+    result
+}
+
+fn set_state(handle: ServiceStatusHandle, state: ServiceState, exit_code: ServiceExitCode) {
+    let status = ServiceStatus {
+        service_type: SERVICE_TYPE,
+        current_state: state,
+        controls_accepted: match state {
+            ServiceState::Running => ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN,
+            _ => ServiceControlAccept::empty(),
+        },
+        exit_code,
+        checkpoint: 0,
+        wait_hint: match state {
+            ServiceState::StopPending => STOP_WAIT_HINT,
+            _ => Duration::ZERO,
+        },
+        process_id: None,
+    };
+    if let Err(e) = handle.set_service_status(status) {
+        log::error!("Failed to report service state {:?}: {}", state, e);
+    }
+}
+
+/// The service has no console, so what `cartridge daemon` prints goes to the event log.
+fn log_event(event: &DaemonEvent) {
+    match event {
+        DaemonEvent::Verified(verification) if verification.report.is_ok() => log::info!(
+            "Verified snapshot {} of '{}' ({} files)",
+            verification.snapshot,
+            verification.report.game,
+            verification.report.checked
+        ),
+        DaemonEvent::Verified(verification) => log::error!(
+            "Snapshot {} of '{}' failed verification, run `cartridge verify {}` for details",
+            verification.snapshot,
+            verification.report.game,
+            verification.report.game
+        ),
+        DaemonEvent::BackedUp {
+            queued,
+            error: None,
+        } => log::info!("Backed up '{}'", queued.game),
+        DaemonEvent::BackedUp {
+            queued,
+            error: Some(error),
+        } => log::error!("Backup of '{}' failed: {}", queued.game, error),
+    }
+}
+
+/// Writes log records to the Windows event log, for the daemon running as a service.
+pub struct EventLog {
+    handle: HANDLE,
+    level: log::LevelFilter,
+}
+
+// Event log handles can be used from any thread
+unsafe impl Send for EventLog {}
+unsafe impl Sync for EventLog {}
+
+impl EventLog {
+    pub fn open(level: log::LevelFilter) -> Result<Self> {
+        let source = wide(SERVICE_NAME);
+        // SAFETY: `source` is a null-terminated UTF-16 string that outlives the call
+        let handle = unsafe { RegisterEventSourceW(std::ptr::null(), source.as_ptr()) };
+        if handle.is_null() {
+            return Err(std::io::Error::last_os_error()).context("Failed to open the event log");
+        }
+        Ok(Self { handle, level })
+    }
+}
+
+impl log::Log for EventLog {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let kind = match record.level() {
+            log::Level::Error => EVENTLOG_ERROR_TYPE,
+            log::Level::Warn => EVENTLOG_WARNING_TYPE,
+            _ => EVENTLOG_INFORMATION_TYPE,
+        };
+        let message = wide(&record.args().to_string());
+        let strings = [message.as_ptr()];
+        // SAFETY: the handle is open until drop, and `strings` holds one null-terminated
+        // UTF-16 string that outlives the call
+        unsafe {
+            ReportEventW(
+                self.handle,
+                kind,
+                0,
+                0,
+                std::ptr::null_mut(),
+                1,
+                0,
+                strings.as_ptr(),
+                std::ptr::null(),
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+impl Drop for EventLog {
+    fn drop(&mut self) {
+        // SAFETY: the handle was opened by `RegisterEventSourceW` and is not used afterwards
+        unsafe {
+            DeregisterEventSource(self.handle);
+        }
+    }
+}
+
+/// A null-terminated UTF-16 copy of `s`, for Windows APIs.
+fn wide(s: &str) -> Vec<u16> {
+    OsStr::new(s)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect()
+}
@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::GameBackup;
+use crate::durable;
+use crate::manifest::Manifest;
+
+/// Append-only record of the play sessions run through `cartridge run`, one JSON object per
+/// line, kept in the backup root.
+pub const SESSIONS_FILE: &str = "sessions.jsonl";
+
+/// A play session and how much it changed the saves, measured against the backup taken
+/// before it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Session {
+    pub game: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Save files that were added or changed
+    pub files_changed: usize,
+    pub files_removed: usize,
+    /// Size of the added and changed files
+    pub bytes_changed: u64,
+    /// Size of all saves after the session
+    pub total_bytes: u64,
+    /// Whether there was no backup before the session, so every file counts as changed
+    #[serde(default)]
+    pub first: bool,
+}
+
+impl Session {
+    pub fn duration(&self) -> TimeDelta {
+        self.ended_at - self.started_at
+    }
+
+    fn new(
+        game: &str,
+        started_at: DateTime<Utc>,
+        ended_at: DateTime<Utc>,
+        before: Option<&Manifest>,
+        after: &Manifest,
+    ) -> Self {
+        let mut session = Self {
+            game: game.to_string(),
+            started_at,
+            ended_at,
+            files_changed: 0,
+            files_removed: 0,
+            bytes_changed: 0,
+            total_bytes: 0,
+            first: before.is_none(),
+        };
+        for (key, entry) in &after.files {
+            session.total_bytes += entry.size;
+            let previous = before.and_then(|before| before.files.get(key));
+            if previous.is_none_or(|previous| previous.hash != entry.hash) {
+                session.files_changed += 1;
+                session.bytes_changed += entry.size;
+            }
+        }
+        if let Some(before) = before {
+            session.files_removed = before
+                .files
+                .keys()
+                .filter(|key| !after.files.contains_key(*key))
+                .count();
+        }
+        session
+    }
+}
+
+impl GameBackup {
+    /// Runs a game, backs it up once it exits and records how much the session changed its
+    /// saves.
+    pub fn run_session(&self, game_name: &str) -> Result<Session> {
+        let game = self.find_game(game_name)?;
+        let game_backup_dir = self.backup_root.join(&game.name);
+        let before = Manifest::load(&game_backup_dir).ok().flatten();
+
+        let started_at = Utc::now();
+        self.run_game(&game.name)?;
+        let ended_at = Utc::now();
+        self.backup_game(&game.name, None)?;
+
+        let after = Manifest::load(&game_backup_dir)?.unwrap_or_else(Manifest::new);
+        let session = Session::new(&game.name, started_at, ended_at, before.as_ref(), &after);
+        if let Err(e) = append(&self.backup_root, &session) {
+            log::error!(
+                "Failed to record the session of '{}' in the session log: {:#}",
+                game.name,
+                e
+            );
+        }
+        Ok(session)
+    }
+
+    /// Recorded play sessions, oldest first, optionally of one game only.
+    pub fn session_history(&self, game_name: Option<&str>) -> Result<Vec<Session>> {
+        if let Some(game_name) = game_name {
+            self.find_game(game_name)?;
+        }
+        Ok(load(&self.backup_root)?
+            .into_iter()
+            .filter(|session| game_name.is_none_or(|game_name| session.game == game_name))
+            .collect())
+    }
+}
+
+fn append(backup_root: &Path, session: &Session) -> Result<()> {
+    let path = backup_root.join(SESSIONS_FILE);
+    let line = serde_json::to_string(session).with_context(|| "Failed to serialize session")?;
+
+    durable::append_line(&path, &line)
+        .with_context(|| format!("Failed to write session log: {}", path.display()))
+}
+
+/// Reads the session log, skipping lines that cannot be parsed.
+fn load(backup_root: &Path) -> Result<Vec<Session>> {
+    let path = backup_root.join(SESSIONS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read session log: {}", path.display()))?;
+
+    let mut sessions = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(session) => sessions.push(session),
+            Err(e) => log::warn!(
+                "Skipping unreadable line {} of {}: {}",
+                i + 1,
+                path.display(),
+                e
+            ),
+        }
+    }
+    Ok(sessions)
+}
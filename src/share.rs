@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+use crate::infer::is_absolute;
+use crate::{GameBackup, RESERVED_VARIABLES};
+
+/// Top-level settings that only make sense on the machine the config was written on.
+const MACHINE_KEYS: &[&str] = &["backup_root", "cold_storage", "machine", "system_vars"];
+
+/// Key names that hold credentials, matched case-insensitively anywhere in a key.
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "passphrase",
+    "secret",
+    "token",
+    "credential",
+    "api_key",
+    "access_key",
+    "private_key",
+];
+
+impl GameBackup {
+    /// A copy of the config file that can be shared with others: machine-specific settings and
+    /// credentials are removed, paths below built-in variables such as the home directory are
+    /// written with those variables, and variables holding other absolute paths get a
+    /// placeholder value.
+    pub fn shareable_config(&self, config_path: &Path) -> Result<String> {
+        let content = fs::read_to_string(config_path)
+            .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+        let mut document: DocumentMut = content
+            .parse()
+            .with_context(|| "Failed to parse TOML configuration")?;
+
+        let mut removed = Vec::new();
+        for key in MACHINE_KEYS {
+            if document.remove(key).is_some() {
+                removed.push(key.to_string());
+            }
+        }
+        strip_secrets(document.as_item_mut(), "", &mut removed);
+
+        let builtins: Vec<(&str, &str)> = RESERVED_VARIABLES
+            .iter()
+            .filter_map(|name| {
+                let value = self.variables.get(*name)?.to_str()?;
+                (!value.is_empty()).then_some((*name, value))
+            })
+            .collect();
+        contract_strings(document.as_item_mut(), &builtins);
+
+        let mut parameterized = Vec::new();
+        if let Some(variables) = document
+            .get_mut("var")
+            .and_then(Item::as_array_of_tables_mut)
+        {
+            for variable in variables.iter_mut() {
+                let Some(name) = variable.get("name").and_then(Item::as_str) else {
+                    continue;
+                };
+                let name = name.to_string();
+                let Some(value) = variable.get_mut("value") else {
+                    continue;
+                };
+                if let Some(value) = value.as_value_mut()
+                    && value.as_str().is_some_and(is_absolute)
+                {
+                    let decor = value.decor().clone();
+                    *value = Value::from(format!("/path/to/{}", name));
+                    *value.decor_mut() = decor;
+                    parameterized.push(name);
+                }
+            }
+        }
+
+        let mut header = String::from("# Shared cartridge configuration\n");
+        if !removed.is_empty() {
+            header.push_str(&format!(
+                "# Removed machine-specific settings: {}\n",
+                removed.join(", ")
+            ));
+        }
+        if !parameterized.is_empty() {
+            header.push_str(&format!(
+                "# Set these variables for your machine: {}\n",
+                parameterized.join(", ")
+            ));
+        }
+        header.push('\n');
+
+        log::info!(
+            "Prepared shareable config: {} settings removed, {} variables parameterized",
+            removed.len(),
+            parameterized.len()
+        );
+        Ok(header + &document.to_string())
+    }
+}
+
+fn is_secret(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SECRET_KEYS.iter().any(|secret| key.contains(secret))
+}
+
+fn strip_secrets(item: &mut Item, path: &str, removed: &mut Vec<String>) {
+    if let Some(table) = item.as_table_like_mut() {
+        strip_table_secrets(table, path, removed);
+    } else if let Some(tables) = item.as_array_of_tables_mut() {
+        for table in tables.iter_mut() {
+            strip_table_secrets(table, path, removed);
+        }
+    }
+}
+
+fn strip_table_secrets(table: &mut dyn TableLike, path: &str, removed: &mut Vec<String>) {
+    let secrets: Vec<String> = table
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| is_secret(key))
+        .collect();
+    for key in secrets {
+        table.remove(&key);
+        removed.push(format!("{}{}", path, key));
+    }
+    for (key, child) in table.iter_mut() {
+        strip_secrets(child, &format!("{}{}.", path, key), removed);
+    }
+}
+
+/// Rewrites every string starting with the value of a built-in variable to use the variable.
+fn contract_strings(item: &mut Item, builtins: &[(&str, &str)]) {
+    if let Some(table) = item.as_table_like_mut() {
+        for (_, child) in table.iter_mut() {
+            contract_strings(child, builtins);
+        }
+    } else if let Some(tables) = item.as_array_of_tables_mut() {
+        for (_, child) in tables.iter_mut().flat_map(|table| table.iter_mut()) {
+            contract_strings(child, builtins);
+        }
+    } else if let Some(value) = item.as_value_mut() {
+        contract_value(value, builtins);
+    }
+}
+
+fn contract_value(value: &mut Value, builtins: &[(&str, &str)]) {
+    if let Some(array) = value.as_array_mut() {
+        for value in array.iter_mut() {
+            contract_value(value, builtins);
+        }
+        return;
+    }
+    let Some(text) = value.as_str() else {
+        return;
+    };
+    let Some((name, prefix)) = builtins
+        .iter()
+        .filter(|(_, prefix)| {
+            text.strip_prefix(*prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '\\']))
+        })
+        .max_by_key(|(_, prefix)| prefix.len())
+    else {
+        return;
+    };
+
+    let contracted = format!("${{{}}}{}", name, &text[prefix.len()..]);
+    let decor = value.decor().clone();
+    *value = Value::from(contracted);
+    *value.decor_mut() = decor;
+}
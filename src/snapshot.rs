@@ -0,0 +1,347 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::durable;
+use crate::manifest::Manifest;
+use crate::{GameBackup, unprotect_backup};
+use crate::{fserror, protect};
+
+/// Older snapshots that are kept besides the current backup, as `.snapshots/<game>/<id>`.
+pub const SNAPSHOTS_DIR: &str = ".snapshots";
+/// Pin reason of a snapshot that is kept because the backups after it failed validation.
+pub const LAST_KNOWN_GOOD: &str = "last-known-good";
+const PIN_EXTENSION: &str = "pin";
+
+#[derive(Debug)]
+pub struct Snapshot {
+    pub id: String,
+    pub path: PathBuf,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Whether this is the latest backup of the game, stored directly in its backup directory
+    pub current: bool,
+    /// Whether the snapshot was moved to cold storage
+    pub cold: bool,
+    pub pin: Option<String>,
+    pub validation_issues: usize,
+    pub label: Option<String>,
+    /// Machine the snapshot was taken on, when it was recorded
+    pub machine: Option<String>,
+}
+
+impl Snapshot {
+    pub fn is_suspect(&self) -> bool {
+        self.validation_issues > 0
+    }
+
+    pub fn is_last_known_good(&self) -> bool {
+        self.pin.as_deref() == Some(LAST_KNOWN_GOOD)
+    }
+
+    fn load(id: String, path: PathBuf, current: bool, cold: bool, pins_dir: &Path) -> Self {
+        let manifest = Manifest::load(&path).ok().flatten();
+        let pin = read_pin(pins_dir, &id);
+        Self {
+            created_at: manifest.as_ref().map(|manifest| manifest.created_at),
+            validation_issues: manifest
+                .as_ref()
+                .map(|manifest| manifest.validation_issues.len())
+                .unwrap_or(0),
+            label: manifest
+                .as_ref()
+                .and_then(|manifest| manifest.label.clone()),
+            machine: manifest.and_then(|manifest| manifest.machine),
+            id,
+            path,
+            current,
+            cold,
+            pin,
+        }
+    }
+}
+
+/// Identifier of a snapshot, derived from the time it was taken.
+pub fn snapshot_id(manifest: &Manifest) -> String {
+    manifest.created_at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+impl GameBackup {
+    /// Lists the current backup of a game followed by the older snapshots that are kept,
+    /// both in the backup root and in cold storage.
+    pub fn list_snapshots(&self, game_name: &str) -> Result<Vec<Snapshot>> {
+        let game = self.find_game(game_name)?;
+        let game_backup_dir = self.backup_root.join(&game.name);
+        let snapshots_dir = self.snapshots_dir(&game.name);
+
+        let mut snapshots = Vec::new();
+        if let Some(manifest) = Manifest::load(&game_backup_dir)? {
+            snapshots.push(Snapshot::load(
+                snapshot_id(&manifest),
+                game_backup_dir,
+                true,
+                false,
+                &snapshots_dir,
+            ));
+        }
+
+        let mut retained = read_snapshots(&snapshots_dir, &snapshots_dir, false)?;
+        if let Some(cold_dir) = self.cold_dir(&game.name) {
+            retained.extend(read_snapshots(&cold_dir, &snapshots_dir, true)?);
+        }
+        retained.sort_by(|a, b| b.id.cmp(&a.id));
+        snapshots.extend(retained);
+
+        Ok(snapshots)
+    }
+
+    /// Backup directory to restore from: the current backup, a snapshot by id, or
+    /// `last-known-good`.
+    pub(crate) fn snapshot_dir(&self, game_name: &str, selector: Option<&str>) -> Result<PathBuf> {
+        let game_backup_dir = self.backup_root.join(game_name);
+        let Some(selector) = selector else {
+            if !game_backup_dir.exists() {
+                return Err(anyhow!("No backup found for game: {}", game_name));
+            }
+            return Ok(game_backup_dir);
+        };
+
+        let snapshots = self.list_snapshots(game_name)?;
+        let snapshot = if selector == LAST_KNOWN_GOOD {
+            snapshots.into_iter().find(Snapshot::is_last_known_good)
+        } else {
+            snapshots
+                .into_iter()
+                .find(|snapshot| snapshot.id == selector)
+        };
+
+        snapshot.map(|snapshot| snapshot.path).ok_or_else(|| {
+            anyhow!(
+                "No snapshot '{}' found for game '{}'. Run `cartridge snapshots {}` to list them",
+                selector,
+                game_name,
+                game_name
+            )
+        })
+    }
+
+    /// Pins a snapshot of a game, the current backup included, so that it is never pruned.
+    /// The reason is shown by `snapshots`.
+    pub fn pin_snapshot(&self, game_name: &str, id: &str, reason: Option<&str>) -> Result<()> {
+        let reason = reason.map(str::trim).unwrap_or_default();
+        if reason == LAST_KNOWN_GOOD {
+            return Err(anyhow!("'{}' is reserved as a pin reason", LAST_KNOWN_GOOD));
+        }
+        if reason.contains('\n') {
+            return Err(anyhow!("Pin reason must be a single line"));
+        }
+
+        let snapshot = self.find_snapshot(game_name, id)?;
+        write_pin(&self.snapshots_dir(game_name), &snapshot.id, reason)?;
+        log::info!("Pinned snapshot {} of '{}'", snapshot.id, game_name);
+        Ok(())
+    }
+
+    /// Removes the pin of a snapshot, leaving it to retention like any other.
+    pub fn unpin_snapshot(&self, game_name: &str, id: &str) -> Result<()> {
+        self.ensure_unlocked("unpin snapshots")?;
+        let snapshot = self.find_snapshot(game_name, id)?;
+        if snapshot.pin.is_none() {
+            return Err(anyhow!(
+                "Snapshot '{}' of '{}' is not pinned",
+                snapshot.id,
+                game_name
+            ));
+        }
+        remove_pin(&self.snapshots_dir(game_name), &snapshot.id)?;
+        log::info!("Unpinned snapshot {} of '{}'", snapshot.id, game_name);
+        Ok(())
+    }
+
+    fn find_snapshot(&self, game_name: &str, id: &str) -> Result<Snapshot> {
+        self.list_snapshots(game_name)?
+            .into_iter()
+            .find(|snapshot| snapshot.id == id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No snapshot '{}' found for game '{}'. Run `cartridge snapshots {}` to list them",
+                    id,
+                    game_name,
+                    game_name
+                )
+            })
+    }
+
+    pub(crate) fn snapshots_dir(&self, game_name: &str) -> PathBuf {
+        self.backup_root.join(SNAPSHOTS_DIR).join(game_name)
+    }
+
+    /// Decides what happens to the backup that was just replaced by a new one. It is kept as the
+    /// last known good snapshot when the new backup is suspect, kept as is when pinned, and
+    /// deleted otherwise.
+    pub(crate) fn retire_previous_backup(
+        &self,
+        game_name: &str,
+        previous_dir: &Path,
+        new_is_suspect: bool,
+    ) -> Result<()> {
+        let snapshots_dir = self.snapshots_dir(game_name);
+        let previous = Manifest::load(previous_dir).ok().flatten();
+        let previous_id = previous.as_ref().map(snapshot_id);
+        let previous_pin = previous_id
+            .as_deref()
+            .and_then(|id| read_pin(&snapshots_dir, id));
+        let previous_is_good = previous
+            .as_ref()
+            .is_some_and(|manifest| manifest.validation_issues.is_empty());
+
+        let keep_all = self.cold_dir(game_name).is_some()
+            || self
+                .find_game(game_name)
+                .is_ok_and(|game| game.keep_snapshots.or(self.config.keep_snapshots).is_some());
+        if !new_is_suspect || previous_is_good {
+            // Either the new backup is good itself, or the previous one is a newer good snapshot
+            for snapshot in self.list_snapshots(game_name)? {
+                if !snapshot.current && snapshot.is_last_known_good() {
+                    log::info!(
+                        "Releasing previous last known good snapshot: {}",
+                        snapshot.id
+                    );
+                    if keep_all {
+                        remove_pin(&snapshots_dir, &snapshot.id)?;
+                    } else {
+                        self.record_removal(game_name, &snapshot.path);
+                        remove_snapshot(&snapshot.path, &snapshots_dir, &snapshot.id)?;
+                    }
+                }
+            }
+        }
+
+        let keep = match (&previous_id, previous_pin) {
+            // A pin set by the user is never replaced by the last known good one
+            (Some(id), Some(pin)) if pin != LAST_KNOWN_GOOD => {
+                log::info!("Keeping pinned snapshot {} ({})", id, pin);
+                Some(id.clone())
+            }
+            (Some(id), _) if new_is_suspect && previous_is_good => {
+                log::warn!(
+                    "New backup of '{}' is suspect, keeping snapshot {} as last known good",
+                    game_name,
+                    id
+                );
+                write_pin(&snapshots_dir, id, LAST_KNOWN_GOOD)?;
+                Some(id.clone())
+            }
+            // Older backups are kept for cold storage or until they are pruned
+            (Some(id), _) if keep_all => {
+                log::debug!("Keeping previous backup as snapshot {}", id);
+                Some(id.clone())
+            }
+            _ => None,
+        };
+
+        let Some(id) = keep else {
+            self.record_removal(game_name, previous_dir);
+            if let Err(e) = fs::remove_dir_all(previous_dir) {
+                log::warn!(
+                    "Failed to remove previous backup {}: {}",
+                    previous_dir.display(),
+                    e
+                );
+            }
+            return Ok(());
+        };
+
+        let snapshot_dir = snapshots_dir.join(&id);
+        if snapshot_dir.exists() {
+            log::warn!(
+                "Snapshot {} is already kept, removing the duplicate: {}",
+                id,
+                previous_dir.display()
+            );
+            return fs::remove_dir_all(previous_dir).with_context(|| {
+                format!(
+                    "Failed to remove previous backup: {}",
+                    previous_dir.display()
+                )
+            });
+        }
+        fs::create_dir_all(&snapshots_dir).with_context(|| {
+            format!(
+                "Failed to create snapshots directory: {}",
+                snapshots_dir.display()
+            )
+        })?;
+        fs::rename(previous_dir, &snapshot_dir)
+            .map_err(|e| fserror::rename_error(e, previous_dir, &snapshot_dir))
+            .with_context(|| {
+                format!(
+                    "Failed to keep previous backup as snapshot: {}",
+                    snapshot_dir.display()
+                )
+            })?;
+        if let Some(manifest) = previous {
+            protect::protect(&snapshot_dir, manifest.protection)?;
+        }
+        Ok(())
+    }
+}
+
+/// Snapshots kept in a directory; pins are always read from the backup root.
+fn read_snapshots(dir: &Path, pins_dir: &Path, cold: bool) -> Result<Vec<Snapshot>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let entry = entry
+            .with_context(|| format!("Failed to read directory entry in: {}", dir.display()))?;
+        if entry.path().is_dir() {
+            let id = entry.file_name().to_string_lossy().to_string();
+            snapshots.push(Snapshot::load(id, entry.path(), false, cold, pins_dir));
+        }
+    }
+    Ok(snapshots)
+}
+
+/// Deletes a kept snapshot together with its pin.
+pub(crate) fn remove_snapshot(path: &Path, snapshots_dir: &Path, id: &str) -> Result<()> {
+    unprotect_backup(path)?;
+    fs::remove_dir_all(path)
+        .with_context(|| format!("Failed to remove snapshot: {}", path.display()))?;
+    remove_pin(snapshots_dir, id)
+}
+
+pub(crate) fn remove_pin(snapshots_dir: &Path, id: &str) -> Result<()> {
+    let pin_path = pin_path(snapshots_dir, id);
+    if pin_path.exists() {
+        fs::remove_file(&pin_path)
+            .with_context(|| format!("Failed to remove pin: {}", pin_path.display()))?;
+    }
+    Ok(())
+}
+
+fn pin_path(snapshots_dir: &Path, id: &str) -> PathBuf {
+    snapshots_dir.join(format!("{}.{}", id, PIN_EXTENSION))
+}
+
+pub(crate) fn read_pin(snapshots_dir: &Path, id: &str) -> Option<String> {
+    fs::read_to_string(pin_path(snapshots_dir, id))
+        .ok()
+        .map(|reason| reason.trim().to_string())
+}
+
+pub(crate) fn write_pin(snapshots_dir: &Path, id: &str, reason: &str) -> Result<()> {
+    fs::create_dir_all(snapshots_dir).with_context(|| {
+        format!(
+            "Failed to create snapshots directory: {}",
+            snapshots_dir.display()
+        )
+    })?;
+    let pin_path = pin_path(snapshots_dir, id);
+    durable::write_atomic(&pin_path, format!("{}\n", reason))
+        .with_context(|| format!("Failed to write pin: {}", pin_path.display()))
+}
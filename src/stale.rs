@@ -0,0 +1,114 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item};
+
+use crate::GameBackup;
+use crate::durable;
+use crate::recent::SaveActivity;
+
+const DEFAULT_STALE_AFTER: TimeDelta = TimeDelta::days(365);
+
+/// A game whose saves have not changed for longer than `stale_after`, or that has no saves
+/// left at all, which usually means it was uninstalled.
+#[derive(Debug, Serialize)]
+pub struct StaleGame {
+    pub name: String,
+    /// Newest modification time of its save files, `None` when none were found
+    pub last_modified: Option<DateTime<Utc>>,
+    /// Size of its save files
+    pub bytes: u64,
+}
+
+impl GameBackup {
+    /// Enabled games that are not archived and look abandoned, see [`StaleGame`]. Games whose
+    /// save paths cannot be resolved are left to `doctor`.
+    pub fn stale_games(&self) -> Vec<StaleGame> {
+        let mut stale = Vec::new();
+        for game in self.list_games() {
+            if game.archived {
+                continue;
+            }
+            let activity = match self.save_activity(game) {
+                Ok(activity) => activity,
+                Err(e) => {
+                    log::debug!("Not checking whether '{}' is stale: {:#}", game.name, e);
+                    continue;
+                }
+            };
+            if self.is_stale(&activity) {
+                stale.push(StaleGame {
+                    name: game.name.clone(),
+                    last_modified: activity.last_modified,
+                    bytes: activity.bytes,
+                });
+            }
+        }
+        stale
+    }
+
+    /// Whether saves last changed as in `activity` have been left alone for longer than
+    /// `stale_after`, or are gone.
+    pub(crate) fn is_stale(&self, activity: &SaveActivity) -> bool {
+        let stale_after = self
+            .config
+            .stale_after
+            .map_or(DEFAULT_STALE_AFTER, |period| period.0);
+        activity
+            .last_modified
+            .is_none_or(|modified| Utc::now() - modified > stale_after)
+    }
+
+    /// Sets `archived` of a game in the config file, keeping the rest of the file as it is.
+    /// Archived games are no longer backed up, and their backups are no longer pruned.
+    pub fn archive_game(&self, config_path: &Path, game_name: &str, archived: bool) -> Result<()> {
+        self.ensure_unlocked("rewrite the configuration")?;
+        let game = self.find_game(game_name)?;
+        if game.archived == archived {
+            log::info!(
+                "'{}' is already {}",
+                game.name,
+                if archived { "archived" } else { "not archived" }
+            );
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(config_path)
+            .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+        let mut document: DocumentMut = content
+            .parse()
+            .with_context(|| "Failed to parse TOML configuration")?;
+        let table = document
+            .get_mut("game")
+            .and_then(Item::as_array_of_tables_mut)
+            .and_then(|games| {
+                games
+                    .iter_mut()
+                    .find(|table| table.get("name").and_then(Item::as_str) == Some(&game.name))
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "Game '{}' not found in: {}",
+                    game.name,
+                    config_path.display()
+                )
+            })?;
+        if archived {
+            table["archived"] = toml_edit::value(true);
+        } else {
+            table.remove("archived");
+        }
+
+        durable::write_atomic(config_path, document.to_string())
+            .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
+        log::info!(
+            "{} '{}' in: {}",
+            if archived { "Archived" } else { "Unarchived" },
+            game.name,
+            config_path.display()
+        );
+        Ok(())
+    }
+}
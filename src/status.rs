@@ -0,0 +1,255 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::durable;
+use crate::manifest::escaped_path;
+use crate::progress::{Operation, Progress};
+use crate::{GameBackup, SaveItem, SaveKind};
+
+/// State of the running or last backup or restore, kept in the backup root for `status` and
+/// external tools to poll.
+pub const STATUS_FILE: &str = "status.json";
+/// How often the status file is rewritten while a run is in progress.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(2);
+/// A running status not rewritten for this long belongs to a run that was killed.
+const STALE_AFTER: TimeDelta = TimeDelta::seconds(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunState {
+    Running,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Status {
+    pub pid: u32,
+    pub operation: Operation,
+    pub state: RunState,
+    /// Game being processed
+    pub game: Option<String>,
+    /// File last backed up or restored
+    #[serde(default, with = "escaped_path::option")]
+    pub file: Option<PathBuf>,
+    pub games_done: usize,
+    pub games_total: usize,
+    pub files_done: usize,
+    /// Files of the current game, when known up front
+    pub files_total: Option<usize>,
+    pub percent: f64,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Status {
+    /// Whether the run stopped updating the status without finishing.
+    pub fn is_stale(&self) -> bool {
+        self.state == RunState::Running && Utc::now() - self.updated_at > STALE_AFTER
+    }
+
+    fn update(&mut self, progress: &Progress) {
+        match progress {
+            Progress::Started { game, files, .. } => {
+                self.game = Some(game.to_string());
+                self.file = None;
+                self.files_done = 0;
+                self.files_total = *files;
+            }
+            Progress::File { path, .. } => {
+                self.file = Some(path.to_path_buf());
+                self.files_done += 1;
+            }
+            Progress::Finished { .. } => {
+                self.file = None;
+                self.games_done += 1;
+                self.files_done = 0;
+                self.files_total = None;
+            }
+        }
+
+        let game_fraction = match self.files_total {
+            Some(total) if total > 0 => (self.files_done as f64 / total as f64).min(1.0),
+            _ => 0.0,
+        };
+        self.percent = if self.games_total == 0 {
+            0.0
+        } else {
+            ((self.games_done as f64 + game_fraction) / self.games_total as f64 * 100.0).min(100.0)
+        };
+    }
+}
+
+/// Keeps the status file of a run up to date from a background thread.
+pub(crate) struct Heartbeat {
+    status: Arc<Mutex<Status>>,
+    stop: mpsc::Sender<()>,
+    thread: JoinHandle<()>,
+    path: PathBuf,
+}
+
+impl GameBackup {
+    /// Starts tracking a run of `games` games in the status file, unless one is tracked
+    /// already. Returns whether it started one, which the caller must then end.
+    pub(crate) fn begin_status(&self, operation: Operation, games: usize) -> bool {
+        let mut heartbeat = self.heartbeat.lock().unwrap_or_else(|e| e.into_inner());
+        if heartbeat.is_some() {
+            return false;
+        }
+
+        let now = Utc::now();
+        let status = Arc::new(Mutex::new(Status {
+            pid: std::process::id(),
+            operation,
+            state: RunState::Running,
+            game: None,
+            file: None,
+            games_done: 0,
+            games_total: games,
+            files_done: 0,
+            files_total: None,
+            percent: 0.0,
+            started_at: now,
+            updated_at: now,
+        }));
+        let path = self.backup_root.join(STATUS_FILE);
+        write_status(&path, &status);
+
+        let (stop, stopped) = mpsc::channel();
+        let thread = {
+            let status = Arc::clone(&status);
+            let path = path.clone();
+            thread::spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(HEARTBEAT_INTERVAL)
+                {
+                    write_status(&path, &status);
+                }
+            })
+        };
+        *heartbeat = Some(Heartbeat {
+            status,
+            stop,
+            thread,
+            path,
+        });
+        true
+    }
+
+    /// Stops tracking the run and records how it ended.
+    pub(crate) fn end_status(&self, ok: bool) {
+        let Some(heartbeat) = self
+            .heartbeat
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+        else {
+            return;
+        };
+        let _ = heartbeat.stop.send(());
+        if heartbeat.thread.join().is_err() {
+            log::warn!("Status heartbeat thread panicked");
+        }
+
+        {
+            let mut status = heartbeat.status.lock().unwrap_or_else(|e| e.into_inner());
+            status.state = if ok {
+                RunState::Finished
+            } else {
+                RunState::Failed
+            };
+            status.file = None;
+            if ok {
+                status.percent = 100.0;
+            }
+        }
+        write_status(&heartbeat.path, &heartbeat.status);
+    }
+
+    pub(crate) fn update_status(&self, progress: &Progress) {
+        let heartbeat = self.heartbeat.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(heartbeat) = heartbeat.as_ref() {
+            heartbeat
+                .status
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .update(progress);
+        }
+    }
+
+    /// Status of the running or last backup or restore, if any was recorded.
+    pub fn read_status(&self) -> Result<Option<Status>> {
+        let path = self.backup_root.join(STATUS_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read status file: {}", path.display()))?;
+        let status = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse status file: {}", path.display()))?;
+        Ok(Some(status))
+    }
+
+    /// Number of files a backup of the game would read, for the progress shown in the
+    /// status. `None` when the save locations cannot be walked.
+    pub(crate) fn count_backup_files(
+        &self,
+        game_name: &str,
+        kind: Option<SaveKind>,
+    ) -> Option<usize> {
+        let game = self.find_game(game_name).ok()?;
+        let mut files = 0;
+        for (i, save_location) in game.saves.iter().enumerate() {
+            if kind.is_some_and(|kind| save_location.kind != kind) {
+                continue;
+            }
+            let Some(source_path) = self.save_path(save_location).ok()? else {
+                continue;
+            };
+            let rules = self.ignore_rules(i, save_location, None);
+            self.walk_save_location(
+                save_location,
+                &source_path,
+                Path::new(""),
+                rules,
+                &mut |item| {
+                    if let SaveItem::File { .. } = item {
+                        files += 1;
+                    }
+                    Ok(())
+                },
+            )
+            .ok()?;
+        }
+        Some(files)
+    }
+}
+
+/// Rewrites the status file through a temporary file, so readers never see half of it.
+/// Failing to do so does not affect the run, so it is only logged.
+fn write_status(path: &Path, status: &Mutex<Status>) {
+    let content = {
+        let mut status = status.lock().unwrap_or_else(|e| e.into_inner());
+        status.updated_at = Utc::now();
+        serde_json::to_string_pretty(&*status)
+    };
+    let result = content
+        .with_context(|| "Failed to serialize status")
+        .and_then(|content| {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            }
+            durable::write_atomic(path, content)
+                .with_context(|| format!("Failed to write status file: {}", path.display()))
+        });
+    if let Err(e) = result {
+        log::debug!("Could not update status file: {:#}", e);
+    }
+}
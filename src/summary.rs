@@ -0,0 +1,85 @@
+use serde::Serialize;
+
+use crate::fserror::{self, MissingSavePath};
+use crate::progress::{Operation, Progress};
+
+/// What happened to each game of a backup or restore, collected from the progress it reports,
+/// for a summary at the end of the run.
+#[derive(Debug, Default, Serialize)]
+pub struct RunSummary {
+    pub games: Vec<GameOutcome>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GameOutcome {
+    pub operation: Operation,
+    pub game: String,
+    /// Why the game failed, `None` when it succeeded
+    pub error: Option<String>,
+    pub next_steps: Vec<NextStep>,
+}
+
+/// A command that may get a failed game through on the next run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NextStep {
+    /// `cartridge doctor`, to see which save paths are missing and why
+    Doctor,
+    /// Back up with `--skip-missing`, keeping missing locations from the current backup
+    SkipMissing,
+    /// Free up space in the backup root with `cartridge prune`
+    FreeSpace,
+    /// Run again for the game alone with `--verbose`, for failures without a known fix
+    Retry,
+}
+
+impl RunSummary {
+    /// Records the outcome of a game when it finishes.
+    pub fn record(&mut self, progress: &Progress) {
+        let Progress::Finished {
+            operation,
+            game,
+            error,
+            ..
+        } = progress
+        else {
+            return;
+        };
+        self.games.push(GameOutcome {
+            operation: *operation,
+            game: game.to_string(),
+            error: error.map(|e| format!("{:#}", e)),
+            next_steps: error.map(|e| next_steps(*operation, e)).unwrap_or_default(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    pub fn succeeded(&self) -> impl Iterator<Item = &GameOutcome> {
+        self.games.iter().filter(|outcome| outcome.error.is_none())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &GameOutcome> {
+        self.games.iter().filter(|outcome| outcome.error.is_some())
+    }
+}
+
+/// Next steps for a failure, from the kind of error that caused it.
+fn next_steps(operation: Operation, error: &anyhow::Error) -> Vec<NextStep> {
+    let missing_save = error
+        .chain()
+        .any(|cause| cause.downcast_ref::<MissingSavePath>().is_some());
+    if missing_save {
+        let mut steps = vec![NextStep::Doctor];
+        if operation == Operation::Backup {
+            steps.push(NextStep::SkipMissing);
+        }
+        return steps;
+    }
+    if fserror::is_disk_full(error) {
+        return vec![NextStep::FreeSpace];
+    }
+    vec![NextStep::Retry]
+}
@@ -0,0 +1,222 @@
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::{Config, SaveKind, SavePath};
+
+/// A save location shared by games with the same layout, e.g. everything built with one
+/// engine, defined once as `[save_template.<name>]`. Its `${...}` placeholders are filled
+/// from the `params` of each save location that uses it; the others are variables.
+#[derive(Debug, Deserialize)]
+pub struct SaveTemplate {
+    pub path: SavePath,
+    /// Kind of the locations made from the template; the location's own `kind` otherwise
+    pub kind: Option<SaveKind>,
+    #[serde(default)]
+    pub files: Vec<String>,
+    pub include_hidden: Option<bool>,
+}
+
+impl Config {
+    /// Fills in the save locations that use a template.
+    pub(crate) fn apply_save_templates(&mut self) -> Result<()> {
+        for game in &mut self.games {
+            for (i, save_location) in game.saves.iter_mut().enumerate() {
+                let source = match (&save_location.template, &save_location.engine) {
+                    (None, None) if save_location.path.is_empty() => {
+                        return Err(anyhow!(
+                            "Save location {} of '{}' has neither a path, a template nor an \
+                             engine",
+                            i + 1,
+                            game.name
+                        ));
+                    }
+                    (None, None) => continue,
+                    (Some(_), Some(_)) => {
+                        return Err(anyhow!(
+                            "Save location {} of '{}' has both a template and an engine",
+                            i + 1,
+                            game.name
+                        ));
+                    }
+                    (Some(name), None) | (None, Some(name)) => name,
+                };
+                if !save_location.path.is_empty() {
+                    return Err(anyhow!(
+                        "Save location {} of '{}' has both a path and '{}'",
+                        i + 1,
+                        game.name,
+                        source
+                    ));
+                }
+
+                let builtin;
+                let template = if let Some(engine) = &save_location.engine {
+                    builtin = engine_template(engine, &save_location.params)
+                        .with_context(|| format!("Invalid save location of '{}'", game.name))?;
+                    &builtin
+                } else {
+                    self.save_templates.get(source).ok_or_else(|| {
+                        anyhow!(
+                            "Unknown save template '{}' in '{}' (defined: {})",
+                            source,
+                            game.name,
+                            self.save_templates
+                                .keys()
+                                .map(String::as_str)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )
+                    })?
+                };
+                log::debug!("Using save template '{}' for '{}'", source, game.name);
+
+                let params = &save_location.params;
+                save_location.path = match &template.path {
+                    SavePath::Any(path) => SavePath::Any(fill(path, params)),
+                    SavePath::PerPlatform(paths) => SavePath::PerPlatform(
+                        paths
+                            .iter()
+                            .map(|(os, path)| (os.clone(), fill(path, params)))
+                            .collect(),
+                    ),
+                };
+                if let Some(kind) = template.kind {
+                    save_location.kind = kind;
+                }
+                if save_location.files.is_empty() {
+                    save_location.files = template
+                        .files
+                        .iter()
+                        .map(|pattern| fill(pattern, params))
+                        .collect();
+                }
+                save_location.include_hidden =
+                    save_location.include_hidden.or(template.include_hidden);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Save layout of a game engine, usable as `engine = "<name>"` without defining a template.
+struct Engine {
+    name: &'static str,
+    /// Placeholders every location using the engine must give in `params`
+    params: &'static [&'static str],
+    /// Path per OS
+    paths: &'static [(&'static str, &'static str)],
+    files: &'static [&'static str],
+}
+
+const ENGINES: &[Engine] = &[
+    Engine {
+        name: "unity",
+        params: &["company", "product"],
+        paths: &[
+            ("windows", "${home}/AppData/LocalLow/${company}/${product}"),
+            ("linux", "${config}/unity3d/${company}/${product}"),
+            ("macos", "${config}/${company}/${product}"),
+        ],
+        files: &[],
+    },
+    Engine {
+        name: "unreal",
+        params: &["project"],
+        paths: &[
+            (
+                "windows",
+                "${home}/AppData/Local/${project}/Saved/SaveGames",
+            ),
+            ("unix", "${config}/Epic/${project}/Saved/SaveGames"),
+        ],
+        files: &[],
+    },
+    Engine {
+        name: "renpy",
+        params: &["save_directory"],
+        paths: &[
+            ("windows", "${config}/RenPy/${save_directory}"),
+            ("linux", "${home}/.renpy/${save_directory}"),
+            ("macos", "${home}/Library/RenPy/${save_directory}"),
+        ],
+        files: &[],
+    },
+    // RPG Maker games keep their saves next to the game
+    Engine {
+        name: "rpgmaker-mv",
+        params: &["game_dir"],
+        paths: &[
+            ("windows", "${game_dir}/www/save"),
+            ("unix", "${game_dir}/www/save"),
+        ],
+        files: &[],
+    },
+    Engine {
+        name: "rpgmaker-mz",
+        params: &["game_dir"],
+        paths: &[
+            ("windows", "${game_dir}/save"),
+            ("unix", "${game_dir}/save"),
+        ],
+        files: &[],
+    },
+    Engine {
+        name: "rpgmaker-vx",
+        params: &["game_dir"],
+        paths: &[("windows", "${game_dir}"), ("unix", "${game_dir}")],
+        files: &["Save*.rvdata2", "Save*.rvdata", "Save*.rxdata"],
+    },
+];
+
+/// The built-in template of an engine, checking that every placeholder it needs is given.
+fn engine_template(name: &str, params: &BTreeMap<String, String>) -> Result<SaveTemplate> {
+    let engine = ENGINES
+        .iter()
+        .find(|engine| engine.name == name)
+        .ok_or_else(|| {
+            anyhow!(
+                "Unknown engine '{}' (known: {})",
+                name,
+                ENGINES
+                    .iter()
+                    .map(|engine| engine.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })?;
+    let missing: Vec<&str> = engine
+        .params
+        .iter()
+        .copied()
+        .filter(|param| !params.contains_key(*param))
+        .collect();
+    if !missing.is_empty() {
+        return Err(anyhow!(
+            "Engine '{}' needs params {}",
+            engine.name,
+            missing.join(", ")
+        ));
+    }
+    Ok(SaveTemplate {
+        path: SavePath::PerPlatform(
+            engine
+                .paths
+                .iter()
+                .map(|(os, path)| (os.to_string(), path.to_string()))
+                .collect(),
+        ),
+        kind: None,
+        files: engine.files.iter().map(|file| file.to_string()).collect(),
+        include_hidden: None,
+    })
+}
+
+/// Replaces the `${name}` placeholders of the given parameters.
+fn fill(value: &str, params: &BTreeMap<String, String>) -> String {
+    params
+        .iter()
+        .fold(value.to_string(), |value, (name, param)| {
+            value.replace(&format!("${{{}}}", name), param)
+        })
+}
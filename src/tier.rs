@@ -0,0 +1,203 @@
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::Manifest;
+use crate::plan::RestoreOptions;
+use crate::{GameBackup, STAGING_DIR, format_size, fserror, protect, unprotect_backup};
+
+/// Slower storage, such as a NAS mount, that older snapshots are moved to so only the newest
+/// ones take up space in the backup root.
+#[derive(Debug, Deserialize)]
+pub struct ColdStorage {
+    /// Directory of the cold tier, relative to the config file
+    pub path: String,
+    /// Number of newest backups of a game, the current one included, kept in the backup root
+    #[serde(default = "default_keep_hot")]
+    pub keep_hot: usize,
+}
+
+fn default_keep_hot() -> usize {
+    3
+}
+
+impl GameBackup {
+    /// Moves the snapshots of a game beyond the newest `keep_hot` to cold storage.
+    /// Returns the number of snapshots moved.
+    pub(crate) fn migrate_to_cold(&self, game_name: &str) -> Result<usize> {
+        let (Some(cold_storage), Some(cold_root)) = (&self.config.cold_storage, &self.cold_root)
+        else {
+            return Ok(0);
+        };
+
+        let hot: Vec<_> = self
+            .list_snapshots(game_name)?
+            .into_iter()
+            .filter(|snapshot| !snapshot.cold)
+            .collect();
+        let mut moved = 0;
+        for snapshot in hot.iter().skip(cold_storage.keep_hot.max(1)) {
+            let dest = cold_root.join(game_name).join(&snapshot.id);
+            if dest.exists() {
+                log::warn!(
+                    "Snapshot {} of '{}' is already in cold storage, leaving it in place",
+                    snapshot.id,
+                    game_name
+                );
+                continue;
+            }
+
+            log::info!(
+                "Moving snapshot {} of '{}' to cold storage: {}",
+                snapshot.id,
+                game_name,
+                dest.display()
+            );
+            let (_, bytes) = dir_size(&snapshot.path)?;
+            move_snapshot(&snapshot.path, &dest)?;
+            self.record_transfer(game_name, cold_root, bytes, 0);
+            moved += 1;
+        }
+        Ok(moved)
+    }
+
+    /// Copies a snapshot from cold storage into the staging area so it can be restored from
+    /// local disk, after reporting how much data that is. Returns `None` for snapshots that
+    /// are not in cold storage, and for dry runs, which read from cold storage directly.
+    pub(crate) fn fetch_from_cold(
+        &self,
+        game_name: &str,
+        snapshot_dir: &Path,
+        options: &RestoreOptions,
+    ) -> Result<Option<PathBuf>> {
+        let Some(cold_root) = &self.cold_root else {
+            return Ok(None);
+        };
+        if !snapshot_dir.starts_with(cold_root) {
+            return Ok(None);
+        }
+
+        let id = snapshot_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let (files, bytes) = dir_size(snapshot_dir)?;
+        log::info!(
+            "Snapshot {} of '{}' is in cold storage, restoring it fetches {} files, {}",
+            id,
+            game_name,
+            files,
+            format_size(bytes)
+        );
+        if let Some(max_download) = options.max_download
+            && bytes > max_download
+        {
+            return Err(anyhow!(
+                "Restoring snapshot {} of '{}' would fetch {} from cold storage, more than the \
+                 {} allowed by --max-download",
+                id,
+                game_name,
+                format_size(bytes),
+                format_size(max_download)
+            ));
+        }
+        if options.dry_run {
+            return Ok(None);
+        }
+
+        let fetched = self
+            .backup_root
+            .join(STAGING_DIR)
+            .join(format!("{}.cold", game_name));
+        if fetched.exists() {
+            unprotect_backup(&fetched)?;
+            fs::remove_dir_all(&fetched).with_context(|| {
+                format!("Failed to clean fetch directory: {}", fetched.display())
+            })?;
+        }
+
+        log::info!(
+            "Fetching snapshot from cold storage: {}",
+            snapshot_dir.display()
+        );
+        copy_dir_all(snapshot_dir, &fetched)?;
+        self.record_transfer(game_name, cold_root, 0, bytes);
+        Ok(Some(fetched))
+    }
+
+    /// Directory of the cold tier, if one is configured.
+    pub(crate) fn cold_dir(&self, game_name: &str) -> Option<PathBuf> {
+        self.cold_root.as_ref().map(|root| root.join(game_name))
+    }
+}
+
+/// Moves a snapshot directory, copying it when the destination is on another file system.
+fn move_snapshot(source: &Path, dest: &Path) -> Result<()> {
+    let protection = Manifest::load(source)
+        .ok()
+        .flatten()
+        .map(|manifest| manifest.protection)
+        .unwrap_or_default();
+    unprotect_backup(source)?;
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    if fs::rename(source, dest).is_err() {
+        let result = copy_dir_all(source, dest);
+        if let Err(e) = result {
+            let _ = fs::remove_dir_all(dest);
+            return Err(e);
+        }
+        fs::remove_dir_all(source)
+            .with_context(|| format!("Failed to remove snapshot: {}", source.display()))?;
+    }
+
+    protect::protect(dest, protection)
+}
+
+/// Number of files in a directory tree and their total size.
+pub(crate) fn dir_size(dir: &Path) -> Result<(usize, u64)> {
+    let mut files = 0;
+    let mut bytes = 0;
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    for entry in entries {
+        let entry = entry
+            .with_context(|| format!("Failed to read directory entry in: {}", dir.display()))?;
+        let path = entry.path();
+        if path.is_dir() {
+            let (dir_files, dir_bytes) = dir_size(&path)?;
+            files += dir_files;
+            bytes += dir_bytes;
+        } else {
+            let metadata = entry
+                .metadata()
+                .with_context(|| format!("Failed to read metadata: {}", path.display()))?;
+            files += 1;
+            bytes += metadata.len();
+        }
+    }
+    Ok((files, bytes))
+}
+
+fn copy_dir_all(source: &Path, dest: &Path) -> Result<()> {
+    fs::create_dir_all(dest)
+        .with_context(|| format!("Failed to create directory: {}", dest.display()))?;
+    let entries = fs::read_dir(source)
+        .with_context(|| format!("Failed to read directory: {}", source.display()))?;
+    for entry in entries {
+        let entry = entry
+            .with_context(|| format!("Failed to read directory entry in: {}", source.display()))?;
+        let path = entry.path();
+        let dest_path = dest.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir_all(&path, &dest_path)?;
+        } else {
+            fs::copy(&path, &dest_path).map_err(|e| fserror::copy_error(e, &path, &dest_path))?;
+        }
+    }
+    Ok(())
+}
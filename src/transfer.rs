@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::GameBackup;
+use crate::durable;
+
+/// Append-only record of the data written to and read from each destination, one JSON
+/// object per line, kept in the backup root.
+pub const TRANSFERS_FILE: &str = "transfers.jsonl";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Transfer {
+    pub time: DateTime<Utc>,
+    /// Directory the data was written to or read from
+    pub destination: String,
+    pub game: String,
+    pub uploaded: u64,
+    pub downloaded: u64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TransferTotals {
+    pub uploaded: u64,
+    pub downloaded: u64,
+    pub transfers: usize,
+}
+
+impl GameBackup {
+    /// Records data moved to or from a destination. Failing to do so does not undo the
+    /// transfer, so it is only reported.
+    pub(crate) fn record_transfer(
+        &self,
+        game_name: &str,
+        destination: &Path,
+        uploaded: u64,
+        downloaded: u64,
+    ) {
+        if uploaded == 0 && downloaded == 0 {
+            return;
+        }
+        let transfer = Transfer {
+            time: Utc::now(),
+            destination: destination.display().to_string(),
+            game: game_name.to_string(),
+            uploaded,
+            downloaded,
+        };
+        if let Err(e) = append(&self.backup_root, &transfer) {
+            log::error!(
+                "Failed to record transfer for '{}' in the transfer log: {:#}",
+                game_name,
+                e
+            );
+        }
+    }
+
+    /// Totals of the recorded transfers per destination, optionally only those made at or
+    /// after `since` and before `until`.
+    pub fn transfer_stats(
+        &self,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<BTreeMap<String, TransferTotals>> {
+        let mut totals: BTreeMap<String, TransferTotals> = BTreeMap::new();
+        for transfer in load(&self.backup_root)? {
+            if since.is_some_and(|since| transfer.time < since)
+                || until.is_some_and(|until| transfer.time >= until)
+            {
+                continue;
+            }
+            let entry = totals.entry(transfer.destination).or_default();
+            entry.uploaded += transfer.uploaded;
+            entry.downloaded += transfer.downloaded;
+            entry.transfers += 1;
+        }
+        Ok(totals)
+    }
+}
+
+fn append(backup_root: &Path, transfer: &Transfer) -> Result<()> {
+    fs::create_dir_all(backup_root).with_context(|| {
+        format!(
+            "Failed to create backup directory: {}",
+            backup_root.display()
+        )
+    })?;
+    let path = backup_root.join(TRANSFERS_FILE);
+    let line = serde_json::to_string(transfer).with_context(|| "Failed to serialize transfer")?;
+
+    durable::append_line(&path, &line)
+        .with_context(|| format!("Failed to write transfer log: {}", path.display()))
+}
+
+/// Reads the transfer log, skipping lines that cannot be parsed.
+fn load(backup_root: &Path) -> Result<Vec<Transfer>> {
+    let path = backup_root.join(TRANSFERS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read transfer log: {}", path.display()))?;
+
+    let mut transfers = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(transfer) => transfers.push(transfer),
+            Err(e) => log::warn!(
+                "Skipping unreadable line {} of {}: {}",
+                i + 1,
+                path.display(),
+                e
+            ),
+        }
+    }
+    Ok(transfers)
+}
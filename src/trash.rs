@@ -0,0 +1,273 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::durable;
+use crate::manifest::Manifest;
+use crate::period::Period;
+use crate::prune::PruneCandidate;
+use crate::tier::dir_size;
+use crate::{GameBackup, protect, snapshot, unprotect_backup};
+
+/// Snapshots deleted by `prune` or `forget`, kept as `.trash/<game>/<id>` in the backup root or
+/// cold storage until their grace period is over.
+pub const TRASH_DIR: &str = ".trash";
+const INFO_EXTENSION: &str = "json";
+
+/// Grace period used when `purge_after` is not configured.
+const DEFAULT_PURGE_AFTER: TimeDelta = TimeDelta::days(7);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrashInfo {
+    deleted_at: DateTime<Utc>,
+    /// Pin of the snapshot, put back when it is restored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pin: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct TrashedSnapshot {
+    pub game: String,
+    pub id: String,
+    pub path: PathBuf,
+    pub deleted_at: DateTime<Utc>,
+    pub purge_at: DateTime<Utc>,
+    pub size: u64,
+    pub cold: bool,
+}
+
+impl GameBackup {
+    /// How long deleted snapshots stay in the trash.
+    pub fn purge_after(&self) -> Period {
+        self.config
+            .purge_after
+            .unwrap_or(Period(DEFAULT_PURGE_AFTER))
+    }
+
+    /// Moves a snapshot to the trash of the storage it is on, or deletes it right away when
+    /// the grace period is zero.
+    pub(crate) fn trash_snapshot(&self, candidate: &PruneCandidate) -> Result<()> {
+        let snapshots_dir = self.snapshots_dir(&candidate.game);
+        self.record_removal(&candidate.game, &candidate.path);
+        if self.purge_after().is_zero() {
+            return snapshot::remove_snapshot(&candidate.path, &snapshots_dir, &candidate.id);
+        }
+
+        let trash_dir = self.trash_root(&candidate.path).join(&candidate.game);
+        let dest = trash_dir.join(&candidate.id);
+        if dest.exists() {
+            return Err(anyhow!(
+                "Snapshot {} of '{}' is already in the trash: {}",
+                candidate.id,
+                candidate.game,
+                dest.display()
+            ));
+        }
+        fs::create_dir_all(&trash_dir)
+            .with_context(|| format!("Failed to create directory: {}", trash_dir.display()))?;
+
+        let info = TrashInfo {
+            deleted_at: Utc::now(),
+            pin: snapshot::read_pin(&snapshots_dir, &candidate.id),
+        };
+        let info_path = info_path(&trash_dir, &candidate.id);
+        let content = serde_json::to_string_pretty(&info)
+            .with_context(|| "Failed to serialize trash info")?;
+        durable::write_atomic(&info_path, content)
+            .with_context(|| format!("Failed to write trash info: {}", info_path.display()))?;
+
+        unprotect_backup(&candidate.path)?;
+        fs::rename(&candidate.path, &dest).with_context(|| {
+            format!(
+                "Failed to move snapshot to the trash: {}",
+                candidate.path.display()
+            )
+        })?;
+        snapshot::remove_pin(&snapshots_dir, &candidate.id)
+    }
+
+    /// Snapshots in the trash of the backup root and of cold storage, oldest deletion first.
+    pub fn list_trash(&self) -> Result<Vec<TrashedSnapshot>> {
+        let mut trashed = Vec::new();
+        let roots = std::iter::once((&self.backup_root, false))
+            .chain(self.cold_root.as_ref().map(|root| (root, true)));
+        for (root, cold) in roots {
+            let trash_root = root.join(TRASH_DIR);
+            if !trash_root.exists() {
+                continue;
+            }
+            for game_entry in read_dir(&trash_root)? {
+                if !game_entry.is_dir() {
+                    continue;
+                }
+                let game = file_name(&game_entry);
+                for path in read_dir(&game_entry)? {
+                    if !path.is_dir() {
+                        continue;
+                    }
+                    let id = file_name(&path);
+                    let info = read_info(&game_entry, &id)?;
+                    let (_, size) = dir_size(&path)?;
+                    trashed.push(TrashedSnapshot {
+                        game: game.clone(),
+                        id,
+                        path,
+                        deleted_at: info.deleted_at,
+                        purge_at: info.deleted_at + self.purge_after().0,
+                        size,
+                        cold,
+                    });
+                }
+            }
+        }
+        trashed.sort_by(|a, b| a.deleted_at.cmp(&b.deleted_at).then(a.id.cmp(&b.id)));
+        Ok(trashed)
+    }
+
+    /// Puts a snapshot from the trash back where it was deleted from.
+    pub fn restore_from_trash(&self, game_name: &str, id: &str) -> Result<()> {
+        let trashed = self
+            .list_trash()?
+            .into_iter()
+            .find(|trashed| trashed.game == game_name && trashed.id == id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No snapshot '{}' of '{}' in the trash. Run `cartridge trash list` to see it",
+                    id,
+                    game_name
+                )
+            })?;
+
+        let snapshots_dir = self.snapshots_dir(game_name);
+        let dest_dir = match (trashed.cold, self.cold_dir(game_name)) {
+            (true, Some(cold_dir)) => cold_dir,
+            _ => snapshots_dir.clone(),
+        };
+        let dest = dest_dir.join(id);
+        if dest.exists() {
+            return Err(anyhow!("Snapshot already exists: {}", dest.display()));
+        }
+        fs::create_dir_all(&dest_dir)
+            .with_context(|| format!("Failed to create directory: {}", dest_dir.display()))?;
+        fs::rename(&trashed.path, &dest).with_context(|| {
+            format!(
+                "Failed to restore snapshot from the trash: {}",
+                trashed.path.display()
+            )
+        })?;
+
+        if let Some(manifest) = Manifest::load(&dest)? {
+            protect::protect(&dest, manifest.protection)?;
+        }
+
+        let trash_dir = trashed.path.parent().unwrap_or(Path::new("."));
+        let info = read_info(trash_dir, id)?;
+        if let Some(pin) = info.pin {
+            snapshot::write_pin(&snapshots_dir, id, &pin)?;
+        }
+        remove_info(trash_dir, id)?;
+
+        log::info!("Restored snapshot {} of '{}' from the trash", id, game_name);
+        Ok(())
+    }
+
+    /// Deletes the snapshots whose grace period is over, or every snapshot in the trash with
+    /// `all`. Returns the number of snapshots deleted and the bytes reclaimed.
+    pub fn purge_trash(&self, all: bool) -> Result<(usize, u64)> {
+        self.ensure_unlocked("purge the trash")?;
+
+        let now = Utc::now();
+        let mut purged = 0;
+        let mut reclaimed = 0;
+        for trashed in self.list_trash()? {
+            if !all && trashed.purge_at > now {
+                continue;
+            }
+            // Snapshots of archived games only leave the trash when asked to
+            if !all
+                && self
+                    .find_game(&trashed.game)
+                    .is_ok_and(|game| game.archived)
+            {
+                log::debug!(
+                    "Keeping snapshot {} of archived game '{}' in the trash",
+                    trashed.id,
+                    trashed.game
+                );
+                continue;
+            }
+            log::info!(
+                "Purging snapshot {} of '{}' from the trash",
+                trashed.id,
+                trashed.game
+            );
+            fs::remove_dir_all(&trashed.path).with_context(|| {
+                format!("Failed to remove snapshot: {}", trashed.path.display())
+            })?;
+            remove_info(trashed.path.parent().unwrap_or(Path::new(".")), &trashed.id)?;
+            purged += 1;
+            reclaimed += trashed.size;
+        }
+        Ok((purged, reclaimed))
+    }
+
+    /// Purges expired snapshots as part of another command, unless the config is locked.
+    pub(crate) fn purge_expired_trash(&self) {
+        if self.is_locked() {
+            return;
+        }
+        match self.purge_trash(false) {
+            Ok((0, _)) => {}
+            Ok((purged, _)) => log::info!("Purged {} expired snapshots from the trash", purged),
+            Err(e) => log::warn!("Failed to purge expired snapshots: {:#}", e),
+        }
+    }
+
+    /// Trash of the storage a snapshot is on.
+    fn trash_root(&self, snapshot_path: &Path) -> PathBuf {
+        match &self.cold_root {
+            Some(cold_root) if snapshot_path.starts_with(cold_root) => cold_root.join(TRASH_DIR),
+            _ => self.backup_root.join(TRASH_DIR),
+        }
+    }
+}
+
+fn info_path(trash_dir: &Path, id: &str) -> PathBuf {
+    trash_dir.join(format!("{}.{}", id, INFO_EXTENSION))
+}
+
+fn read_info(trash_dir: &Path, id: &str) -> Result<TrashInfo> {
+    let path = info_path(trash_dir, id);
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read trash info: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse trash info: {}", path.display()))
+}
+
+fn remove_info(trash_dir: &Path, id: &str) -> Result<()> {
+    let path = info_path(trash_dir, id);
+    if path.exists() {
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove trash info: {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        .map(|entry| {
+            entry
+                .map(|entry| entry.path())
+                .with_context(|| format!("Failed to read directory entry in: {}", dir.display()))
+        })
+        .collect()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,192 @@
+use anyhow::{Context, Result, anyhow};
+use minisign_verify::{PublicKey, Signature};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Latest release of cartridge, as served by the GitHub API.
+pub const RELEASES_URL: &str = "https://api.github.com/repos/lumpsoid/cartridge/releases/latest";
+/// Minisign public key release binaries are signed with. Only release builds set it, other
+/// builds refuse to update themselves.
+const UPDATE_KEY: Option<&str> = option_env!("CARTRIDGE_UPDATE_KEY");
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// A published release with a binary for this platform.
+#[derive(Debug)]
+pub struct Release {
+    pub version: String,
+    binary_url: String,
+    signature_url: String,
+}
+
+impl Release {
+    /// Whether the release is newer than the running binary.
+    pub fn is_newer(&self) -> bool {
+        version_parts(&self.version) > version_parts(env!("CARGO_PKG_VERSION"))
+    }
+}
+
+/// The latest release, failing when it has no signed binary for this platform.
+pub fn latest_release() -> Result<Release> {
+    let body = download(RELEASES_URL)?;
+    let release: GithubRelease = serde_json::from_slice(&body)
+        .with_context(|| format!("Failed to parse release information from {}", RELEASES_URL))?;
+    let name = asset_name();
+    let url_of = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .map(|asset| asset.browser_download_url.clone())
+    };
+    let (Some(binary_url), Some(signature_url)) =
+        (url_of(&name), url_of(&format!("{name}.minisig")))
+    else {
+        return Err(anyhow!(
+            "Release {} has no signed binary for this platform ({})",
+            release.tag_name,
+            name
+        ));
+    };
+    Ok(Release {
+        version: release.tag_name.trim_start_matches('v').to_string(),
+        binary_url,
+        signature_url,
+    })
+}
+
+/// Downloads a release, checks its signature and replaces the running binary with it. The
+/// replaced binary is left next to it until the next update, as Windows cannot delete a
+/// running executable.
+pub fn install(release: &Release) -> Result<PathBuf> {
+    let key = UPDATE_KEY.ok_or_else(|| {
+        anyhow!("This build has no update key, update it the way it was installed instead")
+    })?;
+    let key = PublicKey::from_base64(key).map_err(|e| anyhow!("Invalid update key: {}", e))?;
+    let exe = std::env::current_exe()
+        .and_then(fs::canonicalize)
+        .context("Failed to locate the running binary")?;
+    remove_replaced_binary(&exe);
+
+    log::info!("Downloading cartridge {}", release.version);
+    let binary = download(&release.binary_url)?;
+    let signature = download(&release.signature_url)?;
+    let signature = std::str::from_utf8(&signature)
+        .map_err(|e| anyhow!("Invalid release signature: {}", e))
+        .and_then(|text| {
+            Signature::decode(text).map_err(|e| anyhow!("Invalid release signature: {}", e))
+        })?;
+    key.verify(&binary, &signature, false).map_err(|e| {
+        anyhow!(
+            "The downloaded binary of {} does not match its signature ({}), not installing it",
+            release.version,
+            e
+        )
+    })?;
+    log::info!(
+        "Signature of {} verified, blake3 {}",
+        release.version,
+        blake3::hash(&binary).to_hex()
+    );
+
+    // Written next to the binary, so it is moved into place rather than copied
+    let new = exe.with_file_name(format!(".{}.update", asset_name()));
+    fs::write(&new, &binary).with_context(|| format!("Failed to write {}", new.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&new, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make {} executable", new.display()))?;
+    }
+
+    let old = replaced_path(&exe);
+    if let Err(e) = fs::rename(&exe, &old) {
+        let _ = fs::remove_file(&new);
+        return Err(e).with_context(|| format!("Failed to move {} aside", exe.display()));
+    }
+    if let Err(e) = fs::rename(&new, &exe) {
+        // Put the running binary back rather than leave none
+        let _ = fs::rename(&old, &exe);
+        let _ = fs::remove_file(&new);
+        return Err(e)
+            .with_context(|| format!("Failed to install the new binary at {}", exe.display()));
+    }
+    log::info!(
+        "Installed cartridge {} at {}",
+        release.version,
+        exe.display()
+    );
+    Ok(exe)
+}
+
+/// Deletes the binary an earlier update replaced, if it is still there.
+fn remove_replaced_binary(exe: &Path) {
+    let old = replaced_path(exe);
+    if old.exists()
+        && let Err(e) = fs::remove_file(&old)
+    {
+        log::warn!("Failed to remove {}: {}", old.display(), e);
+    }
+}
+
+fn replaced_path(exe: &Path) -> PathBuf {
+    let mut name = exe.file_name().unwrap_or_default().to_os_string();
+    name.push(".old");
+    exe.with_file_name(name)
+}
+
+/// Name of the release asset built for this platform, e.g. `cartridge-x86_64-windows.exe`.
+fn asset_name() -> String {
+    format!(
+        "cartridge-{}-{}{}",
+        std::env::consts::ARCH,
+        std::env::consts::OS,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// Fetches a URL with the system's `curl`, which ships with Windows 10 and later, macOS and
+/// most Linux distributions.
+fn download(url: &str) -> Result<Vec<u8>> {
+    log::debug!("Downloading {}", url);
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args([
+            "--user-agent",
+            concat!("cartridge/", env!("CARGO_PKG_VERSION")),
+        ])
+        .arg(url)
+        .output()
+        .context("Failed to run curl, which is needed to download updates")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to download {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// Numeric parts of a version, ignoring any pre-release suffix.
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
@@ -0,0 +1,345 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use crate::manifest::Manifest;
+use crate::plan::{self, ConflictPolicy, RestoreOptions};
+use crate::{Game, GameBackup, STAGING_DIR, SaveItem, SaveKind, process, stored_format};
+
+/// How long a `validate_command` may run before it is stopped and counted as failed.
+const VALIDATE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A sanity check run against live save files before they are backed up.
+#[derive(Debug, Deserialize)]
+pub struct Validator {
+    #[serde(flatten)]
+    pub check: Check,
+    /// Glob patterns selecting the files to check; empty checks every file
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "check", rename_all = "kebab-case")]
+pub enum Check {
+    /// The file must not be empty
+    NonEmpty,
+    /// The file must start with the given bytes, written as hex (`magic = "53415645"`)
+    Magic {
+        magic: String,
+        #[serde(default)]
+        offset: u64,
+    },
+    /// The file must be a SQLite database that passes `PRAGMA integrity_check`
+    Sqlite,
+}
+
+/// What to do with a backup whose save files fail validation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InvalidSavePolicy {
+    /// Abort the backup and keep the previous snapshot
+    #[default]
+    Fail,
+    /// Take the backup anyway and record the problems in its manifest
+    Tag,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub path: String,
+    pub problem: String,
+}
+
+impl GameBackup {
+    /// Runs the configured validators of a game against its live save files.
+    pub(crate) fn validate_saves(&self, game: &Game) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        if game.validators.is_empty() {
+            return Ok(issues);
+        }
+
+        log::info!("Validating save files for game: {}", game.name);
+        let patterns = game
+            .validators
+            .iter()
+            .map(|validator| {
+                validator
+                    .files
+                    .iter()
+                    .map(|pattern| {
+                        glob::Pattern::new(pattern)
+                            .with_context(|| format!("Invalid file pattern: {}", pattern))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (i, save_location) in game.saves.iter().enumerate() {
+            let Some(source_path) = self.save_path(save_location)? else {
+                continue;
+            };
+            if !source_path.exists() {
+                continue;
+            }
+
+            let rules = self.ignore_rules(i, save_location, None);
+            self.walk_save_location(
+                save_location,
+                &source_path,
+                Path::new(""),
+                rules,
+                &mut |item| {
+                    let SaveItem::File { source, name } = item else {
+                        return Ok(());
+                    };
+
+                    for (validator, patterns) in game.validators.iter().zip(&patterns) {
+                        if !patterns.is_empty() && !plan::matches_any(patterns, name) {
+                            continue;
+                        }
+                        if let Err(problem) = run_check(&validator.check, source)? {
+                            log::warn!(
+                                "Save file failed validation: {}: {}",
+                                source.display(),
+                                problem
+                            );
+                            issues.push(ValidationIssue {
+                                path: source.display().to_string(),
+                                problem,
+                            });
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+
+        Ok(issues)
+    }
+
+    /// Runs the `validate_command` of every location just backed up into `staging_dir`
+    /// against its copied files, extracted first when the backup is an archive. Failures fail
+    /// the backup or are recorded in its manifest, as `on_invalid` says.
+    pub(crate) fn validate_backup(
+        &self,
+        game: &Game,
+        kind: Option<SaveKind>,
+        staging_dir: &Path,
+    ) -> Result<()> {
+        let hooks: Vec<_> = game
+            .saves
+            .iter()
+            .enumerate()
+            .filter(|(_, location)| kind.is_none_or(|kind| location.kind == kind))
+            .filter_map(|(i, location)| Some((i, location, location.validate_command.as_ref()?)))
+            .collect();
+        if hooks.is_empty() {
+            return Ok(());
+        }
+        let mut manifest = Manifest::load(staging_dir)?
+            .ok_or_else(|| anyhow!("Staged backup has no manifest: {}", staging_dir.display()))?;
+
+        let sandbox = match stored_format(staging_dir, Some(&manifest)).archive() {
+            Some(_) => Some(self.extract_for_validation(game, staging_dir)?),
+            None => None,
+        };
+        let run_hooks = || -> Result<Vec<ValidationIssue>> {
+            let mut issues = Vec::new();
+            for (i, location, hook) in &hooks {
+                let Some(save_path) = self.save_path(location)? else {
+                    continue;
+                };
+                let copy = match &sandbox {
+                    Some(sandbox) => sandbox.join(i.to_string()),
+                    None => {
+                        staging_dir.join(self.backup_prefix(*i, &save_path, Some(&manifest))?)
+                    }
+                };
+                if !copy.exists() {
+                    log::debug!(
+                        "Nothing was backed up for {}, not validating it",
+                        save_path.display()
+                    );
+                    continue;
+                }
+                let mut command = self.command(hook)?;
+                command
+                    .env("CARTRIDGE_GAME", &game.name)
+                    .env("CARTRIDGE_BACKUP_PATH", &copy)
+                    .stdin(Stdio::null())
+                    .stderr(Stdio::inherit());
+                log::info!(
+                    "Validating backup of {} with validate_command",
+                    save_path.display()
+                );
+                let (status, output) =
+                    process::run_hook(&mut command, "validate_command", VALIDATE_TIMEOUT)?;
+                if status.success() {
+                    continue;
+                }
+                // The last line printed usually says what is wrong
+                let output = String::from_utf8_lossy(&output);
+                let problem = match output.lines().rev().find(|line| !line.trim().is_empty()) {
+                    Some(line) => {
+                        format!("validate_command exited with {}: {}", status, line.trim())
+                    }
+                    None => format!("validate_command exited with {}", status),
+                };
+                log::warn!(
+                    "Backup of {} failed validation: {}",
+                    save_path.display(),
+                    problem
+                );
+                issues.push(ValidationIssue {
+                    path: save_path.display().to_string(),
+                    problem,
+                });
+            }
+            Ok(issues)
+        };
+        let result = run_hooks();
+        if let Some(sandbox) = &sandbox
+            && let Err(e) = fs::remove_dir_all(sandbox)
+        {
+            log::warn!(
+                "Failed to remove validation directory: {}: {}",
+                sandbox.display(),
+                e
+            );
+        }
+        let issues = result?;
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        match game.on_invalid {
+            InvalidSavePolicy::Fail => Err(anyhow!(
+                "{} save location(s) of '{}' failed their validate_command, keeping the previous \
+                 backup (set on_invalid = \"tag\" to keep it anyway)",
+                issues.len(),
+                game.name
+            )),
+            InvalidSavePolicy::Tag => {
+                log::warn!(
+                    "Keeping the backup of '{}' despite {} failed validate_command(s), tagging it",
+                    game.name,
+                    issues.len()
+                );
+                manifest.validation_issues.extend(issues);
+                manifest.save(staging_dir)
+            }
+        }
+    }
+
+    /// Restores a staged archive backup into a directory next to it, location `i` into `i`.
+    fn extract_for_validation(&self, game: &Game, staging_dir: &Path) -> Result<PathBuf> {
+        let sandbox = self
+            .backup_root
+            .join(STAGING_DIR)
+            .join(format!("{}.validate", game.name));
+        if sandbox.exists() {
+            fs::remove_dir_all(&sandbox).with_context(|| {
+                format!(
+                    "Failed to clean validation directory: {}",
+                    sandbox.display()
+                )
+            })?;
+        }
+        fs::create_dir_all(&sandbox).with_context(|| {
+            format!(
+                "Failed to create validation directory: {}",
+                sandbox.display()
+            )
+        })?;
+        let options = RestoreOptions {
+            on_conflict: ConflictPolicy::Overwrite,
+            ..RestoreOptions::default()
+        };
+        self.restore_locations(game, staging_dir, &options, Some(&sandbox), &mut Vec::new())?;
+        Ok(sandbox)
+    }
+}
+
+/// Runs a single check. The outer error is for problems with the check itself,
+/// the inner one describes why the file is considered corrupted.
+fn run_check(check: &Check, path: &Path) -> Result<std::result::Result<(), String>> {
+    match check {
+        Check::NonEmpty => {
+            let metadata = fs::metadata(path)
+                .with_context(|| format!("Failed to read metadata: {}", path.display()))?;
+            if metadata.len() == 0 {
+                return Ok(Err("file is empty".to_string()));
+            }
+            Ok(Ok(()))
+        }
+        Check::Magic { magic, offset } => {
+            let expected = parse_hex(magic)?;
+            let mut file = File::open(path)
+                .with_context(|| format!("Failed to open file: {}", path.display()))?;
+            let mut actual = vec![0; expected.len()];
+            let read = file
+                .seek(SeekFrom::Start(*offset))
+                .and_then(|_| file.read_exact(&mut actual));
+            if read.is_err() || actual != expected {
+                return Ok(Err(format!(
+                    "expected magic bytes {} at offset {}",
+                    magic, offset
+                )));
+            }
+            Ok(Ok(()))
+        }
+        Check::Sqlite => sqlite_integrity_check(path),
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_integrity_check(path: &Path) -> Result<std::result::Result<(), String>> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let connection = match Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY) {
+        Ok(connection) => connection,
+        Err(e) => return Ok(Err(format!("not a readable SQLite database: {}", e))),
+    };
+
+    let result = connection
+        .prepare("PRAGMA integrity_check")
+        .and_then(|mut statement| {
+            statement
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()
+        });
+    match result {
+        Ok(messages) if messages == ["ok"] => Ok(Ok(())),
+        Ok(messages) => Ok(Err(format!(
+            "SQLite integrity check failed: {}",
+            messages.join("; ")
+        ))),
+        Err(e) => Ok(Err(format!("SQLite integrity check failed: {}", e))),
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn sqlite_integrity_check(_path: &Path) -> Result<std::result::Result<(), String>> {
+    Err(anyhow!(
+        "SQLite validation is not compiled into this build. Recompile with `--features sqlite`"
+    ))
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>> {
+    let digits: String = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.is_empty() || !digits.is_ascii() || !digits.len().is_multiple_of(2) {
+        return Err(anyhow!("Invalid magic bytes '{}': expected hex pairs", hex));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| anyhow!("Invalid magic bytes '{}': expected hex pairs", hex))
+        })
+        .collect()
+}
@@ -0,0 +1,192 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use crate::chain::ChainReport;
+use crate::durable;
+use crate::manifest::{self, Manifest};
+use crate::{GameBackup, archive, archive_path, stored_format, volume};
+
+#[derive(Debug, Serialize)]
+pub struct VerifyReport {
+    pub game: String,
+    pub checked: usize,
+    pub missing: Vec<String>,
+    pub corrupted: Vec<String>,
+    /// Result of `verify --chain`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain: Option<ChainReport>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty()
+            && self.corrupted.is_empty()
+            && self.chain.as_ref().is_none_or(ChainReport::is_ok)
+    }
+}
+
+impl GameBackup {
+    /// Checks every file recorded in a game's manifest against the stored backup.
+    pub fn verify_game(&self, game_name: &str) -> Result<VerifyReport> {
+        log::info!("Verifying backup for game: {}", game_name);
+
+        let game = self.find_game(game_name)?;
+        let game_backup_dir = self.backup_root.join(&game.name);
+        if !game_backup_dir.exists() {
+            return Err(anyhow!("No backup found for game: {}", game_name));
+        }
+        verify_dir(&game.name, &game_backup_dir)
+    }
+}
+
+/// Checks the files of a backup directory, the current backup or a snapshot, against its
+/// manifest.
+pub(crate) fn verify_dir(game_name: &str, game_backup_dir: &Path) -> Result<VerifyReport> {
+    let manifest = Manifest::load(game_backup_dir)?.ok_or_else(|| {
+        anyhow!(
+            "No manifest found for game '{}'. Run a backup to create one",
+            game_name
+        )
+    })?;
+
+    let mut report = VerifyReport {
+        game: game_name.to_string(),
+        checked: 0,
+        missing: Vec::new(),
+        corrupted: Vec::new(),
+        chain: None,
+    };
+
+    match stored_format(game_backup_dir, Some(&manifest)).archive() {
+        None => {
+            for (key, entry) in &manifest.files {
+                report.checked += 1;
+                let path = game_backup_dir.join(manifest::key_path(key));
+                log::debug!("Verifying file: {}", path.display());
+
+                let Ok(metadata) = fs::metadata(&path) else {
+                    report.missing.push(key.clone());
+                    continue;
+                };
+                if metadata.len() != entry.size || manifest::hash_file(&path)? != entry.hash {
+                    report.corrupted.push(key.clone());
+                }
+            }
+        }
+        Some(archive_format) => {
+            let archive_path = archive_path(game_backup_dir, archive_format);
+            if !volume::exists(&archive_path) {
+                return Err(anyhow!(
+                    "Backup archive does not exist: {}",
+                    archive_path.display()
+                ));
+            }
+
+            let dictionary = archive::read_dictionary(game_backup_dir)?;
+            let stored: HashMap<String, (u64, String)> =
+                archive::hash_files(&archive_path, archive_format, dictionary.as_deref())?
+                    .into_iter()
+                    .map(|(name, size, hash)| (manifest::entry_key(&name), (size, hash)))
+                    .collect();
+
+            for (key, entry) in &manifest.files {
+                report.checked += 1;
+                match stored.get(key) {
+                    None => report.missing.push(key.clone()),
+                    Some((size, hash)) if *size != entry.size || *hash != entry.hash => {
+                        report.corrupted.push(key.clone())
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+
+    log::info!(
+        "Verified {} files for '{}': {} missing, {} corrupted",
+        report.checked,
+        report.game,
+        report.missing.len(),
+        report.corrupted.len()
+    );
+    Ok(report)
+}
+
+/// Metric name, help text and how to read the value from a report.
+type Gauge = (&'static str, &'static str, fn(&VerifyReport) -> usize);
+
+/// Renders verification results in the Prometheus text exposition format.
+pub fn prometheus_metrics(reports: &[VerifyReport]) -> String {
+    let mut metrics = String::new();
+    let gauges: [Gauge; 4] = [
+        (
+            "cartridge_verify_files_checked",
+            "Files recorded in the manifest of the latest backup",
+            |report| report.checked,
+        ),
+        (
+            "cartridge_verify_files_missing",
+            "Files recorded in the manifest but missing from the backup",
+            |report| report.missing.len(),
+        ),
+        (
+            "cartridge_verify_files_corrupted",
+            "Files whose size or hash differs from the manifest",
+            |report| report.corrupted.len(),
+        ),
+        (
+            "cartridge_verify_ok",
+            "Whether every file of the backup verified correctly",
+            |report| usize::from(report.is_ok()),
+        ),
+    ];
+
+    for (name, help, value) in gauges {
+        let _ = writeln!(metrics, "# HELP {} {}", name, help);
+        let _ = writeln!(metrics, "# TYPE {} gauge", name);
+        for report in reports {
+            let _ = writeln!(
+                metrics,
+                "{}{{game=\"{}\"}} {}",
+                name,
+                escape_label(&report.game),
+                value(report)
+            );
+        }
+    }
+
+    let _ = writeln!(
+        metrics,
+        "# HELP cartridge_verify_last_run_timestamp_seconds Time the verification finished"
+    );
+    let _ = writeln!(
+        metrics,
+        "# TYPE cartridge_verify_last_run_timestamp_seconds gauge"
+    );
+    let _ = writeln!(
+        metrics,
+        "cartridge_verify_last_run_timestamp_seconds {}",
+        Utc::now().timestamp()
+    );
+    metrics
+}
+
+/// Writes the metrics for the node_exporter textfile collector. The file is replaced
+/// atomically so the collector never reads a partial file, and the temporary file does not
+/// end in `.prom`, so the collector does not read it either.
+pub fn write_metrics(path: &Path, reports: &[VerifyReport]) -> Result<()> {
+    durable::write_atomic(path, prometheus_metrics(reports))
+        .with_context(|| format!("Failed to write metrics: {}", path.display()))
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,171 @@
+// Archives are only ever read through these with an archive feature enabled
+#![cfg_attr(not(any(feature = "zip", feature = "tar-zst")), allow(dead_code))]
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Path of a volume of a split archive: `saves.tar.zst.001`, `saves.tar.zst.002`, ...
+pub fn volume_path(archive: &Path, index: usize) -> PathBuf {
+    let mut path = archive.as_os_str().to_owned();
+    path.push(format!(".{:03}", index + 1));
+    PathBuf::from(path)
+}
+
+/// Whether an archive exists, either as a single file or split into volumes.
+pub fn exists(archive: &Path) -> bool {
+    archive.exists() || volume_path(archive, 0).exists()
+}
+
+/// Writes an archive as a single file, or split into volumes of at most `volume_size` bytes.
+/// Seeking is supported across volumes so zip archives can be split too.
+pub struct VolumeWriter {
+    archive: PathBuf,
+    volume_size: Option<u64>,
+    volumes: Vec<File>,
+    position: u64,
+}
+
+impl VolumeWriter {
+    pub fn create(archive: &Path, volume_size: Option<u64>) -> io::Result<Self> {
+        let mut writer = Self {
+            archive: archive.to_path_buf(),
+            volume_size,
+            volumes: Vec::new(),
+            position: 0,
+        };
+        writer.volume(0)?;
+        Ok(writer)
+    }
+
+    fn volume(&mut self, index: usize) -> io::Result<&mut File> {
+        while self.volumes.len() <= index {
+            let path = match self.volume_size {
+                Some(_) => volume_path(&self.archive, self.volumes.len()),
+                None => self.archive.clone(),
+            };
+            log::debug!("Creating archive volume: {}", path.display());
+            self.volumes.push(File::create(path)?);
+        }
+        Ok(&mut self.volumes[index])
+    }
+}
+
+impl Write for VolumeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let volume_size = self.volume_size.unwrap_or(u64::MAX);
+        let index = (self.position / volume_size) as usize;
+        let offset = self.position % volume_size;
+        let len = buf.len().min((volume_size - offset) as usize);
+
+        let volume = self.volume(index)?;
+        volume.seek(SeekFrom::Start(offset))?;
+        let written = volume.write(&buf[..len])?;
+        self.position += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        for volume in &mut self.volumes {
+            volume.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Seek for VolumeWriter {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let len = match self.volume_size {
+            Some(volume_size) => self
+                .volumes
+                .iter()
+                .enumerate()
+                .map(|(i, volume)| Ok(i as u64 * volume_size + volume.metadata()?.len()))
+                .collect::<io::Result<Vec<_>>>()?
+                .into_iter()
+                .max()
+                .unwrap_or(0),
+            None => self.volumes[0].metadata()?.len(),
+        };
+        self.position = resolve(pos, self.position, len)?;
+        Ok(self.position)
+    }
+}
+
+/// Reads an archive stored as a single file or as consecutive volumes, as one stream.
+pub struct VolumeReader {
+    volumes: Vec<(File, u64)>,
+    position: u64,
+    len: u64,
+}
+
+impl VolumeReader {
+    pub fn open(archive: &Path) -> io::Result<Self> {
+        let paths = if archive.exists() {
+            vec![archive.to_path_buf()]
+        } else {
+            (0..)
+                .map(|index| volume_path(archive, index))
+                .take_while(|path| path.exists())
+                .collect()
+        };
+        if paths.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Archive not found: {}", archive.display()),
+            ));
+        }
+
+        let mut volumes = Vec::new();
+        for path in paths {
+            let file = File::open(&path)?;
+            let len = file.metadata()?.len();
+            volumes.push((file, len));
+        }
+        let len = volumes.iter().map(|(_, len)| len).sum();
+        Ok(Self {
+            volumes,
+            position: 0,
+            len,
+        })
+    }
+}
+
+impl Read for VolumeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut start = 0;
+        for (volume, len) in &mut self.volumes {
+            if self.position < start + *len {
+                let offset = self.position - start;
+                let max = buf.len().min((*len - offset) as usize);
+                volume.seek(SeekFrom::Start(offset))?;
+                let read = volume.read(&mut buf[..max])?;
+                self.position += read as u64;
+                return Ok(read);
+            }
+            start += *len;
+        }
+        Ok(0)
+    }
+}
+
+impl Seek for VolumeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = resolve(pos, self.position, self.len)?;
+        Ok(self.position)
+    }
+}
+
+fn resolve(pos: SeekFrom, position: u64, len: u64) -> io::Result<u64> {
+    let target = match pos {
+        SeekFrom::Start(offset) => Some(offset),
+        SeekFrom::Current(delta) => position.checked_add_signed(delta),
+        SeekFrom::End(delta) => len.checked_add_signed(delta),
+    };
+    target.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Invalid seek to a negative or overflowing position",
+        )
+    })
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "tar-zst")]
+
+use cartridge::BackupFormat;
+use cartridge::archive::{ArchiveFormat, ArchiveWriter};
+use cartridge::fixture::{FixtureSpec, GameFixture};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> GameFixture {
+    let dir = std::env::temp_dir().join(format!(
+        "cartridge-archives-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    let spec = FixtureSpec {
+        files: 3,
+        special_names: false,
+        non_utf8_names: false,
+        ..FixtureSpec::default()
+    };
+    GameFixture::create(&dir, name, &spec, BackupFormat::Directory).unwrap()
+}
+
+/// Writes an archive like the ones `export-diff` and `share` write, with `description`
+/// holding `json` and the given files, by `<location>/<path>`, in the game's directory.
+fn craft_archive(
+    fixture: &GameFixture,
+    description: &str,
+    json: &str,
+    files: &[(&str, &str)],
+) -> PathBuf {
+    let staging = fixture.root.join("crafted");
+    fs::create_dir_all(&staging).unwrap();
+    let output = fixture.root.join("crafted.tar.zst");
+    let mut writer = ArchiveWriter::create(&output, ArchiveFormat::TarZst, None, None).unwrap();
+    let game_dir = Path::new(&fixture.name);
+    let description_file = staging.join(description);
+    fs::write(&description_file, json).unwrap();
+    writer
+        .add_file(&description_file, &game_dir.join(description))
+        .unwrap();
+    for (i, (name, content)) in files.iter().enumerate() {
+        let source = staging.join(format!("file{}", i));
+        fs::write(&source, content).unwrap();
+        writer.add_file(&source, &game_dir.join(name)).unwrap();
+    }
+    writer.finish().unwrap();
+    output
+}
+
+fn delta_json(fixture: &GameFixture, changed: &[&str], removed: &[&str], hashes: &str) -> String {
+    format!(
+        r#"{{"game": "{}", "from": "a", "to": "b", "changed": {:?}, "removed": {:?}, "hashes": {}}}"#,
+        fixture.name, changed, removed, hashes
+    )
+}
+
+#[test]
+fn importing_a_delta_refuses_paths_outside_the_saves() {
+    let fixture = fixture("delta-traversal");
+    let victim = fixture.root.join("victim.txt");
+    fs::write(&victim, "keep me").unwrap();
+    let json = delta_json(&fixture, &[], &["0/../victim.txt"], "{}");
+    let archive = craft_archive(&fixture, "delta.json", &json, &[]);
+
+    let result = fixture
+        .game_backup()
+        .and_then(|game_backup| game_backup.import_diff(&archive, true, false));
+    let kept = victim.exists();
+    let _ = fs::remove_dir_all(&fixture.root);
+    let error = format!("{:#}", result.unwrap_err());
+    assert!(error.contains("Invalid path in archive"), "{}", error);
+    assert!(kept, "a file outside the saves was removed");
+}
+
+#[test]
+fn importing_a_delta_checks_file_hashes() {
+    let fixture = fixture("delta-hash");
+    let json = delta_json(&fixture, &["0/new.sav"], &[], r#"{"0/new.sav": "00"}"#);
+    let archive = craft_archive(&fixture, "delta.json", &json, &[("0/new.sav", "altered")]);
+
+    let result = fixture
+        .game_backup()
+        .and_then(|game_backup| game_backup.import_diff(&archive, true, false));
+    let written = fixture.save_dir.join("new.sav").exists();
+    let _ = fs::remove_dir_all(&fixture.root);
+    let error = format!("{:#}", result.unwrap_err());
+    assert!(error.contains("does not match its hash"), "{}", error);
+    assert!(!written, "a file that failed its hash check was applied");
+}
+
+fn share_json(fixture: &GameFixture, files: &str) -> String {
+    format!(
+        r#"{{"game": "{}", "id": "b", "parent": null, "shared_at": "2026-01-01T00:00:00Z", "files": {}}}"#,
+        fixture.name, files
+    )
+}
+
+#[test]
+fn receiving_saves_refuses_paths_outside_the_saves() {
+    let fixture = fixture("share-traversal");
+    let json = share_json(&fixture, r#"{"0/a/../../evil.txt": "00"}"#);
+    let archive = craft_archive(&fixture, "share.json", &json, &[("0/evil.txt", "evil")]);
+
+    let result = fixture
+        .game_backup()
+        .and_then(|game_backup| game_backup.receive_saves(&archive, true, false));
+    let written = fixture.root.join("evil.txt").exists();
+    let _ = fs::remove_dir_all(&fixture.root);
+    let error = format!("{:#}", result.unwrap_err());
+    assert!(error.contains("Invalid path in archive"), "{}", error);
+    assert!(!written, "a file outside the saves was written");
+}
+
+#[test]
+fn receiving_saves_checks_file_hashes() {
+    let fixture = fixture("share-hash");
+    let json = share_json(&fixture, r#"{"0/received.sav": "00"}"#);
+    let archive = craft_archive(
+        &fixture,
+        "share.json",
+        &json,
+        &[("0/received.sav", "altered")],
+    );
+
+    let result = fixture
+        .game_backup()
+        .and_then(|game_backup| game_backup.receive_saves(&archive, true, false));
+    let written = fixture.save_dir.join("received.sav").exists();
+    let _ = fs::remove_dir_all(&fixture.root);
+    let error = format!("{:#}", result.unwrap_err());
+    assert!(error.contains("does not match its hash"), "{}", error);
+    assert!(!written, "a file that failed its hash check was received");
+}
@@ -0,0 +1,80 @@
+use cartridge::BackupFormat;
+use cartridge::fixture::{FixtureSpec, GameFixture};
+use cartridge::plan::{ActionKind, ConflictPolicy, ConflictPrompt, RestoreOptions};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// A backed up game whose first save was changed since, so restoring it conflicts.
+fn conflicting_fixture(name: &str) -> (GameFixture, PathBuf) {
+    let dir = std::env::temp_dir().join(format!(
+        "cartridge-conflicts-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    let spec = FixtureSpec {
+        files: 3,
+        special_names: false,
+        non_utf8_names: false,
+        ..FixtureSpec::default()
+    };
+    let fixture = GameFixture::create(&dir, name, &spec, BackupFormat::Directory).unwrap();
+    fixture
+        .game_backup()
+        .unwrap()
+        .backup_game(&fixture.name, None)
+        .unwrap();
+    let changed = fixture.save_dir.join("save0.dat");
+    fs::write(&changed, "changed since the backup").unwrap();
+    (fixture, changed)
+}
+
+#[test]
+fn asking_without_a_prompt_fails() {
+    let (fixture, changed) = conflicting_fixture("no-prompt");
+    let options = RestoreOptions {
+        on_conflict: ConflictPolicy::Ask,
+        ..RestoreOptions::default()
+    };
+
+    let result = fixture
+        .game_backup()
+        .and_then(|game_backup| game_backup.restore_game(&fixture.name, &options));
+    let content = fs::read_to_string(&changed).unwrap();
+    let _ = fs::remove_dir_all(&fixture.root);
+    let error = format!("{:#}", result.unwrap_err());
+    assert!(error.contains("no prompt was given"), "{}", error);
+    assert_eq!(content, "changed since the backup");
+}
+
+#[test]
+fn the_prompt_decides_each_conflict() {
+    let (fixture, changed) = conflicting_fixture("prompt");
+    let asked = Arc::new(Mutex::new(Vec::new()));
+    let options = RestoreOptions {
+        on_conflict: ConflictPolicy::Ask,
+        prompt: Some(ConflictPrompt::new({
+            let asked = Arc::clone(&asked);
+            move |dest| {
+                asked.lock().unwrap().push(dest.to_path_buf());
+                Ok(ConflictPolicy::BackupExisting)
+            }
+        })),
+        ..RestoreOptions::default()
+    };
+
+    let report = fixture
+        .game_backup()
+        .and_then(|game_backup| game_backup.restore_game(&fixture.name, &options));
+    let stashed = report.as_ref().ok().and_then(|report| {
+        report.actions.iter().find_map(|action| match &action.kind {
+            ActionKind::BackupExisting { stash } => fs::read_to_string(stash).ok(),
+            _ => None,
+        })
+    });
+    let _ = fs::remove_dir_all(&fixture.root);
+    report.unwrap();
+    assert_eq!(*asked.lock().unwrap(), vec![changed]);
+    assert_eq!(stashed.as_deref(), Some("changed since the backup"));
+}
@@ -0,0 +1,121 @@
+use cartridge::GameBackup;
+use cartridge::plan::{ConflictPolicy, RestoreOptions};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const EMOJI_NAME: &str = "💾 slot 🎮.sav";
+
+#[cfg(unix)]
+fn non_utf8_name() -> std::ffi::OsString {
+    use std::os::unix::ffi::OsStringExt;
+    std::ffi::OsString::from_vec(b"latin1-\xe9t\xe9.sav".to_vec())
+}
+
+/// Writes a config for a game backed up in `format`, and its saves: a plain file, and a file
+/// named `save` both at the top and in a subdirectory.
+fn game_with_save(name: &str, format: &str, save: &OsStr) -> PathBuf {
+    let root = std::env::temp_dir().join(format!(
+        "cartridge-file-names-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&root);
+    let save_dir = root.join("saves");
+    fs::create_dir_all(save_dir.join("sub")).unwrap();
+    fs::write(save_dir.join("plain.sav"), "plain").unwrap();
+    fs::write(save_dir.join(save), "top").unwrap();
+    fs::write(save_dir.join("sub").join(save), "nested").unwrap();
+    let config = format!(
+        "backup_root = \"backup\"\nformat = \"{}\"\n\n\
+         [[game]]\nname = \"{}\"\n\n[[game.save]]\npath = {}\n",
+        format,
+        name,
+        toml::Value::from(save_dir.to_string_lossy().as_ref())
+    );
+    fs::write(root.join("cartridge.toml"), config).unwrap();
+    root
+}
+
+/// Content of every file below `dir`, by path relative to it.
+fn read_tree(dir: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+    let mut files = BTreeMap::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        for entry in fs::read_dir(dir.join(&relative)).unwrap() {
+            let entry = entry.unwrap();
+            let path = relative.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                pending.push(path);
+            } else {
+                files.insert(path, fs::read(entry.path()).unwrap());
+            }
+        }
+    }
+    files
+}
+
+/// Backs the game up, deletes its saves, restores them and checks that every file came back
+/// under the same name.
+fn round_trip(name: &str, format: &str, save: &OsStr) {
+    let root = game_with_save(name, format, save);
+    let save_dir = root.join("saves");
+    let before = read_tree(&save_dir);
+    let result = GameBackup::new(&root.join("cartridge.toml")).and_then(|game_backup| {
+        game_backup.backup_game(name, None)?;
+        fs::remove_dir_all(&save_dir)?;
+        let options = RestoreOptions {
+            on_conflict: ConflictPolicy::Overwrite,
+            ..RestoreOptions::default()
+        };
+        game_backup.restore_game(name, &options)
+    });
+    let after = result.map(|_| read_tree(&save_dir));
+    let _ = fs::remove_dir_all(&root);
+    assert_eq!(after.unwrap(), before);
+}
+
+#[test]
+fn directory_backup_keeps_emoji_names() {
+    round_trip("emoji-dir", "dir", OsStr::new(EMOJI_NAME));
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn zip_backup_keeps_emoji_names() {
+    round_trip("emoji-zip", "zip", OsStr::new(EMOJI_NAME));
+}
+
+#[cfg(feature = "tar-zst")]
+#[test]
+fn tar_zst_backup_keeps_emoji_names() {
+    round_trip("emoji-tar-zst", "tar.zst", OsStr::new(EMOJI_NAME));
+}
+
+#[cfg(unix)]
+#[test]
+fn directory_backup_keeps_non_utf8_names() {
+    round_trip("non-utf8-dir", "dir", &non_utf8_name());
+}
+
+#[cfg(all(unix, feature = "tar-zst"))]
+#[test]
+fn tar_zst_backup_keeps_non_utf8_names() {
+    round_trip("non-utf8-tar-zst", "tar.zst", &non_utf8_name());
+}
+
+#[cfg(all(unix, feature = "zip"))]
+#[test]
+fn zip_backup_rejects_non_utf8_names() {
+    let root = game_with_save("non-utf8-zip", "zip", &non_utf8_name());
+    let result = GameBackup::new(&root.join("cartridge.toml"))
+        .and_then(|game_backup| game_backup.backup_game("non-utf8-zip", None));
+    let _ = fs::remove_dir_all(&root);
+    let error = format!("{:#}", result.unwrap_err());
+    assert!(
+        error.contains("File name is not valid UTF-8 and cannot be stored in a zip archive"),
+        "{}",
+        error
+    );
+}
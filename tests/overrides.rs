@@ -0,0 +1,126 @@
+use cartridge::{ConfigOverride, GameBackup};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn setting(s: &str) -> ConfigOverride {
+    s.parse().unwrap()
+}
+
+/// Writes `config` into a fresh directory, with `{saves}` replaced by a save directory in it.
+fn write_config(name: &str, config: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!(
+        "cartridge-overrides-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    let saves = toml::Value::from(root.join("saves").to_string_lossy().as_ref()).to_string();
+    let config = config.replace("{saves}", saves.trim_matches('"'));
+    fs::write(root.join("cartridge.toml"), config).unwrap();
+    root
+}
+
+fn load(root: &Path, settings: &[&str]) -> anyhow::Result<GameBackup> {
+    let overrides: Vec<ConfigOverride> = settings.iter().map(|s| setting(s)).collect();
+    GameBackup::with_overrides(&root.join("cartridge.toml"), &overrides)
+}
+
+#[test]
+fn settings_are_read_as_key_and_value() {
+    assert_eq!(setting("keep_snapshots=5").to_string(), "keep_snapshots=5");
+    assert_eq!(
+        setting(" game . Skyrim SE . enabled =false").to_string(),
+        "game.Skyrim SE.enabled=false"
+    );
+    // Only the first `=` separates the value
+    assert_eq!(setting("var.query=a=b").to_string(), "var.query=a=b");
+}
+
+#[test]
+fn quoted_key_parts_keep_dots_and_equals_signs() {
+    let quoted = setting(r#"game."v1.2 = beta".enabled=false"#);
+    assert_eq!(quoted.to_string(), r#"game."v1.2 = beta".enabled=false"#);
+    // What is displayed parses back to the same setting
+    assert_eq!(setting(&quoted.to_string()).to_string(), quoted.to_string());
+}
+
+#[test]
+fn invalid_settings_are_refused() {
+    for invalid in [
+        "keep_snapshots",
+        "=5",
+        "game..enabled=false",
+        r#"game."open.enabled=false"#,
+    ] {
+        assert!(
+            invalid.parse::<ConfigOverride>().is_err(),
+            "accepted: {}",
+            invalid
+        );
+    }
+}
+
+const GAME: &str = r#"backup_root = "backup"
+
+[[game]]
+name = "v1.2 beta"
+launch_cmd = "old-launcher"
+
+[[game.save]]
+path = "{saves}/${slot}"
+
+[[var]]
+name = "slot"
+value = "default"
+"#;
+
+#[test]
+fn game_settings_apply_to_the_named_game() {
+    let root = write_config("game", GAME);
+    let loaded = load(&root, &[r#"game."v1.2 beta".enabled=false"#]);
+    let missing = load(&root, &["game.Unknown.enabled=false"]);
+    let _ = fs::remove_dir_all(&root);
+    assert!(!loaded.unwrap().list_all_games()[0].enabled);
+    let error = format!("{:#}", missing.err().unwrap());
+    assert!(error.contains("game 'Unknown' not found"), "{}", error);
+}
+
+#[test]
+fn variables_are_set_as_strings() {
+    let root = write_config("var", GAME);
+    // `001` would be the integer 1 if it were read as TOML
+    let loaded = load(&root, &["var.slot=001"]);
+    let _ = fs::remove_dir_all(&root);
+    let checks = loaded.unwrap().doctor();
+    let path = checks[0].locations[0].path.as_ref().unwrap();
+    assert_eq!(path.file_name().unwrap(), "001");
+}
+
+#[test]
+fn launch_aliases_replace_each_other() {
+    let root = write_config("launch", GAME);
+    let loaded = load(&root, &[r#"game."v1.2 beta".launch=new-launcher"#]);
+    let _ = fs::remove_dir_all(&root);
+    let game_backup = loaded.unwrap();
+    let launch = format!("{:?}", game_backup.list_all_games()[0].launch);
+    assert!(
+        launch.contains("new-launcher") && !launch.contains("old-launcher"),
+        "{}",
+        launch
+    );
+}
+
+#[test]
+fn locked_configs_cannot_be_unlocked() {
+    let root = write_config("locked", &format!("locked = true\n{}", GAME));
+    let unlocked = load(&root, &["locked=false"]);
+    let _ = fs::remove_dir_all(&root);
+    let error = format!("{:#}", unlocked.err().unwrap());
+    assert!(error.contains("can only be set to true"), "{}", error);
+
+    let root = write_config("lock", GAME);
+    let locked = load(&root, &["locked=true"]);
+    let _ = fs::remove_dir_all(&root);
+    assert!(locked.unwrap().is_locked());
+}
@@ -0,0 +1,308 @@
+use cartridge::GameBackup;
+use cartridge::paths::{
+    USER_HOME, anonymize_unix_path, anonymize_windows_path, normalize, substitute_variables,
+};
+use cartridge::plan::{ConflictPolicy, RestoreOptions};
+use proptest::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+fn expand(value: &str, variables: &HashMap<String, OsString>) -> anyhow::Result<OsString> {
+    substitute_variables(
+        value,
+        |name| variables.get(name).map(OsString::as_os_str),
+        |value| Ok(value.to_os_string()),
+    )
+}
+
+/// Plain path components, never `.`, `..` or anything with a separator.
+fn name() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9 _.\\-ü]{1,12}".prop_filter("not a special component", |name| {
+        name != "." && name != ".."
+    })
+}
+
+/// Windows path as text with its drive letter in either case, along with the lowercase
+/// drive letter and the names in it.
+fn windows_path() -> impl Strategy<Value = (char, Vec<String>, String)> {
+    (
+        prop::char::range('a', 'z'),
+        prop::collection::vec(name(), 0..6),
+        prop::bool::ANY,
+    )
+        .prop_map(|(drive, names, upper)| {
+            let drive = if upper {
+                drive.to_ascii_uppercase()
+            } else {
+                drive
+            };
+            let path = format!("{}:\\{}", drive, names.join("\\"));
+            (drive.to_ascii_lowercase(), names, path)
+        })
+}
+
+fn absolute_path() -> impl Strategy<Value = PathBuf> {
+    prop::collection::vec(name(), 0..6).prop_map(|names| {
+        let mut path = PathBuf::from("/");
+        path.extend(names);
+        path
+    })
+}
+
+proptest! {
+    #[test]
+    fn expansion_never_panics(value in ".*", name in "[a-z]{1,8}", var in ".*") {
+        let variables = HashMap::from([(name, OsString::from(var))]);
+        let _ = expand(&value, &variables);
+    }
+
+    #[test]
+    fn text_without_variables_is_kept(value in "[^$]*") {
+        prop_assert_eq!(expand(&value, &HashMap::new()).unwrap(), OsString::from(value));
+    }
+
+    #[test]
+    fn variables_expand_to_their_value(
+        before in "[^$]*",
+        after in "[^$]*",
+        name in "[a-z_]{1,8}",
+        var in ".*",
+    ) {
+        let variables = HashMap::from([(name.clone(), OsString::from(&var))]);
+        let expanded = expand(&format!("{before}${{{name}}}{after}"), &variables).unwrap();
+        prop_assert_eq!(expanded, OsString::from(format!("{before}{var}{after}")));
+    }
+
+    #[test]
+    fn undefined_variables_are_errors(name in "[a-z_]{1,8}") {
+        let value = format!("${{{name}}}");
+        prop_assert!(expand(&value, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn escaped_values_match_themselves_as_patterns(var in ".*") {
+        let pattern = substitute_variables(
+            "${value}",
+            |_| Some(OsStr::new(&var)),
+            |value| Ok(glob::Pattern::escape(value.to_str().unwrap()).into()),
+        )
+        .unwrap();
+        let pattern = glob::Pattern::new(pattern.to_str().unwrap()).unwrap();
+        prop_assert!(pattern.matches(&var));
+    }
+
+    #[test]
+    fn anonymized_paths_stay_relative(
+        path in "[/a-z.]{0,30}",
+        home in absolute_path(),
+    ) {
+        let anonymized = anonymize_unix_path(Path::new(&path), Some(&home));
+        prop_assert!(anonymized
+            .components()
+            .all(|component| matches!(component, Component::Normal(_))));
+    }
+
+    #[test]
+    fn paths_below_home_map_back(home in absolute_path(), names in prop::collection::vec(name(), 0..6)) {
+        let path = home.join(names.iter().collect::<PathBuf>());
+        let anonymized = anonymize_unix_path(&path, Some(&home));
+        let relative = anonymized.strip_prefix(USER_HOME).unwrap();
+        prop_assert_eq!(normalize(&home.join(relative)), normalize(&path));
+    }
+
+    #[test]
+    fn paths_outside_home_map_back(path in absolute_path(), home in absolute_path()) {
+        prop_assume!(!path.starts_with(&home));
+        let anonymized = anonymize_unix_path(&path, Some(&home));
+        prop_assert_eq!(Path::new("/").join(anonymized), path);
+    }
+
+    #[test]
+    fn different_paths_stay_apart(
+        first in absolute_path(),
+        second in absolute_path(),
+        home in absolute_path(),
+    ) {
+        // The one known overlap, see anonymize_unix_path
+        let in_user_home_dir = |path: &Path| !path.starts_with(&home) && path.starts_with(
+            Path::new("/").join(USER_HOME),
+        );
+        prop_assume!(first != second && !in_user_home_dir(&first) && !in_user_home_dir(&second));
+        prop_assert_ne!(
+            anonymize_unix_path(&first, Some(&home)),
+            anonymize_unix_path(&second, Some(&home))
+        );
+    }
+
+    #[test]
+    fn windows_paths_never_panic(path in "([a-zA-Z]:)?[\\\\/a-z.]{0,30}", profile in ".*") {
+        if let Some(anonymized) = anonymize_windows_path(&path, Some(&profile)) {
+            prop_assert!(anonymized
+                .components()
+                .all(|component| matches!(component, Component::Normal(_))));
+        }
+    }
+
+    #[test]
+    fn windows_paths_below_the_profile_map_back(
+        (drive, _, profile_path) in windows_path(),
+        names in prop::collection::vec(name(), 0..6),
+    ) {
+        // Below the profile, only another `Users\<name>` is anonymized
+        prop_assume!(!names.iter().any(|name| name.eq_ignore_ascii_case("Users")));
+        // The profile is matched ignoring case, like Windows does
+        let path = format!("{}\\{}", profile_path.to_uppercase(), names.join("/"));
+        let anonymized = anonymize_windows_path(&path, Some(&profile_path)).unwrap();
+        let mut expected = PathBuf::from(format!("drive_{}", drive));
+        expected.extend(["Users", USER_HOME]);
+        expected.extend(&names);
+        prop_assert_eq!(anonymized, expected);
+    }
+
+    #[test]
+    fn windows_paths_outside_user_folders_map_back(
+        (drive, names, path) in windows_path(),
+        (_, _, profile) in windows_path(),
+    ) {
+        prop_assume!(!names.iter().any(|name| name.eq_ignore_ascii_case("Users")));
+        prop_assume!(!path.to_lowercase().starts_with(&profile.to_lowercase()));
+        let anonymized = anonymize_windows_path(&path, Some(&profile)).unwrap();
+        let mut expected = PathBuf::from(format!("drive_{}", drive));
+        expected.extend(&names);
+        prop_assert_eq!(anonymized, expected);
+    }
+
+    #[test]
+    fn other_users_share_the_user_folder(
+        (_, names, path) in windows_path(),
+        first in name(),
+        second in name(),
+    ) {
+        let first_path = format!("{}\\Users\\{}", path, first);
+        let second_path = format!("{}/users/{}", path, second);
+        prop_assert_eq!(
+            anonymize_windows_path(&first_path, None),
+            anonymize_windows_path(&second_path, None)
+        );
+        prop_assume!(!names.iter().any(|name| name.eq_ignore_ascii_case("Users")));
+        let anonymized = anonymize_windows_path(&first_path, None).unwrap();
+        prop_assert!(anonymized.ends_with(Path::new("Users").join(USER_HOME)));
+    }
+
+    #[test]
+    fn paths_without_a_drive_are_not_windows_paths(names in prop::collection::vec(name(), 0..6)) {
+        let share = format!("\\\\server\\{}", names.join("\\"));
+        prop_assert_eq!(anonymize_windows_path(&share, None), None);
+    }
+
+    #[test]
+    fn parent_components_are_resolved(home in absolute_path(), names in prop::collection::vec(name(), 1..6)) {
+        // Climbing out of home and back in lands on the same place
+        let mut path = home.clone();
+        path.extend(&names);
+        let mut detour = path.clone();
+        detour.push("..");
+        detour.push(names.last().unwrap());
+        prop_assert_eq!(
+            anonymize_unix_path(&detour, Some(&home)),
+            anonymize_unix_path(&path, Some(&home))
+        );
+    }
+}
+
+/// Writes a config for a game that backs up the files in its save directory matching
+/// `pattern`, with `slot` set to `slot`, and creates the named save files.
+fn game_with_pattern(name: &str, slot: &str, pattern: &str, saves: &[&str]) -> PathBuf {
+    let root =
+        std::env::temp_dir().join(format!("cartridge-paths-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&root);
+    let save_dir = root.join("saves");
+    fs::create_dir_all(&save_dir).unwrap();
+    for save in saves {
+        fs::write(save_dir.join(save), save).unwrap();
+    }
+    let config = format!(
+        "backup_root = \"backup\"\n\n[[var]]\nname = \"slot\"\nvalue = {}\n\n\
+         [[game]]\nname = \"{}\"\n\n[[game.save]]\npath = {}\nfiles = [{}]\n",
+        toml::Value::from(slot),
+        name,
+        toml::Value::from(save_dir.to_string_lossy().as_ref()),
+        toml::Value::from(pattern)
+    );
+    fs::write(root.join("cartridge.toml"), config).unwrap();
+    root
+}
+
+/// Backs the game up, deletes its saves and restores them, returning the names of the files
+/// that came back.
+fn restored_saves(root: &Path, name: &str) -> anyhow::Result<BTreeSet<String>> {
+    let game_backup = GameBackup::new(&root.join("cartridge.toml"))?;
+    game_backup.backup_game(name, None)?;
+    let save_dir = root.join("saves");
+    fs::remove_dir_all(&save_dir)?;
+    let options = RestoreOptions {
+        on_conflict: ConflictPolicy::Overwrite,
+        ..RestoreOptions::default()
+    };
+    game_backup.restore_game(name, &options)?;
+    Ok(fs::read_dir(&save_dir)?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<std::io::Result<_>>()?)
+}
+
+#[test]
+fn variables_in_file_patterns_expand() {
+    let root = game_with_pattern(
+        "pattern-var",
+        "slot1",
+        "${slot}*.sav",
+        &["slot1.sav", "slot1-auto.sav", "slot2.sav"],
+    );
+    let restored = restored_saves(&root, "pattern-var");
+    let _ = fs::remove_dir_all(&root);
+    assert_eq!(
+        restored.unwrap(),
+        BTreeSet::from(["slot1-auto.sav".to_string(), "slot1.sav".to_string()])
+    );
+}
+
+#[test]
+fn undefined_variables_in_file_patterns_are_errors() {
+    let root = game_with_pattern(
+        "pattern-undefined",
+        "slot1",
+        "${missing}*.sav",
+        &["slot1.sav"],
+    );
+    let result = GameBackup::new(&root.join("cartridge.toml"))
+        .and_then(|game_backup| game_backup.backup_game("pattern-undefined", None));
+    let _ = fs::remove_dir_all(&root);
+    let error = format!("{:#}", result.unwrap_err());
+    assert!(error.contains("Undefined variable: missing"), "{}", error);
+}
+
+#[test]
+fn glob_characters_around_variables_match() {
+    let root = game_with_pattern(
+        "pattern-around",
+        "slot1",
+        "${slot}-[0-9].sav",
+        &["slot1-2.sav", "slot1-x.sav", "slot2-3.sav"],
+    );
+    let restored = restored_saves(&root, "pattern-around");
+    let _ = fs::remove_dir_all(&root);
+    assert_eq!(
+        restored.unwrap(),
+        BTreeSet::from(["slot1-2.sav".to_string()])
+    );
+}
+
+#[test]
+fn glob_characters_in_variables_match_literally() {
+    let root = game_with_pattern("pattern-glob", "[1]", "${slot}.sav", &["[1].sav", "1.sav"]);
+    let restored = restored_saves(&root, "pattern-glob");
+    let _ = fs::remove_dir_all(&root);
+    assert_eq!(restored.unwrap(), BTreeSet::from(["[1].sav".to_string()]));
+}
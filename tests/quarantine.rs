@@ -0,0 +1,40 @@
+use cartridge::BackupFormat;
+use cartridge::fixture::{FixtureSpec, GameFixture};
+use std::fs;
+
+#[test]
+fn old_quarantine_entries_move_out_of_a_game_named_quarantine() {
+    let dir = std::env::temp_dir().join(format!("cartridge-quarantine-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let spec = FixtureSpec {
+        files: 3,
+        special_names: false,
+        non_utf8_names: false,
+        ..FixtureSpec::default()
+    };
+    let fixture = GameFixture::create(&dir, "quarantine", &spec, BackupFormat::Directory).unwrap();
+    let game_backup = fixture.game_backup().unwrap();
+    game_backup.backup_game(&fixture.name, None).unwrap();
+
+    // An entry quarantined by an older version, next to the game's backups
+    let backup_root = fixture.root.join("backup");
+    let old_entry = backup_root.join("quarantine").join("old-20200101T000000Z");
+    fs::create_dir_all(old_entry.join("data")).unwrap();
+    fs::write(
+        old_entry.join("reason.toml"),
+        "game = \"old\"\nfailed_at = \"2020-01-01T00:00:00Z\"\nerror = \"interrupted\"\n",
+    )
+    .unwrap();
+
+    let entries = game_backup.list_quarantine();
+    let round_trip = fixture.assert_round_trip();
+    let _ = fs::remove_dir_all(&dir);
+    let entries = entries.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].id, "old-20200101T000000Z");
+    assert_eq!(
+        entries[0].path,
+        backup_root.join(".quarantine").join(&entries[0].id)
+    );
+    round_trip.unwrap();
+}
@@ -0,0 +1,54 @@
+use cartridge::BackupFormat;
+use cartridge::fixture::{FixtureSpec, GameFixture};
+use std::path::PathBuf;
+
+fn fixture_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "cartridge-round-trip-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn round_trip(name: &str, spec: &FixtureSpec, format: BackupFormat) {
+    let dir = fixture_dir(name);
+    let fixture = GameFixture::create(&dir, name, spec, format).unwrap();
+    let result = fixture.assert_round_trip();
+    let _ = std::fs::remove_dir_all(&dir);
+    result.unwrap();
+}
+
+#[test]
+fn directory_backup_restores_every_file() {
+    round_trip("dir", &FixtureSpec::default(), BackupFormat::Directory);
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn zip_backup_restores_every_file() {
+    let spec = FixtureSpec {
+        non_utf8_names: false,
+        ..FixtureSpec::default()
+    };
+    round_trip("zip", &spec, BackupFormat::Zip);
+}
+
+#[cfg(feature = "tar-zst")]
+#[test]
+fn tar_zst_backup_restores_every_file() {
+    round_trip("tar-zst", &FixtureSpec::default(), BackupFormat::TarZst);
+}
+
+#[test]
+fn deep_tree_restores_every_file() {
+    let spec = FixtureSpec {
+        files: 300,
+        depth: 12,
+        max_size: 4096,
+        seed: 7,
+        ..FixtureSpec::default()
+    };
+    round_trip("deep", &spec, BackupFormat::Directory);
+}
@@ -0,0 +1,133 @@
+use cartridge::BackupFormat;
+use cartridge::fixture::{FixtureSpec, GameFixture};
+use std::fs;
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> GameFixture {
+    let dir = std::env::temp_dir().join(format!(
+        "cartridge-snapshots-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    let spec = FixtureSpec {
+        files: 5,
+        special_names: false,
+        non_utf8_names: false,
+        ..FixtureSpec::default()
+    };
+    GameFixture::create(&dir, name, &spec, BackupFormat::Directory).unwrap()
+}
+
+/// Puts a snapshot in the trash that was deleted long before its grace period ran out.
+fn trash_expired_snapshot(fixture: &GameFixture, id: &str) -> PathBuf {
+    let trash_dir = fixture
+        .root
+        .join("backup")
+        .join(".trash")
+        .join(&fixture.name);
+    let snapshot = trash_dir.join(id);
+    fs::create_dir_all(&snapshot).unwrap();
+    fs::write(snapshot.join("save.dat"), "old").unwrap();
+    fs::write(
+        trash_dir.join(format!("{}.json", id)),
+        r#"{"deleted_at": "2000-01-01T00:00:00Z"}"#,
+    )
+    .unwrap();
+    snapshot
+}
+
+#[test]
+fn planning_a_prune_leaves_the_trash_alone() {
+    let fixture = fixture("plan-prune");
+    let game_backup = fixture.game_backup().unwrap();
+    game_backup.backup_game(&fixture.name, None).unwrap();
+    let trashed = trash_expired_snapshot(&fixture, "20000101T000000Z");
+
+    game_backup.plan_prune(&fixture.name, Some(1)).unwrap();
+    game_backup.plan_forget(&fixture.name, &[]).unwrap();
+    let kept = trashed.exists();
+
+    // Removing snapshots does purge it
+    game_backup.remove_snapshots(&[]).unwrap();
+    let purged = !trashed.exists();
+    let _ = fs::remove_dir_all(&fixture.root);
+    assert!(kept, "a dry run purged the trash");
+    assert!(purged, "removing snapshots did not purge expired ones");
+}
+
+/// Every file below `dir`.
+fn files(dir: &std::path::Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(self::files(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// Lifts read-only permissions so the fixture can be removed.
+fn make_writable(dir: &std::path::Path) {
+    let mut permissions = fs::metadata(dir).unwrap().permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    let _ = fs::set_permissions(dir, permissions);
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            make_writable(&path);
+        } else {
+            let mut permissions = fs::metadata(&path).unwrap().permissions();
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+            let _ = fs::set_permissions(&path, permissions);
+        }
+    }
+}
+
+#[test]
+fn pruning_keeps_the_current_backup_protected() {
+    let fixture = fixture("protected");
+    let config = fs::read_to_string(&fixture.config_path).unwrap();
+    fs::write(
+        &fixture.config_path,
+        format!("protect = \"read-only\"\nkeep_snapshots = 5\n{}", config),
+    )
+    .unwrap();
+    let game_backup = fixture.game_backup().unwrap();
+    game_backup.backup_game(&fixture.name, None).unwrap();
+    fs::write(fixture.save_dir.join("changed.sav"), "new").unwrap();
+    game_backup.backup_game(&fixture.name, None).unwrap();
+
+    let candidates = game_backup.plan_prune(&fixture.name, Some(1)).unwrap();
+    let pruned = candidates.len();
+    game_backup.remove_snapshots(&candidates).unwrap();
+
+    let current = fixture.root.join("backup").join(&fixture.name);
+    let writable: Vec<PathBuf> = files(&current)
+        .into_iter()
+        .filter(|file| !fs::metadata(file).unwrap().permissions().readonly())
+        .collect();
+    #[cfg(unix)]
+    let shared: Vec<PathBuf> = {
+        use std::os::unix::fs::MetadataExt;
+        files(&current)
+            .into_iter()
+            .filter(|file| fs::metadata(file).unwrap().nlink() > 1)
+            .collect()
+    };
+    make_writable(&fixture.root);
+    let _ = fs::remove_dir_all(&fixture.root);
+    assert_eq!(pruned, 1);
+    assert!(writable.is_empty(), "writable after prune: {:?}", writable);
+    #[cfg(unix)]
+    assert!(
+        shared.is_empty(),
+        "shared with other snapshots: {:?}",
+        shared
+    );
+}